@@ -0,0 +1,413 @@
+// `ar` archives (static libraries): the GNU/SysV and BSD flavors, with
+// their long-name tables and symbol indices
+// https://en.wikipedia.org/wiki/Ar_(Unix)
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::error::ParseError;
+use crate::input::Input;
+use crate::parse::ELFParser;
+use crate::section::read_str;
+use crate::utils::{checked_slice, Endian, Reader};
+
+pub const AR_MAGIC: &[u8; 8] = b"!<arch>\n";
+// members are references to files outside the archive
+pub const AR_THIN_MAGIC: &[u8; 8] = b"!<thin>\n";
+const HEADER_SIZE: u64 = 60;
+
+#[derive(Debug, Clone)]
+pub struct Member<'a> {
+    // resolved through the long-name table, or the BSD `#1/len` prefix
+    pub name: String,
+    // file offset of the member header, which symbol indices refer to
+    pub offset: u64,
+    // file offset of the contents
+    pub data_offset: u64,
+    pub data: &'a [u8],
+}
+
+#[derive(Debug, Clone)]
+pub struct ArchiveSymbol {
+    pub name: String,
+    // header offset of the member defining the symbol
+    pub offset: u64,
+    // name of that member, if there is one at `offset`
+    pub member: Option<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Archive<'a> {
+    // regular members; the symbol index and long-name table are left out
+    pub members: Vec<Member<'a>>,
+    pub symbols: Vec<ArchiveSymbol>,
+    // bytes of symbol names in the index
+    pub names_size: u64,
+}
+
+pub fn is_archive(contents: &[u8]) -> bool {
+    contents.starts_with(AR_MAGIC) || contents.starts_with(AR_THIN_MAGIC)
+}
+
+// check the magic without reading (or mapping) the whole file
+pub fn is_archive_file(path: &Path) -> bool {
+    let mut magic = [0; 8];
+    File::open(path)
+        .and_then(|mut file| file.read_exact(&mut magic))
+        .is_ok()
+        && is_archive(&magic)
+}
+
+fn bad(offset: u64, reason: &'static str) -> ParseError {
+    ParseError::BadArchive { offset, reason }
+}
+
+// decimal header fields are padded with spaces
+fn number(field: &[u8], offset: u64) -> Result<u64, ParseError> {
+    let text = std::str::from_utf8(field).map_err(|_| bad(offset, "bad number"))?;
+    match text.trim_end() {
+        "" => Ok(0),
+        text => text.parse().map_err(|_| bad(offset, "bad number")),
+    }
+}
+
+// GNU long names end in "/\n"
+fn long_name(table: &[u8], offset: usize) -> String {
+    let name = read_str(table, offset);
+    let end = name.find('\n').unwrap_or(name.len());
+    name[..end].trim_end_matches('/').to_string()
+}
+
+impl<'a> Archive<'a> {
+    pub fn parse(contents: &'a [u8]) -> Result<Archive<'a>, ParseError> {
+        if contents.starts_with(AR_THIN_MAGIC) {
+            return Err(bad(0, "thin archives are not supported"));
+        }
+        if !contents.starts_with(AR_MAGIC) {
+            return Err(ParseError::BadMagic);
+        }
+        let mut archive = Archive::default();
+        let mut long_names: &[u8] = &[];
+        // (member, GNU 64-bit?) of the symbol index, read once all
+        // member offsets are known
+        let mut index = None;
+        let mut offset = AR_MAGIC.len() as u64;
+        while offset + HEADER_SIZE <= contents.len() as u64 {
+            let header = checked_slice(contents, offset, HEADER_SIZE)?;
+            if &header[58..60] != b"`\n" {
+                return Err(bad(offset, "bad member header"));
+            }
+            let size = number(&header[48..58], offset)?;
+            let mut data_offset = offset + HEADER_SIZE;
+            let mut data = checked_slice(contents, data_offset, size)?;
+            let field = String::from_utf8_lossy(&header[..16]);
+            let field = field.trim_end();
+
+            let name = match field {
+                // only the first index is read; later ones (e.g. the second
+                // linker member of Windows import libraries) aren't members
+                "/" | "/SYM64/" => {
+                    index.get_or_insert((data, field == "/SYM64/"));
+                    None
+                }
+                "//" => {
+                    long_names = data;
+                    None
+                }
+                _ if field.starts_with("#1/") => {
+                    // BSD: the name is stored in front of the contents
+                    let len = number(&field.as_bytes()[3..], offset)?;
+                    let name = read_str(checked_slice(data, 0, len)?, 0);
+                    data = &data[len as usize..];
+                    data_offset += len;
+                    Some(name)
+                }
+                _ if field.len() > 1 && field.starts_with('/') => {
+                    let at = number(&field.as_bytes()[1..], offset)?;
+                    Some(long_name(long_names, at as usize))
+                }
+                _ => Some(field.strip_suffix('/').unwrap_or(field).to_string()),
+            };
+            match name {
+                Some(name)
+                    if name.starts_with("__.SYMDEF")
+                        && index.is_none()
+                        && archive.symbols.is_empty() =>
+                {
+                    let wide = name.starts_with("__.SYMDEF_64");
+                    (archive.symbols, archive.names_size) = parse_bsd_index(data, wide)?;
+                }
+                // a later index, or one next to a GNU index
+                Some(name) if name.starts_with("__.SYMDEF") => {}
+                Some(name) => archive.members.push(Member {
+                    name,
+                    offset,
+                    data_offset,
+                    data,
+                }),
+                None => {}
+            }
+            // members are aligned to two bytes
+            offset = data_offset + data.len() as u64;
+            offset += offset & 1;
+        }
+
+        if let Some((data, wide)) = index {
+            (archive.symbols, archive.names_size) = parse_gnu_index(data, wide)?;
+        }
+        let members = &archive.members;
+        for symbol in archive.symbols.iter_mut() {
+            symbol.member = members
+                .iter()
+                .find(|member| member.offset == symbol.offset)
+                .map(|member| member.name.clone());
+        }
+        Ok(archive)
+    }
+
+    // the member whose header is at `offset`
+    pub fn member_at(&self, offset: u64) -> Option<&Member<'a>> {
+        self.members.iter().find(|member| member.offset == offset)
+    }
+}
+
+// An archive with its members parsed as ELF files. The members share the
+// archive's bytes rather than copying them.
+pub struct ElfArchive<'a> {
+    pub symbols: Vec<ArchiveSymbol>,
+    pub names_size: u64,
+    // members that aren't ELF files (e.g. LLVM bitcode) are BadMagic
+    pub members: Vec<(String, Result<ELFParser<'a>, ParseError>)>,
+}
+
+impl<'a> ElfArchive<'a> {
+    pub fn parse(input: Input<'a>) -> Result<ElfArchive<'a>, ParseError> {
+        let input = Arc::new(input);
+        let archive = Archive::parse(&input)?;
+        let members = archive
+            .members
+            .iter()
+            .map(|member| {
+                let start = member.data_offset as usize;
+                let range = start..start + member.data.len();
+                let parsed = ELFParser::from_input(Input::Shared(input.clone(), range));
+                (member.name.clone(), parsed)
+            })
+            .collect();
+        Ok(ElfArchive {
+            symbols: archive.symbols,
+            names_size: archive.names_size,
+            members,
+        })
+    }
+}
+
+// big-endian count and member offsets (32-bit, or 64-bit for /SYM64/),
+// then the symbol names
+fn parse_gnu_index(data: &[u8], wide: bool) -> Result<(Vec<ArchiveSymbol>, u64), ParseError> {
+    let mut reader = Reader::new(data, Endian::Big);
+    let count = if wide {
+        reader.u64()?
+    } else {
+        reader.u32()? as u64
+    };
+    let mut symbols = Vec::new();
+    for _ in 0..count {
+        let offset = if wide {
+            reader.u64()?
+        } else {
+            reader.u32()? as u64
+        };
+        symbols.push(ArchiveSymbol {
+            name: String::new(),
+            offset,
+            member: None,
+        });
+    }
+    let names = reader.position();
+    let mut at = names as usize;
+    for symbol in symbols.iter_mut() {
+        symbol.name = read_str(data, at);
+        at += symbol.name.len() + 1;
+    }
+    Ok((symbols, data.len() as u64 - names))
+}
+
+// __.SYMDEF: byte size of the ranlib array, (name offset, member offset)
+// pairs, byte size of the string table, the strings. Little-endian, as
+// written by the macOS and BSD toolchains in use today. __.SYMDEF_64
+// widens every field but the strings to 8 bytes.
+fn parse_bsd_index(data: &[u8], wide: bool) -> Result<(Vec<ArchiveSymbol>, u64), ParseError> {
+    let mut reader = Reader::new(data, Endian::Little);
+    let field = |reader: &mut Reader| -> Result<u64, ParseError> {
+        if wide {
+            reader.u64()
+        } else {
+            Ok(reader.u32()? as u64)
+        }
+    };
+    let ranlib_size = field(&mut reader)?;
+    reader.skip(ranlib_size);
+    let strings_size = field(&mut reader)?;
+    let strings = reader.bytes(strings_size)?;
+
+    let mut reader = Reader::new(data, Endian::Little);
+    let width = if wide { 8 } else { 4 };
+    reader.seek(width);
+    let mut symbols = Vec::new();
+    for _ in 0..ranlib_size / (2 * width) {
+        let name = field(&mut reader)?;
+        let offset = field(&mut reader)?;
+        symbols.push(ArchiveSymbol {
+            name: read_str(strings, name as usize),
+            offset,
+            member: None,
+        });
+    }
+    Ok((symbols, strings_size))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::archive::{is_archive, Archive, ElfArchive, AR_MAGIC, AR_THIN_MAGIC};
+    use crate::error::ParseError;
+    use crate::input::Input;
+
+    fn member(name: &str, data: &[u8]) -> Vec<u8> {
+        let mut bytes = format!(
+            "{:<16}{:<12}{:<6}{:<6}{:<8}{:<10}`\n",
+            name,
+            0,
+            0,
+            0,
+            644,
+            data.len()
+        )
+        .into_bytes();
+        bytes.extend(data);
+        if bytes.len() % 2 == 1 {
+            bytes.push(b'\n');
+        }
+        bytes
+    }
+
+    #[test]
+    fn gnu_archive() {
+        let long = "a_rather_long_object_name.o";
+        let long_names = format!("{long}/\n");
+        let short = member("short.o/", b"abc");
+        let names = member("//", long_names.as_bytes());
+
+        // the symbol index comes first; its member offsets depend on its size
+        let mut index = Vec::new();
+        index.extend(2_u32.to_be_bytes());
+        let index_size = 60 + (4 + 8 + 8) as u64;
+        let first = 8 + index_size;
+        let second = first + short.len() as u64 + names.len() as u64;
+        index.extend((first as u32).to_be_bytes());
+        index.extend((second as u32).to_be_bytes());
+        index.extend(b"foo\0bar\0");
+
+        let mut bytes = AR_MAGIC.to_vec();
+        bytes.extend(member("/", &index));
+        bytes.extend(short);
+        bytes.extend(names);
+        bytes.extend(member("/0", b"\x7fELF"));
+        // a second index is skipped, not listed as a member
+        bytes.extend(member("/", &2_u32.to_be_bytes()));
+        assert!(is_archive(&bytes));
+
+        let archive = Archive::parse(&bytes).unwrap();
+        let names: Vec<&str> = archive.members.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(names, ["short.o", long]);
+        assert_eq!(archive.members[0].data, b"abc");
+        assert_eq!(archive.members[1].data, b"\x7fELF");
+        assert_eq!(archive.symbols.len(), 2);
+        assert_eq!(archive.symbols[0].name, "foo");
+        assert_eq!(archive.symbols[0].member.as_deref(), Some("short.o"));
+        assert_eq!(archive.symbols[1].name, "bar");
+        assert_eq!(archive.symbols[1].member.as_deref(), Some(long));
+    }
+
+    #[test]
+    fn bsd_archive() {
+        let mut bytes = AR_MAGIC.to_vec();
+        let symdef_name = b"__.SYMDEF\0\0\0";
+        let mut symdef = symdef_name.to_vec();
+        symdef.extend(8_u32.to_le_bytes());
+        symdef.extend(0_u32.to_le_bytes());
+        let symdef_size = 60 + (symdef_name.len() + 8 + 4 + 4 + 4) as u32;
+        symdef.extend((8 + symdef_size).to_le_bytes());
+        symdef.extend(4_u32.to_le_bytes());
+        symdef.extend(b"baz\0");
+        bytes.extend(member(&format!("#1/{}", symdef_name.len()), &symdef));
+        let mut data = b"long_bsd_name.o\0".to_vec();
+        data.extend(b"contents");
+        bytes.extend(member("#1/16", &data));
+
+        let archive = Archive::parse(&bytes).unwrap();
+        assert_eq!(archive.members.len(), 1);
+        assert_eq!(archive.members[0].name, "long_bsd_name.o");
+        assert_eq!(archive.members[0].data, b"contents");
+        assert_eq!(archive.symbols[0].name, "baz");
+        assert_eq!(
+            archive.symbols[0].member.as_deref(),
+            Some("long_bsd_name.o")
+        );
+    }
+
+    #[test]
+    fn bsd_archive_64() {
+        let mut bytes = AR_MAGIC.to_vec();
+        let symdef_name = b"__.SYMDEF_64\0\0\0\0";
+        let mut symdef = symdef_name.to_vec();
+        symdef.extend(16_u64.to_le_bytes());
+        symdef.extend(0_u64.to_le_bytes());
+        let symdef_size = 60 + (symdef_name.len() + 8 + 8 + 8 + 8 + 4) as u64;
+        symdef.extend((8 + symdef_size).to_le_bytes());
+        symdef.extend(4_u64.to_le_bytes());
+        symdef.extend(b"qux\0");
+        bytes.extend(member(&format!("#1/{}", symdef_name.len()), &symdef));
+        bytes.extend(member("wide.o/", b"contents"));
+
+        let archive = Archive::parse(&bytes).unwrap();
+        assert_eq!(archive.members.len(), 1);
+        assert_eq!(archive.symbols.len(), 1);
+        assert_eq!(archive.symbols[0].name, "qux");
+        assert_eq!(archive.symbols[0].member.as_deref(), Some("wide.o"));
+    }
+
+    #[test]
+    fn elf_members() {
+        let exe = std::fs::read(std::env::current_exe().unwrap()).unwrap();
+        let mut bytes = AR_MAGIC.to_vec();
+        bytes.extend(member("notes.txt/", b"not an object"));
+        bytes.extend(member("eva.o/", &exe));
+
+        let archive = ElfArchive::parse(Input::Owned(bytes)).unwrap();
+        assert_eq!(archive.members.len(), 2);
+        assert!(matches!(archive.members[0].1, Err(ParseError::BadMagic)));
+        let (name, parser) = &archive.members[1];
+        assert_eq!(name, "eva.o");
+        let parser = parser.as_ref().unwrap();
+        assert_eq!(parser.contents(), &exe[..]);
+        assert!(!parser.sections().unwrap().is_empty());
+    }
+
+    #[test]
+    fn malformed() {
+        let mut bytes = AR_MAGIC.to_vec();
+        bytes.extend(member("a.o/", b"xy"));
+        let len = bytes.len();
+        bytes[len - 4] = b'!';
+        assert!(matches!(
+            Archive::parse(&bytes),
+            Err(ParseError::BadArchive { offset: 8, .. })
+        ));
+        assert!(matches!(
+            Archive::parse(AR_THIN_MAGIC),
+            Err(ParseError::BadArchive { .. })
+        ));
+    }
+}
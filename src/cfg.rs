@@ -0,0 +1,256 @@
+// Control-flow graphs of the functions in the disassembly. A function is
+// the code from a label to its end (its symbol's size, or the next label);
+// it is split into basic blocks at the targets of its jumps and branches
+// and after every instruction that doesn't continue with the next one.
+// Calls don't end blocks, and jumps out of the function (tail calls)
+// have no edge.
+use std::collections::BTreeSet;
+
+use crate::disasm::{Flow, Instruction, Listing};
+
+// how control gets from a block to a successor
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edge {
+    // an unconditional jump
+    Jump,
+    // a conditional branch, taken or not
+    Taken,
+    NotTaken,
+    // straight on into the next block
+    Next,
+}
+
+impl Edge {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Edge::Jump => "jump",
+            Edge::Taken => "taken",
+            Edge::NotTaken => "not_taken",
+            Edge::Next => "next",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Block {
+    pub address: u64,
+    pub instructions: Vec<Instruction>,
+    // the addresses of the blocks control can go to next
+    pub successors: Vec<(u64, Edge)>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Function {
+    pub name: String,
+    // the section the code is in
+    pub section: String,
+    pub address: u64,
+    pub size: u64,
+    // sorted by address; the first is the entry
+    pub blocks: Vec<Block>,
+}
+
+// The functions of every listing, each with its basic blocks. Code before
+// the first label of a section (or in a section without any) is a function
+// named after the section, as objdump names such code.
+pub fn functions(listings: &[Listing]) -> Vec<Function> {
+    let mut functions = Vec::new();
+    for listing in listings {
+        let end = listing.end();
+        let mut starts: Vec<(u64, u64, &str)> = Vec::new();
+        if listing
+            .labels
+            .first()
+            .is_none_or(|l| l.address > listing.address)
+        {
+            starts.push((listing.address, 0, &listing.name));
+        }
+        starts.extend(
+            listing
+                .labels
+                .iter()
+                .map(|l| (l.address, l.size, l.name.as_str())),
+        );
+        for (i, &(address, size, name)) in starts.iter().enumerate() {
+            // up to the next label, unless the symbol says it ends sooner
+            let next = starts.get(i + 1).map_or(end, |&(next, _, _)| next.min(end));
+            let stop = match size {
+                0 => next,
+                size => next.min(address.saturating_add(size)),
+            };
+            let first = listing
+                .instructions
+                .partition_point(|i| i.address < address);
+            // labels outside the section, or a section wrapping around the
+            // address space, can put the stop before the start
+            let last = listing
+                .instructions
+                .partition_point(|i| i.address < stop)
+                .max(first);
+            if first == last {
+                continue;
+            }
+            functions.push(Function {
+                name: name.to_string(),
+                section: listing.name.clone(),
+                address,
+                size: stop.wrapping_sub(address),
+                blocks: blocks(&listing.instructions[first..last]),
+            });
+        }
+    }
+    functions
+}
+
+// split the code of a function into basic blocks
+fn blocks(code: &[Instruction]) -> Vec<Block> {
+    let inside = |address: u64| code.binary_search_by_key(&address, |i| i.address).is_ok();
+    let mut leaders = BTreeSet::from([code[0].address]);
+    for (i, instruction) in code.iter().enumerate() {
+        if matches!(instruction.flow, Flow::Next | Flow::Call) {
+            continue;
+        }
+        if let Some(target) = instruction.target.filter(|&t| inside(t)) {
+            leaders.insert(target);
+        }
+        if let Some(next) = code.get(i + 1) {
+            leaders.insert(next.address);
+        }
+    }
+
+    let mut blocks = Vec::new();
+    let mut start = 0;
+    for i in 0..code.len() {
+        let next = code.get(i + 1);
+        if next.is_some_and(|next| !leaders.contains(&next.address)) {
+            continue;
+        }
+        let last = &code[i];
+        let target = last.target.filter(|&t| inside(t));
+        let mut successors = Vec::new();
+        match last.flow {
+            Flow::Jump => successors.extend(target.map(|t| (t, Edge::Jump))),
+            Flow::Branch => {
+                successors.extend(target.map(|t| (t, Edge::Taken)));
+                successors.extend(next.map(|n| (n.address, Edge::NotTaken)));
+            }
+            Flow::Next | Flow::Call => successors.extend(next.map(|n| (n.address, Edge::Next))),
+            Flow::Return | Flow::Stop => {}
+        }
+        blocks.push(Block {
+            address: code[start].address,
+            instructions: code[start..=i].to_vec(),
+            successors,
+        });
+        start = i + 1;
+    }
+    blocks
+}
+
+// text for a string in double quotes in DOT
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+// The graphs as a Graphviz digraph, with a cluster for each function and
+// a node with the instructions of each block. Taken branches are green,
+// branches not taken red.
+pub fn dot(functions: &[Function], text: impl Fn(&Instruction) -> String) -> String {
+    let mut dot = String::from("digraph cfg {\n");
+    dot += "  node [shape=box, fontname=\"monospace\"];\n";
+    for (i, function) in functions.iter().enumerate() {
+        // sections of relocatable files overlap, so ids include the section
+        let id = |address: u64| format!("\"{}:{address:#x}\"", escape(&function.section));
+        dot += &format!("  subgraph cluster_{i} {{\n");
+        dot += &format!("    label=\"{}\";\n", escape(&function.name));
+        for block in &function.blocks {
+            let mut lines = String::new();
+            for instruction in &block.instructions {
+                let line = format!("{:#x}: {}", instruction.address, text(instruction));
+                // \l ends a left-aligned line
+                lines += &escape(&line);
+                lines += "\\l";
+            }
+            dot += &format!("    {} [label=\"{lines}\"];\n", id(block.address));
+        }
+        for block in &function.blocks {
+            for &(successor, edge) in &block.successors {
+                let style = match edge {
+                    Edge::Taken => " [color=green]",
+                    Edge::NotTaken => " [color=red]",
+                    Edge::Jump | Edge::Next => "",
+                };
+                dot += &format!("    {} -> {}{style};\n", id(block.address), id(successor));
+            }
+        }
+        dot += "  }\n";
+    }
+    dot += "}\n";
+    dot
+}
+
+#[cfg(test)]
+mod test {
+    use crate::cfg::{self, Edge};
+    use crate::disasm::{self, Syntax};
+    use crate::parse::{ELFParser, EM_X86_64};
+    use crate::render::instruction_text;
+
+    fn fixture() -> Vec<cfg::Function> {
+        let contents = include_bytes!("../tests/fixtures/hello-arm64.o");
+        let parser = ELFParser::from_bytes(contents).unwrap();
+        cfg::functions(&disasm::disassemble(&parser, Syntax::Att).unwrap())
+    }
+
+    #[test]
+    fn functions() {
+        let functions = fixture();
+        let names: Vec<&str> = functions.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, ["_start", "greet"]);
+        assert_eq!((functions[1].address, functions[1].size), (0x28, 0x24));
+        // bl greet doesn't end the block, svc #0 falls off the end
+        assert_eq!(functions[0].blocks.len(), 1);
+        assert!(functions[0].blocks[0].successors.is_empty());
+    }
+
+    #[test]
+    fn blocks() {
+        let functions = fixture();
+        let blocks = &functions[1].blocks;
+        let starts: Vec<u64> = blocks.iter().map(|b| b.address).collect();
+        assert_eq!(starts, [0x28, 0x44, 0x48]);
+        // cbz x0, 1f; ret; 1: b greet
+        assert_eq!(
+            blocks[0].successors,
+            [(0x48, Edge::Taken), (0x44, Edge::NotTaken)]
+        );
+        assert!(blocks[1].successors.is_empty());
+        assert_eq!(blocks[2].successors, [(0x28, Edge::Jump)]);
+    }
+
+    #[test]
+    fn dot() {
+        let dot = cfg::dot(&fixture(), instruction_text);
+        assert!(dot.starts_with("digraph cfg {\n"));
+        assert!(dot.contains("subgraph cluster_1 {\n    label=\"greet\";\n"));
+        assert!(dot.contains("\".text:0x28\" -> \".text:0x48\" [color=green];"));
+        assert!(dot.contains("0x44: ret\\l"));
+    }
+
+    #[test]
+    fn own_text() {
+        let parser = ELFParser::new(std::env::current_exe().unwrap()).unwrap();
+        if parser.elf_header.e_machine != EM_X86_64 {
+            return;
+        }
+        let functions = cfg::functions(&disasm::disassemble(&parser, Syntax::Att).unwrap());
+        let main = functions.iter().find(|f| f.name == "main").unwrap();
+        let size: usize = main
+            .blocks
+            .iter()
+            .flat_map(|b| &b.instructions)
+            .map(|i| i.bytes.len())
+            .sum();
+        assert_eq!(size as u64, main.size);
+    }
+}
@@ -0,0 +1,69 @@
+// When to use ANSI colors: `--color`, NO_COLOR (https://no-color.org)
+// and whether the output stream is a terminal
+use std::fmt::Display;
+use std::io::IsTerminal;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ColorChoice {
+    // color terminals, unless NO_COLOR is set
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    pub fn from_name(name: &str) -> Option<ColorChoice> {
+        match name {
+            "auto" => Some(ColorChoice::Auto),
+            "always" => Some(ColorChoice::Always),
+            "never" => Some(ColorChoice::Never),
+            _ => None,
+        }
+    }
+
+    // whether output written to `stream` should be colored
+    pub fn enabled(self, stream: &impl IsTerminal) -> bool {
+        let no_color = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+        self.decide(no_color, stream.is_terminal())
+    }
+
+    fn decide(self, no_color: bool, terminal: bool) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => !no_color && terminal,
+        }
+    }
+}
+
+// wrap `text` in the SGR sequence `style` (e.g. "1;32") if `enabled`
+pub fn paint(enabled: bool, style: &str, text: impl Display) -> String {
+    if enabled {
+        format!("\x1b[{style}m{text}\x1b[0m")
+    } else {
+        text.to_string()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::color::{paint, ColorChoice};
+
+    #[test]
+    fn policy() {
+        assert!(ColorChoice::Auto.decide(false, true));
+        assert!(!ColorChoice::Auto.decide(true, true));
+        assert!(!ColorChoice::Auto.decide(false, false));
+        assert!(ColorChoice::Always.decide(true, false));
+        assert!(!ColorChoice::Never.decide(false, true));
+        assert_eq!(ColorChoice::from_name("never"), Some(ColorChoice::Never));
+        assert_eq!(ColorChoice::from_name("sometimes"), None);
+    }
+
+    #[test]
+    fn painting() {
+        assert_eq!(paint(true, "1;31", "x"), "\x1b[1;31mx\x1b[0m");
+        assert_eq!(paint(false, "1;31", "x"), "x");
+    }
+}
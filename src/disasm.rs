@@ -0,0 +1,248 @@
+// Disassembly of the executable sections. Each instruction set has a
+// `Decoder`; the listings are rendered like the other views.
+pub mod aarch64;
+pub mod labels;
+pub mod riscv;
+pub mod x86;
+
+use crate::error::ParseError;
+use crate::parse::{Arch, ELFHeader, ELFParser, EM_AARCH64, EM_RISCV, EM_X86_64};
+use crate::section::{SHF_EXECINSTR, SHT_NOBITS};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Syntax {
+    // the GNU assembler's default, as objdump prints it
+    #[default]
+    Att,
+    Intel,
+}
+
+impl Syntax {
+    pub fn from_name(name: &str) -> Option<Syntax> {
+        match name {
+            "att" => Some(Syntax::Att),
+            "intel" => Some(Syntax::Intel),
+            _ => None,
+        }
+    }
+}
+
+// what an instruction does to the flow of control
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flow {
+    // continues with the next instruction
+    Next,
+    // continues at `target`, or somewhere unknown when there is none
+    Jump,
+    // continues at `target` or with the next instruction
+    Branch,
+    // calls `target` (when known), then continues with the next instruction
+    Call,
+    Return,
+    // doesn't continue: hlt, ud2 or int3; brk and udf on AArch64; ebreak
+    // and unimp on RISC-V
+    Stop,
+}
+
+impl Flow {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Flow::Next => "next",
+            Flow::Jump => "jump",
+            Flow::Branch => "branch",
+            Flow::Call => "call",
+            Flow::Return => "return",
+            Flow::Stop => "stop",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Instruction {
+    pub address: u64,
+    pub bytes: Vec<u8>,
+    pub mnemonic: String,
+    pub operands: String,
+    pub flow: Flow,
+    // where a direct jump, branch or call goes
+    pub target: Option<u64>,
+    // the address a memory operand refers to, e.g. a rip-relative one
+    pub reference: Option<u64>,
+    // `target` as symbol+offset, once the listing is symbolized
+    pub target_symbol: Option<String>,
+}
+
+impl Instruction {
+    // undecodable bytes: one byte, or one instruction word for fixed-size
+    // instruction sets
+    pub fn bad(bytes: &[u8], address: u64) -> Instruction {
+        Instruction {
+            address,
+            bytes: bytes.to_vec(),
+            mnemonic: "(bad)".to_string(),
+            operands: String::new(),
+            flow: Flow::Next,
+            target: None,
+            reference: None,
+            target_symbol: None,
+        }
+    }
+}
+
+pub trait Decoder {
+    // Decode the instruction at the start of `code` (which is not empty),
+    // located at `address`. Undecodable bytes come back as `(bad)`
+    // instructions of one byte (or word), so decoding always moves forward.
+    fn decode(&self, code: &[u8], address: u64) -> Instruction;
+}
+
+// a function symbol, or a PLT stub named after the function it jumps to
+#[derive(Debug, Clone)]
+pub struct Label {
+    pub name: String,
+    pub address: u64,
+    // 0 when the symbol doesn't say
+    pub size: u64,
+}
+
+// the instructions of one executable section
+#[derive(Debug, Clone)]
+pub struct Listing {
+    pub name: String,
+    pub address: u64,
+    pub instructions: Vec<Instruction>,
+    // the labels in the section, sorted by address, at most one per address
+    pub labels: Vec<Label>,
+}
+
+impl Listing {
+    // the address after the last instruction
+    pub fn end(&self) -> u64 {
+        match self.instructions.last() {
+            Some(last) => last.address.wrapping_add(last.bytes.len() as u64),
+            None => self.address,
+        }
+    }
+
+    // a section may end past the top of the address space
+    pub fn contains(&self, address: u64) -> bool {
+        address.wrapping_sub(self.address) < self.end().wrapping_sub(self.address)
+    }
+
+    // the label at `address`, if any
+    pub fn label(&self, address: u64) -> Option<&Label> {
+        let i = self.labels.partition_point(|l| l.address < address);
+        self.labels.get(i).filter(|l| l.address == address)
+    }
+
+    // an address in the section as the closest label before it (or the
+    // section) and an offset, e.g. main+0x1c
+    pub fn describe(&self, address: u64) -> String {
+        let i = self.labels.partition_point(|l| l.address <= address);
+        let (name, start) = match i.checked_sub(1) {
+            Some(i) => (&self.labels[i].name, self.labels[i].address),
+            None => (&self.name, self.address),
+        };
+        match address.wrapping_sub(start) {
+            0 => name.clone(),
+            offset => format!("{name}+{offset:#x}"),
+        }
+    }
+}
+
+// the decoder for the file's instruction set
+pub fn decoder(header: &ELFHeader, syntax: Syntax) -> Result<Box<dyn Decoder>, ParseError> {
+    match header.e_machine {
+        EM_X86_64 => Ok(Box::new(x86::X86::new(syntax))),
+        EM_AARCH64 => Ok(Box::new(aarch64::AArch64)),
+        // RV32GC or RV64GC; `disassemble` reads the extensions from the file
+        EM_RISCV => {
+            let xlen = if header.ei_class == Arch::B32 { 32 } else { 64 };
+            Ok(Box::new(riscv::RiscV::new(xlen, riscv::Extensions::all())))
+        }
+        machine => Err(ParseError::UnsupportedMachine(machine)),
+    }
+}
+
+// decode everything from the start of `code` to its end
+pub fn decode_all(decoder: &dyn Decoder, code: &[u8], address: u64) -> Vec<Instruction> {
+    let mut instructions = Vec::new();
+    let mut offset = 0;
+    while offset < code.len() {
+        let instruction = decoder.decode(&code[offset..], address.wrapping_add(offset as u64));
+        offset += instruction.bytes.len();
+        instructions.push(instruction);
+    }
+    instructions
+}
+
+// Disassemble every SHF_EXECINSTR section, at the addresses they are
+// loaded at (sh_addr), with the labels from the symbol tables
+pub fn disassemble(parser: &ELFParser, syntax: Syntax) -> Result<Vec<Listing>, ParseError> {
+    let decoder = match parser.elf_header.e_machine {
+        EM_RISCV => Box::new(riscv::RiscV::for_file(parser)?),
+        _ => decoder(&parser.elf_header, syntax)?,
+    };
+    let mut listings = Vec::new();
+    let mut sections = Vec::new();
+    for (i, section) in parser.sections()?.iter().enumerate() {
+        if section.sh_flags & SHF_EXECINSTR == 0 || section.sh_type == SHT_NOBITS {
+            continue;
+        }
+        let code = parser.section_data(section)?;
+        listings.push(Listing {
+            name: section.name.to_string(),
+            address: section.sh_addr,
+            instructions: decode_all(&*decoder, code, section.sh_addr),
+            labels: Vec::new(),
+        });
+        sections.push(i);
+    }
+    labels::symbolize(parser, &mut listings, &sections)?;
+    Ok(listings)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::disasm::{self, Syntax};
+    use crate::parse::{ELFParser, EM_X86_64};
+
+    #[test]
+    fn own_text() {
+        let parser = ELFParser::new(std::env::current_exe().unwrap()).unwrap();
+        if parser.elf_header.e_machine != EM_X86_64 {
+            return;
+        }
+        let listings = disasm::disassemble(&parser, Syntax::Att).unwrap();
+        let text = listings.iter().find(|l| l.name == ".text").unwrap();
+        let size: usize = text.instructions.iter().map(|i| i.bytes.len()).sum();
+        let section = parser
+            .sections()
+            .unwrap()
+            .iter()
+            .find(|s| s.name == ".text");
+        assert_eq!(size as u64, section.unwrap().sh_size);
+        assert!(text.instructions.iter().any(|i| i.mnemonic == "call"));
+    }
+
+    #[test]
+    fn top_of_address_space() {
+        let mut contents = include_bytes!("../tests/fixtures/hello-arm64.o").to_vec();
+        // sh_addr in the ELF64 section header of .text
+        let at = {
+            let parser = ELFParser::from_bytes(&contents).unwrap();
+            let sections = parser.sections().unwrap();
+            let index = sections.iter().position(|s| s.name == ".text").unwrap();
+            parser.elf_header.e_shoff as usize + index * 64 + 16
+        };
+        contents[at..at + 8].copy_from_slice(&(u64::MAX - 7).to_le_bytes());
+
+        let parser = ELFParser::from_bytes(&contents).unwrap();
+        let listings = disasm::disassemble(&parser, Syntax::Att).unwrap();
+        let text = listings.iter().find(|l| l.name == ".text").unwrap();
+        assert_eq!(text.instructions[1].address, u64::MAX - 3);
+        assert_eq!(text.instructions[2].address, 0);
+        assert!(text.contains(0) && text.contains(u64::MAX - 7));
+        assert!(!text.contains(u64::MAX - 8));
+    }
+}
@@ -0,0 +1,3058 @@
+// AArch64 decoder for the A64 instruction set: the base instructions,
+// SIMD and floating point (with half precision), the LSE atomics, the
+// AES/SHA crypto extensions, dot product, and the pointer authentication
+// and BTI instructions. SVE and SME aren't decoded. Output follows
+// llvm-objdump, which prints the preferred aliases of the Arm manual (mov,
+// cmp, lsl, cset, ...).
+//
+// Every instruction is one little-endian word, whatever the data
+// endianness. The decoding follows the manual's encoding index; patterns
+// like "0xx1" are bits as they appear in its tables.
+use std::fmt::Display;
+
+use crate::disasm::{Decoder, Flow, Instruction};
+
+const CONDITIONS: [&str; 16] = [
+    "eq", "ne", "hs", "lo", "mi", "pl", "vs", "vc", "hi", "ls", "ge", "lt", "gt", "le", "al", "nv",
+];
+const SHIFTS: [&str; 4] = ["lsl", "lsr", "asr", "ror"];
+const EXTENDS: [&str; 8] = [
+    "uxtb", "uxth", "uxtw", "uxtx", "sxtb", "sxth", "sxtw", "sxtx",
+];
+// vector arrangements by size:Q
+const ARRANGEMENTS: [&str; 8] = ["8b", "16b", "4h", "8h", "2s", "4s", "1d", "2d"];
+// the arrangement with elements twice as wide, by size
+const LONG: [&str; 4] = ["8h", "4s", "2d", "1q"];
+// scalar SIMD and floating-point registers by log2 of their size in bytes
+const SCALARS: [char; 5] = ['b', 'h', 's', 'd', 'q'];
+const BARRIERS: [&str; 16] = [
+    "", "oshld", "oshst", "osh", "", "nshld", "nshst", "nsh", "", "ishld", "ishst", "ish", "",
+    "ld", "st", "sy",
+];
+
+pub struct AArch64;
+
+impl Decoder for AArch64 {
+    fn decode(&self, code: &[u8], address: u64) -> Instruction {
+        if code.len() < 4 {
+            return Instruction::bad(code, address);
+        }
+        let word = u32::from_le_bytes([code[0], code[1], code[2], code[3]]);
+        let mut state = State {
+            word,
+            address,
+            flow: Flow::Next,
+            target: None,
+            reference: None,
+        };
+        match state.decode() {
+            Some((mnemonic, operands)) => Instruction {
+                address,
+                bytes: code[..4].to_vec(),
+                mnemonic,
+                operands: operands.join(", "),
+                flow: state.flow,
+                target: state.target,
+                reference: state.reference,
+                target_symbol: None,
+            },
+            None => Instruction::bad(&code[..4], address),
+        }
+    }
+}
+
+// the mnemonic and the operands, or nothing for unallocated encodings
+type Decoded = Option<(String, Vec<String>)>;
+
+fn insn(name: impl Into<String>, operands: Vec<String>) -> Decoded {
+    Some((name.into(), operands))
+}
+
+// whether `value` matches a bit pattern of the manual, like "0xx1"
+fn is(value: u32, pattern: &str) -> bool {
+    pattern
+        .bytes()
+        .rev()
+        .enumerate()
+        .all(|(i, bit)| bit == b'x' || (value >> i & 1) as u8 == bit - b'0')
+}
+
+// the table entries that are empty strings are unallocated
+fn named(name: &'static str) -> Option<&'static str> {
+    (!name.is_empty()).then_some(name)
+}
+
+fn signed(value: u32, bits: u32) -> i64 {
+    (i64::from(value) << (64 - bits)) >> (64 - bits)
+}
+
+// a general-purpose register, with 31 as the zero register
+fn gpr(n: u32, wide: bool) -> String {
+    match (n, wide) {
+        (31, true) => "xzr".to_string(),
+        (31, false) => "wzr".to_string(),
+        (n, true) => format!("x{n}"),
+        (n, false) => format!("w{n}"),
+    }
+}
+
+// a general-purpose register, with 31 as the stack pointer
+fn gpr_sp(n: u32, wide: bool) -> String {
+    match (n, wide) {
+        (31, true) => "sp".to_string(),
+        (31, false) => "wsp".to_string(),
+        _ => gpr(n, wide),
+    }
+}
+
+// a scalar SIMD or floating-point register, e.g. s0
+fn fpr(kind: char, n: u32) -> String {
+    format!("{kind}{n}")
+}
+
+// a vector register with its arrangement, e.g. v0.16b
+fn vreg(n: u32, arrangement: &str) -> String {
+    format!("v{n}.{arrangement}")
+}
+
+// a vector element, e.g. v0.s[1]
+fn lane(n: u32, kind: char, index: u32) -> String {
+    format!("v{n}.{kind}[{index}]")
+}
+
+// consecutive vector registers, e.g. { v0.16b, v1.16b }
+fn list(n: u32, count: u32, arrangement: &str) -> String {
+    let registers: Vec<String> = (0..count)
+        .map(|i| vreg((n + i) % 32, arrangement))
+        .collect();
+    format!("{{ {} }}", registers.join(", "))
+}
+
+fn three(rd: u32, rn: u32, rm: u32, arrangement: &str) -> Vec<String> {
+    vec![
+        vreg(rd, arrangement),
+        vreg(rn, arrangement),
+        vreg(rm, arrangement),
+    ]
+}
+
+fn imm(value: impl Display) -> String {
+    format!("#{value}")
+}
+
+// a hexadecimal immediate, but for #0
+fn hex(value: u64) -> String {
+    if value == 0 {
+        imm(0)
+    } else {
+        format!("#{value:#x}")
+    }
+}
+
+fn address(target: u64) -> String {
+    format!("{target:#x}")
+}
+
+// a base register with an optional offset
+fn offset_address(base: &str, offset: i64) -> String {
+    if offset == 0 {
+        format!("[{base}]")
+    } else {
+        format!("[{base}, #{offset}]")
+    }
+}
+
+// the arrangement of a floating-point vector by sz:Q
+fn fp_arrangement(sz: u32, q: bool) -> Option<&'static str> {
+    match (sz, q) {
+        (0, false) => Some("2s"),
+        (0, true) => Some("4s"),
+        (1, true) => Some("2d"),
+        _ => None,
+    }
+}
+
+// the scalar register of a floating-point type field
+fn fp_kind(ftype: u32) -> Option<char> {
+    match ftype {
+        0 => Some('s'),
+        1 => Some('d'),
+        3 => Some('h'),
+        _ => None,
+    }
+}
+
+// the value of an 8-bit floating-point immediate (VFPExpandImm)
+fn fp_immediate(imm8: u32) -> String {
+    let exponent = if imm8 & 0x40 != 0 {
+        (imm8 >> 4 & 3) as i32 - 3
+    } else {
+        (imm8 >> 4 & 3) as i32 + 1
+    };
+    let value = f64::from(16 + (imm8 & 15)) / 16.0 * 2f64.powi(exponent);
+    let sign = if imm8 & 0x80 != 0 { "-" } else { "" };
+    format!("#{sign}{value:.8}")
+}
+
+// the element size and index of a copy's imm5
+fn element(imm5: u32) -> Option<(u32, char, u32)> {
+    let size = imm5.trailing_zeros();
+    if size > 3 {
+        return None;
+    }
+    Some((size, SCALARS[size as usize], imm5 >> (size + 1)))
+}
+
+// DecodeBitMasks of the manual: the value of a logical immediate, a run of
+// imms + 1 ones rotated right by immr within an element, repeated
+fn bit_mask(n: bool, imms: u32, immr: u32, width: u32) -> Option<u64> {
+    let combined = u32::from(n) << 6 | (!imms & 0x3f);
+    if combined <= 1 {
+        return None;
+    }
+    let length = 31 - combined.leading_zeros();
+    let levels = (1 << length) - 1;
+    let (s, r) = (imms & levels, immr & levels);
+    if s == levels {
+        return None;
+    }
+    let size = 1u32 << length;
+    let mask = if size == 64 {
+        u64::MAX
+    } else {
+        (1 << size) - 1
+    };
+    let ones = (1u64 << (s + 1)) - 1;
+    let element = if r == 0 {
+        ones
+    } else {
+        (ones >> r | ones << (size - r)) & mask
+    };
+    let mut value = 0;
+    for shift in (0..width).step_by(size as usize) {
+        value |= element << shift;
+    }
+    Some(value)
+}
+
+// whether a single movz or movn builds the value of a logical immediate,
+// in which case llvm-objdump shows orr rather than mov
+fn move_wide_possible(value: u64, width: u32) -> bool {
+    let mask = if width == 64 { u64::MAX } else { 0xffff_ffff };
+    let chunk = |value: u64| {
+        (0..width)
+            .step_by(16)
+            .any(|shift| value & !(0xffff << shift) == 0)
+    };
+    chunk(value) || chunk(!value & mask)
+}
+
+// the operand of prfm: the prefetch operation, or its number
+fn prefetch(op: u32) -> String {
+    let kind = ["pld", "pli", "pst"];
+    match (kind.get(op as usize >> 3), op >> 1 & 3) {
+        (Some(kind), target @ 0..=2) => {
+            let policy = if op & 1 == 0 { "keep" } else { "strm" };
+            format!("{kind}l{}{policy}", target + 1)
+        }
+        _ => imm(op),
+    }
+}
+
+struct State {
+    word: u32,
+    address: u64,
+    flow: Flow,
+    target: Option<u64>,
+    reference: Option<u64>,
+}
+
+impl State {
+    fn bits(&self, high: u32, low: u32) -> u32 {
+        self.word >> low & ((1 << (high - low + 1)) - 1)
+    }
+
+    fn bit(&self, n: u32) -> bool {
+        self.word >> n & 1 != 0
+    }
+
+    fn rd(&self) -> u32 {
+        self.bits(4, 0)
+    }
+
+    fn rn(&self) -> u32 {
+        self.bits(9, 5)
+    }
+
+    fn rm(&self) -> u32 {
+        self.bits(20, 16)
+    }
+
+    fn sf(&self) -> bool {
+        self.bit(31)
+    }
+
+    // the address `offset` words away from the instruction
+    fn relative(&self, offset: u32, bits: u32) -> u64 {
+        self.address
+            .wrapping_add((signed(offset, bits) << 2) as u64)
+    }
+
+    fn decode(&mut self) -> Decoded {
+        let op0 = self.bits(28, 25);
+        if is(op0, "0000") {
+            self.reserved()
+        } else if is(op0, "100x") {
+            self.immediate()
+        } else if is(op0, "101x") {
+            self.branch()
+        } else if is(op0, "x1x0") {
+            self.load_store()
+        } else if is(op0, "x101") {
+            self.register()
+        } else if is(op0, "x111") {
+            self.simd_fp()
+        } else {
+            None
+        }
+    }
+
+    // udf, the permanently undefined instruction
+    fn reserved(&mut self) -> Decoded {
+        if self.bits(31, 16) != 0 {
+            return None;
+        }
+        self.flow = Flow::Stop;
+        insn("udf", vec![imm(self.bits(15, 0))])
+    }
+
+    // data processing with an immediate
+    fn immediate(&mut self) -> Decoded {
+        match self.bits(25, 23) {
+            0b000 | 0b001 => self.pc_relative(),
+            0b010 => self.add_sub_immediate(),
+            0b100 => self.logical_immediate(),
+            0b101 => self.move_wide(),
+            0b110 => self.bitfield(),
+            0b111 => self.extract(),
+            _ => None,
+        }
+    }
+
+    fn pc_relative(&mut self) -> Decoded {
+        let offset = signed(self.bits(23, 5) << 2 | self.bits(30, 29), 21);
+        let (name, target) = if self.sf() {
+            let page = self.address & !0xfff;
+            ("adrp", page.wrapping_add((offset << 12) as u64))
+        } else {
+            ("adr", self.address.wrapping_add(offset as u64))
+        };
+        self.reference = Some(target);
+        insn(name, vec![gpr(self.rd(), true), address(target)])
+    }
+
+    fn add_sub_immediate(&mut self) -> Decoded {
+        let (sf, sub, flags, shifted) = (self.sf(), self.bit(30), self.bit(29), self.bit(22));
+        let (rd, rn, imm12) = (self.rd(), self.rn(), self.bits(21, 10));
+        if !sub && !flags && !shifted && imm12 == 0 && (rd == 31 || rn == 31) {
+            return insn("mov", vec![gpr_sp(rd, sf), gpr_sp(rn, sf)]);
+        }
+        let mut operands = vec![gpr_sp(rn, sf), imm(imm12)];
+        if shifted {
+            operands.push("lsl #12".to_string());
+        }
+        if flags && rd == 31 {
+            return insn(if sub { "cmp" } else { "cmn" }, operands);
+        }
+        let name = ["add", "adds", "sub", "subs"][usize::from(sub) << 1 | usize::from(flags)];
+        operands.insert(0, if flags { gpr(rd, sf) } else { gpr_sp(rd, sf) });
+        insn(name, operands)
+    }
+
+    fn logical_immediate(&mut self) -> Decoded {
+        let (sf, opc, n) = (self.sf(), self.bits(30, 29), self.bit(22));
+        let (immr, imms) = (self.bits(21, 16), self.bits(15, 10));
+        if !sf && n {
+            return None;
+        }
+        let value = bit_mask(n, imms, immr, if sf { 64 } else { 32 })?;
+        let (rd, rn) = (self.rd(), self.rn());
+        if opc == 3 && rd == 31 {
+            return insn("tst", vec![gpr(rn, sf), hex(value)]);
+        }
+        if opc == 1 && rn == 31 && !move_wide_possible(value, if sf { 64 } else { 32 }) {
+            // like the movz and movn aliases, in signed decimal
+            let value = if sf {
+                value as i64
+            } else {
+                i64::from(value as i32)
+            };
+            return insn("mov", vec![gpr_sp(rd, sf), imm(value)]);
+        }
+        let name = ["and", "orr", "eor", "ands"][opc as usize];
+        let rd = if opc == 3 {
+            gpr(rd, sf)
+        } else {
+            gpr_sp(rd, sf)
+        };
+        insn(name, vec![rd, gpr(rn, sf), hex(value)])
+    }
+
+    fn move_wide(&mut self) -> Decoded {
+        let (sf, opc, hw) = (self.sf(), self.bits(30, 29), self.bits(22, 21));
+        let (imm16, rd) = (u64::from(self.bits(20, 5)), self.rd());
+        if opc == 1 || (!sf && hw >= 2) {
+            return None;
+        }
+        let shift = hw * 16;
+        // mov unless a zero is shifted, and a 32-bit movn of 0xffff
+        let alias = !(imm16 == 0 && hw != 0);
+        let value = match opc {
+            0 if alias && (sf || imm16 != 0xffff) => Some(!(imm16 << shift)),
+            2 if alias => Some(imm16 << shift),
+            _ => None,
+        };
+        if let Some(value) = value {
+            let value = if sf {
+                value as i64
+            } else {
+                i64::from(value as u32 as i32)
+            };
+            return insn("mov", vec![gpr(rd, sf), imm(value)]);
+        }
+        let mut operands = vec![gpr(rd, sf), imm(imm16)];
+        if shift != 0 {
+            operands.push(format!("lsl #{shift}"));
+        }
+        insn(["movn", "", "movz", "movk"][opc as usize], operands)
+    }
+
+    // sbfm, bfm and ubfm, which are always shown as one of their aliases
+    fn bitfield(&mut self) -> Decoded {
+        let (sf, opc, n) = (self.sf(), self.bits(30, 29), self.bit(22));
+        let (immr, imms) = (self.bits(21, 16), self.bits(15, 10));
+        let width = if sf { 64 } else { 32 };
+        if opc == 3 || sf != n || immr >= width || imms >= width {
+            return None;
+        }
+        let (rd, rn) = (gpr(self.rd(), sf), gpr(self.rn(), sf));
+        let last = width - 1;
+        // BFXPreferred of the manual, which leaves the extensions to sxtb
+        // and friends
+        let extension = immr == 0
+            && if sf {
+                opc == 0 && matches!(imms, 7 | 15 | 31)
+            } else {
+                matches!(imms, 7 | 15)
+            };
+        let extract = imms >= immr && imms != last && !extension;
+        let insert = vec![rd.clone(), rn.clone(), imm(width - immr), imm(imms + 1)];
+        let field = vec![
+            rd.clone(),
+            rn.clone(),
+            imm(immr),
+            imm((imms + 1).saturating_sub(immr)),
+        ];
+        match opc {
+            0 if imms == last => insn("asr", vec![rd, rn, imm(immr)]),
+            0 if imms < immr => insn("sbfiz", insert),
+            0 if extract => insn("sbfx", field),
+            0 => {
+                let name = match imms {
+                    7 => "sxtb",
+                    15 => "sxth",
+                    _ => "sxtw",
+                };
+                insn(name, vec![rd, gpr(self.rn(), false)])
+            }
+            1 if (imms < immr || immr == 0) && self.rn() == 31 => {
+                insn("bfc", vec![rd, imm((width - immr) % width), imm(imms + 1)])
+            }
+            1 if imms < immr => insn("bfi", insert),
+            1 => insn("bfxil", field),
+            _ if imms != last && imms + 1 == immr => insn("lsl", vec![rd, rn, imm(last - imms)]),
+            _ if imms == last => insn("lsr", vec![rd, rn, imm(immr)]),
+            _ if imms < immr => insn("ubfiz", insert),
+            _ if extract => insn("ubfx", field),
+            _ => {
+                let name = if imms == 7 { "uxtb" } else { "uxth" };
+                insn(name, vec![rd, rn])
+            }
+        }
+    }
+
+    fn extract(&mut self) -> Decoded {
+        let (sf, n, imms) = (self.sf(), self.bit(22), self.bits(15, 10));
+        if self.bits(30, 29) != 0 || self.bit(21) || sf != n || (!sf && imms >= 32) {
+            return None;
+        }
+        let (rd, rn, rm) = (self.rd(), self.rn(), self.rm());
+        if rn == rm {
+            return insn("ror", vec![gpr(rd, sf), gpr(rn, sf), imm(imms)]);
+        }
+        insn(
+            "extr",
+            vec![gpr(rd, sf), gpr(rn, sf), gpr(rm, sf), imm(imms)],
+        )
+    }
+
+    // branches, exception generation and system instructions
+    fn branch(&mut self) -> Decoded {
+        let op0 = self.bits(31, 29);
+        match op0 {
+            0b010 if !self.bit(25) => self.conditional_branch(),
+            0b110 if self.bits(25, 24) == 0 => self.exception(),
+            0b110 if self.bits(25, 22) == 0b0100 => self.system(),
+            0b110 if self.bit(25) => self.branch_register(),
+            _ if op0 & 3 == 0 => {
+                let target = self.relative(self.bits(25, 0), 26);
+                self.target = Some(target);
+                let (name, flow) = if self.sf() {
+                    ("bl", Flow::Call)
+                } else {
+                    ("b", Flow::Jump)
+                };
+                self.flow = flow;
+                insn(name, vec![address(target)])
+            }
+            _ if op0 & 3 == 1 && !self.bit(25) => {
+                let target = self.relative(self.bits(23, 5), 19);
+                self.target = Some(target);
+                self.flow = Flow::Branch;
+                let name = if self.bit(24) { "cbnz" } else { "cbz" };
+                insn(name, vec![gpr(self.rd(), self.sf()), address(target)])
+            }
+            _ if op0 & 3 == 1 => {
+                let target = self.relative(self.bits(18, 5), 14);
+                self.target = Some(target);
+                self.flow = Flow::Branch;
+                let name = if self.bit(24) { "tbnz" } else { "tbz" };
+                let bit = self.bits(31, 31) << 5 | self.bits(23, 19);
+                let register = gpr(self.rd(), self.sf());
+                insn(name, vec![register, imm(bit), address(target)])
+            }
+            _ => None,
+        }
+    }
+
+    fn conditional_branch(&mut self) -> Decoded {
+        if self.bit(24) || self.bit(4) {
+            return None;
+        }
+        let condition = self.bits(3, 0) as usize;
+        let target = self.relative(self.bits(23, 5), 19);
+        self.target = Some(target);
+        self.flow = if condition >= 14 {
+            Flow::Jump
+        } else {
+            Flow::Branch
+        };
+        insn(
+            format!("b.{}", CONDITIONS[condition]),
+            vec![address(target)],
+        )
+    }
+
+    fn exception(&mut self) -> Decoded {
+        let (opc, imm16, ll) = (self.bits(23, 21), self.bits(20, 5), self.bits(1, 0));
+        if self.bits(4, 2) != 0 {
+            return None;
+        }
+        let name = match (opc, ll) {
+            (0, 1) => "svc",
+            (0, 2) => "hvc",
+            (0, 3) => "smc",
+            (1, 0) => "brk",
+            (2, 0) => "hlt",
+            (5, 1) => "dcps1",
+            (5, 2) => "dcps2",
+            (5, 3) => "dcps3",
+            _ => return None,
+        };
+        if matches!(name, "brk" | "hlt") {
+            self.flow = Flow::Stop;
+        }
+        if name.starts_with("dcps") && imm16 == 0 {
+            return insn(name, vec![]);
+        }
+        insn(name, vec![hex(u64::from(imm16))])
+    }
+
+    fn system(&mut self) -> Decoded {
+        let (l, op0, op1) = (self.bit(21), self.bits(20, 19), self.bits(18, 16));
+        let (crn, crm, op2, rt) = (
+            self.bits(15, 12),
+            self.bits(11, 8),
+            self.bits(7, 5),
+            self.rd(),
+        );
+        let special = match (l, op0) {
+            (false, 0) if crn == 2 && rt == 31 => hint(crm << 3 | op2),
+            (false, 0) if crn == 3 && rt == 31 => barrier(crm, op2),
+            (false, 0) if crn == 4 && rt == 31 => pstate(op1, crm, op2),
+            (_, 1) => return sys(l, op1, crn, crm, op2, rt),
+            _ => None,
+        };
+        if special.is_some() {
+            return special;
+        }
+        // anything else is a system register, named or not
+        let register = system_register(op0, op1, crn, crm, op2);
+        if l {
+            insn("mrs", vec![gpr(rt, true), register])
+        } else {
+            insn("msr", vec![register, gpr(rt, true)])
+        }
+    }
+
+    fn branch_register(&mut self) -> Decoded {
+        let (opc, op3, rn, op4) = (self.bits(24, 21), self.bits(15, 10), self.rn(), self.rd());
+        if self.bits(20, 16) != 31 {
+            return None;
+        }
+        let register = gpr(rn, true);
+        let (name, operands, flow) = match (opc, op3, op4) {
+            (0, 0, 0) => ("br", vec![register], Flow::Jump),
+            (0, 2, 31) => ("braaz", vec![register], Flow::Jump),
+            (0, 3, 31) => ("brabz", vec![register], Flow::Jump),
+            (1, 0, 0) => ("blr", vec![register], Flow::Call),
+            (1, 2, 31) => ("blraaz", vec![register], Flow::Call),
+            (1, 3, 31) => ("blrabz", vec![register], Flow::Call),
+            (2, 0, 0) if rn == 30 => ("ret", vec![], Flow::Return),
+            (2, 0, 0) => ("ret", vec![register], Flow::Return),
+            (2, 2, 31) if rn == 31 => ("retaa", vec![], Flow::Return),
+            (2, 3, 31) if rn == 31 => ("retab", vec![], Flow::Return),
+            (4, 0, 0) if rn == 31 => ("eret", vec![], Flow::Return),
+            (4, 2, 31) if rn == 31 => ("eretaa", vec![], Flow::Return),
+            (4, 3, 31) if rn == 31 => ("eretab", vec![], Flow::Return),
+            (5, 0, 0) if rn == 31 => ("drps", vec![], Flow::Return),
+            (8, 2, _) => ("braa", vec![register, gpr_sp(op4, true)], Flow::Jump),
+            (8, 3, _) => ("brab", vec![register, gpr_sp(op4, true)], Flow::Jump),
+            (9, 2, _) => ("blraa", vec![register, gpr_sp(op4, true)], Flow::Call),
+            (9, 3, _) => ("blrab", vec![register, gpr_sp(op4, true)], Flow::Call),
+            _ => return None,
+        };
+        self.flow = flow;
+        insn(name, operands)
+    }
+}
+
+// the hint instructions by CRm:op2
+fn hint(number: u32) -> Decoded {
+    let name = match number {
+        0 => "nop",
+        1 => "yield",
+        2 => "wfe",
+        3 => "wfi",
+        4 => "sev",
+        5 => "sevl",
+        7 => "xpaclri",
+        8 => "pacia1716",
+        10 => "pacib1716",
+        12 => "autia1716",
+        14 => "autib1716",
+        16 => "esb",
+        17 => "psb csync",
+        18 => "tsb csync",
+        20 => "csdb",
+        24 => "paciaz",
+        25 => "paciasp",
+        26 => "pacibz",
+        27 => "pacibsp",
+        28 => "autiaz",
+        29 => "autiasp",
+        30 => "autibz",
+        31 => "autibsp",
+        32 => "bti",
+        34 => "bti c",
+        36 => "bti j",
+        38 => "bti jc",
+        _ => return insn("hint", vec![imm(number)]),
+    };
+    match name.split_once(' ') {
+        Some((name, operand)) => insn(name, vec![operand.to_string()]),
+        None => insn(name, vec![]),
+    }
+}
+
+fn barrier(crm: u32, op2: u32) -> Decoded {
+    let option = || match named(BARRIERS[crm as usize]) {
+        Some(name) => name.to_string(),
+        None => imm(crm),
+    };
+    match op2 {
+        2 if crm == 15 => insn("clrex", vec![]),
+        2 => insn("clrex", vec![imm(crm)]),
+        4 if crm == 0 => insn("ssbb", vec![]),
+        4 if crm == 4 => insn("pssbb", vec![]),
+        4 => insn("dsb", vec![option()]),
+        5 => insn("dmb", vec![option()]),
+        6 if crm == 15 => insn("isb", vec![]),
+        6 => insn("isb", vec![imm(crm)]),
+        7 if crm == 0 => insn("sb", vec![]),
+        _ => None,
+    }
+}
+
+// msr with an immediate, which sets a field of PSTATE
+fn pstate(op1: u32, crm: u32, op2: u32) -> Decoded {
+    let field = match (op1, op2) {
+        (0, 0) if crm == 0 => return insn("cfinv", vec![]),
+        (0, 1) if crm == 0 => return insn("xaflag", vec![]),
+        (0, 2) if crm == 0 => return insn("axflag", vec![]),
+        (0, 3) => "UAO",
+        (0, 4) => "PAN",
+        (0, 5) => "SPSel",
+        (3, 1) => "SSBS",
+        (3, 2) => "DIT",
+        (3, 4) => "TCO",
+        (3, 6) => "DAIFSet",
+        (3, 7) => "DAIFClr",
+        _ => return None,
+    };
+    insn("msr", vec![field.to_string(), imm(crm)])
+}
+
+// the cache, address translation and TLB maintenance operations of sys,
+// by op1, CRn, CRm and op2
+const SYS_OPERATIONS: [(&str, &str, u32, u32, u32, u32); 56] = [
+    ("ic", "ialluis", 0, 7, 1, 0),
+    ("ic", "iallu", 0, 7, 5, 0),
+    ("ic", "ivau", 3, 7, 5, 1),
+    ("dc", "ivac", 0, 7, 6, 1),
+    ("dc", "isw", 0, 7, 6, 2),
+    ("dc", "csw", 0, 7, 10, 2),
+    ("dc", "cisw", 0, 7, 14, 2),
+    ("dc", "zva", 3, 7, 4, 1),
+    ("dc", "cvac", 3, 7, 10, 1),
+    ("dc", "cvau", 3, 7, 11, 1),
+    ("dc", "cvap", 3, 7, 12, 1),
+    ("dc", "cvadp", 3, 7, 13, 1),
+    ("dc", "civac", 3, 7, 14, 1),
+    ("cfp", "rctx", 3, 7, 3, 4),
+    ("dvp", "rctx", 3, 7, 3, 5),
+    ("cpp", "rctx", 3, 7, 3, 7),
+    ("at", "s1e1r", 0, 7, 8, 0),
+    ("at", "s1e1w", 0, 7, 8, 1),
+    ("at", "s1e0r", 0, 7, 8, 2),
+    ("at", "s1e0w", 0, 7, 8, 3),
+    ("at", "s1e1rp", 0, 7, 9, 0),
+    ("at", "s1e1wp", 0, 7, 9, 1),
+    ("at", "s1e2r", 4, 7, 8, 0),
+    ("at", "s1e2w", 4, 7, 8, 1),
+    ("at", "s12e1r", 4, 7, 8, 4),
+    ("at", "s12e1w", 4, 7, 8, 5),
+    ("at", "s12e0r", 4, 7, 8, 6),
+    ("at", "s12e0w", 4, 7, 8, 7),
+    ("at", "s1e3r", 6, 7, 8, 0),
+    ("at", "s1e3w", 6, 7, 8, 1),
+    ("tlbi", "vmalle1is", 0, 8, 3, 0),
+    ("tlbi", "vae1is", 0, 8, 3, 1),
+    ("tlbi", "aside1is", 0, 8, 3, 2),
+    ("tlbi", "vaae1is", 0, 8, 3, 3),
+    ("tlbi", "vale1is", 0, 8, 3, 5),
+    ("tlbi", "vaale1is", 0, 8, 3, 7),
+    ("tlbi", "vmalle1", 0, 8, 7, 0),
+    ("tlbi", "vae1", 0, 8, 7, 1),
+    ("tlbi", "aside1", 0, 8, 7, 2),
+    ("tlbi", "vaae1", 0, 8, 7, 3),
+    ("tlbi", "vale1", 0, 8, 7, 5),
+    ("tlbi", "vaale1", 0, 8, 7, 7),
+    ("tlbi", "ipas2e1is", 4, 8, 0, 1),
+    ("tlbi", "ipas2le1is", 4, 8, 0, 5),
+    ("tlbi", "alle2is", 4, 8, 3, 0),
+    ("tlbi", "vae2is", 4, 8, 3, 1),
+    ("tlbi", "alle1is", 4, 8, 3, 4),
+    ("tlbi", "vale2is", 4, 8, 3, 5),
+    ("tlbi", "vmalls12e1is", 4, 8, 3, 6),
+    ("tlbi", "ipas2e1", 4, 8, 4, 1),
+    ("tlbi", "ipas2le1", 4, 8, 4, 5),
+    ("tlbi", "alle2", 4, 8, 7, 0),
+    ("tlbi", "vae2", 4, 8, 7, 1),
+    ("tlbi", "alle1", 4, 8, 7, 4),
+    ("tlbi", "vale2", 4, 8, 7, 5),
+    ("tlbi", "vmalls12e1", 4, 8, 7, 6),
+];
+
+fn sys(l: bool, op1: u32, crn: u32, crm: u32, op2: u32, rt: u32) -> Decoded {
+    let fields = vec![imm(op1), format!("c{crn}"), format!("c{crm}"), imm(op2)];
+    if l {
+        let mut operands = vec![gpr(rt, true)];
+        operands.extend(fields);
+        return insn("sysl", operands);
+    }
+    let operation = SYS_OPERATIONS
+        .iter()
+        .find(|&&(_, _, o1, n, m, o2)| (o1, n, m, o2) == (op1, crn, crm, op2));
+    if let Some(&(name, operation, ..)) = operation {
+        let register = !(operation.starts_with("all")
+            || operation.starts_with("vmall")
+            || name == "ic" && operation != "ivau");
+        if register {
+            return insn(name, vec![operation.to_string(), gpr(rt, true)]);
+        }
+        if rt == 31 {
+            return insn(name, vec![operation.to_string()]);
+        }
+    }
+    let mut operands = fields;
+    if rt != 31 {
+        operands.push(gpr(rt, true));
+    }
+    insn("sys", operands)
+}
+
+// system registers by op0, op1, CRn, CRm and op2
+const SYSTEM_REGISTERS: [(&str, [u32; 5]); 105] = [
+    ("MDCCSR_EL0", [2, 3, 0, 1, 0]),
+    ("DBGDTR_EL0", [2, 3, 0, 4, 0]),
+    ("MDSCR_EL1", [2, 0, 0, 2, 2]),
+    ("OSLAR_EL1", [2, 0, 1, 0, 4]),
+    ("OSDLR_EL1", [2, 0, 1, 3, 4]),
+    ("MIDR_EL1", [3, 0, 0, 0, 0]),
+    ("MPIDR_EL1", [3, 0, 0, 0, 5]),
+    ("REVIDR_EL1", [3, 0, 0, 0, 6]),
+    ("ID_AA64PFR0_EL1", [3, 0, 0, 4, 0]),
+    ("ID_AA64PFR1_EL1", [3, 0, 0, 4, 1]),
+    ("ID_AA64ZFR0_EL1", [3, 0, 0, 4, 4]),
+    ("ID_AA64DFR0_EL1", [3, 0, 0, 5, 0]),
+    ("ID_AA64DFR1_EL1", [3, 0, 0, 5, 1]),
+    ("ID_AA64ISAR0_EL1", [3, 0, 0, 6, 0]),
+    ("ID_AA64ISAR1_EL1", [3, 0, 0, 6, 1]),
+    ("ID_AA64MMFR0_EL1", [3, 0, 0, 7, 0]),
+    ("ID_AA64MMFR1_EL1", [3, 0, 0, 7, 1]),
+    ("ID_AA64MMFR2_EL1", [3, 0, 0, 7, 2]),
+    ("SCTLR_EL1", [3, 0, 1, 0, 0]),
+    ("ACTLR_EL1", [3, 0, 1, 0, 1]),
+    ("CPACR_EL1", [3, 0, 1, 0, 2]),
+    ("TTBR0_EL1", [3, 0, 2, 0, 0]),
+    ("TTBR1_EL1", [3, 0, 2, 0, 1]),
+    ("TCR_EL1", [3, 0, 2, 0, 2]),
+    ("APIAKeyLo_EL1", [3, 0, 2, 1, 0]),
+    ("APIAKeyHi_EL1", [3, 0, 2, 1, 1]),
+    ("APIBKeyLo_EL1", [3, 0, 2, 1, 2]),
+    ("APIBKeyHi_EL1", [3, 0, 2, 1, 3]),
+    ("APDAKeyLo_EL1", [3, 0, 2, 2, 0]),
+    ("APDAKeyHi_EL1", [3, 0, 2, 2, 1]),
+    ("APDBKeyLo_EL1", [3, 0, 2, 2, 2]),
+    ("APDBKeyHi_EL1", [3, 0, 2, 2, 3]),
+    ("APGAKeyLo_EL1", [3, 0, 2, 3, 0]),
+    ("APGAKeyHi_EL1", [3, 0, 2, 3, 1]),
+    ("SPSR_EL1", [3, 0, 4, 0, 0]),
+    ("ELR_EL1", [3, 0, 4, 0, 1]),
+    ("SP_EL0", [3, 0, 4, 1, 0]),
+    ("SPSel", [3, 0, 4, 2, 0]),
+    ("CurrentEL", [3, 0, 4, 2, 2]),
+    ("PAN", [3, 0, 4, 2, 3]),
+    ("UAO", [3, 0, 4, 2, 4]),
+    ("AFSR0_EL1", [3, 0, 5, 1, 0]),
+    ("AFSR1_EL1", [3, 0, 5, 1, 1]),
+    ("ESR_EL1", [3, 0, 5, 2, 0]),
+    ("FAR_EL1", [3, 0, 6, 0, 0]),
+    ("PAR_EL1", [3, 0, 7, 4, 0]),
+    ("MAIR_EL1", [3, 0, 10, 2, 0]),
+    ("AMAIR_EL1", [3, 0, 10, 3, 0]),
+    ("VBAR_EL1", [3, 0, 12, 0, 0]),
+    ("ISR_EL1", [3, 0, 12, 1, 0]),
+    ("CONTEXTIDR_EL1", [3, 0, 13, 0, 1]),
+    ("TPIDR_EL1", [3, 0, 13, 0, 4]),
+    ("CNTKCTL_EL1", [3, 0, 14, 1, 0]),
+    ("CCSIDR_EL1", [3, 1, 0, 0, 0]),
+    ("CLIDR_EL1", [3, 1, 0, 0, 1]),
+    ("CSSELR_EL1", [3, 2, 0, 0, 0]),
+    ("CTR_EL0", [3, 3, 0, 0, 1]),
+    ("DCZID_EL0", [3, 3, 0, 0, 7]),
+    ("RNDR", [3, 3, 2, 4, 0]),
+    ("RNDRRS", [3, 3, 2, 4, 1]),
+    ("NZCV", [3, 3, 4, 2, 0]),
+    ("DAIF", [3, 3, 4, 2, 1]),
+    ("DIT", [3, 3, 4, 2, 5]),
+    ("SSBS", [3, 3, 4, 2, 6]),
+    ("TCO", [3, 3, 4, 2, 7]),
+    ("FPCR", [3, 3, 4, 4, 0]),
+    ("FPSR", [3, 3, 4, 4, 1]),
+    ("DSPSR_EL0", [3, 3, 4, 5, 0]),
+    ("DLR_EL0", [3, 3, 4, 5, 1]),
+    ("PMCR_EL0", [3, 3, 9, 12, 0]),
+    ("PMCNTENSET_EL0", [3, 3, 9, 12, 1]),
+    ("PMCCNTR_EL0", [3, 3, 9, 13, 0]),
+    ("PMUSERENR_EL0", [3, 3, 9, 14, 0]),
+    ("TPIDR_EL0", [3, 3, 13, 0, 2]),
+    ("TPIDRRO_EL0", [3, 3, 13, 0, 3]),
+    ("CNTFRQ_EL0", [3, 3, 14, 0, 0]),
+    ("CNTPCT_EL0", [3, 3, 14, 0, 1]),
+    ("CNTVCT_EL0", [3, 3, 14, 0, 2]),
+    ("CNTP_TVAL_EL0", [3, 3, 14, 2, 0]),
+    ("CNTP_CTL_EL0", [3, 3, 14, 2, 1]),
+    ("CNTP_CVAL_EL0", [3, 3, 14, 2, 2]),
+    ("CNTV_TVAL_EL0", [3, 3, 14, 3, 0]),
+    ("CNTV_CTL_EL0", [3, 3, 14, 3, 1]),
+    ("CNTV_CVAL_EL0", [3, 3, 14, 3, 2]),
+    ("VPIDR_EL2", [3, 4, 0, 0, 0]),
+    ("VMPIDR_EL2", [3, 4, 0, 0, 5]),
+    ("SCTLR_EL2", [3, 4, 1, 0, 0]),
+    ("HCR_EL2", [3, 4, 1, 1, 0]),
+    ("MDCR_EL2", [3, 4, 1, 1, 1]),
+    ("CPTR_EL2", [3, 4, 1, 1, 2]),
+    ("TTBR0_EL2", [3, 4, 2, 0, 0]),
+    ("TCR_EL2", [3, 4, 2, 0, 2]),
+    ("VTTBR_EL2", [3, 4, 2, 1, 0]),
+    ("VTCR_EL2", [3, 4, 2, 1, 2]),
+    ("SPSR_EL2", [3, 4, 4, 0, 0]),
+    ("ELR_EL2", [3, 4, 4, 0, 1]),
+    ("SP_EL1", [3, 4, 4, 1, 0]),
+    ("ESR_EL2", [3, 4, 5, 2, 0]),
+    ("FAR_EL2", [3, 4, 6, 0, 0]),
+    ("MAIR_EL2", [3, 4, 10, 2, 0]),
+    ("VBAR_EL2", [3, 4, 12, 0, 0]),
+    ("TPIDR_EL2", [3, 4, 13, 0, 2]),
+    ("CNTVOFF_EL2", [3, 4, 14, 0, 3]),
+    ("CNTHCTL_EL2", [3, 4, 14, 1, 0]),
+    ("SCTLR_EL3", [3, 6, 1, 0, 0]),
+];
+
+// the name of a system register, or its encoding like S3_3_C15_C2_0
+fn system_register(op0: u32, op1: u32, crn: u32, crm: u32, op2: u32) -> String {
+    let fields = [op0, op1, crn, crm, op2];
+    match SYSTEM_REGISTERS.iter().find(|(_, f)| *f == fields) {
+        Some((name, _)) => name.to_string(),
+        None => format!("S{op0}_{op1}_C{crn}_C{crm}_{op2}"),
+    }
+}
+
+// loads and stores
+impl State {
+    fn load_store(&mut self) -> Decoded {
+        let (op0, op2, op3, op4) = (
+            self.bits(31, 28),
+            self.bits(24, 23),
+            self.bits(21, 16),
+            self.bits(11, 10),
+        );
+        let vector = self.bit(26);
+        if is(op0, "0x00") && vector {
+            return match op2 {
+                0b00 if op3 == 0 => self.structures(false),
+                0b01 if op3 & 0x20 == 0 => self.structures(true),
+                0b10 if op3 & 0x1f == 0 => self.structure(false),
+                0b11 => self.structure(true),
+                _ => None,
+            };
+        }
+        if is(op0, "xx00") && !vector && op2 & 2 == 0 {
+            self.exclusive()
+        } else if is(op0, "xx01") && op2 & 2 == 0 {
+            self.literal()
+        } else if is(op0, "xx01") && op3 & 0x20 == 0 && op4 == 0 && !vector {
+            self.release_consistent()
+        } else if is(op0, "xx10") {
+            self.pair(op2)
+        } else if is(op0, "xx11") && op2 & 2 == 0 && op3 & 0x20 == 0 {
+            self.unscaled(op4)
+        } else if is(op0, "xx11") && op2 & 2 == 0 {
+            match op4 {
+                0 => self.atomic(),
+                2 => self.register_offset(),
+                _ => self.authenticated_load(),
+            }
+        } else if is(op0, "xx11") {
+            let (name, register, scale) = self.transfer()?;
+            let offset = i64::from(self.bits(21, 10)) << scale;
+            let base = gpr_sp(self.rn(), true);
+            insn(name, vec![register, offset_address(&base, offset)])
+        } else {
+            None
+        }
+    }
+
+    // the mnemonic, register and access size (log2 of the bytes) of a load
+    // or store by its size, V and opc fields
+    fn transfer(&self) -> Option<(&'static str, String, u32)> {
+        let (size, opc, rt) = (self.bits(31, 30), self.bits(23, 22), self.rd());
+        if self.bit(26) {
+            let scale = (opc & 2) << 1 | size;
+            let name = if opc & 1 == 1 { "ldr" } else { "str" };
+            let kind = SCALARS.get(scale as usize)?;
+            return Some((name, fpr(*kind, rt), scale));
+        }
+        let (name, wide) = match (size, opc) {
+            (0, 0) => ("strb", false),
+            (0, 1) => ("ldrb", false),
+            (0, 2) => ("ldrsb", true),
+            (0, 3) => ("ldrsb", false),
+            (1, 0) => ("strh", false),
+            (1, 1) => ("ldrh", false),
+            (1, 2) => ("ldrsh", true),
+            (1, 3) => ("ldrsh", false),
+            (2, 0) => ("str", false),
+            (2, 1) => ("ldr", false),
+            (2, 2) => ("ldrsw", true),
+            (3, 0) => ("str", true),
+            (3, 1) => ("ldr", true),
+            (3, 2) => return Some(("prfm", prefetch(rt), 3)),
+            _ => return None,
+        };
+        Some((name, gpr(rt, wide), size))
+    }
+
+    // unscaled (ldur), post-indexed, unprivileged (ldtr) and pre-indexed
+    // loads and stores, all with a signed 9-bit offset
+    fn unscaled(&mut self, op4: u32) -> Decoded {
+        let (name, register, _) = self.transfer()?;
+        let offset = signed(self.bits(20, 12), 9);
+        let base = gpr_sp(self.rn(), true);
+        let prefetch = name == "prfm";
+        match op4 {
+            0 if prefetch => insn("prfum", vec![register, offset_address(&base, offset)]),
+            0 => {
+                let name = format!("{}u{}", &name[..2], &name[2..]);
+                insn(name, vec![register, offset_address(&base, offset)])
+            }
+            1 if !prefetch => insn(name, vec![register, format!("[{base}], #{offset}")]),
+            2 if !prefetch && !self.bit(26) => {
+                let name = format!("{}t{}", &name[..2], &name[2..]);
+                insn(name, vec![register, offset_address(&base, offset)])
+            }
+            3 if !prefetch => insn(name, vec![register, format!("[{base}, #{offset}]!")]),
+            _ => None,
+        }
+    }
+
+    fn register_offset(&mut self) -> Decoded {
+        let (name, register, scale) = self.transfer()?;
+        let (option, shifted) = (self.bits(15, 13), self.bit(12));
+        if option & 2 == 0 {
+            return None;
+        }
+        let mut address = format!(
+            "[{}, {}",
+            gpr_sp(self.rn(), true),
+            gpr(self.rm(), option & 1 == 1)
+        );
+        let amount = if shifted { scale } else { 0 };
+        if option != 3 {
+            address += &format!(", {}", EXTENDS[option as usize]);
+            if shifted {
+                address += &format!(" #{amount}");
+            }
+        } else if shifted {
+            address += &format!(", lsl #{amount}");
+        }
+        address.push(']');
+        insn(name, vec![register, address])
+    }
+
+    fn pair(&mut self, op2: u32) -> Decoded {
+        let (opc, vector, load) = (self.bits(31, 30), self.bit(26), self.bit(22));
+        let (kind, scale) = match (vector, opc) {
+            (false, 0) => ('w', 2),
+            (false, 1) if load && op2 != 0 => ('x', 2),
+            (false, 2) => ('x', 3),
+            (true, 0..=2) => (SCALARS[opc as usize + 2], opc + 2),
+            _ => return None,
+        };
+        let name = match (op2, load) {
+            (0, false) => "stnp",
+            (0, true) => "ldnp",
+            (_, false) => "stp",
+            (_, true) if kind == 'x' && scale == 2 => "ldpsw",
+            (_, true) => "ldp",
+        };
+        let register = |n| match kind {
+            'w' => gpr(n, false),
+            'x' => gpr(n, true),
+            _ => fpr(kind, n),
+        };
+        let offset = signed(self.bits(21, 15), 7) << scale;
+        let base = gpr_sp(self.rn(), true);
+        let address = match op2 {
+            1 => format!("[{base}], #{offset}"),
+            3 => format!("[{base}, #{offset}]!"),
+            _ => offset_address(&base, offset),
+        };
+        insn(
+            name,
+            vec![register(self.rd()), register(self.bits(14, 10)), address],
+        )
+    }
+
+    // load and store exclusive, load-acquire and store-release, and
+    // compare and swap
+    fn exclusive(&mut self) -> Decoded {
+        let (size, o2, load, o1, o0) = (
+            self.bits(31, 30),
+            self.bit(23),
+            self.bit(22),
+            self.bit(21),
+            self.bit(15),
+        );
+        let (rs, rt2, rt) = (self.rm(), self.bits(14, 10), self.rd());
+        let wide = size == 3;
+        let suffix = ["b", "h", "", ""][size as usize];
+        let base = format!("[{}]", gpr_sp(self.rn(), true));
+        let which = usize::from(load) << 1 | usize::from(o0);
+        let acquire = if load { "a" } else { "" };
+        let release = if o0 { "l" } else { "" };
+        match (o2, o1) {
+            (false, false) => {
+                let name = format!("{}{suffix}", ["stxr", "stlxr", "ldxr", "ldaxr"][which]);
+                if load {
+                    insn(name, vec![gpr(rt, wide), base])
+                } else {
+                    insn(name, vec![gpr(rs, false), gpr(rt, wide), base])
+                }
+            }
+            (false, true) if size >= 2 => {
+                let name = ["stxp", "stlxp", "ldxp", "ldaxp"][which];
+                let mut operands = vec![gpr(rt, wide), gpr(rt2, wide), base];
+                if !load {
+                    operands.insert(0, gpr(rs, false));
+                }
+                insn(name, operands)
+            }
+            (false, true) => {
+                if rt2 != 31 || rs % 2 == 1 || rt % 2 == 1 {
+                    return None;
+                }
+                let wide = size == 1;
+                insn(
+                    format!("casp{acquire}{release}"),
+                    vec![
+                        gpr(rs, wide),
+                        gpr(rs + 1, wide),
+                        gpr(rt, wide),
+                        gpr(rt + 1, wide),
+                        base,
+                    ],
+                )
+            }
+            (true, false) => {
+                let name = format!("{}{suffix}", ["stllr", "stlr", "ldlar", "ldar"][which]);
+                insn(name, vec![gpr(rt, wide), base])
+            }
+            (true, true) if rt2 == 31 => insn(
+                format!("cas{acquire}{release}{suffix}"),
+                vec![gpr(rs, wide), gpr(rt, wide), base],
+            ),
+            _ => None,
+        }
+    }
+
+    fn literal(&mut self) -> Decoded {
+        let (opc, rt) = (self.bits(31, 30), self.rd());
+        let (name, register) = match (self.bit(26), opc) {
+            (false, 0) => ("ldr", gpr(rt, false)),
+            (false, 1) => ("ldr", gpr(rt, true)),
+            (false, 2) => ("ldrsw", gpr(rt, true)),
+            (false, _) => ("prfm", prefetch(rt)),
+            (true, 0..=2) => ("ldr", fpr(SCALARS[opc as usize + 2], rt)),
+            _ => return None,
+        };
+        let target = self.relative(self.bits(23, 5), 19);
+        self.reference = Some(target);
+        insn(name, vec![register, address(target)])
+    }
+
+    // ldapur and stlur, the unscaled load-acquire and store-release
+    fn release_consistent(&mut self) -> Decoded {
+        let (name, register, _) = self.transfer()?;
+        let name = match name {
+            "prfm" => return None,
+            _ if name.starts_with("st") => format!("stlur{}", &name[3..]),
+            _ => format!("ldapur{}", &name[3..]),
+        };
+        let offset = signed(self.bits(20, 12), 9);
+        insn(
+            name,
+            vec![register, offset_address(&gpr_sp(self.rn(), true), offset)],
+        )
+    }
+
+    // the LSE atomic memory operations
+    fn atomic(&mut self) -> Decoded {
+        if self.bit(26) {
+            return None;
+        }
+        let (size, a, r) = (self.bits(31, 30), self.bit(23), self.bit(22));
+        let (rs, o3, opc, rt) = (self.rm(), self.bit(15), self.bits(14, 12), self.rd());
+        let wide = size == 3;
+        let suffix = ["b", "h", "", ""][size as usize];
+        let base = format!("[{}]", gpr_sp(self.rn(), true));
+        let acquire = if a { "a" } else { "" };
+        let release = if r { "l" } else { "" };
+        if o3 {
+            return match opc {
+                0 => insn(
+                    format!("swp{acquire}{release}{suffix}"),
+                    vec![gpr(rs, wide), gpr(rt, wide), base],
+                ),
+                4 if a && !r && rs == 31 => {
+                    insn(format!("ldapr{suffix}"), vec![gpr(rt, wide), base])
+                }
+                _ => None,
+            };
+        }
+        let operation = ["add", "clr", "eor", "set", "smax", "smin", "umax", "umin"][opc as usize];
+        // without acquire and a result, the load is a store
+        if !a && rt == 31 {
+            return insn(
+                format!("st{operation}{release}{suffix}"),
+                vec![gpr(rs, wide), base],
+            );
+        }
+        insn(
+            format!("ld{operation}{acquire}{release}{suffix}"),
+            vec![gpr(rs, wide), gpr(rt, wide), base],
+        )
+    }
+
+    // ldraa and ldrab, loads through an authenticated pointer
+    fn authenticated_load(&mut self) -> Decoded {
+        if self.bits(31, 30) != 3 || self.bit(26) {
+            return None;
+        }
+        let offset = signed(self.bits(22, 22) << 9 | self.bits(20, 12), 10) << 3;
+        let name = if self.bit(23) { "ldrab" } else { "ldraa" };
+        let base = gpr_sp(self.rn(), true);
+        let address = if self.bit(11) {
+            format!("[{base}, #{offset}]!")
+        } else {
+            offset_address(&base, offset)
+        };
+        insn(name, vec![gpr(self.rd(), true), address])
+    }
+
+    // the post-index of a structure load or store: a register, or the
+    // number of bytes transferred for register 31
+    fn post_index(&self, bytes: u32) -> String {
+        match self.rm() {
+            31 => imm(bytes),
+            rm => gpr(rm, true),
+        }
+    }
+
+    // ld1-ld4 and st1-st4 of whole registers
+    fn structures(&mut self, post: bool) -> Decoded {
+        let (q, load, opcode, size) = (
+            self.bit(30),
+            self.bit(22),
+            self.bits(15, 12),
+            self.bits(11, 10),
+        );
+        let (count, elements) = match opcode {
+            0b0000 => (4, 4),
+            0b0010 => (4, 1),
+            0b0100 => (3, 3),
+            0b0110 => (3, 1),
+            0b0111 => (1, 1),
+            0b1000 => (2, 2),
+            0b1010 => (2, 1),
+            _ => return None,
+        };
+        if size == 3 && !q && elements != 1 {
+            return None;
+        }
+        let arrangement = ARRANGEMENTS[(size << 1 | u32::from(q)) as usize];
+        let name = format!("{}{elements}", if load { "ld" } else { "st" });
+        let mut operands = vec![
+            list(self.rd(), count, arrangement),
+            format!("[{}]", gpr_sp(self.rn(), true)),
+        ];
+        if post {
+            operands.push(self.post_index(count * if q { 16 } else { 8 }));
+        }
+        insn(name, operands)
+    }
+
+    // ld1-ld4 and st1-st4 of one element, and ld1r-ld4r
+    fn structure(&mut self, post: bool) -> Decoded {
+        let (q, load, r, opcode) = (
+            u32::from(self.bit(30)),
+            self.bit(22),
+            self.bits(21, 21),
+            self.bits(15, 13),
+        );
+        let (s, size) = (self.bits(12, 12), self.bits(11, 10));
+        let count = ((opcode & 1) << 1 | r) + 1;
+        let base = format!("[{}]", gpr_sp(self.rn(), true));
+        let (kind, index, scale) = match opcode >> 1 {
+            0 => ('b', q << 3 | s << 2 | size, 0),
+            1 if size & 1 == 0 => ('h', q << 2 | s << 1 | size >> 1, 1),
+            2 if size == 0 => ('s', q << 1 | s, 2),
+            2 if size == 1 && s == 0 => ('d', q, 3),
+            3 if load && s == 0 => {
+                let arrangement = ARRANGEMENTS[(size << 1 | q) as usize];
+                let mut operands = vec![list(self.rd(), count, arrangement), base];
+                if post {
+                    operands.push(self.post_index(count << size));
+                }
+                return insn(format!("ld{count}r"), operands);
+            }
+            _ => return None,
+        };
+        let registers = list(self.rd(), count, &kind.to_string());
+        let mut operands = vec![format!("{registers}[{index}]"), base];
+        if post {
+            operands.push(self.post_index(count << scale));
+        }
+        insn(
+            format!("{}{count}", if load { "ld" } else { "st" }),
+            operands,
+        )
+    }
+}
+
+// data processing with registers
+impl State {
+    fn register(&mut self) -> Decoded {
+        let op2 = self.bits(24, 21);
+        if !self.bit(28) {
+            return if op2 & 8 == 0 {
+                self.logical_shifted()
+            } else if op2 & 1 == 0 {
+                self.add_sub_shifted()
+            } else {
+                self.add_sub_extended()
+            };
+        }
+        match op2 {
+            0b0110 if self.bit(30) => self.one_source(),
+            0b0110 => self.two_source(),
+            0b0000 if self.bits(15, 10) == 0 => self.carry(),
+            0b0000 => self.flags(),
+            0b0010 => self.conditional_compare(),
+            0b0100 => self.conditional_select(),
+            _ if op2 & 8 != 0 => self.three_source(),
+            _ => None,
+        }
+    }
+
+    // the shifted register operand: Rm with an optional shift
+    fn shifted(&self, sf: bool, shift: u32) -> Vec<String> {
+        let amount = self.bits(15, 10);
+        let mut operands = vec![gpr(self.rm(), sf)];
+        if shift != 0 || amount != 0 {
+            operands.push(format!("{} #{amount}", SHIFTS[shift as usize]));
+        }
+        operands
+    }
+
+    fn logical_shifted(&mut self) -> Decoded {
+        let (sf, opc, shift, n) = (
+            self.sf(),
+            self.bits(30, 29),
+            self.bits(23, 22),
+            self.bit(21),
+        );
+        if !sf && self.bit(15) {
+            return None;
+        }
+        let (rd, rn) = (self.rd(), self.rn());
+        let name = ["and", "bic", "orr", "orn", "eor", "eon", "ands", "bics"]
+            [(opc as usize) << 1 | usize::from(n)];
+        let mut operands = self.shifted(sf, shift);
+        match name {
+            "orr" if rn == 31 && operands.len() == 1 => {
+                insn("mov", vec![gpr(rd, sf), operands.remove(0)])
+            }
+            "orn" if rn == 31 => {
+                operands.insert(0, gpr(rd, sf));
+                insn("mvn", operands)
+            }
+            "ands" if rd == 31 => {
+                operands.insert(0, gpr(rn, sf));
+                insn("tst", operands)
+            }
+            _ => {
+                operands.splice(0..0, [gpr(rd, sf), gpr(rn, sf)]);
+                insn(name, operands)
+            }
+        }
+    }
+
+    fn add_sub_shifted(&mut self) -> Decoded {
+        let (sf, sub, flags, shift) = (self.sf(), self.bit(30), self.bit(29), self.bits(23, 22));
+        if shift == 3 || (!sf && self.bit(15)) {
+            return None;
+        }
+        let (rd, rn) = (self.rd(), self.rn());
+        let mut operands = self.shifted(sf, shift);
+        if flags && rd == 31 {
+            operands.insert(0, gpr(rn, sf));
+            return insn(if sub { "cmp" } else { "cmn" }, operands);
+        }
+        if sub && rn == 31 {
+            operands.insert(0, gpr(rd, sf));
+            return insn(if flags { "negs" } else { "neg" }, operands);
+        }
+        let name = ["add", "adds", "sub", "subs"][usize::from(sub) << 1 | usize::from(flags)];
+        operands.splice(0..0, [gpr(rd, sf), gpr(rn, sf)]);
+        insn(name, operands)
+    }
+
+    fn add_sub_extended(&mut self) -> Decoded {
+        let (sf, sub, flags) = (self.sf(), self.bit(30), self.bit(29));
+        let (option, amount) = (self.bits(15, 13), self.bits(12, 10));
+        if self.bits(23, 22) != 0 || amount > 4 {
+            return None;
+        }
+        let (rd, rn) = (self.rd(), self.rn());
+        let rm = gpr(self.rm(), sf && option & 3 == 3);
+        // with the stack pointer, the extension that doesn't change the
+        // register is shown as lsl
+        let lsl = (rn == 31 || (!flags && rd == 31)) && option == if sf { 3 } else { 2 };
+        let mut operands = vec![gpr_sp(rn, sf), rm];
+        if lsl {
+            if amount != 0 {
+                operands.push(format!("lsl #{amount}"));
+            }
+        } else if amount != 0 {
+            operands.push(format!("{} #{amount}", EXTENDS[option as usize]));
+        } else {
+            operands.push(EXTENDS[option as usize].to_string());
+        }
+        if flags && rd == 31 {
+            return insn(if sub { "cmp" } else { "cmn" }, operands);
+        }
+        let name = ["add", "adds", "sub", "subs"][usize::from(sub) << 1 | usize::from(flags)];
+        operands.insert(0, if flags { gpr(rd, sf) } else { gpr_sp(rd, sf) });
+        insn(name, operands)
+    }
+
+    // adc and sbc
+    fn carry(&mut self) -> Decoded {
+        let (sf, sub, flags) = (self.sf(), self.bit(30), self.bit(29));
+        let (rd, rn, rm) = (gpr(self.rd(), sf), self.rn(), gpr(self.rm(), sf));
+        if sub && rn == 31 {
+            return insn(if flags { "ngcs" } else { "ngc" }, vec![rd, rm]);
+        }
+        let name = ["adc", "adcs", "sbc", "sbcs"][usize::from(sub) << 1 | usize::from(flags)];
+        insn(name, vec![rd, gpr(rn, sf), rm])
+    }
+
+    // rmif, setf8 and setf16, which set the flags from a register
+    fn flags(&mut self) -> Decoded {
+        if self.bits(30, 29) != 1 || self.bit(4) {
+            return None;
+        }
+        let rn = self.rn();
+        if self.sf() && self.bits(14, 10) == 1 {
+            let rotation = imm(self.bits(20, 15));
+            return insn("rmif", vec![gpr(rn, true), rotation, imm(self.bits(3, 0))]);
+        }
+        if !self.sf() && self.bits(20, 15) == 0 && self.bits(13, 10) == 2 && self.bits(3, 0) == 13 {
+            let name = if self.bit(14) { "setf16" } else { "setf8" };
+            return insn(name, vec![gpr(rn, false)]);
+        }
+        None
+    }
+
+    fn conditional_compare(&mut self) -> Decoded {
+        let sf = self.sf();
+        if !self.bit(29) || self.bit(10) || self.bit(4) {
+            return None;
+        }
+        let name = if self.bit(30) { "ccmp" } else { "ccmn" };
+        let second = if self.bit(11) {
+            imm(self.rm())
+        } else {
+            gpr(self.rm(), sf)
+        };
+        let condition = CONDITIONS[self.bits(15, 12) as usize].to_string();
+        insn(
+            name,
+            vec![gpr(self.rn(), sf), second, imm(self.bits(3, 0)), condition],
+        )
+    }
+
+    fn conditional_select(&mut self) -> Decoded {
+        let (sf, op, op2) = (self.sf(), self.bit(30), self.bits(11, 10));
+        if self.bit(29) || op2 > 1 {
+            return None;
+        }
+        let (rd, rn, rm) = (self.rd(), self.rn(), self.rm());
+        let condition = self.bits(15, 12) as usize;
+        // the aliases test the inverse condition, and don't exist for al
+        let inverse = CONDITIONS[condition ^ 1].to_string();
+        let alias = condition < 14 && rn == rm;
+        let name = match (op, op2) {
+            (false, 0) => "csel",
+            (false, _) if alias && rn == 31 => return insn("cset", vec![gpr(rd, sf), inverse]),
+            (false, _) if alias => return insn("cinc", vec![gpr(rd, sf), gpr(rn, sf), inverse]),
+            (false, _) => "csinc",
+            (true, 0) if alias && rn == 31 => return insn("csetm", vec![gpr(rd, sf), inverse]),
+            (true, 0) if alias => return insn("cinv", vec![gpr(rd, sf), gpr(rn, sf), inverse]),
+            (true, 0) => "csinv",
+            (true, _) if alias => return insn("cneg", vec![gpr(rd, sf), gpr(rn, sf), inverse]),
+            (true, _) => "csneg",
+        };
+        insn(
+            name,
+            vec![
+                gpr(rd, sf),
+                gpr(rn, sf),
+                gpr(rm, sf),
+                CONDITIONS[condition].to_string(),
+            ],
+        )
+    }
+
+    // multiply and multiply-add
+    fn three_source(&mut self) -> Decoded {
+        let (sf, op31, o0) = (self.sf(), self.bits(23, 21), self.bit(15));
+        if self.bits(30, 29) != 0 {
+            return None;
+        }
+        let (rd, rn, rm, ra) = (self.rd(), self.rn(), self.rm(), self.bits(14, 10));
+        let (name, alias) = match (op31, o0) {
+            (0, false) => ("madd", "mul"),
+            (0, true) => ("msub", "mneg"),
+            (1, false) if sf => ("smaddl", "smull"),
+            (1, true) if sf => ("smsubl", "smnegl"),
+            (2, false) if sf => {
+                return insn("smulh", vec![gpr(rd, true), gpr(rn, true), gpr(rm, true)])
+            }
+            (5, false) if sf => ("umaddl", "umull"),
+            (5, true) if sf => ("umsubl", "umnegl"),
+            (6, false) if sf => {
+                return insn("umulh", vec![gpr(rd, true), gpr(rn, true), gpr(rm, true)])
+            }
+            _ => return None,
+        };
+        // the long forms multiply w registers into an x register
+        let narrow = op31 == 0 && sf;
+        let mut operands = vec![gpr(rd, sf), gpr(rn, narrow), gpr(rm, narrow)];
+        if ra == 31 {
+            return insn(alias, operands);
+        }
+        operands.push(gpr(ra, sf));
+        insn(name, operands)
+    }
+
+    fn two_source(&mut self) -> Decoded {
+        let (sf, opcode) = (self.sf(), self.bits(15, 10));
+        if self.bit(29) {
+            return None;
+        }
+        let (rd, rn, rm) = (self.rd(), self.rn(), self.rm());
+        let name = match opcode {
+            2 => "udiv",
+            3 => "sdiv",
+            8 => "lsl",
+            9 => "lsr",
+            10 => "asr",
+            11 => "ror",
+            12 if sf => {
+                return insn(
+                    "pacga",
+                    vec![gpr(rd, true), gpr(rn, true), gpr_sp(rm, true)],
+                );
+            }
+            16..=23 => {
+                let size = opcode & 3;
+                if sf != (size == 3) {
+                    return None;
+                }
+                let c = if opcode & 4 != 0 { "c" } else { "" };
+                let name = format!("crc32{c}{}", ["b", "h", "w", "x"][size as usize]);
+                return insn(name, vec![gpr(rd, false), gpr(rn, false), gpr(rm, sf)]);
+            }
+            _ => return None,
+        };
+        insn(name, vec![gpr(rd, sf), gpr(rn, sf), gpr(rm, sf)])
+    }
+
+    fn one_source(&mut self) -> Decoded {
+        let (sf, opcode2, opcode) = (self.sf(), self.bits(20, 16), self.bits(15, 10));
+        if self.bit(29) {
+            return None;
+        }
+        let (rd, rn) = (self.rd(), self.rn());
+        if opcode2 == 1 && sf {
+            let keys = ["ia", "ib", "da", "db"];
+            return match opcode {
+                0..=7 => {
+                    let operation = if opcode < 4 { "pac" } else { "aut" };
+                    let name = format!("{operation}{}", keys[opcode as usize & 3]);
+                    insn(name, vec![gpr(rd, true), gpr_sp(rn, true)])
+                }
+                8..=15 if rn == 31 => {
+                    let operation = if opcode < 12 { "pac" } else { "aut" };
+                    let key = keys[opcode as usize & 3];
+                    let name = format!("{operation}{}z{}", &key[..1], &key[1..]);
+                    insn(name, vec![gpr(rd, true)])
+                }
+                16 if rn == 31 => insn("xpaci", vec![gpr(rd, true)]),
+                17 if rn == 31 => insn("xpacd", vec![gpr(rd, true)]),
+                _ => None,
+            };
+        }
+        if opcode2 != 0 {
+            return None;
+        }
+        let name = match (opcode, sf) {
+            (0, _) => "rbit",
+            (1, _) => "rev16",
+            (2, false) => "rev",
+            (2, true) => "rev32",
+            (3, true) => "rev",
+            (4, _) => "clz",
+            (5, _) => "cls",
+            _ => return None,
+        };
+        insn(name, vec![gpr(rd, sf), gpr(rn, sf)])
+    }
+}
+
+// a handler of the SIMD and floating-point encodings
+type Group = fn(&mut State) -> Decoded;
+
+// The SIMD and floating-point encoding groups by their op0, op1, op2 and
+// op3 fields, the first match wins
+const SIMD_FP: [(&str, &str, &str, &str, Group); 40] = [
+    ("0100", "0x", "x101", "00xxxxx10", State::aes),
+    ("0101", "0x", "x0xx", "xxx0xxx00", State::sha_three),
+    ("0101", "0x", "x101", "00xxxxx10", State::sha_two),
+    ("01x1", "00", "00xx", "xxx0xxxx1", State::scalar_copy),
+    (
+        "01x1",
+        "0x",
+        "10xx",
+        "xxx00xxx1",
+        State::scalar_three_same_fp16,
+    ),
+    (
+        "01x1",
+        "0x",
+        "1111",
+        "00xxxxx10",
+        State::scalar_two_misc_fp16,
+    ),
+    (
+        "01x1",
+        "0x",
+        "x0xx",
+        "xxx1xxxx1",
+        State::scalar_three_same_extra,
+    ),
+    ("01x1", "0x", "x100", "00xxxxx10", State::scalar_two_misc),
+    ("01x1", "0x", "x110", "00xxxxx10", State::scalar_pairwise),
+    (
+        "01x1",
+        "0x",
+        "x1xx",
+        "xxxxxxx00",
+        State::scalar_three_different,
+    ),
+    ("01x1", "0x", "x1xx", "xxxxxxxx1", State::scalar_three_same),
+    ("01x1", "10", "xxxx", "xxxxxxxx1", State::scalar_shift),
+    ("01x1", "1x", "xxxx", "xxxxxxxx0", State::scalar_indexed),
+    ("0x00", "0x", "x0xx", "xxx0xxx00", State::table),
+    ("0x00", "0x", "x0xx", "xxx0xxx10", State::permute),
+    ("0x10", "0x", "x0xx", "xxx0xxxx0", State::extract_vector),
+    ("0xx0", "00", "00xx", "xxx0xxxx1", State::copy),
+    ("0xx0", "0x", "10xx", "xxx00xxx1", State::three_same_fp16),
+    ("0xx0", "0x", "1111", "00xxxxx10", State::two_misc_fp16),
+    ("0xx0", "0x", "x0xx", "xxx1xxxx1", State::three_same_extra),
+    ("0xx0", "0x", "x100", "00xxxxx10", State::two_misc),
+    ("0xx0", "0x", "x110", "00xxxxx10", State::across_lanes),
+    ("0xx0", "0x", "x1xx", "xxxxxxx00", State::three_different),
+    ("0xx0", "0x", "x1xx", "xxxxxxxx1", State::three_same),
+    ("0xx0", "10", "0000", "xxxxxxxx1", State::modified_immediate),
+    ("0xx0", "10", "xxxx", "xxxxxxxx1", State::shift),
+    ("0xx0", "1x", "xxxx", "xxxxxxxx0", State::indexed),
+    ("1100", "00", "11xx", "xxx1x00xx", State::sha512_three),
+    ("1100", "00", "xxxx", "xxx0xxxxx", State::four_register),
+    ("1100", "01", "00xx", "xxxxxxxxx", State::xar),
+    ("1100", "01", "1000", "0001000xx", State::sha512_two),
+    ("x0x1", "0x", "x0xx", "xxxxxxxxx", State::fixed_conversion),
+    ("x0x1", "0x", "x1xx", "xxx000000", State::integer_conversion),
+    ("x0x1", "0x", "x1xx", "xxxx10000", State::fp_one_source),
+    ("x0x1", "0x", "x1xx", "xxxxx1000", State::fp_compare),
+    ("x0x1", "0x", "x1xx", "xxxxxx100", State::fp_move_immediate),
+    (
+        "x0x1",
+        "0x",
+        "x1xx",
+        "xxxxxxx01",
+        State::fp_conditional_compare,
+    ),
+    ("x0x1", "0x", "x1xx", "xxxxxxx10", State::fp_two_source),
+    (
+        "x0x1",
+        "0x",
+        "x1xx",
+        "xxxxxxx11",
+        State::fp_conditional_select,
+    ),
+    ("x0x1", "1x", "xxxx", "xxxxxxxxx", State::fp_three_source),
+];
+
+// the floating-point three same instructions by U, a (size<1>) and the
+// low bits of the opcode
+const FP_THREE_SAME: [[[&str; 8]; 2]; 2] = [
+    [
+        [
+            "fmaxnm", "fmla", "fadd", "fmulx", "fcmeq", "", "fmax", "frecps",
+        ],
+        ["fminnm", "fmls", "fsub", "", "", "", "fmin", "frsqrts"],
+    ],
+    [
+        [
+            "fmaxnmp", "", "faddp", "fmul", "fcmge", "facge", "fmaxp", "fdiv",
+        ],
+        ["fminnmp", "", "fabd", "", "fcmgt", "facgt", "fminp", ""],
+    ],
+];
+
+// the integer three same instructions by U and opcode, but for the logical
+// ones (opcode 3)
+const INTEGER_THREE_SAME: [[&str; 24]; 2] = [
+    [
+        "shadd", "sqadd", "srhadd", "", "shsub", "sqsub", "cmgt", "cmge", "sshl", "sqshl", "srshl",
+        "sqrshl", "smax", "smin", "sabd", "saba", "add", "cmtst", "mla", "mul", "smaxp", "sminp",
+        "sqdmulh", "addp",
+    ],
+    [
+        "uhadd", "uqadd", "urhadd", "", "uhsub", "uqsub", "cmhi", "cmhs", "ushl", "uqshl", "urshl",
+        "uqrshl", "umax", "umin", "uabd", "uaba", "sub", "cmeq", "mls", "pmul", "umaxp", "uminp",
+        "sqrdmulh", "",
+    ],
+];
+
+// the three different instructions by U and opcode
+const THREE_DIFFERENT: [[&str; 15]; 2] = [
+    [
+        "saddl", "saddw", "ssubl", "ssubw", "addhn", "sabal", "subhn", "sabdl", "smlal", "sqdmlal",
+        "smlsl", "sqdmlsl", "smull", "sqdmull", "pmull",
+    ],
+    [
+        "uaddl", "uaddw", "usubl", "usubw", "raddhn", "uabal", "rsubhn", "uabdl", "umlal", "",
+        "umlsl", "", "umull", "", "",
+    ],
+];
+
+// the integer two-register miscellaneous instructions by U and opcode
+const INTEGER_TWO_MISC: [[&str; 21]; 2] = [
+    [
+        "rev64", "rev16", "saddlp", "suqadd", "cls", "cnt", "sadalp", "sqabs", "cmgt", "cmeq",
+        "cmlt", "abs", "", "", "", "", "", "", "xtn", "", "sqxtn",
+    ],
+    [
+        "rev32", "", "uaddlp", "usqadd", "clz", "", "uadalp", "sqneg", "cmge", "cmle", "", "neg",
+        "", "", "", "", "", "", "sqxtun", "shll", "uqxtn",
+    ],
+];
+
+// the floating-point two-register miscellaneous instructions by U, a
+// (size<1>) and opcode; fcvtn, fcvtl and fcvtxn are formatted apart
+fn fp_two_misc(u: bool, a: bool, opcode: u32) -> Option<&'static str> {
+    let name = match (u, a, opcode) {
+        (false, true, 0x0c) => "fcmgt",
+        (false, true, 0x0d) => "fcmeq",
+        (false, true, 0x0e) => "fcmlt",
+        (false, true, 0x0f) => "fabs",
+        (false, false, 0x18) => "frintn",
+        (false, true, 0x18) => "frintp",
+        (false, false, 0x19) => "frintm",
+        (false, true, 0x19) => "frintz",
+        (false, false, 0x1a) => "fcvtns",
+        (false, true, 0x1a) => "fcvtps",
+        (false, false, 0x1b) => "fcvtms",
+        (false, true, 0x1b) => "fcvtzs",
+        (false, false, 0x1c) => "fcvtas",
+        (false, true, 0x1c) => "urecpe",
+        (false, false, 0x1d) => "scvtf",
+        (false, true, 0x1d) => "frecpe",
+        (false, false, 0x1e) => "frint32z",
+        (false, false, 0x1f) => "frint64z",
+        (false, true, 0x1f) => "frecpx",
+        (true, true, 0x0c) => "fcmge",
+        (true, true, 0x0d) => "fcmle",
+        (true, true, 0x0f) => "fneg",
+        (true, false, 0x18) => "frinta",
+        (true, false, 0x19) => "frintx",
+        (true, true, 0x19) => "frinti",
+        (true, false, 0x1a) => "fcvtnu",
+        (true, true, 0x1a) => "fcvtpu",
+        (true, false, 0x1b) => "fcvtmu",
+        (true, true, 0x1b) => "fcvtzu",
+        (true, false, 0x1c) => "fcvtau",
+        (true, true, 0x1c) => "ursqrte",
+        (true, false, 0x1d) => "ucvtf",
+        (true, true, 0x1d) => "frsqrte",
+        (true, false, 0x1e) => "frint32x",
+        (true, false, 0x1f) => "frint64x",
+        (true, true, 0x1f) => "fsqrt",
+        _ => return None,
+    };
+    Some(name)
+}
+
+// the name of a vector instruction on the upper half of its registers
+fn upper(name: &str, q: bool) -> String {
+    if q {
+        format!("{name}2")
+    } else {
+        name.to_string()
+    }
+}
+
+impl State {
+    fn simd_fp(&mut self) -> Decoded {
+        let (op0, op1, op2, op3) = (
+            self.bits(31, 28),
+            self.bits(24, 23),
+            self.bits(22, 19),
+            self.bits(18, 10),
+        );
+        let (.., group) = SIMD_FP
+            .iter()
+            .find(|(p0, p1, p2, p3, _)| is(op0, p0) && is(op1, p1) && is(op2, p2) && is(op3, p3))?;
+        group(self)
+    }
+
+    fn q(&self) -> bool {
+        self.bit(30)
+    }
+
+    fn u(&self) -> bool {
+        self.bit(29)
+    }
+
+    fn size(&self) -> u32 {
+        self.bits(23, 22)
+    }
+
+    // the vector arrangement of the size and Q fields
+    fn arrangement(&self) -> &'static str {
+        ARRANGEMENTS[(self.size() << 1 | u32::from(self.q())) as usize]
+    }
+
+    fn aes(&mut self) -> Decoded {
+        if self.size() != 0 {
+            return None;
+        }
+        let name = match self.bits(16, 12) {
+            4 => "aese",
+            5 => "aesd",
+            6 => "aesmc",
+            7 => "aesimc",
+            _ => return None,
+        };
+        insn(name, vec![vreg(self.rd(), "16b"), vreg(self.rn(), "16b")])
+    }
+
+    fn sha_three(&mut self) -> Decoded {
+        if self.size() != 0 {
+            return None;
+        }
+        let (rd, rn, rm) = (self.rd(), self.rn(), self.rm());
+        let name = match self.bits(14, 12) {
+            0 => "sha1c",
+            1 => "sha1p",
+            2 => "sha1m",
+            3 => return insn("sha1su0", three(rd, rn, rm, "4s")),
+            4 => "sha256h",
+            5 => "sha256h2",
+            6 => return insn("sha256su1", three(rd, rn, rm, "4s")),
+            _ => return None,
+        };
+        let second = if name.starts_with("sha1") {
+            fpr('s', rn)
+        } else {
+            fpr('q', rn)
+        };
+        insn(name, vec![fpr('q', rd), second, vreg(rm, "4s")])
+    }
+
+    fn sha_two(&mut self) -> Decoded {
+        if self.size() != 0 {
+            return None;
+        }
+        let (rd, rn) = (self.rd(), self.rn());
+        match self.bits(16, 12) {
+            0 => insn("sha1h", vec![fpr('s', rd), fpr('s', rn)]),
+            1 => insn("sha1su1", vec![vreg(rd, "4s"), vreg(rn, "4s")]),
+            2 => insn("sha256su0", vec![vreg(rd, "4s"), vreg(rn, "4s")]),
+            _ => None,
+        }
+    }
+
+    fn sha512_three(&mut self) -> Decoded {
+        let (rd, rn, rm) = (self.rd(), self.rn(), self.rm());
+        if self.bit(14) {
+            return None;
+        }
+        match self.bits(11, 10) {
+            0 => insn("sha512h", vec![fpr('q', rd), fpr('q', rn), vreg(rm, "2d")]),
+            1 => insn("sha512h2", vec![fpr('q', rd), fpr('q', rn), vreg(rm, "2d")]),
+            2 => insn("sha512su1", three(rd, rn, rm, "2d")),
+            _ => insn("rax1", three(rd, rn, rm, "2d")),
+        }
+    }
+
+    fn sha512_two(&mut self) -> Decoded {
+        match self.bits(11, 10) {
+            0 => insn(
+                "sha512su0",
+                vec![vreg(self.rd(), "2d"), vreg(self.rn(), "2d")],
+            ),
+            _ => None,
+        }
+    }
+
+    // eor3 and bcax
+    fn four_register(&mut self) -> Decoded {
+        let name = match self.bits(22, 21) {
+            0 => "eor3",
+            1 => "bcax",
+            _ => return None,
+        };
+        let mut operands = three(self.rd(), self.rn(), self.rm(), "16b");
+        operands.push(vreg(self.bits(14, 10), "16b"));
+        insn(name, operands)
+    }
+
+    fn xar(&mut self) -> Decoded {
+        let mut operands = three(self.rd(), self.rn(), self.rm(), "2d");
+        operands.push(imm(self.bits(15, 10)));
+        insn("xar", operands)
+    }
+
+    fn scalar_copy(&mut self) -> Decoded {
+        if self.u() || self.bits(14, 11) != 0 {
+            return None;
+        }
+        let (_, kind, index) = element(self.bits(20, 16))?;
+        insn(
+            "mov",
+            vec![fpr(kind, self.rd()), lane(self.rn(), kind, index)],
+        )
+    }
+
+    fn copy(&mut self) -> Decoded {
+        let (q, op, imm4) = (self.q(), self.u(), self.bits(14, 11));
+        let (rd, rn) = (self.rd(), self.rn());
+        let (size, kind, index) = element(self.bits(20, 16))?;
+        if op {
+            if !q {
+                return None;
+            }
+            let source = lane(rn, kind, imm4 >> size);
+            return insn("mov", vec![lane(rd, kind, index), source]);
+        }
+        let arrangement = ARRANGEMENTS[(size << 1 | u32::from(q)) as usize];
+        match imm4 {
+            0 if size < 3 || q => insn("dup", vec![vreg(rd, arrangement), lane(rn, kind, index)]),
+            1 if size < 3 || q => insn("dup", vec![vreg(rd, arrangement), gpr(rn, size == 3)]),
+            3 if q => insn("mov", vec![lane(rd, kind, index), gpr(rn, size == 3)]),
+            5 if size < 2 || (size == 2 && q) => {
+                insn("smov", vec![gpr(rd, q), lane(rn, kind, index)])
+            }
+            7 if size < 3 && !q || size == 3 && q => {
+                let name = if size >= 2 { "mov" } else { "umov" };
+                insn(name, vec![gpr(rd, q), lane(rn, kind, index)])
+            }
+            _ => None,
+        }
+    }
+
+    fn table(&mut self) -> Decoded {
+        if self.bits(23, 22) != 0 {
+            return None;
+        }
+        let name = if self.bit(12) { "tbx" } else { "tbl" };
+        let arrangement = if self.q() { "16b" } else { "8b" };
+        let registers = list(self.rn(), self.bits(14, 13) + 1, "16b");
+        insn(
+            name,
+            vec![
+                vreg(self.rd(), arrangement),
+                registers,
+                vreg(self.rm(), arrangement),
+            ],
+        )
+    }
+
+    fn permute(&mut self) -> Decoded {
+        let name =
+            ["", "uzp1", "trn1", "zip1", "", "uzp2", "trn2", "zip2"][self.bits(14, 12) as usize];
+        let arrangement = self.arrangement();
+        if arrangement == "1d" {
+            return None;
+        }
+        insn(
+            named(name)?,
+            three(self.rd(), self.rn(), self.rm(), arrangement),
+        )
+    }
+
+    fn extract_vector(&mut self) -> Decoded {
+        let index = self.bits(14, 11);
+        if self.size() != 0 || (!self.q() && index >= 8) {
+            return None;
+        }
+        let arrangement = if self.q() { "16b" } else { "8b" };
+        let mut operands = three(self.rd(), self.rn(), self.rm(), arrangement);
+        operands.push(imm(index));
+        insn("ext", operands)
+    }
+
+    fn three_same(&mut self) -> Decoded {
+        let (q, u, size, opcode) = (self.q(), self.u(), self.size(), self.bits(15, 11));
+        let (rd, rn, rm) = (self.rd(), self.rn(), self.rm());
+        if opcode >= 0x18 {
+            let name = FP_THREE_SAME[usize::from(u)][size as usize >> 1][opcode as usize & 7];
+            let arrangement = fp_arrangement(size & 1, q)?;
+            return insn(named(name)?, three(rd, rn, rm, arrangement));
+        }
+        let arrangement = self.arrangement();
+        if opcode == 3 {
+            let name = if u {
+                ["eor", "bsl", "bit", "bif"][size as usize]
+            } else {
+                ["and", "bic", "orr", "orn"][size as usize]
+            };
+            let arrangement = if q { "16b" } else { "8b" };
+            if name == "orr" && rn == rm {
+                return insn("mov", vec![vreg(rd, arrangement), vreg(rn, arrangement)]);
+            }
+            return insn(name, three(rd, rn, rm, arrangement));
+        }
+        let name = named(INTEGER_THREE_SAME[usize::from(u)][opcode as usize])?;
+        let valid = match name {
+            "mla" | "mls" | "mul" | "smax" | "smin" | "umax" | "umin" | "sabd" | "uabd"
+            | "saba" | "uaba" | "shadd" | "uhadd" | "srhadd" | "urhadd" | "shsub" | "uhsub"
+            | "smaxp" | "sminp" | "umaxp" | "uminp" => size < 3,
+            "pmul" => size == 0,
+            "sqdmulh" | "sqrdmulh" => size == 1 || size == 2,
+            _ => arrangement != "1d",
+        };
+        valid.then_some(())?;
+        insn(name, three(rd, rn, rm, arrangement))
+    }
+
+    fn scalar_three_same(&mut self) -> Decoded {
+        let (u, size, opcode) = (self.u(), self.size(), self.bits(15, 11));
+        let (rd, rn, rm) = (self.rd(), self.rn(), self.rm());
+        if opcode >= 0x18 {
+            let name = FP_THREE_SAME[usize::from(u)][size as usize >> 1][opcode as usize & 7];
+            let allowed = [
+                "fmulx", "fcmeq", "frecps", "frsqrts", "fcmge", "facge", "fabd", "fcmgt", "facgt",
+            ];
+            if !allowed.contains(&name) {
+                return None;
+            }
+            let kind = if size & 1 == 1 { 'd' } else { 's' };
+            return insn(name, vec![fpr(kind, rd), fpr(kind, rn), fpr(kind, rm)]);
+        }
+        let name = named(INTEGER_THREE_SAME[usize::from(u)][opcode as usize])?;
+        let valid = match name {
+            "sqadd" | "uqadd" | "sqsub" | "uqsub" | "sqshl" | "uqshl" | "sqrshl" | "uqrshl" => true,
+            "sqdmulh" | "sqrdmulh" => size == 1 || size == 2,
+            "cmgt" | "cmhi" | "cmge" | "cmhs" | "sshl" | "ushl" | "srshl" | "urshl" | "add"
+            | "sub" | "cmtst" | "cmeq" => size == 3,
+            _ => false,
+        };
+        valid.then_some(())?;
+        let kind = SCALARS[size as usize];
+        insn(name, vec![fpr(kind, rd), fpr(kind, rn), fpr(kind, rm)])
+    }
+
+    fn three_same_fp16(&mut self) -> Decoded {
+        let name = FP_THREE_SAME[usize::from(self.u())][self.bits(23, 23) as usize]
+            [self.bits(13, 11) as usize];
+        let arrangement = if self.q() { "8h" } else { "4h" };
+        insn(
+            named(name)?,
+            three(self.rd(), self.rn(), self.rm(), arrangement),
+        )
+    }
+
+    fn scalar_three_same_fp16(&mut self) -> Decoded {
+        let name = FP_THREE_SAME[usize::from(self.u())][self.bits(23, 23) as usize]
+            [self.bits(13, 11) as usize];
+        let allowed = [
+            "fmulx", "fcmeq", "frecps", "frsqrts", "fcmge", "facge", "fabd", "fcmgt", "facgt",
+        ];
+        if !allowed.contains(&name) {
+            return None;
+        }
+        let (rd, rn, rm) = (self.rd(), self.rn(), self.rm());
+        insn(name, vec![fpr('h', rd), fpr('h', rn), fpr('h', rm)])
+    }
+
+    // dot products, rounding doubling multiply-accumulate and complex
+    // arithmetic
+    fn three_same_extra(&mut self) -> Decoded {
+        let (q, u, size, opcode) = (self.q(), self.u(), self.size(), self.bits(14, 11));
+        let (rd, rn, rm) = (self.rd(), self.rn(), self.rm());
+        let arrangement = self.arrangement();
+        match (u, opcode) {
+            (_, 2) if size == 2 => {
+                let name = if u { "udot" } else { "sdot" };
+                let source = if q { "16b" } else { "8b" };
+                insn(
+                    name,
+                    vec![vreg(rd, arrangement), vreg(rn, source), vreg(rm, source)],
+                )
+            }
+            (true, 0 | 1) if size == 1 || size == 2 => {
+                let name = if opcode == 0 { "sqrdmlah" } else { "sqrdmlsh" };
+                insn(name, three(rd, rn, rm, arrangement))
+            }
+            (true, 8..=15) if size != 0 && arrangement != "1d" => {
+                let mut operands = three(rd, rn, rm, arrangement);
+                if opcode & 4 == 0 {
+                    operands.push(imm((opcode & 3) * 90));
+                    insn("fcmla", operands)
+                } else if opcode & 1 == 0 {
+                    operands.push(imm(90 + (opcode >> 1 & 1) * 180));
+                    insn("fcadd", operands)
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+
+    fn scalar_three_same_extra(&mut self) -> Decoded {
+        let (size, opcode) = (self.size(), self.bits(14, 11));
+        if !self.u() || opcode > 1 || !(size == 1 || size == 2) {
+            return None;
+        }
+        let name = if opcode == 0 { "sqrdmlah" } else { "sqrdmlsh" };
+        let kind = SCALARS[size as usize];
+        insn(
+            name,
+            vec![
+                fpr(kind, self.rd()),
+                fpr(kind, self.rn()),
+                fpr(kind, self.rm()),
+            ],
+        )
+    }
+
+    fn three_different(&mut self) -> Decoded {
+        let (q, u, size, opcode) = (self.q(), self.u(), self.size(), self.bits(15, 12));
+        let (rd, rn, rm) = (self.rd(), self.rn(), self.rm());
+        let name = named(THREE_DIFFERENT[usize::from(u)].get(opcode as usize)?)?;
+        let narrow = self.arrangement();
+        let valid = match name {
+            "pmull" => size == 0 || size == 3,
+            "sqdmlal" | "sqdmlsl" | "sqdmull" => size == 1 || size == 2,
+            _ => size < 3,
+        };
+        valid.then_some(())?;
+        let wide = LONG[size as usize];
+        let operands = if name.ends_with('w') {
+            vec![vreg(rd, wide), vreg(rn, wide), vreg(rm, narrow)]
+        } else if name.ends_with("hn") {
+            vec![vreg(rd, narrow), vreg(rn, wide), vreg(rm, wide)]
+        } else {
+            vec![vreg(rd, wide), vreg(rn, narrow), vreg(rm, narrow)]
+        };
+        insn(upper(name, q), operands)
+    }
+
+    fn scalar_three_different(&mut self) -> Decoded {
+        let (size, opcode) = (self.size(), self.bits(15, 12));
+        let name = match (self.u(), opcode) {
+            (false, 9) => "sqdmlal",
+            (false, 11) => "sqdmlsl",
+            (false, 13) => "sqdmull",
+            _ => return None,
+        };
+        if !(size == 1 || size == 2) {
+            return None;
+        }
+        let (wide, narrow) = (SCALARS[size as usize + 1], SCALARS[size as usize]);
+        insn(
+            name,
+            vec![
+                fpr(wide, self.rd()),
+                fpr(narrow, self.rn()),
+                fpr(narrow, self.rm()),
+            ],
+        )
+    }
+
+    fn two_misc(&mut self) -> Decoded {
+        let (q, u, size, opcode) = (self.q(), self.u(), self.size(), self.bits(16, 12));
+        let (rd, rn) = (self.rd(), self.rn());
+        let arrangement = self.arrangement();
+        let sz = size & 1;
+        match (u, opcode) {
+            (false, 0x16) | (false, 0x17) | (true, 0x16) if size < 2 => {
+                let narrow = ARRANGEMENTS[((1 + sz) << 1 | u32::from(q)) as usize];
+                let wide = ["4s", "2d"][sz as usize];
+                let (name, operands) = match (u, opcode) {
+                    (false, 0x16) => ("fcvtn", vec![vreg(rd, narrow), vreg(rn, wide)]),
+                    (false, _) => ("fcvtl", vec![vreg(rd, wide), vreg(rn, narrow)]),
+                    _ if sz == 1 => ("fcvtxn", vec![vreg(rd, narrow), vreg(rn, wide)]),
+                    _ => return None,
+                };
+                return insn(upper(name, q), operands);
+            }
+            (true, 5) if size < 2 => {
+                let name = if size == 0 { "mvn" } else { "rbit" };
+                let arrangement = if q { "16b" } else { "8b" };
+                return insn(name, vec![vreg(rd, arrangement), vreg(rn, arrangement)]);
+            }
+            _ if opcode >= 0x0c && !(0x12..=0x14).contains(&opcode) => {
+                let name = fp_two_misc(u, size & 2 != 0, opcode)?;
+                if name == "frecpx" {
+                    return None;
+                }
+                if name == "urecpe" || name == "ursqrte" {
+                    let arrangement = fp_arrangement(0, q).filter(|_| sz == 0)?;
+                    return insn(name, vec![vreg(rd, arrangement), vreg(rn, arrangement)]);
+                }
+                let arrangement = fp_arrangement(sz, q)?;
+                let mut operands = vec![vreg(rd, arrangement), vreg(rn, arrangement)];
+                if name.starts_with("fcm") {
+                    operands.push("#0.0".to_string());
+                }
+                return insn(name, operands);
+            }
+            _ => {}
+        }
+        let name = named(INTEGER_TWO_MISC[usize::from(u)].get(opcode as usize)?)?;
+        let valid = match name {
+            "rev64" => size < 3,
+            "rev32" => size < 2,
+            "rev16" | "cnt" => size == 0,
+            "saddlp" | "uaddlp" | "sadalp" | "uadalp" | "cls" | "clz" | "xtn" | "sqxtn"
+            | "uqxtn" | "sqxtun" | "shll" => size < 3,
+            _ => arrangement != "1d",
+        };
+        valid.then_some(())?;
+        let operands = match name {
+            "saddlp" | "uaddlp" | "sadalp" | "uadalp" => {
+                let wide = ARRANGEMENTS[((size + 1) << 1 | u32::from(q)) as usize];
+                vec![vreg(rd, wide), vreg(rn, arrangement)]
+            }
+            "xtn" | "sqxtn" | "uqxtn" | "sqxtun" => {
+                return insn(
+                    upper(name, q),
+                    vec![vreg(rd, arrangement), vreg(rn, LONG[size as usize])],
+                );
+            }
+            "shll" => {
+                return insn(
+                    upper(name, q),
+                    vec![
+                        vreg(rd, LONG[size as usize]),
+                        vreg(rn, arrangement),
+                        imm(8 << size),
+                    ],
+                );
+            }
+            "cmgt" | "cmeq" | "cmlt" | "cmge" | "cmle" => {
+                vec![vreg(rd, arrangement), vreg(rn, arrangement), imm(0)]
+            }
+            _ => vec![vreg(rd, arrangement), vreg(rn, arrangement)],
+        };
+        insn(name, operands)
+    }
+
+    fn scalar_two_misc(&mut self) -> Decoded {
+        let (u, size, opcode) = (self.u(), self.size(), self.bits(16, 12));
+        let (rd, rn) = (self.rd(), self.rn());
+        let sz = size & 1;
+        if u && opcode == 0x16 {
+            return (size == 1)
+                .then_some(())
+                .and_then(|_| insn("fcvtxn", vec![fpr('s', rd), fpr('d', rn)]));
+        }
+        if opcode >= 0x0c && !(0x12..=0x14).contains(&opcode) {
+            let name = fp_two_misc(u, size & 2 != 0, opcode)?;
+            let scalar = name.starts_with("fcvt")
+                || name.starts_with("fcm")
+                || name.ends_with("cvtf")
+                || name == "frecpe"
+                || name == "frecpx"
+                || name == "frsqrte";
+            if !scalar {
+                return None;
+            }
+            let kind = if sz == 1 { 'd' } else { 's' };
+            let mut operands = vec![fpr(kind, rd), fpr(kind, rn)];
+            if name.starts_with("fcm") {
+                operands.push("#0.0".to_string());
+            }
+            return insn(name, operands);
+        }
+        let name = named(INTEGER_TWO_MISC[usize::from(u)].get(opcode as usize)?)?;
+        let kind = SCALARS[size as usize];
+        match name {
+            "suqadd" | "usqadd" | "sqabs" | "sqneg" => {
+                insn(name, vec![fpr(kind, rd), fpr(kind, rn)])
+            }
+            "cmgt" | "cmeq" | "cmlt" | "cmge" | "cmle" if size == 3 => {
+                insn(name, vec![fpr(kind, rd), fpr(kind, rn), imm(0)])
+            }
+            "abs" | "neg" if size == 3 => insn(name, vec![fpr(kind, rd), fpr(kind, rn)]),
+            "sqxtn" | "uqxtn" | "sqxtun" if size < 3 => insn(
+                name,
+                vec![fpr(kind, rd), fpr(SCALARS[size as usize + 1], rn)],
+            ),
+            _ => None,
+        }
+    }
+
+    fn two_misc_fp16(&mut self) -> Decoded {
+        let (q, opcode) = (self.q(), self.bits(16, 12));
+        let name = fp_two_misc(self.u(), self.bit(23), opcode)?;
+        if name == "frecpx" || name.starts_with("frint3") || name.starts_with("frint6") {
+            return None;
+        }
+        if name == "urecpe" || name == "ursqrte" {
+            return None;
+        }
+        let arrangement = if q { "8h" } else { "4h" };
+        let mut operands = vec![vreg(self.rd(), arrangement), vreg(self.rn(), arrangement)];
+        if name.starts_with("fcm") {
+            operands.push("#0.0".to_string());
+        }
+        insn(name, operands)
+    }
+
+    fn scalar_two_misc_fp16(&mut self) -> Decoded {
+        let name = fp_two_misc(self.u(), self.bit(23), self.bits(16, 12))?;
+        let scalar = name.starts_with("fcvt")
+            || name.starts_with("fcm")
+            || name.ends_with("cvtf")
+            || name == "frecpe"
+            || name == "frecpx"
+            || name == "frsqrte";
+        if !scalar {
+            return None;
+        }
+        let mut operands = vec![fpr('h', self.rd()), fpr('h', self.rn())];
+        if name.starts_with("fcm") {
+            operands.push("#0.0".to_string());
+        }
+        insn(name, operands)
+    }
+
+    fn across_lanes(&mut self) -> Decoded {
+        let (q, u, size, opcode) = (self.q(), self.u(), self.size(), self.bits(16, 12));
+        let (rd, rn) = (self.rd(), self.rn());
+        if opcode == 0x0c || opcode == 0x0f {
+            let minimum = size & 2 != 0;
+            let name = match (opcode, minimum) {
+                (0x0c, false) => "fmaxnmv",
+                (0x0c, true) => "fminnmv",
+                (_, false) => "fmaxv",
+                (_, true) => "fminv",
+            };
+            return match (u, size & 1) {
+                (false, 0) => {
+                    let arrangement = if q { "8h" } else { "4h" };
+                    insn(name, vec![fpr('h', rd), vreg(rn, arrangement)])
+                }
+                (true, 0) if q => insn(name, vec![fpr('s', rd), vreg(rn, "4s")]),
+                _ => None,
+            };
+        }
+        let name = match (u, opcode) {
+            (false, 0x03) => "saddlv",
+            (true, 0x03) => "uaddlv",
+            (false, 0x0a) => "smaxv",
+            (true, 0x0a) => "umaxv",
+            (false, 0x1a) => "sminv",
+            (true, 0x1a) => "uminv",
+            (false, 0x1b) => "addv",
+            _ => return None,
+        };
+        if size == 3 || (size == 2 && !q) {
+            return None;
+        }
+        let kind = if name.ends_with("lv") {
+            SCALARS[size as usize + 1]
+        } else {
+            SCALARS[size as usize]
+        };
+        insn(name, vec![fpr(kind, rd), vreg(rn, self.arrangement())])
+    }
+
+    fn scalar_pairwise(&mut self) -> Decoded {
+        let (u, size, opcode) = (self.u(), self.size(), self.bits(16, 12));
+        let (rd, rn) = (self.rd(), self.rn());
+        if !u && opcode == 0x1b {
+            return (size == 3)
+                .then_some(())
+                .and_then(|_| insn("addp", vec![fpr('d', rd), vreg(rn, "2d")]));
+        }
+        let minimum = size & 2 != 0;
+        let name = match (opcode, minimum) {
+            (0x0c, false) => "fmaxnmp",
+            (0x0c, true) => "fminnmp",
+            (0x0d, false) => "faddp",
+            (0x0f, false) => "fmaxp",
+            (0x0f, true) => "fminp",
+            _ => return None,
+        };
+        let (kind, arrangement) = match (u, size & 1) {
+            (false, 0) => ('h', "2h"),
+            (true, 0) => ('s', "2s"),
+            (true, _) => ('d', "2d"),
+            _ => return None,
+        };
+        insn(name, vec![fpr(kind, rd), vreg(rn, arrangement)])
+    }
+
+    // the element size of a shift by immediate, by the highest bit of immh,
+    // and the shift amounts to the right and to the left
+    fn shift_amounts(&self) -> Option<(u32, u32, u32)> {
+        let size = self.bits(22, 19).checked_ilog2()?;
+        let esize = 8 << size;
+        let value = self.bits(22, 16);
+        Some((size, 2 * esize - value, value - esize))
+    }
+
+    fn shift(&mut self) -> Decoded {
+        let (q, u, opcode) = (self.q(), self.u(), self.bits(15, 11));
+        let (rd, rn) = (self.rd(), self.rn());
+        let (size, right, left) = self.shift_amounts()?;
+        let arrangement = ARRANGEMENTS[(size << 1 | u32::from(q)) as usize];
+        if arrangement == "1d" {
+            return None;
+        }
+        let name = shift_name(u, opcode)?;
+        match opcode {
+            0x10..=0x13 => {
+                if size == 3 {
+                    return None;
+                }
+                insn(
+                    upper(name, q),
+                    vec![
+                        vreg(rd, arrangement),
+                        vreg(rn, LONG[size as usize]),
+                        imm(right),
+                    ],
+                )
+            }
+            0x14 => {
+                if size == 3 {
+                    return None;
+                }
+                // llvm-objdump doesn't use the sxtl and uxtl aliases
+                insn(
+                    upper(name, q),
+                    vec![
+                        vreg(rd, LONG[size as usize]),
+                        vreg(rn, arrangement),
+                        imm(left),
+                    ],
+                )
+            }
+            0x1c | 0x1f => {
+                let arrangement = match size {
+                    0 => return None,
+                    1 if q => "8h",
+                    1 => "4h",
+                    _ => fp_arrangement(size - 2, q)?,
+                };
+                insn(
+                    name,
+                    vec![vreg(rd, arrangement), vreg(rn, arrangement), imm(right)],
+                )
+            }
+            _ => {
+                let amount = if is_left_shift(name) { left } else { right };
+                insn(
+                    name,
+                    vec![vreg(rd, arrangement), vreg(rn, arrangement), imm(amount)],
+                )
+            }
+        }
+    }
+
+    fn scalar_shift(&mut self) -> Decoded {
+        let (u, opcode) = (self.u(), self.bits(15, 11));
+        let (rd, rn) = (self.rd(), self.rn());
+        let (size, right, left) = self.shift_amounts()?;
+        let name = shift_name(u, opcode)?;
+        let kind = SCALARS[size as usize];
+        match (name, opcode) {
+            ("shrn" | "rshrn", _) | (_, 0x14) => None,
+            (_, 0x10..=0x13) if size < 3 => insn(
+                name,
+                vec![
+                    fpr(kind, rd),
+                    fpr(SCALARS[size as usize + 1], rn),
+                    imm(right),
+                ],
+            ),
+            (_, 0x10..=0x13) => None,
+            (_, 0x1c | 0x1f) if size >= 1 => {
+                insn(name, vec![fpr(kind, rd), fpr(kind, rn), imm(right)])
+            }
+            ("sqshl" | "uqshl" | "sqshlu", _) => {
+                insn(name, vec![fpr(kind, rd), fpr(kind, rn), imm(left)])
+            }
+            (_, 0x1c | 0x1f) => None,
+            _ if size == 3 => {
+                let amount = if is_left_shift(name) { left } else { right };
+                insn(name, vec![fpr(kind, rd), fpr(kind, rn), imm(amount)])
+            }
+            _ => None,
+        }
+    }
+
+    fn modified_immediate(&mut self) -> Decoded {
+        let (q, op, cmode, o2) = (self.q(), self.u(), self.bits(15, 12), self.bit(11));
+        let imm8 = self.bits(18, 16) << 5 | self.bits(9, 5);
+        let rd = self.rd();
+        if o2 && (op || cmode != 15) {
+            return None;
+        }
+        let (name, arrangement, operands) = match cmode {
+            0b1111 => {
+                let arrangement = match (op, o2) {
+                    (false, true) => {
+                        if q {
+                            "8h"
+                        } else {
+                            "4h"
+                        }
+                    }
+                    (false, false) => {
+                        if q {
+                            "4s"
+                        } else {
+                            "2s"
+                        }
+                    }
+                    (true, _) if q => "2d",
+                    _ => return None,
+                };
+                ("fmov", arrangement, vec![fp_immediate(imm8)])
+            }
+            0b1110 if op => {
+                // each bit of imm8 is a byte of ones or zeros
+                let value = (0..8).fold(0u64, |value, i| {
+                    value
+                        | if imm8 >> i & 1 != 0 {
+                            0xff << (8 * i)
+                        } else {
+                            0
+                        }
+                });
+                if !q {
+                    return insn("movi", vec![fpr('d', rd), hex(value)]);
+                }
+                ("movi", "2d", vec![hex(value)])
+            }
+            0b1110 => ("movi", if q { "16b" } else { "8b" }, vec![imm(imm8)]),
+            0b1100 | 0b1101 => {
+                let name = if op { "mvni" } else { "movi" };
+                let shift = format!("msl #{}", 8 << (cmode & 1));
+                (name, if q { "4s" } else { "2s" }, vec![imm(imm8), shift])
+            }
+            _ => {
+                let halves = cmode & 8 != 0;
+                let shift = if halves {
+                    cmode >> 1 & 1
+                } else {
+                    cmode >> 1 & 3
+                } * 8;
+                let name = match (cmode & 1, op) {
+                    (0, false) => "movi",
+                    (0, true) => "mvni",
+                    (_, false) => "orr",
+                    (_, true) => "bic",
+                };
+                let arrangement = match (halves, q) {
+                    (true, false) => "4h",
+                    (true, true) => "8h",
+                    (false, false) => "2s",
+                    (false, true) => "4s",
+                };
+                let mut operands = vec![imm(imm8)];
+                if shift != 0 {
+                    operands.push(format!("lsl #{shift}"));
+                }
+                (name, arrangement, operands)
+            }
+        };
+        let mut all = vec![vreg(rd, arrangement)];
+        all.extend(operands);
+        insn(name, all)
+    }
+
+    // the register and the index of an element operand in by element
+    // instructions, for an element of 1 << size bytes
+    fn indexed_element(&self, size: u32) -> Option<(u32, u32)> {
+        let (h, l, m) = (self.bits(11, 11), self.bits(21, 21), self.bits(20, 20));
+        match size {
+            1 => Some((self.bits(19, 16), h << 2 | l << 1 | m)),
+            2 => Some((self.rm(), h << 1 | l)),
+            3 if l == 0 => Some((self.rm(), h)),
+            _ => None,
+        }
+    }
+
+    fn indexed(&mut self) -> Decoded {
+        let (q, u, size, opcode) = (self.q(), self.u(), self.size(), self.bits(15, 12));
+        let (rd, rn) = (self.rd(), self.rn());
+        let arrangement = self.arrangement();
+        let fp = matches!((u, opcode), (false, 1 | 5 | 9) | (true, 9));
+        if fp {
+            let name = ["fmla", "fmls", "fmul", "fmulx"][(opcode >> 2) as usize + usize::from(u)];
+            let (element, arrangement) = match size {
+                0 => (1, if q { "8h" } else { "4h" }),
+                2 | 3 => (size, fp_arrangement(size & 1, q)?),
+                _ => return None,
+            };
+            let (rm, index) = self.indexed_element(element)?;
+            let kind = SCALARS[element as usize];
+            return insn(
+                name,
+                vec![
+                    vreg(rd, arrangement),
+                    vreg(rn, arrangement),
+                    lane(rm, kind, index),
+                ],
+            );
+        }
+        if u && opcode & 9 == 1 {
+            // fcmla by element, of pairs of elements
+            let (rm, index) = match (size, q) {
+                (1, _) if q || !self.bit(11) => {
+                    (self.rm(), self.bits(11, 11) << 1 | self.bits(21, 21))
+                }
+                (2, true) if !self.bit(21) => (self.rm(), self.bits(11, 11)),
+                _ => return None,
+            };
+            let kind = SCALARS[size as usize];
+            let rotation = imm((opcode >> 1 & 3) * 90);
+            return insn(
+                "fcmla",
+                vec![
+                    vreg(rd, arrangement),
+                    vreg(rn, arrangement),
+                    lane(rm, kind, index),
+                    rotation,
+                ],
+            );
+        }
+        if opcode == 14 {
+            if size != 2 {
+                return None;
+            }
+            let (rm, index) = self.indexed_element(2)?;
+            let name = if u { "udot" } else { "sdot" };
+            let source = if q { "16b" } else { "8b" };
+            return insn(
+                name,
+                vec![
+                    vreg(rd, arrangement),
+                    vreg(rn, source),
+                    format!("v{rm}.4b[{index}]"),
+                ],
+            );
+        }
+        let name = match (u, opcode) {
+            (false, 2) => "smlal",
+            (false, 3) => "sqdmlal",
+            (false, 6) => "smlsl",
+            (false, 7) => "sqdmlsl",
+            (false, 8) => "mul",
+            (false, 10) => "smull",
+            (false, 11) => "sqdmull",
+            (false, 12) => "sqdmulh",
+            (false, 13) => "sqrdmulh",
+            (true, 0) => "mla",
+            (true, 2) => "umlal",
+            (true, 4) => "mls",
+            (true, 6) => "umlsl",
+            (true, 10) => "umull",
+            (true, 13) => "sqrdmlah",
+            (true, 15) => "sqrdmlsh",
+            _ => return None,
+        };
+        if !(size == 1 || size == 2) {
+            return None;
+        }
+        let (rm, index) = self.indexed_element(size)?;
+        let element = lane(rm, SCALARS[size as usize], index);
+        let long = matches!(
+            name,
+            "smlal"
+                | "sqdmlal"
+                | "smlsl"
+                | "sqdmlsl"
+                | "smull"
+                | "sqdmull"
+                | "umlal"
+                | "umlsl"
+                | "umull"
+        );
+        if long {
+            return insn(
+                upper(name, q),
+                vec![
+                    vreg(rd, LONG[size as usize]),
+                    vreg(rn, arrangement),
+                    element,
+                ],
+            );
+        }
+        insn(
+            name,
+            vec![vreg(rd, arrangement), vreg(rn, arrangement), element],
+        )
+    }
+
+    fn scalar_indexed(&mut self) -> Decoded {
+        let (u, size, opcode) = (self.u(), self.size(), self.bits(15, 12));
+        let (rd, rn) = (self.rd(), self.rn());
+        let fp = matches!((u, opcode), (false, 1 | 5 | 9) | (true, 9));
+        if fp {
+            let name = ["fmla", "fmls", "fmul", "fmulx"][(opcode >> 2) as usize + usize::from(u)];
+            let element = match size {
+                0 => 1,
+                2 | 3 => size,
+                _ => return None,
+            };
+            let (rm, index) = self.indexed_element(element)?;
+            let kind = SCALARS[element as usize];
+            return insn(
+                name,
+                vec![fpr(kind, rd), fpr(kind, rn), lane(rm, kind, index)],
+            );
+        }
+        let name = match (u, opcode) {
+            (false, 3) => "sqdmlal",
+            (false, 7) => "sqdmlsl",
+            (false, 11) => "sqdmull",
+            (false, 12) => "sqdmulh",
+            (false, 13) => "sqrdmulh",
+            (true, 13) => "sqrdmlah",
+            (true, 15) => "sqrdmlsh",
+            _ => return None,
+        };
+        if !(size == 1 || size == 2) {
+            return None;
+        }
+        let (rm, index) = self.indexed_element(size)?;
+        let kind = SCALARS[size as usize];
+        let destination = if name.ends_with('l') {
+            SCALARS[size as usize + 1]
+        } else {
+            kind
+        };
+        insn(
+            name,
+            vec![fpr(destination, rd), fpr(kind, rn), lane(rm, kind, index)],
+        )
+    }
+}
+
+// the shifts by immediate by U and opcode
+fn shift_name(u: bool, opcode: u32) -> Option<&'static str> {
+    let name = match (u, opcode) {
+        (false, 0x00) => "sshr",
+        (false, 0x02) => "ssra",
+        (false, 0x04) => "srshr",
+        (false, 0x06) => "srsra",
+        (false, 0x0a) => "shl",
+        (false, 0x0e) => "sqshl",
+        (false, 0x10) => "shrn",
+        (false, 0x11) => "rshrn",
+        (false, 0x12) => "sqshrn",
+        (false, 0x13) => "sqrshrn",
+        (false, 0x14) => "sshll",
+        (false, 0x1c) => "scvtf",
+        (false, 0x1f) => "fcvtzs",
+        (true, 0x00) => "ushr",
+        (true, 0x02) => "usra",
+        (true, 0x04) => "urshr",
+        (true, 0x06) => "ursra",
+        (true, 0x08) => "sri",
+        (true, 0x0a) => "sli",
+        (true, 0x0c) => "sqshlu",
+        (true, 0x0e) => "uqshl",
+        (true, 0x10) => "sqshrun",
+        (true, 0x11) => "sqrshrun",
+        (true, 0x12) => "uqshrn",
+        (true, 0x13) => "uqrshrn",
+        (true, 0x14) => "ushll",
+        (true, 0x1c) => "ucvtf",
+        (true, 0x1f) => "fcvtzu",
+        _ => return None,
+    };
+    Some(name)
+}
+
+fn is_left_shift(name: &str) -> bool {
+    matches!(name, "shl" | "sli" | "sqshl" | "uqshl" | "sqshlu")
+}
+
+// scalar floating point
+impl State {
+    // the register of a floating-point instruction's type field; M and S
+    // must be clear
+    fn fp_register(&self) -> Option<char> {
+        if self.bit(31) || self.bit(29) {
+            return None;
+        }
+        fp_kind(self.size())
+    }
+
+    fn fixed_conversion(&mut self) -> Decoded {
+        let (sf, scale) = (self.sf(), self.bits(15, 10));
+        let kind = fp_kind(self.size()).filter(|_| !self.bit(29))?;
+        if !sf && scale < 32 {
+            return None;
+        }
+        let fbits = imm(64 - scale);
+        let (rd, rn) = (self.rd(), self.rn());
+        match self.bits(20, 16) {
+            0b00010 => insn("scvtf", vec![fpr(kind, rd), gpr(rn, sf), fbits]),
+            0b00011 => insn("ucvtf", vec![fpr(kind, rd), gpr(rn, sf), fbits]),
+            0b11000 => insn("fcvtzs", vec![gpr(rd, sf), fpr(kind, rn), fbits]),
+            0b11001 => insn("fcvtzu", vec![gpr(rd, sf), fpr(kind, rn), fbits]),
+            _ => None,
+        }
+    }
+
+    fn integer_conversion(&mut self) -> Decoded {
+        let (sf, ftype, rmode, opcode) =
+            (self.sf(), self.size(), self.bits(20, 19), self.bits(18, 16));
+        let (rd, rn) = (self.rd(), self.rn());
+        if self.bit(29) {
+            return None;
+        }
+        match (rmode, opcode) {
+            (1, 6) if sf && ftype == 2 => {
+                return insn("fmov", vec![gpr(rd, true), lane(rn, 'd', 1)]);
+            }
+            (1, 7) if sf && ftype == 2 => {
+                return insn("fmov", vec![lane(rd, 'd', 1), gpr(rn, true)]);
+            }
+            (3, 6) if !sf && ftype == 1 => {
+                return insn("fjcvtzs", vec![gpr(rd, false), fpr('d', rn)]);
+            }
+            _ => {}
+        }
+        let kind = fp_kind(ftype)?;
+        let name = match (rmode, opcode) {
+            (_, 0) => ["fcvtns", "fcvtps", "fcvtms", "fcvtzs"][rmode as usize],
+            (_, 1) => ["fcvtnu", "fcvtpu", "fcvtmu", "fcvtzu"][rmode as usize],
+            (0, 2) => "scvtf",
+            (0, 3) => "ucvtf",
+            (0, 4) => "fcvtas",
+            (0, 5) => "fcvtau",
+            (0, 6 | 7) => {
+                // moves keep the bits, between registers of the same size
+                if kind == 's' && sf || kind == 'd' && !sf {
+                    return None;
+                }
+                if opcode == 6 {
+                    return insn("fmov", vec![gpr(rd, sf), fpr(kind, rn)]);
+                }
+                return insn("fmov", vec![fpr(kind, rd), gpr(rn, sf)]);
+            }
+            _ => return None,
+        };
+        if opcode == 2 || opcode == 3 {
+            insn(name, vec![fpr(kind, rd), gpr(rn, sf)])
+        } else {
+            insn(name, vec![gpr(rd, sf), fpr(kind, rn)])
+        }
+    }
+
+    fn fp_one_source(&mut self) -> Decoded {
+        let kind = self.fp_register()?;
+        let (rd, rn) = (self.rd(), self.rn());
+        let opcode = self.bits(20, 15);
+        let name = match opcode {
+            0 => "fmov",
+            1 => "fabs",
+            2 => "fneg",
+            3 => "fsqrt",
+            4..=7 => {
+                let target = fp_kind(opcode & 3).filter(|&target| target != kind)?;
+                return insn("fcvt", vec![fpr(target, rd), fpr(kind, rn)]);
+            }
+            8 => "frintn",
+            9 => "frintp",
+            10 => "frintm",
+            11 => "frintz",
+            12 => "frinta",
+            14 => "frintx",
+            15 => "frinti",
+            16..=19 if kind != 'h' => {
+                ["frint32z", "frint32x", "frint64z", "frint64x"][opcode as usize - 16]
+            }
+            _ => return None,
+        };
+        insn(name, vec![fpr(kind, rd), fpr(kind, rn)])
+    }
+
+    fn fp_compare(&mut self) -> Decoded {
+        let kind = self.fp_register()?;
+        if self.bits(15, 14) != 0 || self.bits(2, 0) != 0 {
+            return None;
+        }
+        let name = if self.bit(4) { "fcmpe" } else { "fcmp" };
+        let second = if self.bit(3) {
+            "#0.0".to_string()
+        } else {
+            fpr(kind, self.rm())
+        };
+        insn(name, vec![fpr(kind, self.rn()), second])
+    }
+
+    fn fp_move_immediate(&mut self) -> Decoded {
+        let kind = self.fp_register()?;
+        if self.bits(9, 5) != 0 {
+            return None;
+        }
+        insn(
+            "fmov",
+            vec![fpr(kind, self.rd()), fp_immediate(self.bits(20, 13))],
+        )
+    }
+
+    fn fp_conditional_compare(&mut self) -> Decoded {
+        let kind = self.fp_register()?;
+        let name = if self.bit(4) { "fccmpe" } else { "fccmp" };
+        insn(
+            name,
+            vec![
+                fpr(kind, self.rn()),
+                fpr(kind, self.rm()),
+                imm(self.bits(3, 0)),
+                CONDITIONS[self.bits(15, 12) as usize].to_string(),
+            ],
+        )
+    }
+
+    fn fp_two_source(&mut self) -> Decoded {
+        let kind = self.fp_register()?;
+        let name = [
+            "fmul", "fdiv", "fadd", "fsub", "fmax", "fmin", "fmaxnm", "fminnm", "fnmul",
+        ]
+        .get(self.bits(15, 12) as usize)?;
+        insn(
+            *name,
+            vec![
+                fpr(kind, self.rd()),
+                fpr(kind, self.rn()),
+                fpr(kind, self.rm()),
+            ],
+        )
+    }
+
+    fn fp_conditional_select(&mut self) -> Decoded {
+        let kind = self.fp_register()?;
+        insn(
+            "fcsel",
+            vec![
+                fpr(kind, self.rd()),
+                fpr(kind, self.rn()),
+                fpr(kind, self.rm()),
+                CONDITIONS[self.bits(15, 12) as usize].to_string(),
+            ],
+        )
+    }
+
+    fn fp_three_source(&mut self) -> Decoded {
+        let kind = self.fp_register()?;
+        let name = ["fmadd", "fmsub", "fnmadd", "fnmsub"]
+            [(self.bits(21, 21) << 1 | self.bits(15, 15)) as usize];
+        insn(
+            name,
+            vec![
+                fpr(kind, self.rd()),
+                fpr(kind, self.rn()),
+                fpr(kind, self.rm()),
+                fpr(kind, self.bits(14, 10)),
+            ],
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::disasm::aarch64::AArch64;
+    use crate::disasm::{self, Decoder, Flow, Syntax};
+    use crate::parse::ELFParser;
+
+    // the instruction as llvm-objdump prints it
+    fn text(word: u32) -> String {
+        let instruction = AArch64.decode(&word.to_le_bytes(), 0x1000);
+        assert_eq!(instruction.bytes.len(), 4);
+        format!("{} {}", instruction.mnemonic, instruction.operands)
+            .trim_end()
+            .to_string()
+    }
+
+    #[test]
+    fn aliases() {
+        assert_eq!(text(0x910003fd), "mov x29, sp");
+        assert_eq!(text(0xaa0103e0), "mov x0, x1");
+        assert_eq!(text(0xb2407fe0), "mov x0, #4294967295");
+        assert_eq!(text(0xf100101f), "cmp x0, #4");
+        assert_eq!(text(0x1a9f17e0), "cset w0, eq");
+        assert_eq!(text(0xd37df020), "lsl x0, x1, #3");
+        assert_eq!(text(0x9b027c20), "mul x0, x1, x2");
+        assert_eq!(text(0xd503233f), "paciasp");
+        assert_eq!(text(0xd503245f), "bti c");
+    }
+
+    #[test]
+    fn simd_fp() {
+        assert_eq!(text(0x4ea28420), "add v0.4s, v1.4s, v2.4s");
+        assert_eq!(text(0x1e2e1000), "fmov s0, #1.00000000");
+        assert_eq!(text(0x4c40a000), "ld1 { v0.16b, v1.16b }, [x0]");
+        assert_eq!(text(0x4f0007e0), "movi v0.4s, #31");
+        assert_eq!(text(0x4e284820), "aese v0.16b, v1.16b");
+        assert_eq!(text(0x6fbf2820), "umlal2 v0.2d, v1.4s, v31.s[3]");
+    }
+
+    #[test]
+    fn control_flow() {
+        let decode = |word: u32| AArch64.decode(&word.to_le_bytes(), 0x1000);
+        let call = decode(0x94000006);
+        assert_eq!((call.flow, call.target), (Flow::Call, Some(0x1018)));
+        let branch = decode(0xb4000040);
+        assert_eq!((branch.flow, branch.target), (Flow::Branch, Some(0x1008)));
+        assert_eq!(decode(0xd65f03c0).flow, Flow::Return);
+        assert_eq!(decode(0xd61f0200).flow, Flow::Jump);
+        let load = decode(0x58000080);
+        assert_eq!(
+            (load.operands.as_str(), load.reference),
+            ("x0, 0x1010", Some(0x1010))
+        );
+        // undecodable words are skipped whole, a trailing partial one too
+        assert_eq!(text(0xffffffff), "(bad)");
+        assert_eq!(AArch64.decode(&[0, 0], 0).bytes.len(), 2);
+    }
+
+    #[test]
+    fn fixture() {
+        let contents = include_bytes!("../../tests/fixtures/hello-arm64.o");
+        let parser = ELFParser::from_bytes(contents).unwrap();
+        let listings = disasm::disassemble(&parser, Syntax::Att).unwrap();
+        let lines: Vec<String> = listings[0]
+            .instructions
+            .iter()
+            .map(|i| format!("{} {}", i.mnemonic, i.operands))
+            .collect();
+        assert_eq!(lines.len(), 19);
+        assert_eq!(lines[2], "stp x29, x30, [sp, #-16]!");
+        assert_eq!(lines[4], "bl 0x28");
+        assert_eq!(lines[8], "mov x8, #93");
+        assert_eq!(lines[11], "adrp x1, 0x0");
+        assert_eq!(lines[18], "b 0x28");
+    }
+}
@@ -0,0 +1,282 @@
+// Names for the code in the listings, like objdump gives them: the
+// function symbols of each section, and the PLT stubs, which are named
+// after the function whose GOT slot they jump through (puts@plt). The
+// targets of jumps, branches and calls are then described as
+// `symbol+offset`.
+use std::collections::HashMap;
+
+use crate::disasm::{Label, Listing};
+use crate::error::ParseError;
+use crate::header::ObjectType;
+use crate::parse::{ELFParser, EM_AARCH64, EM_RISCV, EM_X86_64};
+use crate::section::SectionHeader;
+use crate::symbol::{Symbol, STB_LOCAL, STT_FUNC, STT_GNU_IFUNC, STT_NOTYPE};
+
+// Label the listings, the code of the sections with the indices in
+// `sections`, and name the targets of their instructions
+pub fn symbolize(
+    parser: &ELFParser,
+    listings: &mut [Listing],
+    sections: &[usize],
+) -> Result<(), ParseError> {
+    let relocatable = parser.elf_header.object_type() == ObjectType::Relocatable;
+    let headers = parser.sections()?;
+    // sorted by address, then by how good a name the label is
+    let mut labels: Vec<Vec<(u64, u8, Label)>> = vec![Vec::new(); listings.len()];
+    for symbol in parser.symbols()?.iter().chain(parser.dynamic_symbols()?) {
+        let Some(rank) = rank(symbol) else { continue };
+        let shndx = symbol.shndx as usize;
+        let Some(i) = sections.iter().position(|&s| s == shndx) else {
+            continue;
+        };
+        // symbols of relocatable files are offsets into their section
+        let base = if relocatable {
+            headers[shndx].sh_addr
+        } else {
+            0
+        };
+        let label = Label {
+            name: symbol.name.to_string(),
+            address: base.wrapping_add(symbol.st_value),
+            size: symbol.st_size,
+        };
+        labels[i].push((label.address, rank, label));
+    }
+    if !relocatable {
+        let slots = got_slots(parser)?;
+        for (i, listing) in listings.iter().enumerate() {
+            if listing.name.starts_with(".plt") {
+                let section = &headers[sections[i]];
+                let code = parser.section_data(section)?;
+                let stubs = plt_stubs(parser.elf_header.e_machine, section, code);
+                labels[i].extend(plt_labels(listing, &stubs, &slots));
+            }
+        }
+    }
+    for (listing, mut labels) in listings.iter_mut().zip(labels) {
+        labels.sort_by_key(|(address, rank, _)| (*address, *rank));
+        labels.dedup_by_key(|(address, _, _)| *address);
+        listing.labels = labels.into_iter().map(|(_, _, label)| label).collect();
+    }
+
+    // relocatable files have all their sections at 0, so only targets in
+    // the same section are known
+    let names: Vec<Vec<Option<String>>> = listings
+        .iter()
+        .map(|listing| {
+            let candidates = if relocatable {
+                std::slice::from_ref(listing)
+            } else {
+                &*listings
+            };
+            let names = listing.instructions.iter().map(|instruction| {
+                let target = instruction.target?;
+                // a target right after a section, e.g. past a call that
+                // doesn't return, is still described from that section
+                let listing = candidates
+                    .iter()
+                    .find(|l| l.contains(target))
+                    .or_else(|| candidates.iter().find(|l| l.end() == target))?;
+                Some(listing.describe(target))
+            });
+            names.collect()
+        })
+        .collect();
+    for (listing, names) in listings.iter_mut().zip(names) {
+        for (instruction, name) in listing.instructions.iter_mut().zip(names) {
+            instruction.target_symbol = name;
+        }
+    }
+    Ok(())
+}
+
+// How good a label the symbol makes, lower being better: global functions,
+// local ones, then symbols without a type (e.g. from assembly). None for
+// symbols that don't name code.
+fn rank(symbol: &Symbol) -> Option<u8> {
+    // mapping symbols ($x, $d) and assembler-local labels
+    if symbol.name.is_empty() || symbol.name.starts_with(['$', '.']) {
+        return None;
+    }
+    let local = u8::from(symbol.st_bind == STB_LOCAL);
+    match symbol.st_type {
+        STT_FUNC | STT_GNU_IFUNC => Some(local),
+        STT_NOTYPE => Some(2 + local),
+        _ => None,
+    }
+}
+
+// the symbols of the GOT slots that dynamic relocations fill in, by slot
+fn got_slots(parser: &ELFParser) -> Result<HashMap<u64, String>, ParseError> {
+    let mut slots = HashMap::new();
+    for table in parser.relocations()? {
+        for relocation in &table.entries {
+            if relocation.r_sym != 0 && !relocation.symbol_name.is_empty() {
+                slots
+                    .entry(relocation.r_offset)
+                    .or_insert_with(|| relocation.symbol_name.clone());
+            }
+        }
+    }
+    Ok(slots)
+}
+
+// labels for the stubs whose GOT slot has a symbol; each stub ends where
+// the next one starts
+fn plt_labels(
+    listing: &Listing,
+    stubs: &[(u64, u64)],
+    slots: &HashMap<u64, String>,
+) -> Vec<(u64, u8, Label)> {
+    let end = listing.end();
+    let mut labels = Vec::new();
+    for (i, &(address, slot)) in stubs.iter().enumerate() {
+        let Some(name) = slots.get(&slot) else {
+            continue;
+        };
+        let next = stubs.get(i + 1).map_or(end, |&(next, _)| next);
+        let label = Label {
+            name: format!("{name}@plt"),
+            address,
+            size: next.wrapping_sub(address),
+        };
+        labels.push((address, 0, label));
+    }
+    labels
+}
+
+// The stubs of a PLT section, as their address and the GOT slot they load
+// the address to jump to from
+fn plt_stubs(machine: u16, section: &SectionHeader, code: &[u8]) -> Vec<(u64, u64)> {
+    let mut stubs = Vec::new();
+    let word = |at: usize| {
+        code.get(at..at + 4)
+            .map(|w| u32::from_le_bytes(w.try_into().unwrap()))
+    };
+    match machine {
+        // jmp *slot(%rip) somewhere in each entry, after endbr64 or bnd
+        EM_X86_64 => {
+            let size = if section.sh_entsize == 0 {
+                16
+            } else {
+                section.sh_entsize as usize
+            };
+            for (i, entry) in code.chunks(size).enumerate() {
+                let Some(at) = entry.windows(6).position(|w| w[..2] == [0xff, 0x25]) else {
+                    continue;
+                };
+                let displacement = i32::from_le_bytes(entry[at + 2..at + 6].try_into().unwrap());
+                let start = section.sh_addr.wrapping_add((i * size) as u64);
+                let next = start.wrapping_add((at + 6) as u64);
+                stubs.push((start, next.wrapping_add(displacement as i64 as u64)));
+            }
+        }
+        // adrp x16, page; ldr x17, [x16, #offset], after a bti c
+        EM_AARCH64 => {
+            for at in (0..code.len()).step_by(4) {
+                let (Some(adrp), Some(ldr)) = (word(at), word(at + 4)) else {
+                    break;
+                };
+                if adrp & 0x9f00_0000 != 0x9000_0000
+                    || ldr & 0xffc0_0000 != 0xf940_0000
+                    || ldr >> 5 & 0x1f != adrp & 0x1f
+                {
+                    continue;
+                }
+                let address = section.sh_addr.wrapping_add(at as u64);
+                let pages = ((adrp >> 5 & 0x7ffff) << 2 | adrp >> 29 & 3) as i64;
+                let page = (address & !0xfff).wrapping_add(((pages << 43) >> 31) as u64);
+                let slot = page.wrapping_add(u64::from(ldr >> 10 & 0xfff) * 8);
+                let bti = at >= 4 && word(at - 4) == Some(0xd503_245f);
+                let start = if bti {
+                    address.wrapping_sub(4)
+                } else {
+                    address
+                };
+                stubs.push((start, slot));
+            }
+        }
+        // auipc t3, %pcrel_hi(slot); l[wd] t3, %pcrel_lo(slot)(t3)
+        EM_RISCV => {
+            for at in (0..code.len()).step_by(4) {
+                let (Some(auipc), Some(load)) = (word(at), word(at + 4)) else {
+                    break;
+                };
+                let rd = auipc >> 7 & 0x1f;
+                if auipc & 0x7f != 0x17
+                    || load & 0x707f & !0x1000 != 0x2003
+                    || load >> 15 & 0x1f != rd
+                {
+                    continue;
+                }
+                let address = section.sh_addr.wrapping_add(at as u64);
+                let offset = (auipc & 0xffff_f000) as i32 as i64 + ((load as i32) >> 20) as i64;
+                stubs.push((address, address.wrapping_add(offset as u64)));
+            }
+        }
+        _ => {}
+    }
+    stubs
+}
+
+#[cfg(test)]
+mod test {
+    use crate::disasm::labels::plt_stubs;
+    use crate::disasm::{self, Syntax};
+    use crate::parse::{ELFParser, EM_AARCH64, EM_RISCV, EM_X86_64};
+    use crate::section::SectionHeader;
+
+    fn plt(address: u64, entsize: u64) -> SectionHeader<'static> {
+        SectionHeader {
+            sh_addr: address,
+            sh_entsize: entsize,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn stubs() {
+        // PLT0, then jmp *0x2fca(%rip); push $0; jmp PLT0
+        let mut code = vec![0xff, 0x35, 0, 0, 0, 0, 0xff, 0x25, 0, 0, 0, 0, 0, 0, 0, 0];
+        code.extend([
+            0xff, 0x25, 0xca, 0x2f, 0, 0, 0x68, 0, 0, 0, 0, 0xe9, 0, 0, 0, 0,
+        ]);
+        let stubs = plt_stubs(EM_X86_64, &plt(0x1020, 16), &code);
+        assert_eq!(stubs, [(0x1020, 0x102c), (0x1030, 0x4000)]);
+
+        // bti c; adrp x16, 0x11000; ldr x17, [x16, #24]
+        let words = [0xd503245fu32, 0xb0000090, 0xf9400e11];
+        let code: Vec<u8> = words.iter().flat_map(|w| w.to_le_bytes()).collect();
+        let stubs = plt_stubs(EM_AARCH64, &plt(0, 0), &code);
+        assert_eq!(stubs, [(0, 0x11018)]);
+
+        // auipc t3, 2; ld t3, -16(t3)
+        let words = [0x00002e17u32, 0xff0e3e03];
+        let code: Vec<u8> = words.iter().flat_map(|w| w.to_le_bytes()).collect();
+        let stubs = plt_stubs(EM_RISCV, &plt(0x1020, 0), &code);
+        assert_eq!(stubs, [(0x1020, 0x3010)]);
+
+        // sections at the top of the address space wrap around
+        let stubs = plt_stubs(EM_RISCV, &plt(u64::MAX - 3, 0), &code);
+        assert_eq!(stubs, [(u64::MAX - 3, 0x1fec)]);
+        let code = [0xff, 0x25, 0, 0, 0, 0].repeat(2);
+        let stubs = plt_stubs(EM_X86_64, &plt(u64::MAX - 5, 6), &code);
+        assert_eq!(stubs, [(u64::MAX - 5, 0), (0, 6)]);
+    }
+
+    #[test]
+    fn fixture() {
+        let contents = include_bytes!("../../tests/fixtures/hello-arm64.o");
+        let parser = ELFParser::from_bytes(contents).unwrap();
+        let listings = disasm::disassemble(&parser, Syntax::Att).unwrap();
+        let names: Vec<&str> = listings[0].labels.iter().map(|l| l.name.as_str()).collect();
+        assert_eq!(names, ["_start", "greet"]);
+        assert_eq!(listings[0].labels[1].address, 0x28);
+        let target = |i: usize| listings[0].instructions[i].target_symbol.as_deref();
+        // bl greet, cbz x0, 1f and b greet
+        assert_eq!(target(4), Some("greet"));
+        assert_eq!(target(16), Some("greet+0x20"));
+        assert_eq!(target(18), Some("greet"));
+        assert_eq!(target(0), None);
+    }
+}
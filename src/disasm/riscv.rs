@@ -0,0 +1,1342 @@
+// RISC-V decoder for RV32 and RV64: the base integer instructions, the M,
+// A, F, D and C extensions, Zicsr and Zifencei, and the privileged
+// instructions firmware uses (mret, wfi, sfence.vma). Output follows
+// llvm-objdump, with its aliases (li, mv, ret, beqz, csrr, ...), and
+// compressed instructions are shown as the instructions they expand to.
+//
+// Which extensions decode comes from the Tag_RISCV_arch build attribute of
+// the .riscv.attributes section. Files without one get RV32GC or RV64GC,
+// like GNU objdump assumes. Instructions of other extensions come
+// out as (bad).
+use crate::disasm::{Decoder, Flow, Instruction};
+use crate::error::ParseError;
+use crate::parse::{Arch, ELFParser};
+use crate::utils::{Endian, Reader};
+
+// section type of .riscv.attributes
+pub const SHT_RISCV_ATTRIBUTES: u32 = 0x7000_0003;
+// the attribute tag holding the ISA string, e.g. "rv64i2p1_m2p0_c2p0"
+const TAG_RISCV_ARCH: u64 = 5;
+
+const REGISTERS: [&str; 32] = [
+    "zero", "ra", "sp", "gp", "tp", "t0", "t1", "t2", "s0", "s1", "a0", "a1", "a2", "a3", "a4",
+    "a5", "a6", "a7", "s2", "s3", "s4", "s5", "s6", "s7", "s8", "s9", "s10", "s11", "t3", "t4",
+    "t5", "t6",
+];
+const FP_REGISTERS: [&str; 32] = [
+    "ft0", "ft1", "ft2", "ft3", "ft4", "ft5", "ft6", "ft7", "fs0", "fs1", "fa0", "fa1", "fa2",
+    "fa3", "fa4", "fa5", "fa6", "fa7", "fs2", "fs3", "fs4", "fs5", "fs6", "fs7", "fs8", "fs9",
+    "fs10", "fs11", "ft8", "ft9", "ft10", "ft11",
+];
+// rounding modes by the rm field; 7 is the dynamic one, which isn't shown
+const ROUNDING: [&str; 8] = ["rne", "rtz", "rdn", "rup", "rmm", "", "", "dyn"];
+
+// the standard extensions beyond the base integer instructions
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Extensions {
+    pub m: bool,
+    pub a: bool,
+    pub f: bool,
+    pub d: bool,
+    pub c: bool,
+}
+
+impl Extensions {
+    // G (IMAFD) and C
+    pub fn all() -> Extensions {
+        Extensions {
+            m: true,
+            a: true,
+            f: true,
+            d: true,
+            c: true,
+        }
+    }
+
+    // The XLEN and extensions of an ISA string such as "rv32imac" or
+    // "rv64i2p1_m2p0_a2p1_f2p2_d2p2_c2p0_zicsr2p0"; None if it isn't one.
+    // Multi-letter extensions (Zicsr, ...) are ignored.
+    pub fn from_arch(arch: &str) -> Option<(u32, Extensions)> {
+        let arch = arch.to_ascii_lowercase();
+        let (xlen, rest) = if let Some(rest) = arch.strip_prefix("rv32") {
+            (32, rest)
+        } else {
+            (64, arch.strip_prefix("rv64")?)
+        };
+        let mut extensions = Extensions::default();
+        for (i, part) in rest.split('_').enumerate() {
+            if i > 0 && part.starts_with(['z', 's', 'x']) {
+                continue;
+            }
+            let bytes = part.as_bytes();
+            let mut at = 0;
+            while at < bytes.len() {
+                let letter = bytes[at];
+                at += 1;
+                // skip the version, e.g. 2p1
+                let start = at;
+                while at < bytes.len() && bytes[at].is_ascii_digit() {
+                    at += 1;
+                }
+                let minor = bytes.get(at + 1).is_some_and(u8::is_ascii_digit);
+                if at > start && bytes.get(at) == Some(&b'p') && minor {
+                    at += 1;
+                    while at < bytes.len() && bytes[at].is_ascii_digit() {
+                        at += 1;
+                    }
+                }
+                match letter {
+                    b'g' => {
+                        extensions = Extensions {
+                            c: extensions.c,
+                            ..Extensions::all()
+                        }
+                    }
+                    b'm' => extensions.m = true,
+                    b'a' => extensions.a = true,
+                    b'f' => extensions.f = true,
+                    b'd' => extensions.d = true,
+                    b'c' => extensions.c = true,
+                    _ => {}
+                }
+            }
+        }
+        Some((xlen, extensions))
+    }
+}
+
+fn uleb128(reader: &mut Reader) -> Option<u64> {
+    let mut value = 0u64;
+    let mut shift = 0;
+    loop {
+        let byte = reader.u8().ok()?;
+        if shift < 64 {
+            value |= u64::from(byte & 0x7f) << shift;
+        }
+        shift += 7;
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+}
+
+// a NUL-terminated string ending before `end`
+fn string(reader: &mut Reader, end: u64) -> Option<String> {
+    let mut bytes = Vec::new();
+    while reader.position() < end {
+        match reader.u8().ok()? {
+            0 => return Some(String::from_utf8_lossy(&bytes).into_owned()),
+            byte => bytes.push(byte),
+        }
+    }
+    None
+}
+
+// The Tag_RISCV_arch string of a .riscv.attributes section: a format
+// version 'A', then subsections of a vendor ("riscv") holding the
+// attributes of the whole file (tag 1), as tag and value pairs where odd
+// tags have string values and even ones numbers
+pub fn arch_attribute(data: &[u8], endian: Endian) -> Option<String> {
+    let mut reader = Reader::new(data, endian);
+    if reader.u8().ok()? != b'A' {
+        return None;
+    }
+    while reader.position() < data.len() as u64 {
+        let start = reader.position();
+        let length = u64::from(reader.u32().ok()?);
+        let end = start.checked_add(length)?.min(data.len() as u64);
+        if length < 4 {
+            return None;
+        }
+        if string(&mut reader, end)? != "riscv" {
+            reader.seek(end);
+            continue;
+        }
+        while reader.position() < end {
+            let sub_start = reader.position();
+            let tag = uleb128(&mut reader)?;
+            let sub_length = u64::from(reader.u32().ok()?);
+            let sub_end = sub_start.checked_add(sub_length)?.min(end);
+            if sub_length == 0 {
+                return None;
+            }
+            if tag != 1 {
+                reader.seek(sub_end);
+                continue;
+            }
+            while reader.position() < sub_end {
+                let attribute = uleb128(&mut reader)?;
+                if attribute == TAG_RISCV_ARCH {
+                    return string(&mut reader, sub_end);
+                } else if !attribute.is_multiple_of(2) {
+                    string(&mut reader, sub_end)?;
+                } else {
+                    uleb128(&mut reader)?;
+                }
+            }
+        }
+    }
+    None
+}
+
+pub struct RiscV {
+    xlen: u32,
+    extensions: Extensions,
+}
+
+impl RiscV {
+    pub fn new(xlen: u32, extensions: Extensions) -> RiscV {
+        RiscV { xlen, extensions }
+    }
+
+    // the decoder for a file's XLEN (its class) and the extensions its
+    // attributes name
+    pub fn for_file(parser: &ELFParser) -> Result<RiscV, ParseError> {
+        let header = &parser.elf_header;
+        let xlen = if header.ei_class == Arch::B32 { 32 } else { 64 };
+        let section = parser
+            .sections()?
+            .iter()
+            .find(|s| s.sh_type == SHT_RISCV_ATTRIBUTES);
+        let arch = match section {
+            Some(section) => arch_attribute(parser.section_data(section)?, header.ei_data),
+            None => None,
+        };
+        Ok(match arch.as_deref().and_then(Extensions::from_arch) {
+            Some((xlen, extensions)) => RiscV::new(xlen, extensions),
+            None => RiscV::new(xlen, Extensions::all()),
+        })
+    }
+}
+
+impl Decoder for RiscV {
+    fn decode(&self, code: &[u8], address: u64) -> Instruction {
+        // the low bits of the first parcel give the length: 11 for 32-bit
+        // instructions, but 11111 for longer ones, which aren't decoded
+        let length = match code[0] & 0x1f {
+            0x1f => return Instruction::bad(&code[..code.len().min(2)], address),
+            low if low & 3 == 3 => 4,
+            _ => 2,
+        };
+        if code.len() < length {
+            return Instruction::bad(&code[..code.len().min(2)], address);
+        }
+        let mut state = State {
+            word: 0,
+            address,
+            xlen: self.xlen,
+            extensions: self.extensions,
+            flow: Flow::Next,
+            target: None,
+        };
+        let decoded = if length == 2 {
+            let parcel = u32::from(u16::from_le_bytes([code[0], code[1]]));
+            if parcel == 0 && self.extensions.c {
+                Some(("unimp".to_string(), Vec::new()))
+            } else {
+                state.hint(parcel).or_else(|| {
+                    state.word = state.expand(parcel)?;
+                    state.decode()
+                })
+            }
+        } else {
+            state.word = u32::from_le_bytes([code[0], code[1], code[2], code[3]]);
+            state.decode()
+        };
+        if decoded.as_ref().is_some_and(|(name, _)| name == "unimp") {
+            state.flow = Flow::Stop;
+        }
+        match decoded {
+            Some((mnemonic, operands)) => Instruction {
+                address,
+                bytes: code[..length].to_vec(),
+                mnemonic,
+                operands: operands.join(", "),
+                flow: state.flow,
+                target: state.target,
+                reference: None,
+                target_symbol: None,
+            },
+            None => Instruction::bad(&code[..length], address),
+        }
+    }
+}
+
+// the mnemonic and the operands, or nothing for encodings that aren't
+// decoded
+type Decoded = Option<(String, Vec<String>)>;
+
+fn insn(name: impl Into<String>, operands: Vec<String>) -> Decoded {
+    Some((name.into(), operands))
+}
+
+fn x(n: u32) -> String {
+    REGISTERS[n as usize].to_string()
+}
+
+fn f(n: u32) -> String {
+    FP_REGISTERS[n as usize].to_string()
+}
+
+// a memory operand, e.g. 8(sp)
+fn memory(offset: i32, base: u32) -> String {
+    format!("{offset}({})", REGISTERS[base as usize])
+}
+
+fn signed(value: u32, bits: u32) -> i32 {
+    ((value << (32 - bits)) as i32) >> (32 - bits)
+}
+
+// the predecessor or successor set of a fence, e.g. rw
+fn fence_set(bits: u32) -> String {
+    if bits == 0 {
+        return "unknown".to_string();
+    }
+    "iorw"
+        .chars()
+        .enumerate()
+        .filter(|(i, _)| bits >> (3 - i) & 1 != 0)
+        .map(|(_, c)| c)
+        .collect()
+}
+
+// the names llvm-objdump gives control and status registers, apart from
+// the numbered ones (pmpaddr0, mhpmcounter3, ...)
+const CSRS: [(u32, &str); 108] = [
+    (0x000, "ustatus"),
+    (0x001, "fflags"),
+    (0x002, "frm"),
+    (0x003, "fcsr"),
+    (0x004, "uie"),
+    (0x005, "utvec"),
+    (0x008, "vstart"),
+    (0x009, "vxsat"),
+    (0x00a, "vxrm"),
+    (0x00f, "vcsr"),
+    (0x015, "seed"),
+    (0x040, "uscratch"),
+    (0x041, "uepc"),
+    (0x042, "ucause"),
+    (0x043, "utval"),
+    (0x044, "uip"),
+    (0x100, "sstatus"),
+    (0x102, "sedeleg"),
+    (0x103, "sideleg"),
+    (0x104, "sie"),
+    (0x105, "stvec"),
+    (0x106, "scounteren"),
+    (0x10a, "senvcfg"),
+    (0x10c, "sstateen0"),
+    (0x10d, "sstateen1"),
+    (0x10e, "sstateen2"),
+    (0x10f, "sstateen3"),
+    (0x140, "sscratch"),
+    (0x141, "sepc"),
+    (0x142, "scause"),
+    (0x143, "stval"),
+    (0x144, "sip"),
+    (0x14d, "stimecmp"),
+    (0x180, "satp"),
+    (0x200, "vsstatus"),
+    (0x204, "vsie"),
+    (0x205, "vstvec"),
+    (0x240, "vsscratch"),
+    (0x241, "vsepc"),
+    (0x242, "vscause"),
+    (0x243, "vstval"),
+    (0x244, "vsip"),
+    (0x24d, "vstimecmp"),
+    (0x280, "vsatp"),
+    (0x300, "mstatus"),
+    (0x301, "misa"),
+    (0x302, "medeleg"),
+    (0x303, "mideleg"),
+    (0x304, "mie"),
+    (0x305, "mtvec"),
+    (0x306, "mcounteren"),
+    (0x30a, "menvcfg"),
+    (0x30c, "mstateen0"),
+    (0x30d, "mstateen1"),
+    (0x30e, "mstateen2"),
+    (0x30f, "mstateen3"),
+    (0x320, "mcountinhibit"),
+    (0x340, "mscratch"),
+    (0x341, "mepc"),
+    (0x342, "mcause"),
+    (0x343, "mtval"),
+    (0x344, "mip"),
+    (0x34a, "mtinst"),
+    (0x34b, "mtval2"),
+    (0x5a8, "scontext"),
+    (0x600, "hstatus"),
+    (0x602, "hedeleg"),
+    (0x603, "hideleg"),
+    (0x604, "hie"),
+    (0x605, "htimedelta"),
+    (0x606, "hcounteren"),
+    (0x607, "hgeie"),
+    (0x60a, "henvcfg"),
+    (0x60c, "hstateen0"),
+    (0x60d, "hstateen1"),
+    (0x60e, "hstateen2"),
+    (0x60f, "hstateen3"),
+    (0x643, "htval"),
+    (0x644, "hip"),
+    (0x645, "hvip"),
+    (0x64a, "htinst"),
+    (0x680, "hgatp"),
+    (0x6a8, "hcontext"),
+    (0x747, "mseccfg"),
+    (0x7a0, "tselect"),
+    (0x7a1, "tdata1"),
+    (0x7a2, "tdata2"),
+    (0x7a3, "tdata3"),
+    (0x7a8, "mcontext"),
+    (0x7b0, "dcsr"),
+    (0x7b1, "dpc"),
+    (0x7b2, "dscratch0"),
+    (0x7b3, "dscratch1"),
+    (0xb00, "mcycle"),
+    (0xb02, "minstret"),
+    (0xc00, "cycle"),
+    (0xc01, "time"),
+    (0xc02, "instret"),
+    (0xc20, "vl"),
+    (0xc21, "vtype"),
+    (0xc22, "vlenb"),
+    (0xda0, "scountovf"),
+    (0xe12, "hgeip"),
+    (0xf11, "mvendorid"),
+    (0xf12, "marchid"),
+    (0xf13, "mimpid"),
+    (0xf14, "mhartid"),
+    (0xf15, "mconfigptr"),
+];
+// the high halves of 64-bit registers on RV32
+const RV32_CSRS: [(u32, &str); 20] = [
+    (0x15d, "stimecmph"),
+    (0x25d, "vstimecmph"),
+    (0x310, "mstatush"),
+    (0x31a, "menvcfgh"),
+    (0x31c, "mstateen0h"),
+    (0x31d, "mstateen1h"),
+    (0x31e, "mstateen2h"),
+    (0x31f, "mstateen3h"),
+    (0x615, "htimedeltah"),
+    (0x61a, "henvcfgh"),
+    (0x61c, "hstateen0h"),
+    (0x61d, "hstateen1h"),
+    (0x61e, "hstateen2h"),
+    (0x61f, "hstateen3h"),
+    (0x757, "mseccfgh"),
+    (0xb80, "mcycleh"),
+    (0xb82, "minstreth"),
+    (0xc80, "cycleh"),
+    (0xc81, "timeh"),
+    (0xc82, "instreth"),
+];
+
+fn csr_name(csr: u32, xlen: u32) -> String {
+    let rv32 = if xlen == 32 { &RV32_CSRS[..] } else { &[] };
+    if let Some((_, name)) = CSRS.iter().chain(rv32).find(|&&(number, _)| number == csr) {
+        return name.to_string();
+    }
+    // the high halves are RV32 only, and so are the odd pmpcfg registers
+    let n = csr & 0x1f;
+    match csr {
+        0x3a0..=0x3af if xlen == 32 || csr.is_multiple_of(2) => format!("pmpcfg{}", csr - 0x3a0),
+        0x3b0..=0x3ef => format!("pmpaddr{}", csr - 0x3b0),
+        0x323..=0x33f => format!("mhpmevent{n}"),
+        0x723..=0x73f if xlen == 32 => format!("mhpmevent{n}h"),
+        0xb03..=0xb1f => format!("mhpmcounter{n}"),
+        0xb83..=0xb9f if xlen == 32 => format!("mhpmcounter{n}h"),
+        0xc03..=0xc1f => format!("hpmcounter{n}"),
+        0xc83..=0xc9f if xlen == 32 => format!("hpmcounter{n}h"),
+        _ => csr.to_string(),
+    }
+}
+
+// instruction formats, to build the instructions compressed ones expand to
+fn r_type(opcode: u32, rd: u32, funct3: u32, rs1: u32, rs2: u32, funct7: u32) -> u32 {
+    funct7 << 25 | rs2 << 20 | rs1 << 15 | funct3 << 12 | rd << 7 | opcode
+}
+
+fn i_type(opcode: u32, rd: u32, funct3: u32, rs1: u32, imm: i32) -> u32 {
+    (imm as u32 & 0xfff) << 20 | rs1 << 15 | funct3 << 12 | rd << 7 | opcode
+}
+
+fn s_type(opcode: u32, funct3: u32, rs1: u32, rs2: u32, imm: i32) -> u32 {
+    let imm = imm as u32;
+    (imm >> 5 & 0x7f) << 25 | rs2 << 20 | rs1 << 15 | funct3 << 12 | (imm & 0x1f) << 7 | opcode
+}
+
+fn b_type(funct3: u32, rs1: u32, rs2: u32, imm: i32) -> u32 {
+    let imm = imm as u32;
+    (imm >> 12 & 1) << 31
+        | (imm >> 5 & 0x3f) << 25
+        | rs2 << 20
+        | rs1 << 15
+        | funct3 << 12
+        | (imm >> 1 & 0xf) << 8
+        | (imm >> 11 & 1) << 7
+        | 0x63
+}
+
+fn j_type(rd: u32, imm: i32) -> u32 {
+    let imm = imm as u32;
+    (imm >> 20 & 1) << 31
+        | (imm >> 1 & 0x3ff) << 21
+        | (imm >> 11 & 1) << 20
+        | (imm >> 12 & 0xff) << 12
+        | rd << 7
+        | 0x6f
+}
+
+const LOAD: u32 = 0x03;
+const LOAD_FP: u32 = 0x07;
+const OP_IMM: u32 = 0x13;
+const OP_IMM_32: u32 = 0x1b;
+const STORE: u32 = 0x23;
+const STORE_FP: u32 = 0x27;
+const OP: u32 = 0x33;
+const LUI: u32 = 0x37;
+const OP_32: u32 = 0x3b;
+const JALR: u32 = 0x67;
+const SYSTEM: u32 = 0x73;
+
+struct State {
+    word: u32,
+    address: u64,
+    xlen: u32,
+    extensions: Extensions,
+    flow: Flow,
+    target: Option<u64>,
+}
+
+// compressed instructions
+impl State {
+    // The 32-bit instruction a compressed one expands to. c.mv expands to
+    // addi rather than add, which llvm-objdump shows as mv as well.
+    fn expand(&self, h: u32) -> Option<u32> {
+        if !self.extensions.c {
+            return None;
+        }
+        let bit = |n: u32| h >> n & 1;
+        let bits = |high: u32, low: u32| h >> low & ((1 << (high - low + 1)) - 1);
+        let rd = bits(11, 7);
+        let rs2 = bits(6, 2);
+        // the registers x8-x15 of the three-bit fields
+        let rd_short = bits(4, 2) + 8;
+        let rs1_short = bits(9, 7) + 8;
+        let rv64 = self.xlen == 64;
+        let immediate = signed(bit(12) << 5 | bits(6, 2), 6);
+        let word_offset = bits(12, 10) << 3 | bit(6) << 2 | bit(5) << 6;
+        let double_offset = (bits(12, 10) << 3 | bits(6, 5) << 6) as i32;
+        let shift = bit(12) << 5 | bits(6, 2);
+        let word = match (bits(1, 0), bits(15, 13)) {
+            (0, 0) => {
+                let imm = bits(10, 7) << 6 | bits(12, 11) << 4 | bit(5) << 3 | bit(6) << 2;
+                if imm == 0 {
+                    return None;
+                }
+                i_type(OP_IMM, rd_short, 0, 2, imm as i32)
+            }
+            (0, 1) if self.extensions.d => i_type(LOAD_FP, rd_short, 3, rs1_short, double_offset),
+            (0, 2) => i_type(LOAD, rd_short, 2, rs1_short, word_offset as i32),
+            (0, 3) if rv64 => i_type(LOAD, rd_short, 3, rs1_short, double_offset),
+            (0, 3) if self.extensions.f => {
+                i_type(LOAD_FP, rd_short, 2, rs1_short, word_offset as i32)
+            }
+            (0, 5) if self.extensions.d => s_type(STORE_FP, 3, rs1_short, rd_short, double_offset),
+            (0, 6) => s_type(STORE, 2, rs1_short, rd_short, word_offset as i32),
+            (0, 7) if rv64 => s_type(STORE, 3, rs1_short, rd_short, double_offset),
+            (0, 7) if self.extensions.f => {
+                s_type(STORE_FP, 2, rs1_short, rd_short, word_offset as i32)
+            }
+            (1, 0) => i_type(OP_IMM, rd, 0, rd, immediate),
+            (1, 1) if rv64 && rd != 0 => i_type(OP_IMM_32, rd, 0, rd, immediate),
+            (1, 1) if !rv64 => j_type(1, self.jump_offset(h)),
+            (1, 2) => i_type(OP_IMM, rd, 0, 0, immediate),
+            (1, 3) if rd == 2 => {
+                let imm = bit(12) << 9 | bits(4, 3) << 7 | bit(5) << 6 | bit(2) << 5 | bit(6) << 4;
+                if imm == 0 {
+                    return None;
+                }
+                i_type(OP_IMM, 2, 0, 2, signed(imm, 10))
+            }
+            (1, 3) => (immediate as u32 & 0xfffff) << 12 | rd << 7 | LUI,
+            (1, 4) => {
+                let rd = rs1_short;
+                match bits(11, 10) {
+                    0 | 1 if !rv64 && bit(12) == 1 => return None,
+                    0 => i_type(OP_IMM, rd, 5, rd, shift as i32),
+                    1 => i_type(OP_IMM, rd, 5, rd, (shift | 0x400) as i32),
+                    2 => i_type(OP_IMM, rd, 7, rd, immediate),
+                    _ => {
+                        let (opcode, funct3, funct7) = match (bit(12), bits(6, 5)) {
+                            (0, 0) => (OP, 0, 0x20),
+                            (0, 1) => (OP, 4, 0),
+                            (0, 2) => (OP, 6, 0),
+                            (0, _) => (OP, 7, 0),
+                            (_, 0) if rv64 => (OP_32, 0, 0x20),
+                            (_, 1) if rv64 => (OP_32, 0, 0),
+                            _ => return None,
+                        };
+                        r_type(opcode, rd, funct3, rd, rd_short, funct7)
+                    }
+                }
+            }
+            (1, 5) => j_type(0, self.jump_offset(h)),
+            (1, 6 | 7) => {
+                let imm = bit(12) << 8
+                    | bits(6, 5) << 6
+                    | bit(2) << 5
+                    | bits(11, 10) << 3
+                    | bits(4, 3) << 1;
+                b_type(bits(15, 13) - 6, rs1_short, 0, signed(imm, 9))
+            }
+            (2, 0) if rv64 || bit(12) == 0 => i_type(OP_IMM, rd, 1, rd, shift as i32),
+            (2, 1) if self.extensions.d => {
+                let imm = bit(12) << 5 | bits(6, 5) << 3 | bits(4, 2) << 6;
+                i_type(LOAD_FP, rd, 3, 2, imm as i32)
+            }
+            (2, 2) if rd != 0 => {
+                let imm = bit(12) << 5 | bits(6, 4) << 2 | bits(3, 2) << 6;
+                i_type(LOAD, rd, 2, 2, imm as i32)
+            }
+            (2, 3) if rv64 && rd != 0 => {
+                let imm = bit(12) << 5 | bits(6, 5) << 3 | bits(4, 2) << 6;
+                i_type(LOAD, rd, 3, 2, imm as i32)
+            }
+            (2, 3) if !rv64 && self.extensions.f => {
+                let imm = bit(12) << 5 | bits(6, 4) << 2 | bits(3, 2) << 6;
+                i_type(LOAD_FP, rd, 2, 2, imm as i32)
+            }
+            (2, 4) => match (bit(12), rd, rs2) {
+                (0, 0, _) => return None,
+                (0, _, 0) => i_type(JALR, 0, 0, rd, 0),
+                (0, _, _) => i_type(OP_IMM, rd, 0, rs2, 0),
+                (_, 0, 0) => 0x0010_0073,
+                (_, _, 0) => i_type(JALR, 1, 0, rd, 0),
+                _ => r_type(OP, rd, 0, rd, rs2, 0),
+            },
+            (2, 5) if self.extensions.d => {
+                let imm = bits(12, 10) << 3 | bits(9, 7) << 6;
+                s_type(STORE_FP, 3, 2, rs2, imm as i32)
+            }
+            (2, 6) => {
+                let imm = bits(12, 9) << 2 | bits(8, 7) << 6;
+                s_type(STORE, 2, 2, rs2, imm as i32)
+            }
+            (2, 7) if rv64 => {
+                let imm = bits(12, 10) << 3 | bits(9, 7) << 6;
+                s_type(STORE, 3, 2, rs2, imm as i32)
+            }
+            (2, 7) if self.extensions.f => {
+                let imm = bits(12, 9) << 2 | bits(8, 7) << 6;
+                s_type(STORE_FP, 2, 2, rs2, imm as i32)
+            }
+            _ => return None,
+        };
+        Some(word)
+    }
+
+    // HINTs, which llvm-objdump shows as the compressed instructions
+    // rather than their expansions, e.g. c.li zero, 3. The RV32 shifts by
+    // 32 or more are reserved, but shown that way as well.
+    fn hint(&self, h: u32) -> Decoded {
+        if !self.extensions.c {
+            return None;
+        }
+        let rd = h >> 7 & 0x1f;
+        let rs2 = h >> 2 & 0x1f;
+        let rd_short = REGISTERS[(h >> 7 & 7) as usize + 8];
+        let immediate = signed((h >> 12 & 1) << 5 | rs2, 6);
+        let shift = (h >> 12 & 1) << 5 | rs2;
+        let wide = self.xlen == 32 && shift >= 32;
+        match (h & 3, h >> 13) {
+            (1, 0) if rd == 0 && immediate != 0 => insn("c.nop", vec![immediate.to_string()]),
+            (1, 2) if rd == 0 => insn("c.li", vec![x(0), immediate.to_string()]),
+            (1, 3) if rd == 0 => insn("c.lui", vec![x(0), immediate.to_string()]),
+            (1, 4) if h >> 10 & 3 < 2 => {
+                let name = ["c.srli", "c.srai"][(h >> 10 & 1) as usize];
+                match shift {
+                    0 => insn(format!("{name}64"), vec![rd_short.to_string()]),
+                    _ if wide => insn(name, vec![rd_short.to_string(), shift.to_string()]),
+                    _ => None,
+                }
+            }
+            (2, 0) if shift == 0 => insn("c.slli64", vec![x(rd)]),
+            (2, 0) if rd == 0 || wide => insn("c.slli", vec![x(rd), shift.to_string()]),
+            (2, 4) if rd == 0 && rs2 != 0 => {
+                let name = ["c.mv", "c.add"][(h >> 12 & 1) as usize];
+                insn(name, vec![x(0), x(rs2)])
+            }
+            _ => None,
+        }
+    }
+
+    // the offset of c.j and c.jal
+    fn jump_offset(&self, h: u32) -> i32 {
+        let bit = |n: u32| h >> n & 1;
+        let imm = bit(12) << 11
+            | bit(11) << 4
+            | (h >> 9 & 3) << 8
+            | bit(8) << 10
+            | bit(7) << 6
+            | bit(6) << 7
+            | (h >> 3 & 7) << 1
+            | bit(2) << 5;
+        signed(imm, 12)
+    }
+}
+
+impl State {
+    fn rd(&self) -> u32 {
+        self.word >> 7 & 0x1f
+    }
+
+    fn rs1(&self) -> u32 {
+        self.word >> 15 & 0x1f
+    }
+
+    fn rs2(&self) -> u32 {
+        self.word >> 20 & 0x1f
+    }
+
+    fn rs3(&self) -> u32 {
+        self.word >> 27
+    }
+
+    fn funct3(&self) -> u32 {
+        self.word >> 12 & 7
+    }
+
+    fn funct7(&self) -> u32 {
+        self.word >> 25
+    }
+
+    fn i_immediate(&self) -> i32 {
+        self.word as i32 >> 20
+    }
+
+    fn s_immediate(&self) -> i32 {
+        (self.word as i32 >> 25) << 5 | self.rd() as i32
+    }
+
+    fn rv64(&self) -> bool {
+        self.xlen == 64
+    }
+
+    // the address `offset` bytes away from the instruction, wrapping
+    // around at XLEN
+    fn relative(&self, offset: i32) -> u64 {
+        let address = self.address.wrapping_add(offset as i64 as u64);
+        if self.rv64() {
+            address
+        } else {
+            address & 0xffff_ffff
+        }
+    }
+
+    fn decode(&mut self) -> Decoded {
+        match self.word & 0x7f {
+            LUI => insn("lui", vec![x(self.rd()), (self.word >> 12).to_string()]),
+            0x17 => insn("auipc", vec![x(self.rd()), (self.word >> 12).to_string()]),
+            0x6f => self.jal(),
+            JALR if self.funct3() == 0 => self.jalr(),
+            0x63 => self.branch(),
+            LOAD => self.load(),
+            STORE => self.store(),
+            OP_IMM => self.op_immediate(),
+            OP_IMM_32 if self.rv64() => self.op_immediate_32(),
+            OP => self.op(),
+            OP_32 if self.rv64() => self.op_32(),
+            0x0f => self.fence(),
+            SYSTEM => self.system(),
+            0x2f if self.extensions.a => self.atomic(),
+            LOAD_FP | STORE_FP => self.fp_memory(),
+            0x43 | 0x47 | 0x4b | 0x4f => self.fused(),
+            0x53 => self.fp(),
+            _ => None,
+        }
+    }
+
+    fn jal(&mut self) -> Decoded {
+        let w = self.word;
+        let imm =
+            (w >> 31) << 20 | (w >> 12 & 0xff) << 12 | (w >> 20 & 1) << 11 | (w >> 21 & 0x3ff) << 1;
+        let target = self.relative(signed(imm, 21));
+        self.target = Some(target);
+        let address = format!("{target:#x}");
+        match self.rd() {
+            0 => {
+                self.flow = Flow::Jump;
+                insn("j", vec![address])
+            }
+            1 => {
+                self.flow = Flow::Call;
+                insn("jal", vec![address])
+            }
+            rd => {
+                self.flow = Flow::Call;
+                insn("jal", vec![x(rd), address])
+            }
+        }
+    }
+
+    fn jalr(&mut self) -> Decoded {
+        let (rd, rs1, imm) = (self.rd(), self.rs1(), self.i_immediate());
+        self.flow = if rd == 0 { Flow::Jump } else { Flow::Call };
+        let target = if imm == 0 { x(rs1) } else { memory(imm, rs1) };
+        match rd {
+            0 if rs1 == 1 && imm == 0 => {
+                self.flow = Flow::Return;
+                insn("ret", vec![])
+            }
+            0 => insn("jr", vec![target]),
+            1 => insn("jalr", vec![target]),
+            _ => insn("jalr", vec![x(rd), target]),
+        }
+    }
+
+    fn branch(&mut self) -> Decoded {
+        let w = self.word;
+        let imm =
+            (w >> 31) << 12 | (w >> 7 & 1) << 11 | (w >> 25 & 0x3f) << 5 | (w >> 8 & 0xf) << 1;
+        let target = self.relative(signed(imm, 13));
+        let (rs1, rs2) = (self.rs1(), self.rs2());
+        let name = ["beq", "bne", "", "", "blt", "bge", "bltu", "bgeu"][self.funct3() as usize];
+        if name.is_empty() {
+            return None;
+        }
+        self.flow = Flow::Branch;
+        self.target = Some(target);
+        let address = format!("{target:#x}");
+        match (name, rs1, rs2) {
+            ("blt", 0, _) if rs2 != 0 => insn("bgtz", vec![x(rs2), address]),
+            ("bge", 0, _) => insn("blez", vec![x(rs2), address]),
+            ("beq" | "bne" | "blt" | "bge", _, 0) => {
+                let alias = [
+                    ("beq", "beqz"),
+                    ("bne", "bnez"),
+                    ("blt", "bltz"),
+                    ("bge", "bgez"),
+                ];
+                let (_, alias) = alias.iter().find(|(n, _)| *n == name)?;
+                insn(*alias, vec![x(rs1), address])
+            }
+            _ => insn(name, vec![x(rs1), x(rs2), address]),
+        }
+    }
+
+    fn load(&mut self) -> Decoded {
+        let name = match self.funct3() {
+            0 => "lb",
+            1 => "lh",
+            2 => "lw",
+            3 if self.rv64() => "ld",
+            4 => "lbu",
+            5 => "lhu",
+            6 if self.rv64() => "lwu",
+            _ => return None,
+        };
+        insn(
+            name,
+            vec![x(self.rd()), memory(self.i_immediate(), self.rs1())],
+        )
+    }
+
+    fn store(&mut self) -> Decoded {
+        let name = match self.funct3() {
+            0 => "sb",
+            1 => "sh",
+            2 => "sw",
+            3 if self.rv64() => "sd",
+            _ => return None,
+        };
+        insn(
+            name,
+            vec![x(self.rs2()), memory(self.s_immediate(), self.rs1())],
+        )
+    }
+
+    fn op_immediate(&mut self) -> Decoded {
+        let (rd, rs1, imm) = (self.rd(), self.rs1(), self.i_immediate());
+        // RV32 shifts by 32 or more are reserved, but shown like on RV64
+        let shamt = self.word >> 20 & 0x3f;
+        let high = self.word >> 26;
+        let name = match self.funct3() {
+            0 if rd == 0 && rs1 == 0 && imm == 0 => return insn("nop", vec![]),
+            0 if rs1 == 0 => return insn("li", vec![x(rd), imm.to_string()]),
+            0 if imm == 0 => return insn("mv", vec![x(rd), x(rs1)]),
+            0 => "addi",
+            1 if high == 0 => return insn("slli", vec![x(rd), x(rs1), shamt.to_string()]),
+            2 => "slti",
+            3 if imm == 1 => return insn("seqz", vec![x(rd), x(rs1)]),
+            3 => "sltiu",
+            4 if imm == -1 => return insn("not", vec![x(rd), x(rs1)]),
+            4 => "xori",
+            5 if high == 0 => return insn("srli", vec![x(rd), x(rs1), shamt.to_string()]),
+            5 if high == 0x10 => {
+                return insn("srai", vec![x(rd), x(rs1), shamt.to_string()]);
+            }
+            6 => "ori",
+            7 => "andi",
+            _ => return None,
+        };
+        insn(name, vec![x(rd), x(rs1), imm.to_string()])
+    }
+
+    fn op_immediate_32(&mut self) -> Decoded {
+        let (rd, rs1, imm) = (self.rd(), self.rs1(), self.i_immediate());
+        let shamt = self.rs2().to_string();
+        match (self.funct3(), self.funct7()) {
+            (0, _) if imm == 0 => insn("sext.w", vec![x(rd), x(rs1)]),
+            (0, _) => insn("addiw", vec![x(rd), x(rs1), imm.to_string()]),
+            (1, 0) => insn("slliw", vec![x(rd), x(rs1), shamt]),
+            (5, 0) => insn("srliw", vec![x(rd), x(rs1), shamt]),
+            (5, 0x20) => insn("sraiw", vec![x(rd), x(rs1), shamt]),
+            _ => None,
+        }
+    }
+
+    fn op(&mut self) -> Decoded {
+        let (rd, rs1, rs2) = (self.rd(), self.rs1(), self.rs2());
+        let name = match (self.funct7(), self.funct3()) {
+            (0, 0) => "add",
+            (0x20, 0) if rs1 == 0 => return insn("neg", vec![x(rd), x(rs2)]),
+            (0x20, 0) => "sub",
+            (0, 1) => "sll",
+            (0, 2) if rs2 == 0 => return insn("sltz", vec![x(rd), x(rs1)]),
+            (0, 2) if rs1 == 0 => return insn("sgtz", vec![x(rd), x(rs2)]),
+            (0, 2) => "slt",
+            (0, 3) if rs1 == 0 => return insn("snez", vec![x(rd), x(rs2)]),
+            (0, 3) => "sltu",
+            (0, 4) => "xor",
+            (0, 5) => "srl",
+            (0x20, 5) => "sra",
+            (0, 6) => "or",
+            (0, 7) => "and",
+            (1, funct3) if self.extensions.m => [
+                "mul", "mulh", "mulhsu", "mulhu", "div", "divu", "rem", "remu",
+            ][funct3 as usize],
+            _ => return None,
+        };
+        insn(name, vec![x(rd), x(rs1), x(rs2)])
+    }
+
+    fn op_32(&mut self) -> Decoded {
+        let (rd, rs1, rs2) = (self.rd(), self.rs1(), self.rs2());
+        let name = match (self.funct7(), self.funct3()) {
+            (0, 0) => "addw",
+            (0x20, 0) if rs1 == 0 => return insn("negw", vec![x(rd), x(rs2)]),
+            (0x20, 0) => "subw",
+            (0, 1) => "sllw",
+            (0, 5) => "srlw",
+            (0x20, 5) => "sraw",
+            (1, 0) if self.extensions.m => "mulw",
+            (1, 4) if self.extensions.m => "divw",
+            (1, 5) if self.extensions.m => "divuw",
+            (1, 6) if self.extensions.m => "remw",
+            (1, 7) if self.extensions.m => "remuw",
+            _ => return None,
+        };
+        insn(name, vec![x(rd), x(rs1), x(rs2)])
+    }
+
+    fn fence(&mut self) -> Decoded {
+        let (pred, succ, fm) = (self.word >> 24 & 15, self.word >> 20 & 15, self.word >> 28);
+        if self.rd() != 0 || self.rs1() != 0 || fm != 0 && fm != 8 {
+            return None;
+        }
+        match self.funct3() {
+            0 if fm == 8 && pred == 3 && succ == 3 => insn("fence.tso", vec![]),
+            0 if fm == 8 => None,
+            0 if pred == 15 && succ == 15 => insn("fence", vec![]),
+            0 => insn("fence", vec![fence_set(pred), fence_set(succ)]),
+            1 if self.word >> 20 == 0 => insn("fence.i", vec![]),
+            _ => None,
+        }
+    }
+
+    fn system(&mut self) -> Decoded {
+        let (rd, rs1, funct3) = (self.rd(), self.rs1(), self.funct3());
+        let csr = self.word >> 20;
+        if funct3 == 0 {
+            if rd != 0 {
+                return None;
+            }
+            return match (self.funct7(), self.rs2(), rs1) {
+                (0, 0, 0) => insn("ecall", vec![]),
+                (0, 1, 0) => {
+                    self.flow = Flow::Stop;
+                    insn("ebreak", vec![])
+                }
+                (0x08, 2, 0) | (0x18, 2, 0) => {
+                    self.flow = Flow::Return;
+                    insn(if csr == 0x102 { "sret" } else { "mret" }, vec![])
+                }
+                (0x08, 5, 0) => insn("wfi", vec![]),
+                (0x09, rs2, rs1) => {
+                    let operands = match (rs1, rs2) {
+                        (0, 0) => vec![],
+                        (_, 0) => vec![x(rs1)],
+                        _ => vec![x(rs1), x(rs2)],
+                    };
+                    insn("sfence.vma", operands)
+                }
+                _ => None,
+            };
+        }
+        let name = [
+            "", "csrrw", "csrrs", "csrrc", "", "csrrwi", "csrrsi", "csrrci",
+        ][funct3 as usize];
+        if name.is_empty() {
+            return None;
+        }
+        let immediate = funct3 >= 5;
+        let source = if immediate { rs1.to_string() } else { x(rs1) };
+        if let Some(alias) = self.csr_alias(name, csr, rd, rs1) {
+            return alias;
+        }
+        let csr = csr_name(csr, self.xlen);
+        match (name, rd, rs1) {
+            ("csrrs", _, 0) => insn("csrr", vec![x(rd), csr]),
+            (_, 0, _) => {
+                // csrrw zero, csr, rs is csrw csr, rs and so on
+                let alias = format!("csr{}", &name[4..]);
+                insn(alias, vec![csr, source])
+            }
+            _ => insn(name, vec![x(rd), csr, source]),
+        }
+    }
+
+    // the aliases of the CSR instructions on the floating-point CSRs and
+    // the counters, e.g. frcsr and rdcycle
+    fn csr_alias(&self, name: &str, csr: u32, rd: u32, rs1: u32) -> Option<Decoded> {
+        // csrrw zero, cycle, zero is unimp (the 32-bit one)
+        if name == "csrrw" && csr == 0xc00 && rd == 0 && rs1 == 0 {
+            return Some(insn("unimp", vec![]));
+        }
+        let counters = [(0xc00, "rdcycle"), (0xc01, "rdtime"), (0xc02, "rdinstret")];
+        if name == "csrrs" && rs1 == 0 {
+            let counter = counters.iter().find(|(number, _)| *number == csr & !0x80);
+            if let Some((_, counter)) = counter {
+                let high = if csr & 0x80 != 0 { "h" } else { "" };
+                if high.is_empty() || !self.rv64() {
+                    return Some(insn(format!("{counter}{high}"), vec![x(rd)]));
+                }
+            }
+        }
+        let suffix = match csr {
+            _ if !self.extensions.f => return None,
+            1 => "flags",
+            2 => "rm",
+            3 => "csr",
+            _ => return None,
+        };
+        match name {
+            "csrrs" if rs1 == 0 => Some(insn(format!("fr{suffix}"), vec![x(rd)])),
+            "csrrw" if rd == 0 => Some(insn(format!("fs{suffix}"), vec![x(rs1)])),
+            "csrrw" => Some(insn(format!("fs{suffix}"), vec![x(rd), x(rs1)])),
+            "csrrwi" if csr != 3 && rd == 0 => {
+                Some(insn(format!("fs{suffix}i"), vec![rs1.to_string()]))
+            }
+            "csrrwi" if csr != 3 => {
+                Some(insn(format!("fs{suffix}i"), vec![x(rd), rs1.to_string()]))
+            }
+            _ => None,
+        }
+    }
+
+    fn atomic(&mut self) -> Decoded {
+        let width = match self.funct3() {
+            2 => "w",
+            3 if self.rv64() => "d",
+            _ => return None,
+        };
+        let ordering = ["", ".rl", ".aq", ".aqrl"][(self.word >> 25 & 3) as usize];
+        let (rd, rs1, rs2) = (self.rd(), self.rs1(), self.rs2());
+        let address = format!("({})", REGISTERS[rs1 as usize]);
+        let operation = match self.word >> 27 {
+            0b00010 if rs2 == 0 => {
+                return insn(format!("lr.{width}{ordering}"), vec![x(rd), address]);
+            }
+            0b00011 => "sc",
+            0b00001 => "amoswap",
+            0b00000 => "amoadd",
+            0b00100 => "amoxor",
+            0b01100 => "amoand",
+            0b01000 => "amoor",
+            0b10000 => "amomin",
+            0b10100 => "amomax",
+            0b11000 => "amominu",
+            0b11100 => "amomaxu",
+            _ => return None,
+        };
+        insn(
+            format!("{operation}.{width}{ordering}"),
+            vec![x(rd), x(rs2), address],
+        )
+    }
+
+    // the precision suffix of a floating-point format field, if that
+    // extension is enabled
+    fn precision(&self, format: u32) -> Option<&'static str> {
+        match format {
+            0 if self.extensions.f => Some("s"),
+            1 if self.extensions.d => Some("d"),
+            _ => None,
+        }
+    }
+
+    fn fp_memory(&mut self) -> Decoded {
+        let (load, width) = (self.word & 0x7f == LOAD_FP, self.funct3());
+        let precision = match width {
+            2 => self.precision(0)?,
+            3 => self.precision(1)?,
+            _ => return None,
+        };
+        let width = if precision == "s" { "w" } else { "d" };
+        if load {
+            insn(
+                format!("fl{width}"),
+                vec![f(self.rd()), memory(self.i_immediate(), self.rs1())],
+            )
+        } else {
+            insn(
+                format!("fs{width}"),
+                vec![f(self.rs2()), memory(self.s_immediate(), self.rs1())],
+            )
+        }
+    }
+
+    // the rounding mode operand, unless it's the dynamic one or the
+    // operation is exact; None for the modes that aren't decoded
+    fn rounding(&self, operands: &mut Vec<String>, exact: bool) -> Option<()> {
+        let rm = ROUNDING[self.funct3() as usize];
+        if rm.is_empty() {
+            return None;
+        }
+        match rm {
+            // llvm-objdump only decodes exact operations with rne
+            _ if exact => (rm == "rne").then_some(()),
+            "dyn" => Some(()),
+            _ => {
+                operands.push(rm.to_string());
+                Some(())
+            }
+        }
+    }
+
+    fn fused(&mut self) -> Decoded {
+        let precision = self.precision(self.word >> 25 & 3)?;
+        let name = ["fmadd", "fmsub", "fnmsub", "fnmadd"][(self.word >> 2 & 3) as usize];
+        let mut operands = vec![f(self.rd()), f(self.rs1()), f(self.rs2()), f(self.rs3())];
+        self.rounding(&mut operands, false)?;
+        insn(format!("{name}.{precision}"), operands)
+    }
+
+    fn fp(&mut self) -> Decoded {
+        let (funct7, funct3) = (self.funct7(), self.funct3());
+        let (rd, rs1, rs2) = (self.rd(), self.rs1(), self.rs2());
+        let precision = self.precision(funct7 & 3)?;
+        let integer = |n: u32| -> Option<&'static str> {
+            match n {
+                0 => Some("w"),
+                1 => Some("wu"),
+                2 if self.rv64() => Some("l"),
+                3 if self.rv64() => Some("lu"),
+                _ => None,
+            }
+        };
+        let name = match funct7 >> 2 {
+            0 => "fadd",
+            1 => "fsub",
+            2 => "fmul",
+            3 => "fdiv",
+            0x0b if rs2 == 0 => {
+                let mut operands = vec![f(rd), f(rs1)];
+                self.rounding(&mut operands, false)?;
+                return insn(format!("fsqrt.{precision}"), operands);
+            }
+            4 => {
+                let name = ["fsgnj", "fsgnjn", "fsgnjx"].get(funct3 as usize)?;
+                if rs1 == rs2 {
+                    let alias = ["fmv", "fneg", "fabs"][funct3 as usize];
+                    return insn(format!("{alias}.{precision}"), vec![f(rd), f(rs1)]);
+                }
+                return insn(format!("{name}.{precision}"), vec![f(rd), f(rs1), f(rs2)]);
+            }
+            5 => {
+                let name = ["fmin", "fmax"].get(funct3 as usize)?;
+                return insn(format!("{name}.{precision}"), vec![f(rd), f(rs1), f(rs2)]);
+            }
+            8 => {
+                // between single and double precision
+                let source = self.precision(rs2).filter(|&source| source != precision)?;
+                let mut operands = vec![f(rd), f(rs1)];
+                self.rounding(&mut operands, precision == "d")?;
+                return insn(format!("fcvt.{precision}.{source}"), operands);
+            }
+            0x14 => {
+                let name = ["fle", "flt", "feq"].get(funct3 as usize)?;
+                return insn(format!("{name}.{precision}"), vec![x(rd), f(rs1), f(rs2)]);
+            }
+            0x18 => {
+                let mut operands = vec![x(rd), f(rs1)];
+                self.rounding(&mut operands, false)?;
+                return insn(format!("fcvt.{}.{precision}", integer(rs2)?), operands);
+            }
+            0x1a => {
+                let mut operands = vec![f(rd), x(rs1)];
+                // converting a word to double precision is exact
+                self.rounding(&mut operands, precision == "d" && rs2 < 2)?;
+                return insn(format!("fcvt.{precision}.{}", integer(rs2)?), operands);
+            }
+            0x1c if rs2 == 0 => {
+                let name = match (funct3, precision) {
+                    (0, "s") => "fmv.x.w",
+                    (0, _) if self.rv64() => "fmv.x.d",
+                    (1, _) => return insn(format!("fclass.{precision}"), vec![x(rd), f(rs1)]),
+                    _ => return None,
+                };
+                return insn(name, vec![x(rd), f(rs1)]);
+            }
+            0x1e if rs2 == 0 && funct3 == 0 => {
+                let name = match precision {
+                    "s" => "fmv.w.x",
+                    _ if self.rv64() => "fmv.d.x",
+                    _ => return None,
+                };
+                return insn(name, vec![f(rd), x(rs1)]);
+            }
+            _ => return None,
+        };
+        let mut operands = vec![f(rd), f(rs1), f(rs2)];
+        self.rounding(&mut operands, false)?;
+        insn(format!("{name}.{precision}"), operands)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::disasm::riscv::{arch_attribute, Extensions, RiscV};
+    use crate::disasm::{self, Decoder, Flow, Syntax};
+    use crate::parse::ELFParser;
+    use crate::utils::Endian;
+
+    // the instruction as llvm-objdump prints it
+    fn text(decoder: &RiscV, code: &[u8]) -> String {
+        let instruction = decoder.decode(code, 0x1000);
+        format!("{} {}", instruction.mnemonic, instruction.operands)
+            .trim_end()
+            .to_string()
+    }
+
+    #[test]
+    fn aliases() {
+        let rv64 = RiscV::new(64, Extensions::all());
+        let text = |word: u32| text(&rv64, &word.to_le_bytes());
+        assert_eq!(text(0x00008067), "ret");
+        assert_eq!(text(0x00000013), "nop");
+        assert_eq!(text(0x02a00513), "li a0, 42");
+        assert_eq!(text(0x00058513), "mv a0, a1");
+        assert_eq!(text(0xfff54513), "not a0, a0");
+        assert_eq!(text(0x0005051b), "sext.w a0, a0");
+        assert_eq!(text(0x300025f3), "csrr a1, mstatus");
+        assert_eq!(text(0x30046073), "csrsi mstatus, 8");
+        assert_eq!(text(0xc0002573), "rdcycle a0");
+        assert_eq!(text(0xc0001073), "unimp");
+        assert_eq!(text(0x0ff0000f), "fence");
+        assert_eq!(text(0x0310000f), "fence rw, w");
+        assert_eq!(text(0x1405a52f), "lr.w.aq a0, (a1)");
+        assert_eq!(text(0x22b58553), "fmv.d fa0, fa1");
+        assert_eq!(text(0x02c59553), "fadd.d fa0, fa1, fa2, rtz");
+        assert_eq!(text(0xc0051553), "fcvt.w.s a0, fa0, rtz");
+    }
+
+    #[test]
+    fn compressed() {
+        let rv32 = RiscV::new(32, Extensions::all());
+        let rv32_text = |parcel: u16| text(&rv32, &parcel.to_le_bytes());
+        assert_eq!(rv32_text(0x4505), "li a0, 1");
+        assert_eq!(rv32_text(0x1141), "addi sp, sp, -16");
+        assert_eq!(rv32_text(0xc606), "sw ra, 12(sp)");
+        assert_eq!(rv32_text(0x852e), "mv a0, a1");
+        assert_eq!(rv32_text(0x8082), "ret");
+        assert_eq!(rv32_text(0x9002), "ebreak");
+        assert_eq!(rv32_text(0x0000), "unimp");
+        // HINTs stay compressed
+        assert_eq!(rv32_text(0x400d), "c.li zero, 3");
+        // c.jal only exists on RV32; on RV64 it's c.addiw
+        assert_eq!(rv32_text(0x2801), "jal 0x1010");
+        let rv64 = RiscV::new(64, Extensions::all());
+        assert_eq!(text(&rv64, &[0x01, 0x28]), "sext.w a6, a6");
+    }
+
+    #[test]
+    fn control_flow() {
+        let rv32 = RiscV::new(32, Extensions::all());
+        let decode = |word: u32| rv32.decode(&word.to_le_bytes(), 0x1000);
+        let call = decode(0x010000ef);
+        assert_eq!((call.flow, call.target), (Flow::Call, Some(0x1010)));
+        let branch = decode(0xfe050ee3);
+        assert_eq!((branch.flow, branch.target), (Flow::Branch, Some(0xffc)));
+        assert_eq!(decode(0x00008067).flow, Flow::Return);
+        assert_eq!(decode(0x30200073).flow, Flow::Return);
+        assert_eq!(decode(0x00050067).flow, Flow::Jump);
+        assert_eq!(decode(0x00100073).flow, Flow::Stop);
+    }
+
+    #[test]
+    fn extensions() {
+        assert_eq!(
+            Extensions::from_arch("rv64gc"),
+            Some((64, Extensions::all()))
+        );
+        let (xlen, extensions) = Extensions::from_arch("rv32i2p1_m2p0_c2p0_zicsr2p0").unwrap();
+        assert_eq!(xlen, 32);
+        assert!(extensions.m && extensions.c && !extensions.a && !extensions.f);
+        assert_eq!(Extensions::from_arch("x86"), None);
+
+        // tag 4 (stack alignment) is a number, then tag 5 the string
+        let mut section = b"A\x1c\0\0\0riscv\0\x01\x12\0\0\0\x04\x10\x05rv32imc\0".to_vec();
+        assert_eq!(
+            arch_attribute(&section, Endian::Little).as_deref(),
+            Some("rv32imc")
+        );
+        section.truncate(20);
+        assert_eq!(arch_attribute(&section, Endian::Little), None);
+
+        // instructions of disabled extensions, and RV64 ones on RV32
+        let rv32i = RiscV::new(32, Extensions::default());
+        assert_eq!(text(&rv32i, &0x02c58533u32.to_le_bytes()), "(bad)");
+        assert_eq!(rv32i.decode(&[0x05, 0x45], 0).bytes.len(), 2);
+        assert_eq!(text(&rv32i, &[0x05, 0x45]), "(bad)");
+        assert_eq!(text(&rv32i, &0x0085b503u32.to_le_bytes()), "(bad)");
+    }
+
+    #[test]
+    fn fixture() {
+        let contents = include_bytes!("../../tests/fixtures/hello-riscv.o");
+        let parser = ELFParser::from_bytes(contents).unwrap();
+        let decoder = RiscV::for_file(&parser).unwrap();
+        assert_eq!(decoder.xlen, 32);
+        assert!(decoder.extensions.a && !decoder.extensions.f);
+        let listings = disasm::disassemble(&parser, Syntax::Att).unwrap();
+        let lines: Vec<String> = listings[0]
+            .instructions
+            .iter()
+            .map(|i| format!("{} {}", i.mnemonic, i.operands))
+            .collect();
+        assert_eq!(lines.len(), 19);
+        assert_eq!(lines[1], "sw ra, 12(sp)");
+        assert_eq!(lines[2], "jal 0x14");
+        assert_eq!(lines[6], "li a7, 93");
+        assert_eq!(lines[14], "beqz a0, 0x34");
+        assert_eq!(lines[16], "amoadd.w.aq zero, a0, (a1)");
+        assert_eq!(lines[18], "j 0x14");
+    }
+}
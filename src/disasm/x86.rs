@@ -0,0 +1,2380 @@
+// x86-64 decoder for 64-bit mode, printing Intel or AT&T syntax the way
+// GNU objdump does.
+//
+// The opcode tables use the operand notation of the Intel manual: a
+// letter for where the operand comes from (E: ModRM r/m, G: ModRM reg,
+// I: immediate, V/W: xmm or ymm in reg and r/m, H: VEX.vvvv, ...) and one
+// for its size (b, w, d, q, v: the operand size, x: xmm or ymm by VEX.L,
+// ...). SSE entries are shared with their VEX forms, which add the `v` and
+// the extra H source.
+use std::borrow::Cow;
+
+use crate::disasm::{Decoder, Flow, Instruction, Syntax};
+
+const REG64: [&str; 16] = [
+    "rax", "rcx", "rdx", "rbx", "rsp", "rbp", "rsi", "rdi", "r8", "r9", "r10", "r11", "r12", "r13",
+    "r14", "r15",
+];
+const REG32: [&str; 16] = [
+    "eax", "ecx", "edx", "ebx", "esp", "ebp", "esi", "edi", "r8d", "r9d", "r10d", "r11d", "r12d",
+    "r13d", "r14d", "r15d",
+];
+const REG16: [&str; 16] = [
+    "ax", "cx", "dx", "bx", "sp", "bp", "si", "di", "r8w", "r9w", "r10w", "r11w", "r12w", "r13w",
+    "r14w", "r15w",
+];
+// with a REX prefix, 4 to 7 are the low bytes of rsp, rbp, rsi and rdi
+const REG8_REX: [&str; 16] = [
+    "al", "cl", "dl", "bl", "spl", "bpl", "sil", "dil", "r8b", "r9b", "r10b", "r11b", "r12b",
+    "r13b", "r14b", "r15b",
+];
+const REG8: [&str; 8] = ["al", "cl", "dl", "bl", "ah", "ch", "dh", "bh"];
+// the index registers of VSIB memory operands
+const XMM: [&str; 16] = [
+    "xmm0", "xmm1", "xmm2", "xmm3", "xmm4", "xmm5", "xmm6", "xmm7", "xmm8", "xmm9", "xmm10",
+    "xmm11", "xmm12", "xmm13", "xmm14", "xmm15",
+];
+const YMM: [&str; 16] = [
+    "ymm0", "ymm1", "ymm2", "ymm3", "ymm4", "ymm5", "ymm6", "ymm7", "ymm8", "ymm9", "ymm10",
+    "ymm11", "ymm12", "ymm13", "ymm14", "ymm15",
+];
+const SEGMENTS: [&str; 6] = ["es", "cs", "ss", "ds", "fs", "gs"];
+
+const ALU: [&str; 8] = ["add", "or", "adc", "sbb", "and", "sub", "xor", "cmp"];
+const SHIFTS: [&str; 8] = ["rol", "ror", "rcl", "rcr", "shl", "shr", "shl", "sar"];
+const CONDITIONS: [&str; 16] = [
+    "o", "no", "b", "ae", "e", "ne", "be", "a", "s", "ns", "p", "np", "l", "ge", "le", "g",
+];
+// the predicates of cmpps and friends: SSE has the first 8, AVX all 32
+const COMPARISONS: [&str; 32] = [
+    "eq", "lt", "le", "unord", "neq", "nlt", "nle", "ord", "eq_uq", "nge", "ngt", "false",
+    "neq_oq", "ge", "gt", "true", "eq_os", "lt_oq", "le_oq", "unord_s", "neq_us", "nlt_uq",
+    "nle_uq", "ord_s", "eq_us", "nge_uq", "ngt_uq", "false_os", "neq_os", "ge_oq", "gt_oq",
+    "true_us",
+];
+
+// instructions are at most 15 bytes long
+const MAX_LENGTH: usize = 15;
+
+pub struct X86 {
+    syntax: Syntax,
+}
+
+impl X86 {
+    pub fn new(syntax: Syntax) -> X86 {
+        X86 { syntax }
+    }
+}
+
+impl Decoder for X86 {
+    fn decode(&self, code: &[u8], address: u64) -> Instruction {
+        let code = &code[..code.len().min(MAX_LENGTH)];
+        let mut state = State::new(code, address, self.syntax);
+        match state.decode() {
+            Some(()) => state.finish(),
+            None => Instruction::bad(&code[..1], address),
+        }
+    }
+}
+
+// an opcode table entry: the mnemonic and the operand specification
+type Entry = (Cow<'static, str>, &'static str);
+
+// "mnemonic operands" as an entry
+fn entry(text: &'static str) -> Option<Entry> {
+    let (name, spec) = text.split_once(' ').unwrap_or((text, ""));
+    Some((Cow::Borrowed(name), spec))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Size {
+    None,
+    B,
+    W,
+    D,
+    Q,
+    // x87 extended precision
+    T,
+    // cmpxchg16b
+    O,
+    X,
+    Y,
+    // a far pointer
+    F,
+}
+
+impl Size {
+    fn ptr(self) -> &'static str {
+        match self {
+            Size::None => "",
+            Size::B => "BYTE PTR ",
+            Size::W => "WORD PTR ",
+            Size::D => "DWORD PTR ",
+            Size::Q => "QWORD PTR ",
+            Size::T => "TBYTE PTR ",
+            Size::O => "OWORD PTR ",
+            Size::X => "XMMWORD PTR ",
+            Size::Y => "YMMWORD PTR ",
+            Size::F => "FWORD PTR ",
+        }
+    }
+
+    // AT&T mnemonic suffix
+    fn suffix(self) -> &'static str {
+        match self {
+            Size::B => "b",
+            Size::W => "w",
+            Size::D => "l",
+            Size::Q => "q",
+            _ => "",
+        }
+    }
+
+    fn mask(self, value: u64) -> u64 {
+        match self {
+            Size::B => value & 0xff,
+            Size::W => value & 0xffff,
+            Size::D => value & 0xffff_ffff,
+            _ => value,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Memory {
+    size: Size,
+    segment: Option<&'static str>,
+    base: Option<&'static str>,
+    index: Option<&'static str>,
+    scale: u8,
+    disp: i64,
+    // a displacement is encoded, even if it is zero
+    has_disp: bool,
+}
+
+#[derive(Debug, Clone)]
+enum Operand {
+    // general-purpose register: the AT&T suffix isn't needed
+    Gpr(&'static str),
+    Reg(String),
+    Imm(u64),
+    // the implicit count of D0-D3 shifts
+    One,
+    // the I/O port in dx
+    Port,
+    Memory(Memory),
+    Relative(u64),
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Vex {
+    // 1: 0F, 2: 0F38, 3: 0F3A
+    map: u8,
+    w: bool,
+    l: bool,
+    vvvv: usize,
+    // implied prefix; 0: none, 1: 66, 2: F3, 3: F2
+    pp: usize,
+    r: bool,
+    x: bool,
+    b: bool,
+}
+
+struct State<'a> {
+    code: &'a [u8],
+    pos: usize,
+    address: u64,
+    syntax: Syntax,
+    // legacy prefixes, in order
+    prefixes: Vec<u8>,
+    rex: u8,
+    vex: Option<Vex>,
+    // the 66, F2 or F3 prefix that selected an SSE instruction
+    mandatory: Option<u8>,
+    sse: bool,
+    opcode: u8,
+    modrm: Option<u8>,
+    mnemonic: String,
+    operands: Vec<Operand>,
+    // the size of an integer memory operand, for the AT&T suffix
+    sized: Option<Size>,
+    // call or jump through a register or memory: `*` in AT&T
+    indirect: bool,
+    string: bool,
+    // the displacement of a rip-relative operand
+    rip_disp: Option<i64>,
+    target: Option<u64>,
+    // the SIB index is a vector register, a ymm one when true (gathers)
+    vsib: Option<bool>,
+    // the number of that register
+    vsib_index: Option<usize>,
+}
+
+impl<'a> State<'a> {
+    fn new(code: &'a [u8], address: u64, syntax: Syntax) -> State<'a> {
+        State {
+            code,
+            pos: 0,
+            address,
+            syntax,
+            prefixes: Vec::new(),
+            rex: 0,
+            vex: None,
+            mandatory: None,
+            sse: false,
+            opcode: 0,
+            modrm: None,
+            mnemonic: String::new(),
+            operands: Vec::new(),
+            sized: None,
+            indirect: false,
+            string: false,
+            rip_disp: None,
+            target: None,
+            vsib: None,
+            vsib_index: None,
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.code.get(self.pos).copied()
+    }
+
+    fn byte(&mut self) -> Option<u8> {
+        let byte = self.peek()?;
+        self.pos += 1;
+        Some(byte)
+    }
+
+    // a little-endian immediate or displacement of `size` bytes, sign-extended
+    fn signed(&mut self, size: usize) -> Option<i64> {
+        let bytes = self.code.get(self.pos..self.pos + size)?;
+        self.pos += size;
+        let mut value = 0u64;
+        for (i, byte) in bytes.iter().enumerate() {
+            value |= (*byte as u64) << (8 * i);
+        }
+        let shift = 64 - 8 * size as u32;
+        Some(((value << shift) as i64) >> shift)
+    }
+
+    fn has(&self, prefix: u8) -> bool {
+        self.prefixes.contains(&prefix)
+    }
+
+    // the last of F2 and F3
+    fn rep(&self) -> Option<u8> {
+        self.prefixes
+            .iter()
+            .rev()
+            .find(|&&p| p == 0xf2 || p == 0xf3)
+            .copied()
+    }
+
+    fn w(&self) -> bool {
+        self.vex.map_or(self.rex & 8 != 0, |vex| vex.w)
+    }
+
+    fn r(&self) -> usize {
+        let set = self.vex.map_or(self.rex & 4 != 0, |vex| vex.r);
+        if set {
+            8
+        } else {
+            0
+        }
+    }
+
+    fn x(&self) -> usize {
+        let set = self.vex.map_or(self.rex & 2 != 0, |vex| vex.x);
+        if set {
+            8
+        } else {
+            0
+        }
+    }
+
+    fn b(&self) -> usize {
+        let set = self.vex.map_or(self.rex & 1 != 0, |vex| vex.b);
+        if set {
+            8
+        } else {
+            0
+        }
+    }
+
+    fn l(&self) -> bool {
+        self.vex.is_some_and(|vex| vex.l)
+    }
+
+    // 66 as the operand-size prefix rather than a mandatory one
+    fn opsize(&self) -> bool {
+        self.has(0x66) && self.mandatory != Some(0x66)
+    }
+
+    fn addrsize(&self) -> bool {
+        self.has(0x67)
+    }
+
+    fn osize(&self) -> Size {
+        if self.w() {
+            Size::Q
+        } else if self.opsize() {
+            Size::W
+        } else {
+            Size::D
+        }
+    }
+
+    // for instructions that default to 64 bits: push, pop, call, jmp
+    fn osize64(&self) -> Size {
+        if self.opsize() {
+            Size::W
+        } else {
+            Size::Q
+        }
+    }
+
+    fn modrm(&mut self) -> Option<u8> {
+        if self.modrm.is_none() {
+            self.modrm = Some(self.byte()?);
+        }
+        self.modrm
+    }
+
+    fn mode(&mut self) -> Option<u8> {
+        Some(self.modrm()? >> 6)
+    }
+
+    fn reg(&mut self) -> Option<usize> {
+        Some((self.modrm()? >> 3 & 7) as usize)
+    }
+
+    fn gpr(&self, n: usize, size: Size) -> &'static str {
+        match size {
+            Size::B if self.rex != 0 || self.vex.is_some() => REG8_REX[n],
+            Size::B => REG8[n & 7],
+            Size::W => REG16[n],
+            Size::D => REG32[n],
+            _ => REG64[n],
+        }
+    }
+
+    fn segment(&self) -> Option<&'static str> {
+        self.prefixes.iter().rev().find_map(|p| match p {
+            0x64 => Some("fs"),
+            0x65 => Some("gs"),
+            _ => None,
+        })
+    }
+
+    // the memory operand of ModRM, whose mode isn't 3
+    fn memory(&mut self, size: Size) -> Option<Operand> {
+        let modrm = self.modrm()?;
+        let mode = modrm >> 6;
+        let names = if self.addrsize() { &REG32 } else { &REG64 };
+        let mut memory = Memory {
+            size,
+            segment: self.segment(),
+            base: None,
+            index: None,
+            scale: 1,
+            disp: 0,
+            has_disp: mode != 0,
+        };
+        let rm = modrm & 7;
+        if rm != 4 && self.vsib.is_some() {
+            return None;
+        }
+        if rm == 4 {
+            let sib = self.byte()?;
+            let index = (sib >> 3 & 7) as usize | self.x();
+            memory.scale = 1 << (sib >> 6);
+            let no_base = sib & 7 == 5 && mode == 0;
+            if let Some(ymm) = self.vsib {
+                memory.index = Some(if ymm { YMM[index] } else { XMM[index] });
+                self.vsib_index = Some(index);
+            } else if index != 4 {
+                memory.index = Some(names[index]);
+            } else if memory.scale != 1 || sib & 7 != 4 && !no_base {
+                // no index, but a SIB byte that doesn't need to be there
+                memory.index = Some(if self.addrsize() { "eiz" } else { "riz" });
+            }
+            if no_base {
+                memory.has_disp = true;
+                memory.disp = self.signed(4)?;
+                return Some(Operand::Memory(memory));
+            }
+            memory.base = Some(names[(sib & 7) as usize | self.b()]);
+        } else if rm == 5 && mode == 0 {
+            memory.base = Some(if self.addrsize() { "eip" } else { "rip" });
+            memory.has_disp = true;
+            memory.disp = self.signed(4)?;
+            self.rip_disp = Some(memory.disp);
+            return Some(Operand::Memory(memory));
+        } else {
+            memory.base = Some(names[rm as usize | self.b()]);
+        }
+        match mode {
+            1 => memory.disp = self.signed(1)?,
+            2 => memory.disp = self.signed(4)?,
+            _ => {}
+        }
+        Some(Operand::Memory(memory))
+    }
+
+    // the r/m operand as a general-purpose register or memory
+    fn rm_gpr(&mut self, size: Size) -> Option<Operand> {
+        if self.mode()? == 3 {
+            let n = (self.modrm()? & 7) as usize | self.b();
+            return Some(Operand::Gpr(self.gpr(n, size)));
+        }
+        self.sized = Some(size);
+        self.memory(size)
+    }
+
+    // the r/m operand as an xmm or ymm register, or memory of `size`
+    fn rm_vector(&mut self, ymm: bool, size: Size) -> Option<Operand> {
+        if self.mode()? == 3 {
+            let n = (self.modrm()? & 7) as usize | self.b();
+            return Some(vector(n, ymm));
+        }
+        self.memory(size)
+    }
+
+    fn rm_mmx(&mut self, size: Size) -> Option<Operand> {
+        if self.mode()? == 3 {
+            let n = self.modrm()? & 7;
+            return Some(Operand::Reg(format!("mm{n}")));
+        }
+        self.memory(size)
+    }
+
+    fn memory_only(&mut self, size: Size) -> Option<Operand> {
+        if self.mode()? == 3 {
+            return None;
+        }
+        self.memory(size)
+    }
+
+    fn register_only(&mut self) -> Option<usize> {
+        if self.mode()? != 3 {
+            return None;
+        }
+        Some((self.modrm()? & 7) as usize | self.b())
+    }
+
+    fn immediate(&mut self, bytes: usize, size: Size) -> Option<Operand> {
+        let value = self.signed(bytes)? as u64;
+        Some(Operand::Imm(size.mask(value)))
+    }
+
+    fn relative(&mut self, bytes: usize) -> Option<Operand> {
+        let disp = self.signed(bytes)?;
+        // relative to the end of the instruction; the offset is last
+        let target = self
+            .address
+            .wrapping_add(self.pos as u64)
+            .wrapping_add(disp as u64);
+        self.target = Some(target);
+        Some(Operand::Relative(target))
+    }
+
+    // one operand of a specification
+    fn operand(&mut self, token: &str) -> Option<Option<Operand>> {
+        let vex = self.vex;
+        let l = self.l();
+        let operand = match token {
+            "Eb" => self.rm_gpr(Size::B)?,
+            "Ew" => self.rm_gpr(Size::W)?,
+            "Ed" => self.rm_gpr(Size::D)?,
+            "Eq" => self.rm_gpr(Size::Q)?,
+            "Ev" => self.rm_gpr(self.osize())?,
+            "Ey" => self.rm_gpr(if self.w() { Size::Q } else { Size::D })?,
+            "Evq" => self.rm_gpr(self.osize64())?,
+            // a register of the operand size, or a word in memory
+            "RvMw" if self.mode()? == 3 => self.rm_gpr(self.osize())?,
+            "RvMw" => self.memory(Size::W)?,
+            // a 32-bit register, or memory of the element size
+            "RdMb" | "RdMw" if self.mode()? == 3 => self.rm_gpr(Size::D)?,
+            "RdMb" => self.memory(Size::B)?,
+            "RdMw" => self.memory(Size::W)?,
+            "Rq" => Operand::Gpr(REG64[self.register_only()?]),
+            "Ry" => {
+                let n = self.register_only()?;
+                let size = if self.w() { Size::Q } else { Size::D };
+                Operand::Gpr(self.gpr(n, size))
+            }
+            "Rv" => {
+                let n = self.register_only()?;
+                Operand::Gpr(self.gpr(n, self.osize()))
+            }
+            "Gb" | "Gw" | "Gd" | "Gq" | "Gv" | "Gy" => {
+                let size = match token {
+                    "Gb" => Size::B,
+                    "Gw" => Size::W,
+                    "Gd" => Size::D,
+                    "Gq" => Size::Q,
+                    "Gv" => self.osize(),
+                    _ if self.w() => Size::Q,
+                    _ => Size::D,
+                };
+                let n = self.reg()? | self.r();
+                Operand::Gpr(self.gpr(n, size))
+            }
+            // the register in the low bits of the opcode
+            "Zb" => Operand::Gpr(self.gpr((self.opcode & 7) as usize | self.b(), Size::B)),
+            "Zv" => Operand::Gpr(self.gpr((self.opcode & 7) as usize | self.b(), self.osize())),
+            "Zq" => Operand::Gpr(self.gpr((self.opcode & 7) as usize | self.b(), self.osize64())),
+            // VEX.vvvv as a general-purpose register
+            "By" => {
+                let size = if self.w() { Size::Q } else { Size::D };
+                Operand::Gpr(self.gpr(vex?.vvvv, size))
+            }
+            "M" => self.memory_only(Size::None)?,
+            "Mb" => self.memory_only(Size::B)?,
+            "Mw" => self.memory_only(Size::W)?,
+            "Md" => self.memory_only(Size::D)?,
+            "Mq" => self.memory_only(Size::Q)?,
+            "Mt" => self.memory_only(Size::T)?,
+            "Mo" => self.memory_only(Size::O)?,
+            "Mf" => self.memory_only(Size::F)?,
+            "Mdq" => self.memory_only(Size::X)?,
+            "Mx" => self.memory_only(if l { Size::Y } else { Size::X })?,
+            // VSIB: elements of W's size, indexed by xmm, or by the
+            // vector length
+            "MVdq" | "MVx" => {
+                self.vsib = Some(token == "MVx" && l);
+                let size = if self.w() { Size::Q } else { Size::D };
+                self.memory_only(size)?
+            }
+            "Mv" => {
+                let size = self.osize();
+                self.memory_only(size)?
+            }
+            "My" => {
+                let size = if self.w() { Size::Q } else { Size::D };
+                self.sized = Some(size);
+                self.memory_only(size)?
+            }
+            "Ib" => self.immediate(1, Size::B)?,
+            "Iw" => self.immediate(2, Size::W)?,
+            // an 8-bit immediate sign-extended to the operand size
+            "Is" => self.immediate(1, self.osize())?,
+            "Isq" => self.immediate(1, self.osize64())?,
+            "Iz" | "Izq" => {
+                let size = if token == "Iz" {
+                    self.osize()
+                } else {
+                    self.osize64()
+                };
+                let bytes = if size == Size::W { 2 } else { 4 };
+                self.immediate(bytes, size)?
+            }
+            "Iv" => match self.osize() {
+                Size::W => self.immediate(2, Size::W)?,
+                Size::D => self.immediate(4, Size::D)?,
+                _ => self.immediate(8, Size::Q)?,
+            },
+            "I1" => Operand::One,
+            "Jb" => self.relative(1)?,
+            "Jz" => self.relative(4)?,
+            // a 64-bit absolute address; the register gives the size
+            "Ob" | "Ov" => {
+                let bytes = if self.addrsize() { 4 } else { 8 };
+                Operand::Memory(Memory {
+                    size: Size::None,
+                    segment: self.segment(),
+                    base: None,
+                    index: None,
+                    scale: 1,
+                    disp: self.signed(bytes)?,
+                    has_disp: true,
+                })
+            }
+            "Sw" => Operand::Reg(SEGMENTS.get(self.reg()?)?.to_string()),
+            "Cq" => Operand::Reg(format!("cr{}", self.reg()? | self.r())),
+            "Dq" => Operand::Reg(format!("db{}", self.reg()? | self.r())),
+            "AL" => Operand::Gpr("al"),
+            "CL" => Operand::Gpr("cl"),
+            "DX" => Operand::Port,
+            "rAX" => Operand::Gpr(self.gpr(0, self.osize())),
+            "eAX" => Operand::Gpr(if self.opsize() { "ax" } else { "eax" }),
+            "XMM0" => Operand::Reg("xmm0".to_string()),
+            "FS" => Operand::Reg("fs".to_string()),
+            "GS" => Operand::Reg("gs".to_string()),
+            // vector registers in reg
+            "Vx" => vector(self.reg()? | self.r(), l),
+            "Vdq" => vector(self.reg()? | self.r(), false),
+            "Vqq" => vector(self.reg()? | self.r(), true),
+            // vector registers in VEX.vvvv, only there with VEX
+            "Hx" | "Hdq" | "Hqq" => match vex {
+                None => return Some(None),
+                Some(vex) => vector(vex.vvvv, token == "Hqq" || token == "Hx" && l),
+            },
+            // vector registers or memory in r/m
+            "Wx" => self.rm_vector(l, if l { Size::Y } else { Size::X })?,
+            "Wdq" => self.rm_vector(false, Size::X)?,
+            "Wqq" => self.rm_vector(true, Size::Y)?,
+            "Wss" | "Wd" => self.rm_vector(false, Size::D)?,
+            "Wsd" | "Wq" => self.rm_vector(false, Size::Q)?,
+            "Ww" => self.rm_vector(false, Size::W)?,
+            "Wb" => self.rm_vector(false, Size::B)?,
+            // half, quarter and eighth of the destination's width
+            "Wh" => self.rm_vector(false, if l { Size::X } else { Size::Q })?,
+            "Wqu" => self.rm_vector(false, if l { Size::Q } else { Size::D })?,
+            "We" => self.rm_vector(false, if l { Size::D } else { Size::W })?,
+            // movddup reads a double, or all of a ymm
+            "Wdup" => self.rm_vector(l, if l { Size::Y } else { Size::Q })?,
+            "Ux" => {
+                let n = self.register_only()?;
+                vector(n, l)
+            }
+            "Udq" => {
+                let n = self.register_only()?;
+                vector(n, false)
+            }
+            // register in the high bits of an 8-bit immediate
+            "Lx" => {
+                let n = self.byte()? >> 4;
+                vector(n as usize, l)
+            }
+            // MMX
+            "Pq" => Operand::Reg(format!("mm{}", self.reg()?)),
+            "Qq" => self.rm_mmx(Size::Q)?,
+            "Qd" => self.rm_mmx(Size::D)?,
+            "Nq" => {
+                let n = self.register_only()? & 7;
+                Operand::Reg(format!("mm{n}"))
+            }
+            _ => unreachable!("operand {token}"),
+        };
+        Some(Some(operand))
+    }
+
+    // the instruction of an entry: mnemonic and operands
+    fn apply(&mut self, entry: Option<Entry>) -> Option<()> {
+        let (name, spec) = entry?;
+        if self.vex.is_some() && self.sse {
+            // MMX and the legacy-only instructions have no VEX form
+            let legacy = ["Pq", "Qq", "Qd", "Nq", "XMM0"];
+            if spec.split(',').any(|token| legacy.contains(&token)) || name.starts_with("sha") {
+                return None;
+            }
+        }
+        for token in spec.split(',').filter(|token| !token.is_empty()) {
+            if let Some(operand) = self.operand(token)? {
+                self.operands.push(operand);
+            }
+        }
+        // "intel|att" and "w0/w1" alternatives
+        let name = match name.split_once('|') {
+            Some((intel, _)) if self.syntax == Syntax::Intel => intel,
+            Some((_, att)) => att,
+            None => &name,
+        };
+        let name = match name.split_once('/') {
+            Some((_, w1)) if self.w() => w1,
+            Some((w0, _)) => w0,
+            None => name,
+        };
+        let vex = if self.vex.is_some() && self.sse {
+            "v"
+        } else {
+            ""
+        };
+        self.mnemonic = format!("{vex}{name}");
+        // movzx and movsx carry the destination size in AT&T
+        if let Some(name) = self.mnemonic.strip_suffix('*') {
+            self.mnemonic = format!("{name}{}", self.osize().suffix());
+        }
+        Some(())
+    }
+
+    fn decode(&mut self) -> Option<()> {
+        while let Some(byte) = self.peek() {
+            match byte {
+                0xf0 | 0xf2 | 0xf3 | 0x26 | 0x2e | 0x36 | 0x3e | 0x64 | 0x65 | 0x66 | 0x67 => {
+                    self.prefixes.push(byte);
+                    self.pos += 1;
+                }
+                _ => break,
+            }
+        }
+        if self.peek()? & 0xf0 == 0x40 {
+            self.rex = self.byte()?;
+        }
+        let opcode = self.byte()?;
+        self.opcode = opcode;
+        let entry = match opcode {
+            0xc4 | 0xc5 => {
+                // VEX can't follow REX, 66, F2 or F3
+                if self.rex != 0 || self.has(0x66) || self.rep().is_some() {
+                    return None;
+                }
+                self.vex(opcode)?;
+                self.vex_entry()
+            }
+            0x0f => {
+                let opcode = self.byte()?;
+                self.opcode = opcode;
+                match opcode {
+                    0x38 => {
+                        self.opcode = self.byte()?;
+                        self.map_0f38(self.opcode)
+                    }
+                    0x3a => {
+                        self.opcode = self.byte()?;
+                        self.map_0f3a(self.opcode)
+                    }
+                    _ => self.map_0f(opcode),
+                }
+            }
+            0x9b if matches!(self.peek(), Some(0xd9 | 0xdb | 0xdd | 0xdf)) => {
+                return self.waiting()
+            }
+            0xd8..=0xdf => return self.x87(opcode),
+            0x6c..=0x6f | 0xa4..=0xa7 | 0xaa..=0xaf => return self.string(opcode),
+            _ => self.one_byte(opcode),
+        };
+        self.apply(entry)
+    }
+
+    fn vex(&mut self, opcode: u8) -> Option<()> {
+        let first = self.byte()?;
+        let (map, w, second) = if opcode == 0xc5 {
+            (1, false, first)
+        } else {
+            let second = self.byte()?;
+            (first & 0x1f, second & 0x80 != 0, second)
+        };
+        self.vex = Some(Vex {
+            map,
+            w,
+            l: second & 4 != 0,
+            vvvv: (!second >> 3 & 15) as usize,
+            pp: (second & 3) as usize,
+            r: first & 0x80 == 0,
+            x: opcode == 0xc4 && first & 0x40 == 0,
+            b: opcode == 0xc4 && first & 0x20 == 0,
+        });
+        Some(())
+    }
+
+    fn vex_entry(&mut self) -> Option<Entry> {
+        let vex = self.vex?;
+        let opcode = self.byte()?;
+        self.opcode = opcode;
+        match vex.map {
+            1 => match opcode {
+                0x77 if vex.l => entry("vzeroall"),
+                0x77 => entry("vzeroupper"),
+                // only the SSE instructions have VEX forms
+                _ => self.map_0f(opcode).filter(|_| self.sse),
+            },
+            2 => match self.vex_0f38(opcode) {
+                Some(entry) => Some(entry),
+                None => self.map_0f38(opcode).filter(|_| self.sse),
+            },
+            3 => match self.vex_0f3a(opcode) {
+                Some(entry) => Some(entry),
+                None => self.map_0f3a(opcode).filter(|_| self.sse),
+            },
+            _ => None,
+        }
+    }
+
+    // the variant for the mandatory prefix: none, 66, F3 or F2
+    fn sse(&mut self, variants: [&'static str; 4]) -> Option<Entry> {
+        let index = match (self.vex, self.rep()) {
+            (Some(vex), _) => vex.pp,
+            (None, Some(0xf3)) => 2,
+            (None, Some(0xf2)) => 3,
+            (None, _) if self.has(0x66) => 1,
+            _ => 0,
+        };
+        self.sse = true;
+        if self.vex.is_none() {
+            self.mandatory = [None, Some(0x66), Some(0xf3), Some(0xf2)][index];
+        }
+        match variants[index] {
+            "" => None,
+            text => entry(text),
+        }
+    }
+
+    fn one_byte(&mut self, opcode: u8) -> Option<Entry> {
+        let low = opcode & 7;
+        match opcode {
+            0x00..=0x3f if low < 6 => {
+                let alu = ALU[(opcode >> 3) as usize];
+                let spec = ["Eb,Gb", "Ev,Gv", "Gb,Eb", "Gv,Ev", "AL,Ib", "rAX,Iz"][low as usize];
+                Some((Cow::Borrowed(alu), spec))
+            }
+            0x50..=0x57 => entry("push Zq"),
+            0x58..=0x5f => entry("pop Zq"),
+            0x63 if self.w() => entry("movsxd|movslq Gv,Ed"),
+            0x63 => entry("movsxd Gv,Ed"),
+            0x68 => entry("push Izq"),
+            0x69 => entry("imul Gv,Ev,Iz"),
+            0x6a => entry("push Isq"),
+            0x6b => entry("imul Gv,Ev,Is"),
+            0x70..=0x7f => self.conditional("j", "Jb"),
+            0x80 | 0x81 | 0x83 => {
+                let alu = ALU[self.reg()?];
+                let spec = match opcode {
+                    0x80 => "Eb,Ib",
+                    0x81 => "Ev,Iz",
+                    _ => "Ev,Is",
+                };
+                Some((Cow::Borrowed(alu), spec))
+            }
+            0x84 => entry("test Eb,Gb"),
+            0x85 => entry("test Ev,Gv"),
+            0x86 => entry("xchg Eb,Gb"),
+            0x87 => entry("xchg Ev,Gv"),
+            0x88 => entry("mov Eb,Gb"),
+            0x89 => entry("mov Ev,Gv"),
+            0x8a => entry("mov Gb,Eb"),
+            0x8b => entry("mov Gv,Ev"),
+            0x8c => entry("mov RvMw,Sw"),
+            0x8d if self.mode()? != 3 => entry("lea Gv,M"),
+            0x8e => entry("mov Sw,RvMw"),
+            0x8f if self.reg()? == 0 => entry("pop Evq"),
+            0x90 if self.b() != 0 => entry("xchg Zv,rAX"),
+            0x90 if self.rep() == Some(0xf3) => {
+                self.mandatory = Some(0xf3);
+                entry("pause")
+            }
+            0x90 if self.opsize() => entry("xchg rAX,rAX"),
+            0x90 => entry("nop"),
+            0x91..=0x97 => entry("xchg Zv,rAX"),
+            0x98 => match self.osize() {
+                Size::W => entry("cbw|cbtw"),
+                Size::D => entry("cwde|cwtl"),
+                _ => entry("cdqe|cltq"),
+            },
+            0x99 => match self.osize() {
+                Size::W => entry("cwd|cwtd"),
+                Size::D => entry("cdq|cltd"),
+                _ => entry("cqo|cqto"),
+            },
+            0x9b => entry("fwait"),
+            0x9c if self.opsize() => entry("pushfw"),
+            0x9c => entry("pushf"),
+            0x9d if self.opsize() => entry("popfw"),
+            0x9d => entry("popf"),
+            0x9e => entry("sahf"),
+            0x9f => entry("lahf"),
+            0xa0 => entry("movabs AL,Ob"),
+            0xa1 => entry("movabs rAX,Ov"),
+            0xa2 => entry("movabs Ob,AL"),
+            0xa3 => entry("movabs Ov,rAX"),
+            0xa8 => entry("test AL,Ib"),
+            0xa9 => entry("test rAX,Iz"),
+            0xb0..=0xb7 => entry("mov Zb,Ib"),
+            0xb8..=0xbf if self.w() => entry("movabs Zv,Iv"),
+            0xb8..=0xbf => entry("mov Zv,Iv"),
+            0xc0 | 0xc1 | 0xd0..=0xd3 => {
+                let shift = SHIFTS[self.reg()?];
+                let spec = match opcode {
+                    0xc0 => "Eb,Ib",
+                    0xc1 => "Ev,Ib",
+                    0xd0 => "Eb,I1",
+                    0xd1 => "Ev,I1",
+                    0xd2 => "Eb,CL",
+                    _ => "Ev,CL",
+                };
+                Some((Cow::Borrowed(shift), spec))
+            }
+            0xc2 => entry("ret Iw"),
+            0xc3 => entry("ret"),
+            0xc6 if self.modrm()? == 0xf8 => entry("xabort Ib"),
+            0xc6 if self.reg()? == 0 => entry("mov Eb,Ib"),
+            0xc7 if self.modrm()? == 0xf8 => entry("xbegin Jz"),
+            0xc7 if self.reg()? == 0 => entry("mov Ev,Iz"),
+            0xc8 => entry("enter Iw,Ib"),
+            0xc9 => entry("leave"),
+            0xca => entry("retf|lret Iw"),
+            0xcb => entry("retf|lret"),
+            0xcc => entry("int3"),
+            0xcd => entry("int Ib"),
+            0xcf if self.w() => entry("iretq"),
+            0xcf if self.opsize() => entry("iretw"),
+            0xcf => entry("iret"),
+            0xd7 => {
+                self.string = true;
+                let base = if self.addrsize() { "ebx" } else { "rbx" };
+                self.operands.push(Operand::Memory(Memory {
+                    size: Size::B,
+                    segment: Some(self.segment().unwrap_or("ds")),
+                    base: Some(base),
+                    index: None,
+                    scale: 1,
+                    disp: 0,
+                    has_disp: false,
+                }));
+                entry("xlat")
+            }
+            0xe0 => entry("loopne Jb"),
+            0xe1 => entry("loope Jb"),
+            0xe2 => entry("loop Jb"),
+            0xe3 if self.addrsize() => entry("jecxz Jb"),
+            0xe3 => entry("jrcxz Jb"),
+            0xe4 => entry("in AL,Ib"),
+            0xe5 => entry("in eAX,Ib"),
+            0xe6 => entry("out Ib,AL"),
+            0xe7 => entry("out Ib,eAX"),
+            0xe8 => entry("call Jz"),
+            0xe9 => entry("jmp Jz"),
+            0xeb => entry("jmp Jb"),
+            0xec => entry("in AL,DX"),
+            0xed => entry("in eAX,DX"),
+            0xee => entry("out DX,AL"),
+            0xef => entry("out DX,eAX"),
+            0xf1 => entry("int1"),
+            0xf4 => entry("hlt"),
+            0xf5 => entry("cmc"),
+            0xf6 | 0xf7 => {
+                let reg = self.reg()?;
+                let name = ["test", "test", "not", "neg", "mul", "imul", "div", "idiv"][reg];
+                let spec = match (opcode, reg) {
+                    (0xf6, 0 | 1) => "Eb,Ib",
+                    (0xf6, _) => "Eb",
+                    (_, 0 | 1) => "Ev,Iz",
+                    _ => "Ev",
+                };
+                Some((Cow::Borrowed(name), spec))
+            }
+            0xf8 => entry("clc"),
+            0xf9 => entry("stc"),
+            0xfa => entry("cli"),
+            0xfb => entry("sti"),
+            0xfc => entry("cld"),
+            0xfd => entry("std"),
+            0xfe => match self.reg()? {
+                0 => entry("inc Eb"),
+                1 => entry("dec Eb"),
+                _ => None,
+            },
+            0xff => {
+                let reg = self.reg()?;
+                self.indirect = (2..=5).contains(&reg);
+                match reg {
+                    0 => entry("inc Ev"),
+                    1 => entry("dec Ev"),
+                    2 => entry("call Evq"),
+                    3 => entry("call|lcall Mf"),
+                    4 => entry("jmp Evq"),
+                    5 => entry("jmp|ljmp Mf"),
+                    6 => entry("push Evq"),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    // jcc, setcc and cmovcc, from the low 4 bits of the opcode
+    fn conditional(&mut self, name: &str, spec: &'static str) -> Option<Entry> {
+        let condition = CONDITIONS[(self.opcode & 15) as usize];
+        Some((Cow::Owned(format!("{name}{condition}")), spec))
+    }
+
+    fn map_0f(&mut self, opcode: u8) -> Option<Entry> {
+        match opcode {
+            0x00 => {
+                let name = ["sldt", "str", "lldt", "ltr", "verr", "verw"].get(self.reg()?)?;
+                let spec = if self.reg()? < 2 { "RvMw" } else { "Ew" };
+                Some((Cow::Borrowed(*name), spec))
+            }
+            0x01 => self.group7(),
+            0x02 => entry("lar Gv,Ew"),
+            0x03 => entry("lsl Gv,Ew"),
+            0x05 => entry("syscall"),
+            0x06 => entry("clts"),
+            0x07 => entry("sysretd/sysretq|sysretl/sysretq"),
+            0x08 => entry("invd"),
+            0x09 => entry("wbinvd"),
+            0x0b => entry("ud2"),
+            0x0d => match self.reg()? {
+                1 => entry("prefetchw Mb"),
+                2 => entry("prefetchwt1 Mb"),
+                _ => entry("prefetch Mb"),
+            },
+            // register-to-register moves of scalars merge the upper part
+            // from VEX.vvvv; loads and stores don't have it
+            0x10 if self.mode()? == 3 => self.sse([
+                "movups Vx,Wx",
+                "movupd Vx,Wx",
+                "movss Vdq,Hdq,Udq",
+                "movsd Vdq,Hdq,Udq",
+            ]),
+            0x10 => self.sse([
+                "movups Vx,Wx",
+                "movupd Vx,Wx",
+                "movss Vdq,Wss",
+                "movsd Vdq,Wsd",
+            ]),
+            0x11 if self.mode()? == 3 => self.sse([
+                "movups Wx,Vx",
+                "movupd Wx,Vx",
+                "movss Udq,Hdq,Vdq",
+                "movsd Udq,Hdq,Vdq",
+            ]),
+            0x11 => self.sse([
+                "movups Wx,Vx",
+                "movupd Wx,Vx",
+                "movss Wss,Vdq",
+                "movsd Wsd,Vdq",
+            ]),
+            0x12 => {
+                let register = self.mode()? == 3;
+                self.sse([
+                    if register {
+                        "movhlps Vdq,Hdq,Udq"
+                    } else {
+                        "movlps Vdq,Hdq,Mq"
+                    },
+                    "movlpd Vdq,Hdq,Mq",
+                    "movsldup Vx,Wx",
+                    "movddup Vx,Wdup",
+                ])
+            }
+            0x13 => self.sse(["movlps Mq,Vdq", "movlpd Mq,Vdq", "", ""]),
+            0x14 => self.sse(["unpcklps Vx,Hx,Wx", "unpcklpd Vx,Hx,Wx", "", ""]),
+            0x15 => self.sse(["unpckhps Vx,Hx,Wx", "unpckhpd Vx,Hx,Wx", "", ""]),
+            0x16 => {
+                let register = self.mode()? == 3;
+                self.sse([
+                    if register {
+                        "movlhps Vdq,Hdq,Udq"
+                    } else {
+                        "movhps Vdq,Hdq,Mq"
+                    },
+                    "movhpd Vdq,Hdq,Mq",
+                    "movshdup Vx,Wx",
+                    "",
+                ])
+            }
+            0x17 => self.sse(["movhps Mq,Vdq", "movhpd Mq,Vdq", "", ""]),
+            0x18 => match self.reg()? {
+                reg @ 0..=3 if self.mode()? != 3 => {
+                    let name = ["prefetchnta", "prefetcht0", "prefetcht1", "prefetcht2"][reg];
+                    Some((Cow::Borrowed(name), "Mb"))
+                }
+                _ => entry("nop Ev"),
+            },
+            0x1e if self.rep() == Some(0xf3) && self.modrm()? == 0xfa => {
+                self.mandatory = Some(0xf3);
+                entry("endbr64")
+            }
+            0x1e if self.rep() == Some(0xf3) && self.modrm()? == 0xfb => {
+                self.mandatory = Some(0xf3);
+                entry("endbr32")
+            }
+            0x19..=0x1f => entry("nop Ev"),
+            0x20 => entry("mov Rq,Cq"),
+            0x21 => entry("mov Rq,Dq"),
+            0x22 => entry("mov Cq,Rq"),
+            0x23 => entry("mov Dq,Rq"),
+            0x28 => self.sse(["movaps Vx,Wx", "movapd Vx,Wx", "", ""]),
+            0x29 => self.sse(["movaps Wx,Vx", "movapd Wx,Vx", "", ""]),
+            0x2a => self.sse([
+                "cvtpi2ps Vdq,Qq",
+                "cvtpi2pd Vdq,Qq",
+                "cvtsi2ss Vdq,Hdq,Ey",
+                "cvtsi2sd Vdq,Hdq,Ey",
+            ]),
+            0x2b => self.sse(["movntps Mx,Vx", "movntpd Mx,Vx", "", ""]),
+            0x2c => self.sse([
+                "cvttps2pi Pq,Wq",
+                "cvttpd2pi Pq,Wdq",
+                "cvttss2si Gy,Wss",
+                "cvttsd2si Gy,Wsd",
+            ]),
+            0x2d => self.sse([
+                "cvtps2pi Pq,Wq",
+                "cvtpd2pi Pq,Wdq",
+                "cvtss2si Gy,Wss",
+                "cvtsd2si Gy,Wsd",
+            ]),
+            0x2e => self.sse(["ucomiss Vdq,Wss", "ucomisd Vdq,Wsd", "", ""]),
+            0x2f => self.sse(["comiss Vdq,Wss", "comisd Vdq,Wsd", "", ""]),
+            0x30 => entry("wrmsr"),
+            0x31 => entry("rdtsc"),
+            0x32 => entry("rdmsr"),
+            0x33 => entry("rdpmc"),
+            0x34 => entry("sysenter"),
+            0x35 => entry("sysexitd/sysexitq|sysexitl/sysexitq"),
+            0x40..=0x4f => self.conditional("cmov", "Gv,Ev"),
+            0x50 => self.sse(["movmskps Gd,Ux", "movmskpd Gd,Ux", "", ""]),
+            0x51 => self.sse([
+                "sqrtps Vx,Wx",
+                "sqrtpd Vx,Wx",
+                "sqrtss Vdq,Hdq,Wss",
+                "sqrtsd Vdq,Hdq,Wsd",
+            ]),
+            0x52 => self.sse(["rsqrtps Vx,Wx", "", "rsqrtss Vdq,Hdq,Wss", ""]),
+            0x53 => self.sse(["rcpps Vx,Wx", "", "rcpss Vdq,Hdq,Wss", ""]),
+            0x54 => self.sse(["andps Vx,Hx,Wx", "andpd Vx,Hx,Wx", "", ""]),
+            0x55 => self.sse(["andnps Vx,Hx,Wx", "andnpd Vx,Hx,Wx", "", ""]),
+            0x56 => self.sse(["orps Vx,Hx,Wx", "orpd Vx,Hx,Wx", "", ""]),
+            0x57 => self.sse(["xorps Vx,Hx,Wx", "xorpd Vx,Hx,Wx", "", ""]),
+            0x58 => self.sse(arithmetic("add")),
+            0x59 => self.sse(arithmetic("mul")),
+            0x5a => self.sse([
+                "cvtps2pd Vx,Wh",
+                "cvtpd2ps Vdq,Wx",
+                "cvtss2sd Vdq,Hdq,Wss",
+                "cvtsd2ss Vdq,Hdq,Wsd",
+            ]),
+            0x5b => self.sse(["cvtdq2ps Vx,Wx", "cvtps2dq Vx,Wx", "cvttps2dq Vx,Wx", ""]),
+            0x5c => self.sse(arithmetic("sub")),
+            0x5d => self.sse(arithmetic("min")),
+            0x5e => self.sse(arithmetic("div")),
+            0x5f => self.sse(arithmetic("max")),
+            0x60..=0x6b => {
+                let (name, spec) = [
+                    ("punpcklbw Pq,Qd", "punpcklbw Vx,Hx,Wx"),
+                    ("punpcklwd Pq,Qd", "punpcklwd Vx,Hx,Wx"),
+                    ("punpckldq Pq,Qd", "punpckldq Vx,Hx,Wx"),
+                    ("packsswb Pq,Qq", "packsswb Vx,Hx,Wx"),
+                    ("pcmpgtb Pq,Qq", "pcmpgtb Vx,Hx,Wx"),
+                    ("pcmpgtw Pq,Qq", "pcmpgtw Vx,Hx,Wx"),
+                    ("pcmpgtd Pq,Qq", "pcmpgtd Vx,Hx,Wx"),
+                    ("packuswb Pq,Qq", "packuswb Vx,Hx,Wx"),
+                    ("punpckhbw Pq,Qq", "punpckhbw Vx,Hx,Wx"),
+                    ("punpckhwd Pq,Qq", "punpckhwd Vx,Hx,Wx"),
+                    ("punpckhdq Pq,Qq", "punpckhdq Vx,Hx,Wx"),
+                    ("packssdw Pq,Qq", "packssdw Vx,Hx,Wx"),
+                ][(opcode - 0x60) as usize];
+                self.sse([name, spec, "", ""])
+            }
+            0x6c => self.sse(["", "punpcklqdq Vx,Hx,Wx", "", ""]),
+            0x6d => self.sse(["", "punpckhqdq Vx,Hx,Wx", "", ""]),
+            0x6e => self.sse(["movd/movq Pq,Ey", "movd/movq Vdq,Ey", "", ""]),
+            0x6f => self.sse(["movq Pq,Qq", "movdqa Vx,Wx", "movdqu Vx,Wx", ""]),
+            0x70 => self.sse([
+                "pshufw Pq,Qq,Ib",
+                "pshufd Vx,Wx,Ib",
+                "pshufhw Vx,Wx,Ib",
+                "pshuflw Vx,Wx,Ib",
+            ]),
+            0x71..=0x73 => {
+                let names = match opcode {
+                    0x71 => ["", "", "psrlw", "", "psraw", "", "psllw", ""],
+                    0x72 => ["", "", "psrld", "", "psrad", "", "pslld", ""],
+                    _ => ["", "", "psrlq", "psrldq", "", "", "psllq", "pslldq"],
+                };
+                let reg = self.reg()?;
+                let name = names[reg];
+                if name.is_empty() {
+                    return None;
+                }
+                // the whole-register byte shifts only exist for xmm
+                let mmx = if reg == 3 || reg == 7 { "" } else { "_ Nq,Ib" };
+                let (_, spec) = self.sse([mmx, "_ Hx,Ux,Ib", "", ""])?;
+                Some((Cow::Borrowed(name), spec))
+            }
+            0x74 => self.sse(["pcmpeqb Pq,Qq", "pcmpeqb Vx,Hx,Wx", "", ""]),
+            0x75 => self.sse(["pcmpeqw Pq,Qq", "pcmpeqw Vx,Hx,Wx", "", ""]),
+            0x76 => self.sse(["pcmpeqd Pq,Qq", "pcmpeqd Vx,Hx,Wx", "", ""]),
+            0x77 => entry("emms"),
+            0x7c => self.sse(["", "haddpd Vx,Hx,Wx", "", "haddps Vx,Hx,Wx"]),
+            0x7d => self.sse(["", "hsubpd Vx,Hx,Wx", "", "hsubps Vx,Hx,Wx"]),
+            0x7e => self.sse(["movd/movq Ey,Pq", "movd/movq Ey,Vdq", "movq Vdq,Wq", ""]),
+            0x7f => self.sse(["movq Qq,Pq", "movdqa Wx,Vx", "movdqu Wx,Vx", ""]),
+            0x80..=0x8f => self.conditional("j", "Jz"),
+            0x90..=0x9f => self.conditional("set", "Eb"),
+            0xa0 => entry("push FS"),
+            0xa1 => entry("pop FS"),
+            0xa2 => entry("cpuid"),
+            0xa3 => entry("bt Ev,Gv"),
+            0xa4 => entry("shld Ev,Gv,Ib"),
+            0xa5 => entry("shld Ev,Gv,CL"),
+            0xa8 => entry("push GS"),
+            0xa9 => entry("pop GS"),
+            0xaa => entry("rsm"),
+            0xab => entry("bts Ev,Gv"),
+            0xac => entry("shrd Ev,Gv,Ib"),
+            0xad => entry("shrd Ev,Gv,CL"),
+            0xae => self.group15(),
+            0xaf => entry("imul Gv,Ev"),
+            0xb0 => entry("cmpxchg Eb,Gb"),
+            0xb1 => entry("cmpxchg Ev,Gv"),
+            0xb3 => entry("btr Ev,Gv"),
+            0xb6 => entry("movzx|movzb* Gv,Eb"),
+            0xb7 => entry("movzx|movzw* Gv,Ew"),
+            0xb8 if self.rep() == Some(0xf3) => {
+                self.mandatory = Some(0xf3);
+                entry("popcnt Gv,Ev")
+            }
+            0xb9 => entry("ud1 Gv,Ev"),
+            0xba => {
+                let name = ["", "", "", "", "bt", "bts", "btr", "btc"][self.reg()?];
+                if name.is_empty() {
+                    return None;
+                }
+                Some((Cow::Borrowed(name), "Ev,Ib"))
+            }
+            0xbb => entry("btc Ev,Gv"),
+            0xbc if self.rep() == Some(0xf3) => {
+                self.mandatory = Some(0xf3);
+                entry("tzcnt Gv,Ev")
+            }
+            0xbc => entry("bsf Gv,Ev"),
+            0xbd if self.rep() == Some(0xf3) => {
+                self.mandatory = Some(0xf3);
+                entry("lzcnt Gv,Ev")
+            }
+            0xbd => entry("bsr Gv,Ev"),
+            0xbe => entry("movsx|movsb* Gv,Eb"),
+            0xbf => entry("movsx|movsw* Gv,Ew"),
+            0xc0 => entry("xadd Eb,Gb"),
+            0xc1 => entry("xadd Ev,Gv"),
+            0xc2 => self.sse([
+                "cmpps Vx,Hx,Wx,Ib",
+                "cmppd Vx,Hx,Wx,Ib",
+                "cmpss Vdq,Hdq,Wss,Ib",
+                "cmpsd Vdq,Hdq,Wsd,Ib",
+            ]),
+            0xc3 => entry("movnti My,Gy"),
+            0xc4 => self.sse(["pinsrw Pq,RdMw,Ib", "pinsrw Vdq,Hdq,RdMw,Ib", "", ""]),
+            0xc5 => self.sse(["pextrw Gd,Nq,Ib", "pextrw Gd,Udq,Ib", "", ""]),
+            0xc6 => self.sse(["shufps Vx,Hx,Wx,Ib", "shufpd Vx,Hx,Wx,Ib", "", ""]),
+            0xc7 => self.group9(),
+            0xc8..=0xcf => entry("bswap Zv"),
+            0xd0 => self.sse(["", "addsubpd Vx,Hx,Wx", "", "addsubps Vx,Hx,Wx"]),
+            0xd6 => self.sse(["", "movq Wq,Vdq", "movq2dq Vdq,Nq", "movdq2q Pq,Udq"]),
+            0xd7 => self.sse(["pmovmskb Gd,Nq", "pmovmskb Gd,Ux", "", ""]),
+            0xe6 => self.sse(["", "cvttpd2dq Vdq,Wx", "cvtdq2pd Vx,Wh", "cvtpd2dq Vdq,Wx"]),
+            0xe7 => self.sse(["movntq Mq,Pq", "movntdq Mx,Vx", "", ""]),
+            0xf0 => self.sse(["", "", "", "lddqu Vx,Mx"]),
+            0xf7 => self.sse(["maskmovq Pq,Nq", "maskmovdqu Vdq,Udq", "", ""]),
+            0xff => entry("ud0 Gv,Ev"),
+            0xd1..=0xfe => {
+                let name = match opcode {
+                    0xd1 => "psrlw",
+                    0xd2 => "psrld",
+                    0xd3 => "psrlq",
+                    0xd4 => "paddq",
+                    0xd5 => "pmullw",
+                    0xd8 => "psubusb",
+                    0xd9 => "psubusw",
+                    0xda => "pminub",
+                    0xdb => "pand",
+                    0xdc => "paddusb",
+                    0xdd => "paddusw",
+                    0xde => "pmaxub",
+                    0xdf => "pandn",
+                    0xe0 => "pavgb",
+                    0xe1 => "psraw",
+                    0xe2 => "psrad",
+                    0xe3 => "pavgw",
+                    0xe4 => "pmulhuw",
+                    0xe5 => "pmulhw",
+                    0xe8 => "psubsb",
+                    0xe9 => "psubsw",
+                    0xea => "pminsw",
+                    0xeb => "por",
+                    0xec => "paddsb",
+                    0xed => "paddsw",
+                    0xee => "pmaxsw",
+                    0xef => "pxor",
+                    0xf1 => "psllw",
+                    0xf2 => "pslld",
+                    0xf3 => "psllq",
+                    0xf4 => "pmuludq",
+                    0xf5 => "pmaddwd",
+                    0xf6 => "psadbw",
+                    0xf8 => "psubb",
+                    0xf9 => "psubw",
+                    0xfa => "psubd",
+                    0xfb => "psubq",
+                    0xfc => "paddb",
+                    0xfd => "paddw",
+                    0xfe => "paddd",
+                    _ => return None,
+                };
+                // shifts by a count in an xmm register
+                let shift = matches!(opcode, 0xd1..=0xd3 | 0xe1 | 0xe2 | 0xf1..=0xf3);
+                let xmm = if shift { "_ Vx,Hx,Wdq" } else { "_ Vx,Hx,Wx" };
+                let (_, spec) = self.sse(["_ Pq,Qq", xmm, "", ""])?;
+                Some((Cow::Borrowed(name), spec))
+            }
+            _ => None,
+        }
+    }
+
+    fn group7(&mut self) -> Option<Entry> {
+        let modrm = self.modrm()?;
+        let reg = self.reg()?;
+        if modrm >> 6 != 3 {
+            return match reg {
+                0 => entry("sgdt M"),
+                1 => entry("sidt M"),
+                2 => entry("lgdt M"),
+                3 => entry("lidt M"),
+                4 => entry("smsw RvMw"),
+                6 => entry("lmsw Ew"),
+                7 => entry("invlpg Mb"),
+                _ => None,
+            };
+        }
+        match modrm {
+            0xc1 => entry("vmcall"),
+            0xc2 => entry("vmlaunch"),
+            0xc3 => entry("vmresume"),
+            0xc4 => entry("vmxoff"),
+            0xc8 => entry("monitor"),
+            0xc9 => entry("mwait"),
+            0xca => entry("clac"),
+            0xcb => entry("stac"),
+            0xd0 => entry("xgetbv"),
+            0xd1 => entry("xsetbv"),
+            0xd5 => entry("xend"),
+            0xd6 => entry("xtest"),
+            0xe8 if self.rep() == Some(0xf3) => {
+                self.mandatory = Some(0xf3);
+                entry("setssbsy")
+            }
+            0xe8 => entry("serialize"),
+            0xee => entry("rdpkru"),
+            0xef => entry("wrpkru"),
+            0xf8 => entry("swapgs"),
+            0xf9 => entry("rdtscp"),
+            0xe0..=0xe7 => entry("smsw RvMw"),
+            0xf0..=0xf7 => entry("lmsw Ew"),
+            _ => None,
+        }
+    }
+
+    fn group9(&mut self) -> Option<Entry> {
+        let reg = self.reg()?;
+        if self.mode()? == 3 {
+            return match reg {
+                6 => entry("rdrand Rv"),
+                7 if self.rep() == Some(0xf3) => {
+                    self.mandatory = Some(0xf3);
+                    entry("rdpid Rq")
+                }
+                7 => entry("rdseed Rv"),
+                _ => None,
+            };
+        }
+        match reg {
+            1 if self.w() => entry("cmpxchg16b Mo"),
+            1 => entry("cmpxchg8b Mq"),
+            3 => entry("xrstors/xrstors64 M"),
+            4 => entry("xsavec/xsavec64 M"),
+            5 => entry("xsaves/xsaves64 M"),
+            6 => match self.rep() {
+                Some(0xf3) => {
+                    self.mandatory = Some(0xf3);
+                    entry("vmxon Mq")
+                }
+                _ if self.has(0x66) => {
+                    self.mandatory = Some(0x66);
+                    entry("vmclear Mq")
+                }
+                _ => entry("vmptrld Mq"),
+            },
+            7 => entry("vmptrst Mq"),
+            _ => None,
+        }
+    }
+
+    fn group15(&mut self) -> Option<Entry> {
+        let reg = self.reg()?;
+        if self.vex.is_some() {
+            // only vldmxcsr and vstmxcsr have VEX forms
+            self.sse = true;
+            return match reg {
+                2 => entry("ldmxcsr Md"),
+                3 => entry("stmxcsr Md"),
+                _ => None,
+            };
+        }
+        if self.mode()? == 3 {
+            if self.rep() == Some(0xf3) {
+                self.mandatory = Some(0xf3);
+                let name = ["rdfsbase", "rdgsbase", "wrfsbase", "wrgsbase"].get(reg)?;
+                return Some((Cow::Borrowed(*name), "Ry"));
+            }
+            return match reg {
+                5 => entry("lfence"),
+                6 => entry("mfence"),
+                7 => entry("sfence"),
+                _ => None,
+            };
+        }
+        if self.has(0x66) && reg >= 6 {
+            self.mandatory = Some(0x66);
+            return if reg == 6 {
+                entry("clwb Mb")
+            } else {
+                entry("clflushopt Mb")
+            };
+        }
+        match reg {
+            0 => entry("fxsave/fxsave64 M"),
+            1 => entry("fxrstor/fxrstor64 M"),
+            2 => entry("ldmxcsr Md"),
+            3 => entry("stmxcsr Md"),
+            4 => entry("xsave/xsave64 M"),
+            5 => entry("xrstor/xrstor64 M"),
+            6 => entry("xsaveopt/xsaveopt64 M"),
+            _ => entry("clflush Mb"),
+        }
+    }
+
+    fn map_0f38(&mut self, opcode: u8) -> Option<Entry> {
+        let ssse3 = [
+            "pshufb",
+            "phaddw",
+            "phaddd",
+            "phaddsw",
+            "pmaddubsw",
+            "phsubw",
+            "phsubd",
+            "phsubsw",
+            "psignb",
+            "psignw",
+            "psignd",
+            "pmulhrsw",
+        ];
+        match opcode {
+            0x00..=0x0b => {
+                let (_, spec) = self.sse(["_ Pq,Qq", "_ Vx,Hx,Wx", "", ""])?;
+                Some((Cow::Borrowed(ssse3[opcode as usize]), spec))
+            }
+            0x1c..=0x1e => {
+                let (_, spec) = self.sse(["_ Pq,Qq", "_ Vx,Wx", "", ""])?;
+                let name = ["pabsb", "pabsw", "pabsd"][(opcode - 0x1c) as usize];
+                Some((Cow::Borrowed(name), spec))
+            }
+            0xf0 | 0xf1 if self.rep() == Some(0xf2) => {
+                self.mandatory = Some(0xf2);
+                // objdump leaves the size to the source register
+                if opcode == 0xf0 {
+                    entry("crc32 Gy,Eb")
+                } else {
+                    entry("crc32 Gy,Ev")
+                }
+            }
+            0xf6 if self.has(0x66) || self.rep() == Some(0xf3) => {
+                let (prefix, name) = match self.rep() {
+                    Some(0xf3) => (0xf3, "adox Gy,Ey"),
+                    _ => (0x66, "adcx Gy,Ey"),
+                };
+                self.mandatory = Some(prefix);
+                entry(name)
+            }
+            0xf0 => entry("movbe Gv,Mv"),
+            0xf1 => entry("movbe Mv,Gv"),
+            0xc8..=0xcd => {
+                let name = [
+                    "sha1nexte Vdq,Wdq",
+                    "sha1msg1 Vdq,Wdq",
+                    "sha1msg2 Vdq,Wdq",
+                    "sha256rnds2 Vdq,Wdq,XMM0",
+                    "sha256msg1 Vdq,Wdq",
+                    "sha256msg2 Vdq,Wdq",
+                ][(opcode - 0xc8) as usize];
+                self.sse([name, "", "", ""])
+            }
+            _ => {
+                let name = match opcode {
+                    0x10 => "pblendvb Vdq,Wdq,XMM0",
+                    0x14 => "blendvps Vdq,Wdq,XMM0",
+                    0x15 => "blendvpd Vdq,Wdq,XMM0",
+                    0x17 => "ptest Vx,Wx",
+                    0x20 => "pmovsxbw Vx,Wh",
+                    0x21 => "pmovsxbd Vx,Wqu",
+                    0x22 => "pmovsxbq Vx,We",
+                    0x23 => "pmovsxwd Vx,Wh",
+                    0x24 => "pmovsxwq Vx,Wqu",
+                    0x25 => "pmovsxdq Vx,Wh",
+                    0x28 => "pmuldq Vx,Hx,Wx",
+                    0x29 => "pcmpeqq Vx,Hx,Wx",
+                    0x2a => "movntdqa Vx,Mx",
+                    0x2b => "packusdw Vx,Hx,Wx",
+                    0x30 => "pmovzxbw Vx,Wh",
+                    0x31 => "pmovzxbd Vx,Wqu",
+                    0x32 => "pmovzxbq Vx,We",
+                    0x33 => "pmovzxwd Vx,Wh",
+                    0x34 => "pmovzxwq Vx,Wqu",
+                    0x35 => "pmovzxdq Vx,Wh",
+                    0x37 => "pcmpgtq Vx,Hx,Wx",
+                    0x38 => "pminsb Vx,Hx,Wx",
+                    0x39 => "pminsd Vx,Hx,Wx",
+                    0x3a => "pminuw Vx,Hx,Wx",
+                    0x3b => "pminud Vx,Hx,Wx",
+                    0x3c => "pmaxsb Vx,Hx,Wx",
+                    0x3d => "pmaxsd Vx,Hx,Wx",
+                    0x3e => "pmaxuw Vx,Hx,Wx",
+                    0x3f => "pmaxud Vx,Hx,Wx",
+                    0x40 => "pmulld Vx,Hx,Wx",
+                    0x41 => "phminposuw Vdq,Wdq",
+                    0xdb => "aesimc Vdq,Wdq",
+                    0xdc => "aesenc Vx,Hx,Wx",
+                    0xdd => "aesenclast Vx,Hx,Wx",
+                    0xde => "aesdec Vx,Hx,Wx",
+                    0xdf => "aesdeclast Vx,Hx,Wx",
+                    _ => return None,
+                };
+                self.sse(["", name, "", ""])
+            }
+        }
+    }
+
+    fn map_0f3a(&mut self, opcode: u8) -> Option<Entry> {
+        let name = match opcode {
+            0x0f => return self.sse(["palignr Pq,Qq,Ib", "palignr Vx,Hx,Wx,Ib", "", ""]),
+            0xcc => return self.sse(["sha1rnds4 Vdq,Wdq,Ib", "", "", ""]),
+            0x08 => "roundps Vx,Wx,Ib",
+            0x09 => "roundpd Vx,Wx,Ib",
+            0x0a => "roundss Vdq,Hdq,Wss,Ib",
+            0x0b => "roundsd Vdq,Hdq,Wsd,Ib",
+            0x0c => "blendps Vx,Hx,Wx,Ib",
+            0x0d => "blendpd Vx,Hx,Wx,Ib",
+            0x0e => "pblendw Vx,Hx,Wx,Ib",
+            0x14 => "pextrb RdMb,Vdq,Ib",
+            0x15 => "pextrw RdMw,Vdq,Ib",
+            0x16 => "pextrd/pextrq Ey,Vdq,Ib",
+            0x17 => "extractps Ed,Vdq,Ib",
+            0x20 => "pinsrb Vdq,Hdq,RdMb,Ib",
+            0x21 => "insertps Vdq,Hdq,Wd,Ib",
+            0x22 => "pinsrd/pinsrq Vdq,Hdq,Ey,Ib",
+            0x40 => "dpps Vx,Hx,Wx,Ib",
+            0x41 => "dppd Vdq,Hdq,Wdq,Ib",
+            0x42 => "mpsadbw Vx,Hx,Wx,Ib",
+            0x44 => "pclmulqdq Vx,Hx,Wx,Ib",
+            0x60 => "pcmpestrm Vdq,Wdq,Ib",
+            0x61 => "pcmpestri Vdq,Wdq,Ib",
+            0x62 => "pcmpistrm Vdq,Wdq,Ib",
+            0x63 => "pcmpistri Vdq,Wdq,Ib",
+            0xdf => "aeskeygenassist Vdq,Wdq,Ib",
+            _ => return None,
+        };
+        self.sse(["", name, "", ""])
+    }
+
+    // the 0F38 instructions that only exist with VEX
+    fn vex_0f38(&mut self, opcode: u8) -> Option<Entry> {
+        let vex = self.vex?;
+        let name = match (vex.pp, opcode) {
+            (1, 0x0c) => "vpermilps Vx,Hx,Wx",
+            (1, 0x0d) => "vpermilpd Vx,Hx,Wx",
+            (1, 0x0e) => "vtestps Vx,Wx",
+            (1, 0x0f) => "vtestpd Vx,Wx",
+            (1, 0x13) => "vcvtph2ps Vx,Wh",
+            (1, 0x16) => "vpermps Vqq,Hqq,Wqq",
+            (1, 0x18) => "vbroadcastss Vx,Wd",
+            (1, 0x19) => "vbroadcastsd Vqq,Wq",
+            (1, 0x1a) => "vbroadcastf128 Vqq,Mdq",
+            (1, 0x2c) => "vmaskmovps Vx,Hx,Mx",
+            (1, 0x2d) => "vmaskmovpd Vx,Hx,Mx",
+            (1, 0x2e) => "vmaskmovps Mx,Hx,Vx",
+            (1, 0x2f) => "vmaskmovpd Mx,Hx,Vx",
+            (1, 0x36) => "vpermd Vqq,Hqq,Wqq",
+            (1, 0x45) => "vpsrlvd/vpsrlvq Vx,Hx,Wx",
+            (1, 0x46) => "vpsravd Vx,Hx,Wx",
+            (1, 0x47) => "vpsllvd/vpsllvq Vx,Hx,Wx",
+            (1, 0x58) => "vpbroadcastd Vx,Wd",
+            (1, 0x59) => "vpbroadcastq Vx,Wq",
+            (1, 0x5a) => "vbroadcasti128 Vqq,Mdq",
+            (1, 0x78) => "vpbroadcastb Vx,Wb",
+            (1, 0x79) => "vpbroadcastw Vx,Ww",
+            (1, 0x8c) => "vpmaskmovd/vpmaskmovq Vx,Hx,Mx",
+            (1, 0x8e) => "vpmaskmovd/vpmaskmovq Mx,Hx,Vx",
+            // gathers; dword indices of qwords only fill half an index
+            // register, and qword indices of dwords half the destination
+            (1, 0x90) if self.w() => "vpgatherdq Vx,MVdq,Hx",
+            (1, 0x90) => "vpgatherdd Vx,MVx,Hx",
+            (1, 0x91) if self.w() => "vpgatherqq Vx,MVx,Hx",
+            (1, 0x91) => "vpgatherqd Vdq,MVx,Hdq",
+            (1, 0x92) if self.w() => "vgatherdpd Vx,MVdq,Hx",
+            (1, 0x92) => "vgatherdps Vx,MVx,Hx",
+            (1, 0x93) if self.w() => "vgatherqpd Vx,MVx,Hx",
+            (1, 0x93) => "vgatherqps Vdq,MVx,Hdq",
+            (1, 0x96..=0x9f | 0xa6..=0xaf | 0xb6..=0xbf) => return self.fma(opcode),
+            (0, 0xf2) => "andn Gy,By,Ey",
+            (0, 0xf3) => match self.reg()? {
+                1 => "blsr By,Ey",
+                2 => "blsmsk By,Ey",
+                3 => "blsi By,Ey",
+                _ => return None,
+            },
+            (0, 0xf5) => "bzhi Gy,Ey,By",
+            (2, 0xf5) => "pext Gy,By,Ey",
+            (3, 0xf5) => "pdep Gy,By,Ey",
+            (3, 0xf6) => "mulx Gy,By,Ey",
+            (0, 0xf7) => "bextr Gy,Ey,By",
+            (1, 0xf7) => "shlx Gy,Ey,By",
+            (2, 0xf7) => "sarx Gy,Ey,By",
+            (3, 0xf7) => "shrx Gy,Ey,By",
+            _ => return None,
+        };
+        entry(name)
+    }
+
+    // vfmadd132ps and the rest of the fused multiply-adds
+    fn fma(&mut self, opcode: u8) -> Option<Entry> {
+        let column = (opcode & 15) as usize;
+        let operation = [
+            "fmaddsub", "fmsubadd", "fmadd", "fmadd", "fmsub", "fmsub", "fnmadd", "fnmadd",
+            "fnmsub", "fnmsub",
+        ][column - 6];
+        let order = ["132", "213", "231"][(opcode >> 4) as usize - 9];
+        // odd columns from 9 on are scalar
+        let scalar = column >= 9 && column % 2 == 1;
+        let (kind, spec) = match (scalar, self.w()) {
+            (false, false) => ("ps", "Vx,Hx,Wx"),
+            (false, true) => ("pd", "Vx,Hx,Wx"),
+            (true, false) => ("ss", "Vdq,Hdq,Wss"),
+            (true, true) => ("sd", "Vdq,Hdq,Wsd"),
+        };
+        Some((Cow::Owned(format!("v{operation}{order}{kind}")), spec))
+    }
+
+    // the 0F3A instructions that only exist with VEX
+    fn vex_0f3a(&mut self, opcode: u8) -> Option<Entry> {
+        let vex = self.vex?;
+        let name = match (vex.pp, opcode) {
+            (1, 0x00) => "vpermq Vqq,Wqq,Ib",
+            (1, 0x01) => "vpermpd Vqq,Wqq,Ib",
+            (1, 0x02) => "vpblendd Vx,Hx,Wx,Ib",
+            (1, 0x04) => "vpermilps Vx,Wx,Ib",
+            (1, 0x05) => "vpermilpd Vx,Wx,Ib",
+            (1, 0x06) => "vperm2f128 Vqq,Hqq,Wqq,Ib",
+            (1, 0x18) => "vinsertf128 Vqq,Hqq,Wdq,Ib",
+            (1, 0x19) => "vextractf128 Wdq,Vqq,Ib",
+            (1, 0x1d) => "vcvtps2ph Wh,Vx,Ib",
+            (1, 0x38) => "vinserti128 Vqq,Hqq,Wdq,Ib",
+            (1, 0x39) => "vextracti128 Wdq,Vqq,Ib",
+            (1, 0x46) => "vperm2i128 Vqq,Hqq,Wqq,Ib",
+            (1, 0x4a) => "vblendvps Vx,Hx,Wx,Lx",
+            (1, 0x4b) => "vblendvpd Vx,Hx,Wx,Lx",
+            (1, 0x4c) => "vpblendvb Vx,Hx,Wx,Lx",
+            (3, 0xf0) => "rorx Gy,Ey,Ib",
+            _ => return None,
+        };
+        entry(name)
+    }
+
+    // movs, cmps, stos, lods, scas, ins and outs, with their implicit
+    // operands
+    fn string(&mut self, opcode: u8) -> Option<()> {
+        self.string = true;
+        let size = if opcode & 1 == 0 {
+            Size::B
+        } else if opcode < 0x70 && !self.opsize() {
+            Size::D
+        } else {
+            self.osize()
+        };
+        let (rsi, rdi) = if self.addrsize() {
+            ("esi", "edi")
+        } else {
+            ("rsi", "rdi")
+        };
+        let pointer = |segment, base| {
+            Operand::Memory(Memory {
+                size,
+                segment: Some(segment),
+                base: Some(base),
+                index: None,
+                scale: 1,
+                disp: 0,
+                has_disp: false,
+            })
+        };
+        let source = pointer(self.segment().unwrap_or("ds"), rsi);
+        let destination = pointer("es", rdi);
+        let accumulator = Operand::Gpr(self.gpr(0, size));
+        let (name, operands) = match opcode {
+            0x6c | 0x6d => ("ins", vec![destination, Operand::Port]),
+            0x6e | 0x6f => ("outs", vec![Operand::Port, source]),
+            0xa4 | 0xa5 => ("movs", vec![destination, source]),
+            0xa6 | 0xa7 => ("cmps", vec![source, destination]),
+            0xaa | 0xab => ("stos", vec![destination, accumulator]),
+            0xac | 0xad => ("lods", vec![accumulator, source]),
+            _ => ("scas", vec![accumulator, destination]),
+        };
+        self.mnemonic = name.to_string();
+        self.operands = operands;
+        self.sized = Some(size);
+        Some(())
+    }
+
+    // fwait before a non-waiting control instruction makes the waiting
+    // form: fstsw is fwait; fnstsw
+    fn waiting(&mut self) -> Option<()> {
+        let start = self.pos;
+        let opcode = self.byte()?;
+        if self.x87(opcode).is_some() && self.mnemonic.starts_with("fn") {
+            self.mnemonic.remove(1);
+            return Some(());
+        }
+        self.pos = start;
+        self.modrm = None;
+        self.operands.clear();
+        self.mnemonic = "fwait".to_string();
+        Some(())
+    }
+
+    fn x87(&mut self, opcode: u8) -> Option<()> {
+        let modrm = self.modrm()?;
+        let reg = self.reg()?;
+        let att = self.syntax == Syntax::Att;
+        if modrm >> 6 != 3 {
+            // Intel name, AT&T name and operand size
+            let arithmetic = [
+                "fadd", "fmul", "fcom", "fcomp", "fsub", "fsubr", "fdiv", "fdivr",
+            ];
+            let integer = [
+                "fiadd", "fimul", "ficom", "ficomp", "fisub", "fisubr", "fidiv", "fidivr",
+            ];
+            let (name, suffix, size) = match (opcode, reg) {
+                (0xd8, _) => (arithmetic[reg], "s", Size::D),
+                (0xdc, _) => (arithmetic[reg], "l", Size::Q),
+                (0xda, _) => (integer[reg], "l", Size::D),
+                (0xde, _) => (integer[reg], "s", Size::W),
+                (0xd9, 0) => ("fld", "s", Size::D),
+                (0xd9, 2) => ("fst", "s", Size::D),
+                (0xd9, 3) => ("fstp", "s", Size::D),
+                (0xd9, 4) => ("fldenv", "", Size::None),
+                (0xd9, 5) => ("fldcw", "", Size::W),
+                (0xd9, 6) => ("fnstenv", "", Size::None),
+                (0xd9, 7) => ("fnstcw", "", Size::W),
+                (0xdb, 0) => ("fild", "l", Size::D),
+                (0xdb, 1) => ("fisttp", "l", Size::D),
+                (0xdb, 2) => ("fist", "l", Size::D),
+                (0xdb, 3) => ("fistp", "l", Size::D),
+                (0xdb, 5) => ("fld", "t", Size::T),
+                (0xdb, 7) => ("fstp", "t", Size::T),
+                (0xdd, 0) => ("fld", "l", Size::Q),
+                (0xdd, 1) => ("fisttp", "ll", Size::Q),
+                (0xdd, 2) => ("fst", "l", Size::Q),
+                (0xdd, 3) => ("fstp", "l", Size::Q),
+                (0xdd, 4) => ("frstor", "", Size::None),
+                (0xdd, 6) => ("fnsave", "", Size::None),
+                (0xdd, 7) => ("fnstsw", "", Size::W),
+                (0xdf, 0) => ("fild", "s", Size::W),
+                (0xdf, 1) => ("fisttp", "s", Size::W),
+                (0xdf, 2) => ("fist", "s", Size::W),
+                (0xdf, 3) => ("fistp", "s", Size::W),
+                (0xdf, 4) => ("fbld", "", Size::T),
+                (0xdf, 5) => ("fild", "ll", Size::Q),
+                (0xdf, 6) => ("fbstp", "", Size::T),
+                (0xdf, 7) => ("fistp", "ll", Size::Q),
+                _ => return None,
+            };
+            let memory = self.memory(size)?;
+            self.operands.push(memory);
+            self.mnemonic = if att {
+                format!("{name}{suffix}")
+            } else {
+                name.to_string()
+            };
+            return Some(());
+        }
+
+        let i = (modrm & 7) as usize;
+        let st = || Operand::Reg("st".to_string());
+        let sti = || Operand::Reg(format!("st({i})"));
+        let (name, operands) = match (opcode, reg) {
+            (0xd8, _) => {
+                let name = [
+                    "fadd", "fmul", "fcom", "fcomp", "fsub", "fsubr", "fdiv", "fdivr",
+                ][reg];
+                if reg == 2 || reg == 3 {
+                    (name, vec![sti()])
+                } else {
+                    (name, vec![st(), sti()])
+                }
+            }
+            (0xd9, 0) => ("fld", vec![sti()]),
+            (0xd9, 1) => ("fxch", vec![sti()]),
+            (0xd9, _) => {
+                let name = match modrm {
+                    0xd0 => "fnop",
+                    0xe0 => "fchs",
+                    0xe1 => "fabs",
+                    0xe4 => "ftst",
+                    0xe5 => "fxam",
+                    0xe8 => "fld1",
+                    0xe9 => "fldl2t",
+                    0xea => "fldl2e",
+                    0xeb => "fldpi",
+                    0xec => "fldlg2",
+                    0xed => "fldln2",
+                    0xee => "fldz",
+                    0xf0 => "f2xm1",
+                    0xf1 => "fyl2x",
+                    0xf2 => "fptan",
+                    0xf3 => "fpatan",
+                    0xf4 => "fxtract",
+                    0xf5 => "fprem1",
+                    0xf6 => "fdecstp",
+                    0xf7 => "fincstp",
+                    0xf8 => "fprem",
+                    0xf9 => "fyl2xp1",
+                    0xfa => "fsqrt",
+                    0xfb => "fsincos",
+                    0xfc => "frndint",
+                    0xfd => "fscale",
+                    0xfe => "fsin",
+                    0xff => "fcos",
+                    _ => return None,
+                };
+                (name, vec![])
+            }
+            (0xda, 0..=3) => (
+                ["fcmovb", "fcmove", "fcmovbe", "fcmovu"][reg],
+                vec![st(), sti()],
+            ),
+            (0xda, _) if modrm == 0xe9 => ("fucompp", vec![]),
+            (0xdb, 0..=3) => (
+                ["fcmovnb", "fcmovne", "fcmovnbe", "fcmovnu"][reg],
+                vec![st(), sti()],
+            ),
+            (0xdb, _) if modrm == 0xe2 => ("fnclex", vec![]),
+            (0xdb, _) if modrm == 0xe3 => ("fninit", vec![]),
+            (0xdb, 5) => ("fucomi", vec![st(), sti()]),
+            (0xdb, 6) => ("fcomi", vec![st(), sti()]),
+            // the GNU assembler swaps the reversed subtractions and
+            // divisions of DC and DE, and objdump follows it in AT&T
+            (0xdc, 0 | 1 | 4..=7) => {
+                let names = if att {
+                    ["fadd", "fmul", "", "", "fsub", "fsubr", "fdiv", "fdivr"]
+                } else {
+                    ["fadd", "fmul", "", "", "fsubr", "fsub", "fdivr", "fdiv"]
+                };
+                (names[reg], vec![sti(), st()])
+            }
+            (0xdd, 0) => ("ffree", vec![sti()]),
+            (0xdd, 2) => ("fst", vec![sti()]),
+            (0xdd, 3) => ("fstp", vec![sti()]),
+            (0xdd, 4) => ("fucom", vec![sti()]),
+            (0xdd, 5) => ("fucomp", vec![sti()]),
+            (0xde, _) if modrm == 0xd9 => ("fcompp", vec![]),
+            (0xde, 0 | 1 | 4..=7) => {
+                let names = if att {
+                    [
+                        "faddp", "fmulp", "", "", "fsubp", "fsubrp", "fdivp", "fdivrp",
+                    ]
+                } else {
+                    [
+                        "faddp", "fmulp", "", "", "fsubrp", "fsubp", "fdivrp", "fdivp",
+                    ]
+                };
+                (names[reg], vec![sti(), st()])
+            }
+            (0xdf, 0) => ("ffreep", vec![sti()]),
+            (0xdf, _) if modrm == 0xe0 => ("fnstsw", vec![Operand::Gpr("ax")]),
+            (0xdf, 5) => ("fucomip", vec![st(), sti()]),
+            (0xdf, 6) => ("fcomip", vec![st(), sti()]),
+            _ => return None,
+        };
+        self.mnemonic = name.to_string();
+        self.operands = operands;
+        Some(())
+    }
+
+    fn uses_memory(&self) -> bool {
+        self.operands
+            .iter()
+            .any(|operand| matches!(operand, Operand::Memory(_)))
+    }
+
+    // the prefixes that didn't take part in decoding, as objdump names them
+    fn prefix_names(&self) -> Vec<String> {
+        let branch =
+            self.mnemonic.starts_with('j') || matches!(self.mnemonic.as_str(), "call" | "ret");
+        let compare = matches!(&self.mnemonic[..], "cmps" | "scas")
+            || self.string && self.mnemonic.starts_with("cmps")
+            || self.string && self.mnemonic.starts_with("scas");
+        let last_66 = self.prefixes.iter().rposition(|&p| p == 0x66);
+        let relative = self.target.is_some();
+        let mut names = Vec::new();
+        for (i, &prefix) in self.prefixes.iter().enumerate() {
+            let name = match prefix {
+                0xf0 => "lock",
+                0xf3 | 0xf2 if self.mandatory == Some(prefix) => continue,
+                0xf3 if self.string && !compare => "rep",
+                0xf3 => "repz",
+                0xf2 if branch => "bnd",
+                0xf2 => "repnz",
+                0x66 if self.mandatory == Some(0x66) => continue,
+                // REX.W overrides it, and relative branches ignore it
+                0x66 if Some(i) == last_66 && self.vex.is_none() && !self.w() && !relative => {
+                    continue
+                }
+                0x66 => "data16",
+                0x67 if self.uses_memory() || self.string => continue,
+                0x67 => "addr32",
+                0x64 | 0x65 if self.uses_memory() => continue,
+                0x64 => "fs",
+                0x65 => "gs",
+                0x3e if self.indirect => "notrack",
+                0x3e if self.string => continue,
+                0x2e | 0x3e if Some(i) == self.hint() => continue,
+                0x2e => "cs",
+                0x36 => "ss",
+                0x3e => "ds",
+                _ => "es",
+            };
+            names.push(name.to_string());
+        }
+        if relative && self.rex != 0 {
+            let bits = [(8, 'W'), (4, 'R'), (2, 'X'), (1, 'B')];
+            let bits: String = bits
+                .iter()
+                .filter(|(bit, _)| self.rex & bit != 0)
+                .map(|(_, letter)| letter)
+                .collect();
+            names.push(if bits.is_empty() {
+                "rex".to_string()
+            } else {
+                format!("rex.{bits}")
+            });
+        }
+        names
+    }
+
+    // the cs (not taken) or ds (taken) prefix hinting a conditional branch
+    fn hint(&self) -> Option<usize> {
+        if self.flow() != Flow::Branch {
+            return None;
+        }
+        self.prefixes.iter().rposition(|&p| p == 0x2e || p == 0x3e)
+    }
+
+    fn flow(&self) -> Flow {
+        let name = self.mnemonic.as_str();
+        match name {
+            "jmp" | "ljmp" => Flow::Jump,
+            "call" | "lcall" => Flow::Call,
+            "ret" | "retf" | "lret" | "iret" | "iretw" | "iretq" => Flow::Return,
+            _ if name.starts_with("sysret") || name.starts_with("sysexit") => Flow::Return,
+            "hlt" | "ud0" | "ud1" | "ud2" | "int3" => Flow::Stop,
+            "loop" | "loope" | "loopne" | "jrcxz" | "jecxz" | "xbegin" => Flow::Branch,
+            _ if name.starts_with('j') => Flow::Branch,
+            _ => Flow::Next,
+        }
+    }
+
+    // pseudo-op names for the comparison predicates and carry-less
+    // multiplications, which replace the immediate
+    fn pseudo_ops(&mut self) {
+        let base = self.mnemonic.trim_start_matches('v');
+        let vex = self.vex.is_some();
+        let last = match self.operands.last() {
+            Some(Operand::Imm(value)) => *value as usize,
+            _ => return,
+        };
+        let name = match base {
+            "cmpps" | "cmppd" | "cmpss" | "cmpsd" if last < if vex { 32 } else { 8 } => {
+                let kind = &base[3..];
+                format!("cmp{}{kind}", COMPARISONS[last])
+            }
+            "pclmulqdq" if matches!(last, 0x00 | 0x01 | 0x10 | 0x11) => {
+                let low = if last & 1 == 0 { "l" } else { "h" };
+                let high = if last & 0x10 == 0 { "l" } else { "h" };
+                format!("pclmul{low}q{high}qdq")
+            }
+            _ => return,
+        };
+        let v = if vex { "v" } else { "" };
+        self.mnemonic = format!("{v}{name}");
+        self.operands.pop();
+    }
+
+    fn finish(mut self) -> Instruction {
+        self.pseudo_ops();
+        let flow = self.flow();
+        let reference = self.rip_disp.map(|disp| {
+            self.address
+                .wrapping_add(self.pos as u64)
+                .wrapping_add(disp as u64)
+        });
+        let att = self.syntax == Syntax::Att;
+        let mut mnemonic = self.mnemonic.clone();
+        // enter keeps the Intel order in AT&T
+        if att && self.mnemonic == "enter" {
+            self.operands.reverse();
+        }
+        // AT&T spells out the size when no register operand shows it; the
+        // count of a shift doesn't, nor does crc32's destination
+        let crc32 = self.mnemonic == "crc32";
+        let shift = matches!(
+            &self.mnemonic[..],
+            "rol" | "ror" | "rcl" | "rcr" | "shl" | "shr" | "sal" | "sar"
+        );
+        let shows_size = |o: &Operand| matches!(o, Operand::Gpr(r) if !(shift && *r == "cl"));
+        if att && (crc32 || !self.operands.iter().any(shows_size)) {
+            if let Some(size) = self.sized {
+                if self.suffixed() {
+                    mnemonic.push_str(size.suffix());
+                }
+            }
+        }
+        if let Some(i) = self.hint() {
+            mnemonic.push_str(if self.prefixes[i] == 0x2e {
+                ",pn"
+            } else {
+                ",pt"
+            });
+        }
+        let mut prefixes = self.prefix_names();
+        if !prefixes.is_empty() {
+            prefixes.push(mnemonic);
+            mnemonic = prefixes.join(" ");
+        }
+        let bad = self.gather_conflicts();
+        let text = |(i, operand): (usize, &Operand)| {
+            let text = if att {
+                self.att(operand)
+            } else {
+                self.intel(operand)
+            };
+            if bad[i] {
+                text + "/(bad)"
+            } else {
+                text
+            }
+        };
+        let operands: Vec<String> = if att {
+            let operands = self.operands.iter().enumerate().rev();
+            operands
+                .filter(|(_, o)| !matches!(o, Operand::One))
+                .map(text)
+                .collect()
+        } else {
+            self.operands.iter().enumerate().map(text).collect()
+        };
+        Instruction {
+            address: self.address,
+            bytes: self.code[..self.pos].to_vec(),
+            mnemonic,
+            operands: operands.join(","),
+            flow,
+            target: self.target,
+            reference,
+            target_symbol: None,
+        }
+    }
+
+    // The operands of a gather (destination, VSIB memory and mask) that
+    // use the same register as another one, which the instruction doesn't
+    // allow; objdump marks them. None for other instructions.
+    fn gather_conflicts(&self) -> Vec<bool> {
+        let (Some(vex), Some(index), Some(modrm)) = (self.vex, self.vsib_index, self.modrm) else {
+            return vec![false; self.operands.len()];
+        };
+        let registers = [(modrm >> 3 & 7) as usize | self.r(), index, vex.vvvv];
+        let shared = |i: usize| (0..3).any(|j| j != i && registers[j] == registers[i]);
+        (0..3).map(shared).collect()
+    }
+
+    // whether an AT&T suffix goes on the mnemonic: for integer
+    // instructions whose size isn't implied otherwise
+    fn suffixed(&self) -> bool {
+        let name = self.mnemonic.as_str();
+        if self.sse {
+            return name.contains("cvtsi2");
+        }
+        let implied = [
+            "push",
+            "pop",
+            "call",
+            "jmp",
+            "lcall",
+            "ljmp",
+            "invlpg",
+            "clflush",
+            "clflushopt",
+            "clwb",
+            "smsw",
+            "lmsw",
+            "sldt",
+            "str",
+            "lldt",
+            "ltr",
+            "verr",
+            "verw",
+            "cmpxchg8b",
+            "cmpxchg16b",
+            "movnti",
+            "xlat",
+        ];
+        !(implied.contains(&name) || name.starts_with("set") || name.starts_with("prefetch"))
+    }
+
+    fn intel(&self, operand: &Operand) -> String {
+        match operand {
+            Operand::Gpr(name) => name.to_string(),
+            Operand::Reg(name) => name.clone(),
+            Operand::Imm(value) => format!("{value:#x}"),
+            Operand::One => "1".to_string(),
+            Operand::Port => "dx".to_string(),
+            Operand::Relative(target) => format!("{target:#x}"),
+            Operand::Memory(memory) => {
+                let mut text = memory.size.ptr().to_string();
+                if memory.base.is_none() && memory.index.is_none() {
+                    let segment = memory.segment.unwrap_or("ds");
+                    return format!("{text}{segment}:{:#x}", self.absolute(memory));
+                }
+                if let Some(segment) = memory.segment {
+                    text += segment;
+                    text += ":";
+                }
+                text += "[";
+                if let Some(base) = memory.base {
+                    text += base;
+                }
+                if let Some(index) = memory.index {
+                    if memory.base.is_some() {
+                        text += "+";
+                    }
+                    text += &format!("{index}*{}", memory.scale);
+                }
+                if memory.has_disp {
+                    if memory.base == Some("rip") || memory.base == Some("eip") {
+                        text += &format!("+{:#x}", memory.disp as u64);
+                    } else if memory.disp < 0 {
+                        text += &format!("-{:#x}", memory.disp.unsigned_abs());
+                    } else {
+                        text += &format!("+{:#x}", memory.disp);
+                    }
+                }
+                text + "]"
+            }
+        }
+    }
+
+    fn att(&self, operand: &Operand) -> String {
+        let star = if self.indirect { "*" } else { "" };
+        match operand {
+            Operand::Gpr(name) => format!("{star}%{name}"),
+            Operand::Reg(name) => format!("%{name}"),
+            Operand::Imm(value) => format!("${value:#x}"),
+            Operand::One => String::new(),
+            Operand::Port => "(%dx)".to_string(),
+            Operand::Relative(target) => format!("{target:#x}"),
+            Operand::Memory(memory) => {
+                let mut text = star.to_string();
+                if let Some(segment) = memory.segment {
+                    text += &format!("%{segment}:");
+                }
+                if memory.base.is_none() && memory.index.is_none() {
+                    return format!("{text}{:#x}", self.absolute(memory));
+                }
+                if memory.has_disp {
+                    if memory.disp < 0 {
+                        text += &format!("-{:#x}", memory.disp.unsigned_abs());
+                    } else {
+                        text += &format!("{:#x}", memory.disp);
+                    }
+                }
+                text += "(";
+                if let Some(base) = memory.base {
+                    text += &format!("%{base}");
+                }
+                if let Some(index) = memory.index {
+                    text += &format!(",%{index},{}", memory.scale);
+                }
+                text + ")"
+            }
+        }
+    }
+
+    // an address without base or index, in the address size
+    fn absolute(&self, memory: &Memory) -> u64 {
+        if self.addrsize() {
+            memory.disp as u64 & 0xffff_ffff
+        } else {
+            memory.disp as u64
+        }
+    }
+}
+
+fn vector(n: usize, ymm: bool) -> Operand {
+    let kind = if ymm { "ymm" } else { "xmm" };
+    Operand::Reg(format!("{kind}{n}"))
+}
+
+// the four forms of an SSE arithmetic operation
+fn arithmetic(name: &'static str) -> [&'static str; 4] {
+    match name {
+        "add" => [
+            "addps Vx,Hx,Wx",
+            "addpd Vx,Hx,Wx",
+            "addss Vdq,Hdq,Wss",
+            "addsd Vdq,Hdq,Wsd",
+        ],
+        "mul" => [
+            "mulps Vx,Hx,Wx",
+            "mulpd Vx,Hx,Wx",
+            "mulss Vdq,Hdq,Wss",
+            "mulsd Vdq,Hdq,Wsd",
+        ],
+        "sub" => [
+            "subps Vx,Hx,Wx",
+            "subpd Vx,Hx,Wx",
+            "subss Vdq,Hdq,Wss",
+            "subsd Vdq,Hdq,Wsd",
+        ],
+        "min" => [
+            "minps Vx,Hx,Wx",
+            "minpd Vx,Hx,Wx",
+            "minss Vdq,Hdq,Wss",
+            "minsd Vdq,Hdq,Wsd",
+        ],
+        "div" => [
+            "divps Vx,Hx,Wx",
+            "divpd Vx,Hx,Wx",
+            "divss Vdq,Hdq,Wss",
+            "divsd Vdq,Hdq,Wsd",
+        ],
+        _ => [
+            "maxps Vx,Hx,Wx",
+            "maxpd Vx,Hx,Wx",
+            "maxss Vdq,Hdq,Wss",
+            "maxsd Vdq,Hdq,Wsd",
+        ],
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::disasm::x86::X86;
+    use crate::disasm::{Decoder, Flow, Syntax};
+
+    // the instruction as objdump prints it, in both syntaxes
+    fn both(code: &[u8]) -> (String, String) {
+        let text = |syntax| {
+            let instruction = X86::new(syntax).decode(code, 0x1000);
+            assert_eq!(instruction.bytes, code);
+            format!("{} {}", instruction.mnemonic, instruction.operands)
+                .trim_end()
+                .to_string()
+        };
+        (text(Syntax::Att), text(Syntax::Intel))
+    }
+
+    #[test]
+    fn operands() {
+        assert_eq!(
+            both(&[0x48, 0x89, 0xe5]),
+            ("mov %rsp,%rbp".into(), "mov rbp,rsp".into())
+        );
+        assert_eq!(
+            both(&[0x48, 0x83, 0xec, 0x10]),
+            ("sub $0x10,%rsp".into(), "sub rsp,0x10".into())
+        );
+        assert_eq!(
+            both(&[0x8b, 0x44, 0x8b, 0xfc]),
+            (
+                "mov -0x4(%rbx,%rcx,4),%eax".into(),
+                "mov eax,DWORD PTR [rbx+rcx*4-0x4]".into()
+            )
+        );
+        assert_eq!(
+            both(&[0xc7, 0x00, 0x01, 0x00, 0x00, 0x00]),
+            ("movl $0x1,(%rax)".into(), "mov DWORD PTR [rax],0x1".into())
+        );
+        assert_eq!(
+            both(&[0xd3, 0x37]),
+            ("shll %cl,(%rdi)".into(), "shl DWORD PTR [rdi],cl".into())
+        );
+        assert_eq!(
+            both(&[0xc5, 0xfd, 0xef, 0xc1]),
+            (
+                "vpxor %ymm1,%ymm0,%ymm0".into(),
+                "vpxor ymm0,ymm0,ymm1".into()
+            )
+        );
+        assert_eq!(
+            both(&[0x66, 0x48, 0x0f, 0x6e, 0xd0]),
+            ("movq %rax,%xmm2".into(), "movq xmm2,rax".into())
+        );
+        assert_eq!(both(&[0x0f, 0x07]), ("sysretl".into(), "sysretd".into()));
+        assert_eq!(both(&[0x48, 0x0f, 0x07]).0, "sysretq");
+        assert_eq!(
+            both(&[0xf2, 0x48, 0x0f, 0x38, 0xf1, 0xd8]).0,
+            "crc32 %rax,%rbx"
+        );
+        assert_eq!(
+            both(&[0xf2, 0x0f, 0x38, 0xf1, 0x06]).0,
+            "crc32l (%rsi),%eax"
+        );
+    }
+
+    #[test]
+    fn gathers() {
+        assert_eq!(
+            both(&[0xc4, 0xe2, 0x69, 0x92, 0x04, 0x88]),
+            (
+                "vgatherdps %xmm2,(%rax,%xmm1,4),%xmm0".into(),
+                "vgatherdps xmm0,DWORD PTR [rax+xmm1*4],xmm2".into()
+            )
+        );
+        // qword indices of dwords: a ymm index, an xmm destination
+        assert_eq!(
+            both(&[0xc4, 0xe2, 0x6d, 0x91, 0x04, 0x88]).0,
+            "vpgatherqd %xmm2,(%rax,%ymm1,4),%xmm0"
+        );
+        assert_eq!(
+            both(&[0xc4, 0x02, 0xed, 0x93, 0x54, 0xc8, 0x10]).1,
+            "vgatherqpd ymm10,QWORD PTR [r8+ymm9*8+0x10],ymm2"
+        );
+        // the mask can't be the destination
+        assert_eq!(
+            both(&[0xc4, 0xe2, 0xcd, 0x90, 0x34, 0x5b]).0,
+            "vpgatherdq %ymm6/(bad),(%rbx,%xmm3,2),%ymm6/(bad)"
+        );
+        // without a SIB byte there is no index
+        let bad = X86::new(Syntax::Att).decode(&[0xc4, 0xe2, 0x69, 0x92, 0x00], 0x1000);
+        assert_eq!(bad.mnemonic, "(bad)");
+    }
+
+    #[test]
+    fn prefixes() {
+        assert_eq!(both(&[0xf3, 0x48, 0xab]).0, "rep stos %rax,%es:(%rdi)");
+        assert_eq!(
+            both(&[0xf0, 0x0f, 0xb1, 0x17]).0,
+            "lock cmpxchg %edx,(%rdi)"
+        );
+        assert_eq!(both(&[0xf3, 0x0f, 0x1e, 0xfa]).0, "endbr64");
+        assert_eq!(both(&[0x2e, 0x74, 0x02]).0, "je,pn 0x1005");
+    }
+
+    #[test]
+    fn control_flow() {
+        let decoder = X86::new(Syntax::Att);
+        let call = decoder.decode(&[0xe8, 0xfb, 0xff, 0xff, 0xff], 0x1000);
+        assert_eq!((call.flow, call.target), (Flow::Call, Some(0x1000)));
+        let branch = decoder.decode(&[0x75, 0x10], 0x1000);
+        assert_eq!((branch.flow, branch.target), (Flow::Branch, Some(0x1012)));
+        let jump = decoder.decode(&[0xff, 0xe0], 0x1000);
+        assert_eq!((jump.flow, jump.target), (Flow::Jump, None));
+        assert_eq!(decoder.decode(&[0xc3], 0x1000).flow, Flow::Return);
+        assert_eq!(decoder.decode(&[0x0f, 0x0b], 0x1000).flow, Flow::Stop);
+
+        // lea 0x10(%rip),%rax refers to the end of the instruction + 0x10
+        let lea = decoder.decode(&[0x48, 0x8d, 0x05, 0x10, 0x00, 0x00, 0x00], 0x1000);
+        assert_eq!(lea.reference, Some(0x1017));
+    }
+
+    #[test]
+    fn bad_bytes() {
+        let decoder = X86::new(Syntax::Att);
+        let bad = decoder.decode(&[0x06], 0x1000);
+        assert_eq!((bad.mnemonic.as_str(), bad.bytes.len()), ("(bad)", 1));
+        // truncated instructions don't read past the end
+        assert_eq!(decoder.decode(&[0x48, 0x8b], 0x1000).bytes.len(), 1);
+    }
+}
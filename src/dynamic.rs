@@ -0,0 +1,438 @@
+use crate::error::ParseError;
+use crate::parse::{vaddr_to_offset, Arch, ELFHeader, Pheader, PT_DYNAMIC};
+use crate::section::{read_str, SectionHeader, SHT_DYNAMIC, SHT_STRTAB};
+use crate::utils::{checked_slice, Reader};
+
+// dynamic array tags (d_tag)
+pub const DT_NULL: u64 = 0;
+pub const DT_NEEDED: u64 = 1;
+pub const DT_PLTRELSZ: u64 = 2;
+pub const DT_PLTGOT: u64 = 3;
+pub const DT_HASH: u64 = 4;
+pub const DT_STRTAB: u64 = 5;
+pub const DT_SYMTAB: u64 = 6;
+pub const DT_RELA: u64 = 7;
+pub const DT_RELASZ: u64 = 8;
+pub const DT_RELAENT: u64 = 9;
+pub const DT_STRSZ: u64 = 10;
+pub const DT_SYMENT: u64 = 11;
+pub const DT_INIT: u64 = 12;
+pub const DT_FINI: u64 = 13;
+pub const DT_SONAME: u64 = 14;
+pub const DT_RPATH: u64 = 15;
+pub const DT_SYMBOLIC: u64 = 16;
+pub const DT_REL: u64 = 17;
+pub const DT_RELSZ: u64 = 18;
+pub const DT_RELENT: u64 = 19;
+pub const DT_PLTREL: u64 = 20;
+pub const DT_DEBUG: u64 = 21;
+pub const DT_TEXTREL: u64 = 22;
+pub const DT_JMPREL: u64 = 23;
+pub const DT_BIND_NOW: u64 = 24;
+pub const DT_INIT_ARRAY: u64 = 25;
+pub const DT_FINI_ARRAY: u64 = 26;
+pub const DT_INIT_ARRAYSZ: u64 = 27;
+pub const DT_FINI_ARRAYSZ: u64 = 28;
+pub const DT_RUNPATH: u64 = 29;
+pub const DT_FLAGS: u64 = 30;
+pub const DT_PREINIT_ARRAY: u64 = 32;
+pub const DT_PREINIT_ARRAYSZ: u64 = 33;
+pub const DT_SYMTAB_SHNDX: u64 = 34;
+pub const DT_RELRSZ: u64 = 35;
+pub const DT_RELR: u64 = 36;
+pub const DT_RELRENT: u64 = 37;
+pub const DT_GNU_HASH: u64 = 0x6fff_fef5;
+pub const DT_VERSYM: u64 = 0x6fff_fff0;
+pub const DT_RELACOUNT: u64 = 0x6fff_fff9;
+pub const DT_RELCOUNT: u64 = 0x6fff_fffa;
+pub const DT_FLAGS_1: u64 = 0x6fff_fffb;
+pub const DT_VERDEF: u64 = 0x6fff_fffc;
+pub const DT_VERDEFNUM: u64 = 0x6fff_fffd;
+pub const DT_VERNEED: u64 = 0x6fff_fffe;
+pub const DT_VERNEEDNUM: u64 = 0x6fff_ffff;
+pub const DT_AUXILIARY: u64 = 0x7fff_fffd;
+pub const DT_FILTER: u64 = 0x7fff_ffff;
+
+// DT_FLAGS values
+pub const DF_ORIGIN: u64 = 0x1;
+pub const DF_SYMBOLIC: u64 = 0x2;
+pub const DF_TEXTREL: u64 = 0x4;
+pub const DF_BIND_NOW: u64 = 0x8;
+pub const DF_STATIC_TLS: u64 = 0x10;
+
+// DT_FLAGS_1 values
+pub const DF_1_NOW: u64 = 0x1;
+pub const DF_1_GLOBAL: u64 = 0x2;
+pub const DF_1_GROUP: u64 = 0x4;
+pub const DF_1_NODELETE: u64 = 0x8;
+pub const DF_1_LOADFLTR: u64 = 0x10;
+pub const DF_1_INITFIRST: u64 = 0x20;
+pub const DF_1_NOOPEN: u64 = 0x40;
+pub const DF_1_ORIGIN: u64 = 0x80;
+pub const DF_1_DIRECT: u64 = 0x100;
+pub const DF_1_INTERPOSE: u64 = 0x400;
+pub const DF_1_NODEFLIB: u64 = 0x800;
+pub const DF_1_NODUMP: u64 = 0x1000;
+pub const DF_1_CONFALT: u64 = 0x2000;
+pub const DF_1_ENDFILTEE: u64 = 0x4000;
+pub const DF_1_DISPRELDNE: u64 = 0x8000;
+pub const DF_1_DISPRELPND: u64 = 0x10000;
+pub const DF_1_NODIRECT: u64 = 0x20000;
+pub const DF_1_PIE: u64 = 0x0800_0000;
+
+// Decoded meaning of d_val/d_ptr, based on the tag
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DynamicValue {
+    // offset into the dynamic string table, already resolved
+    Str(String),
+    // virtual address (d_ptr)
+    Address(u64),
+    // size in bytes
+    Size(u64),
+    // DT_FLAGS / DT_FLAGS_1 bit set
+    Flags(u64),
+    // DT_PLTREL: DT_REL or DT_RELA
+    PltRel(u64),
+    // plain integer: counts, or tags with no value
+    Value(u64),
+}
+
+#[derive(Debug, Clone)]
+pub struct DynamicEntry {
+    pub d_tag: u64,
+    pub d_val: u64,
+    pub value: DynamicValue,
+}
+
+impl DynamicEntry {
+    pub fn tag_name(&self) -> String {
+        let name = match self.d_tag {
+            DT_NULL => "NULL",
+            DT_NEEDED => "NEEDED",
+            DT_PLTRELSZ => "PLTRELSZ",
+            DT_PLTGOT => "PLTGOT",
+            DT_HASH => "HASH",
+            DT_STRTAB => "STRTAB",
+            DT_SYMTAB => "SYMTAB",
+            DT_RELA => "RELA",
+            DT_RELASZ => "RELASZ",
+            DT_RELAENT => "RELAENT",
+            DT_STRSZ => "STRSZ",
+            DT_SYMENT => "SYMENT",
+            DT_INIT => "INIT",
+            DT_FINI => "FINI",
+            DT_SONAME => "SONAME",
+            DT_RPATH => "RPATH",
+            DT_SYMBOLIC => "SYMBOLIC",
+            DT_REL => "REL",
+            DT_RELSZ => "RELSZ",
+            DT_RELENT => "RELENT",
+            DT_PLTREL => "PLTREL",
+            DT_DEBUG => "DEBUG",
+            DT_TEXTREL => "TEXTREL",
+            DT_JMPREL => "JMPREL",
+            DT_BIND_NOW => "BIND_NOW",
+            DT_INIT_ARRAY => "INIT_ARRAY",
+            DT_FINI_ARRAY => "FINI_ARRAY",
+            DT_INIT_ARRAYSZ => "INIT_ARRAYSZ",
+            DT_FINI_ARRAYSZ => "FINI_ARRAYSZ",
+            DT_RUNPATH => "RUNPATH",
+            DT_FLAGS => "FLAGS",
+            DT_PREINIT_ARRAY => "PREINIT_ARRAY",
+            DT_PREINIT_ARRAYSZ => "PREINIT_ARRAYSZ",
+            DT_SYMTAB_SHNDX => "SYMTAB_SHNDX",
+            DT_RELRSZ => "RELRSZ",
+            DT_RELR => "RELR",
+            DT_RELRENT => "RELRENT",
+            DT_GNU_HASH => "GNU_HASH",
+            DT_VERSYM => "VERSYM",
+            DT_RELACOUNT => "RELACOUNT",
+            DT_RELCOUNT => "RELCOUNT",
+            DT_FLAGS_1 => "FLAGS_1",
+            DT_VERDEF => "VERDEF",
+            DT_VERDEFNUM => "VERDEFNUM",
+            DT_VERNEED => "VERNEED",
+            DT_VERNEEDNUM => "VERNEEDNUM",
+            DT_AUXILIARY => "AUXILIARY",
+            DT_FILTER => "FILTER",
+            _ => return format!("{:#x}", self.d_tag),
+        };
+        name.to_string()
+    }
+
+    // human readable value, in the spirit of `readelf -d`
+    pub fn value_string(&self) -> String {
+        match &self.value {
+            DynamicValue::Str(s) => match self.d_tag {
+                DT_NEEDED => format!("Shared library: [{s}]"),
+                DT_SONAME => format!("Library soname: [{s}]"),
+                DT_RPATH => format!("Library rpath: [{s}]"),
+                DT_RUNPATH => format!("Library runpath: [{s}]"),
+                _ => format!("[{s}]"),
+            },
+            DynamicValue::Address(addr) => format!("{addr:#x}"),
+            DynamicValue::Size(size) => format!("{size} (bytes)"),
+            DynamicValue::Flags(flags) if self.d_tag == DT_FLAGS => {
+                format!("Flags: {}", flag_names(*flags, DF_NAMES))
+            }
+            DynamicValue::Flags(flags) => format!("Flags: {}", flag_names(*flags, DF_1_NAMES)),
+            DynamicValue::PltRel(DT_RELA) => "RELA".to_string(),
+            DynamicValue::PltRel(DT_REL) => "REL".to_string(),
+            DynamicValue::PltRel(other) => format!("{other:#x}"),
+            DynamicValue::Value(val) => val.to_string(),
+        }
+    }
+}
+
+const DF_NAMES: &[(u64, &str)] = &[
+    (DF_ORIGIN, "ORIGIN"),
+    (DF_SYMBOLIC, "SYMBOLIC"),
+    (DF_TEXTREL, "TEXTREL"),
+    (DF_BIND_NOW, "BIND_NOW"),
+    (DF_STATIC_TLS, "STATIC_TLS"),
+];
+
+const DF_1_NAMES: &[(u64, &str)] = &[
+    (DF_1_NOW, "NOW"),
+    (DF_1_GLOBAL, "GLOBAL"),
+    (DF_1_GROUP, "GROUP"),
+    (DF_1_NODELETE, "NODELETE"),
+    (DF_1_LOADFLTR, "LOADFLTR"),
+    (DF_1_INITFIRST, "INITFIRST"),
+    (DF_1_NOOPEN, "NOOPEN"),
+    (DF_1_ORIGIN, "ORIGIN"),
+    (DF_1_DIRECT, "DIRECT"),
+    (DF_1_INTERPOSE, "INTERPOSE"),
+    (DF_1_NODEFLIB, "NODEFLIB"),
+    (DF_1_NODUMP, "NODUMP"),
+    (DF_1_CONFALT, "CONFALT"),
+    (DF_1_ENDFILTEE, "ENDFILTEE"),
+    (DF_1_DISPRELDNE, "DISPRELDNE"),
+    (DF_1_DISPRELPND, "DISPRELPND"),
+    (DF_1_NODIRECT, "NODIRECT"),
+    (DF_1_PIE, "PIE"),
+];
+
+fn flag_names(flags: u64, names: &[(u64, &str)]) -> String {
+    let mut out: Vec<String> = names
+        .iter()
+        .filter(|(bit, _)| flags & bit != 0)
+        .map(|(_, name)| name.to_string())
+        .collect();
+    let unknown = names.iter().fold(flags, |rest, (bit, _)| rest & !bit);
+    if unknown != 0 {
+        out.push(format!("{unknown:#x}"));
+    }
+    out.join(" ")
+}
+
+fn decode_value(d_tag: u64, d_val: u64, strtab: &[u8]) -> DynamicValue {
+    match d_tag {
+        DT_NEEDED | DT_SONAME | DT_RPATH | DT_RUNPATH | DT_AUXILIARY | DT_FILTER => {
+            DynamicValue::Str(read_str(strtab, d_val as usize))
+        }
+        DT_PLTGOT | DT_HASH | DT_STRTAB | DT_SYMTAB | DT_RELA | DT_INIT | DT_FINI | DT_REL
+        | DT_DEBUG | DT_JMPREL | DT_INIT_ARRAY | DT_FINI_ARRAY | DT_PREINIT_ARRAY
+        | DT_SYMTAB_SHNDX | DT_RELR | DT_GNU_HASH | DT_VERSYM | DT_VERDEF | DT_VERNEED => {
+            DynamicValue::Address(d_val)
+        }
+        DT_PLTRELSZ | DT_RELASZ | DT_RELAENT | DT_STRSZ | DT_SYMENT | DT_RELSZ | DT_RELENT
+        | DT_INIT_ARRAYSZ | DT_FINI_ARRAYSZ | DT_PREINIT_ARRAYSZ | DT_RELRSZ | DT_RELRENT => {
+            DynamicValue::Size(d_val)
+        }
+        DT_FLAGS | DT_FLAGS_1 => DynamicValue::Flags(d_val),
+        DT_PLTREL => DynamicValue::PltRel(d_val),
+        _ => DynamicValue::Value(d_val),
+    }
+}
+
+// Raw (d_tag, d_val) pairs of a dynamic array, up to and including DT_NULL
+fn parse_raw(table: &[u8], header: &ELFHeader) -> Result<Vec<(u64, u64)>, ParseError> {
+    let entsize = if header.ei_class == Arch::B32 { 8 } else { 16 };
+    let mut reader = Reader::new(table, header.ei_data);
+    let mut entries = Vec::new();
+    for _ in 0..table.len() as u64 / entsize {
+        let d_tag = reader.word(&header.ei_class)?;
+        let d_val = reader.word(&header.ei_class)?;
+        entries.push((d_tag, d_val));
+        if d_tag == DT_NULL {
+            break;
+        }
+    }
+    Ok(entries)
+}
+
+// Decode the dynamic array. It's located through PT_DYNAMIC, falling
+// back to the SHT_DYNAMIC section when there are no program headers.
+// String values are resolved through DT_STRTAB/DT_STRSZ, or the string
+// table the SHT_DYNAMIC section links to when DT_STRTAB isn't in a
+// segment; without either, entries with strings are an error.
+pub fn parse_dynamic(
+    contents: &[u8],
+    header: &ELFHeader,
+    pheaders: Option<&[Pheader]>,
+    sections: Option<&[SectionHeader]>,
+) -> Result<Option<Vec<DynamicEntry>>, ParseError> {
+    let pheaders = pheaders.unwrap_or(&[]);
+    let sections = sections.unwrap_or(&[]);
+
+    let table = if let Some(dynamic) = pheaders.iter().find(|p| p.p_type == PT_DYNAMIC) {
+        dynamic.data(contents)?
+    } else if let Some(dynamic) = sections.iter().find(|s| s.sh_type == SHT_DYNAMIC) {
+        dynamic.data(contents)?
+    } else {
+        return Ok(None);
+    };
+    let raw = parse_raw(table, header)?;
+
+    let find = |tag| raw.iter().find(|(d_tag, _)| *d_tag == tag).map(|e| e.1);
+    let mapped = match (find(DT_STRTAB), find(DT_STRSZ)) {
+        (Some(addr), Some(size)) => vaddr_to_offset(pheaders, addr)
+            .map(|offset| checked_slice(contents, offset, size))
+            .transpose()?,
+        _ => None,
+    };
+    // e.g. a bare object, or segments that don't cover DT_STRTAB
+    let linked = sections
+        .iter()
+        .find(|s| s.sh_type == SHT_DYNAMIC)
+        .and_then(|s| sections.get(s.sh_link as usize))
+        .filter(|s| s.sh_type == SHT_STRTAB);
+    let strtab = match (mapped, linked) {
+        (Some(strtab), _) => strtab,
+        (None, Some(section)) => section.data(contents)?,
+        (None, None) => {
+            let strings = [
+                DT_NEEDED,
+                DT_SONAME,
+                DT_RPATH,
+                DT_RUNPATH,
+                DT_AUXILIARY,
+                DT_FILTER,
+            ];
+            if raw.iter().any(|(d_tag, _)| strings.contains(d_tag)) {
+                return Err(ParseError::NoDynamicStrings);
+            }
+            &[]
+        }
+    };
+
+    let entries = raw
+        .into_iter()
+        .map(|(d_tag, d_val)| DynamicEntry {
+            d_tag,
+            d_val,
+            value: decode_value(d_tag, d_val, strtab),
+        })
+        .collect();
+    Ok(Some(entries))
+}
+
+fn strings(entries: &[DynamicEntry], tag: u64) -> Vec<&str> {
+    entries
+        .iter()
+        .filter(|e| e.d_tag == tag)
+        .filter_map(|e| match &e.value {
+            DynamicValue::Str(s) => Some(s.as_str()),
+            _ => None,
+        })
+        .collect()
+}
+
+// shared libraries the object depends on (DT_NEEDED)
+pub fn needed(entries: &[DynamicEntry]) -> Vec<&str> {
+    strings(entries, DT_NEEDED)
+}
+
+pub fn soname(entries: &[DynamicEntry]) -> Option<&str> {
+    strings(entries, DT_SONAME).first().copied()
+}
+
+pub fn rpath(entries: &[DynamicEntry]) -> Option<&str> {
+    strings(entries, DT_RPATH).first().copied()
+}
+
+pub fn runpath(entries: &[DynamicEntry]) -> Option<&str> {
+    strings(entries, DT_RUNPATH).first().copied()
+}
+
+// whether all symbols are bound at load time (-z now)
+pub fn bind_now(entries: &[DynamicEntry]) -> bool {
+    entries.iter().any(|e| match e.d_tag {
+        DT_BIND_NOW => true,
+        DT_FLAGS => e.d_val & DF_BIND_NOW != 0,
+        DT_FLAGS_1 => e.d_val & DF_1_NOW != 0,
+        _ => false,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::dynamic::{self, DynamicEntry, DynamicValue, DT_FLAGS_1, DT_NEEDED, DT_STRTAB};
+    use crate::error::ParseError;
+    use crate::parse::{ELFParser, PT_DYNAMIC};
+    use crate::section::SHT_DYNAMIC;
+
+    #[test]
+    fn flags_1() {
+        let entry = DynamicEntry {
+            d_tag: DT_FLAGS_1,
+            d_val: 0x0800_0001,
+            value: DynamicValue::Flags(0x0800_0001),
+        };
+        assert_eq!(entry.value_string(), "Flags: NOW PIE");
+        assert!(dynamic::bind_now(&[entry]));
+    }
+
+    #[test]
+    fn own_dependencies() {
+        let parser = ELFParser::new(std::env::current_exe().unwrap()).unwrap();
+        let entries = parser.dynamic().unwrap();
+        assert!(dynamic::needed(entries)
+            .iter()
+            .any(|lib| lib.starts_with("libc.so")));
+        assert!(entries
+            .iter()
+            .any(|e| e.d_tag == DT_NEEDED && e.value_string().starts_with("Shared library: [")));
+    }
+
+    #[test]
+    fn unmapped_strtab() {
+        let mut contents = std::fs::read(std::env::current_exe().unwrap()).unwrap();
+        let (strtab, link) = {
+            let parser = ELFParser::from_bytes(&contents).unwrap();
+            let pheaders = parser.program_headers().unwrap();
+            let dynamic = pheaders.iter().find(|p| p.p_type == PT_DYNAMIC).unwrap();
+            let index =
+                dynamic::parse_raw(&contents[dynamic.p_offset as usize..], &parser.elf_header)
+                    .unwrap()
+                    .iter()
+                    .position(|(d_tag, _)| *d_tag == DT_STRTAB)
+                    .unwrap();
+            let sections = parser.sections().unwrap();
+            let section = sections
+                .iter()
+                .position(|s| s.sh_type == SHT_DYNAMIC)
+                .unwrap();
+            // d_val of DT_STRTAB, and sh_link of the ELF64 section header
+            let strtab = dynamic.p_offset as usize + index * 16 + 8;
+            (
+                strtab,
+                parser.elf_header.e_shoff as usize + section * 64 + 40,
+            )
+        };
+        // DT_STRTAB outside every segment: the linked .dynstr still works
+        contents[strtab..strtab + 8].copy_from_slice(&0xdead_0000_0000_u64.to_le_bytes());
+        let parser = ELFParser::from_bytes(&contents).unwrap();
+        let needed = dynamic::needed(parser.dynamic().unwrap());
+        assert!(needed.iter().any(|lib| lib.starts_with("libc.so")));
+
+        // and without a link either, the names are an error, not blanks
+        contents[link..link + 4].fill(0);
+        let parser = ELFParser::from_bytes(&contents).unwrap();
+        assert!(matches!(
+            parser.dynamic(),
+            Err(ParseError::NoDynamicStrings)
+        ));
+    }
+}
@@ -4,6 +4,43 @@ pub enum ParseError {
     UnsupportedClass,
     UnsupportedEndianess,
     UnsupportedVersion,
+    // a read of `needed` bytes at `offset` ran past the end of the file
+    Truncated {
+        offset: u64,
+        needed: u64,
+    },
+    // a header field disagrees with the size of the structure it describes
+    SizeMismatch {
+        field: &'static str,
+        found: u64,
+        expected: u64,
+    },
+    // two header tables share bytes
+    Overlap {
+        first: &'static str,
+        second: &'static str,
+    },
+    // a table, segment or section extends past the end of the file
+    OutOfFile {
+        what: String,
+        end: u64,
+        size: u64,
+    },
+    // e_shstrndx doesn't name one of the sections
+    ShstrndxOutOfRange {
+        index: u64,
+        count: u64,
+    },
+    // an ar archive member header at `offset` doesn't make sense
+    BadArchive {
+        offset: u64,
+        reason: &'static str,
+    },
+    // there is no disassembler for this e_machine
+    UnsupportedMachine(u16),
+    // the strings of the dynamic section are neither at DT_STRTAB nor in
+    // the section it links to
+    NoDynamicStrings,
     Utf8Error(core::str::Utf8Error),
     TryFromSliceError(core::array::TryFromSliceError),
     TryFromIntError(core::num::TryFromIntError),
@@ -14,28 +51,65 @@ impl std::fmt::Display for ParseError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::BadMagic => {
-                write!(f, "\x1b[1;31mError: Bad magic\x1b[0m")
+                write!(f, "Bad magic")
             }
             Self::UnsupportedClass => {
-                write!(f, "\x1b[1;31mError: Unsupported ELF class\x1b[0m")
+                write!(f, "Unsupported ELF class")
             }
             Self::UnsupportedEndianess => {
-                write!(f, "\x1b[1;31mError: Unsupported ELF Endianess\x1b[0m")
+                write!(f, "Unsupported ELF Endianess")
             }
             Self::UnsupportedVersion => {
-                write!(f, "\x1b[1;31mError: Unsupported ELF format version\x1b[0m")
+                write!(f, "Unsupported ELF format version")
+            }
+            Self::Truncated { offset, needed } => {
+                write!(
+                    f,
+                    "Truncated file: needed {needed} bytes at offset {offset:#x}"
+                )
+            }
+            Self::SizeMismatch {
+                field,
+                found,
+                expected,
+            } => {
+                write!(f, "{field} is {found}, expected {expected}")
+            }
+            Self::Overlap { first, second } => {
+                write!(f, "The {second} overlaps the {first}")
+            }
+            Self::OutOfFile { what, end, size } => {
+                write!(
+                    f,
+                    "The {what} ends at {end:#x}, past the end of the file ({size:#x})"
+                )
+            }
+            Self::ShstrndxOutOfRange { index, count } => {
+                write!(
+                    f,
+                    "e_shstrndx is {index}, but there are only {count} sections"
+                )
+            }
+            Self::BadArchive { offset, reason } => {
+                write!(f, "Malformed archive at offset {offset:#x}: {reason}")
+            }
+            Self::UnsupportedMachine(machine) => {
+                write!(f, "No disassembler for machine {machine:#x}")
+            }
+            Self::NoDynamicStrings => {
+                write!(f, "The string table of the dynamic section can't be found")
             }
             Self::Utf8Error(e) => {
-                write!(f, "\x1b[1;31mError: Parsing utf8: {e}\x1b[0m")
+                write!(f, "Parsing utf8: {e}")
             }
             Self::TryFromSliceError(e) => {
-                write!(f, "\x1b[1;31mError: Parsing slice: {e}\x1b[0m")
+                write!(f, "Parsing slice: {e}")
             }
             Self::TryFromIntError(e) => {
-                write!(f, "\x1b[1;31mError: Parsing integer: {e}\x1b[0m")
+                write!(f, "Parsing integer: {e}")
             }
             Self::IOError(e) => {
-                write!(f, "\x1b[1;31mError: IO error: {e}\x1b[0m")
+                write!(f, "IO error: {e}")
             }
         }
     }
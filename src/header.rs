@@ -0,0 +1,198 @@
+// Typed views of the ELF header's identification fields
+use std::fmt::{self, Display};
+
+// a field-less enum over the known values of a raw header field, with
+// an `Unknown` variant for everything else
+macro_rules! header_enum {
+    ($(#[$meta:meta])* $name:ident($raw:ty), $unknown:literal, {
+        $($variant:ident = $value:literal => $text:literal,)*
+    }) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub enum $name {
+            $($variant,)*
+            Unknown($raw),
+        }
+
+        impl From<$raw> for $name {
+            fn from(value: $raw) -> Self {
+                match value {
+                    $($value => $name::$variant,)*
+                    other => $name::Unknown(other),
+                }
+            }
+        }
+
+        impl $name {
+            // the value as stored in the header
+            pub fn raw(&self) -> $raw {
+                match self {
+                    $($name::$variant => $value,)*
+                    $name::Unknown(other) => *other,
+                }
+            }
+        }
+
+        impl Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                match self {
+                    $($name::$variant => f.write_str($text),)*
+                    $name::Unknown(_) => f.write_str($unknown),
+                }
+            }
+        }
+    };
+}
+
+header_enum!(
+    // target operating system ABI (e_ident[EI_OSABI])
+    OsAbi(u8), "UNRECOGNIZED", {
+        SystemV = 0x00 => "Unix - System V",
+        HpUx = 0x01 => "HP-UX",
+        NetBsd = 0x02 => "NetBSD",
+        Linux = 0x03 => "Linux",
+        Hurd = 0x04 => "GNU Hurd",
+        Solaris = 0x06 => "Solaris",
+        Aix = 0x07 => "AIX(Monterey)",
+        Irix = 0x08 => "IRIX",
+        FreeBsd = 0x09 => "FreeBSD",
+        Tru64 = 0x0A => "Tru64",
+        Modesto = 0x0B => "Novell Modesto",
+        OpenBsd = 0x0C => "OpenBSD",
+        OpenVms = 0x0D => "OpenVMS",
+        Nsk = 0x0E => "NonStop Kernel",
+        Aros = 0x0F => "AROS",
+        FenixOs = 0x10 => "FenixOS",
+        CloudAbi = 0x11 => "Nuxi CloudABI",
+        OpenVos = 0x12 => "Stratus Technologies OpenVOS",
+    }
+);
+
+header_enum!(
+    // instruction set architecture (e_machine)
+    // source: https://en.wikipedia.org/wiki/Executable_and_Linkable_Format
+    Machine(u16), "Unspecified/Unknown", {
+        We32100 = 0x01 => "AT&T WE 32100",
+        Sparc = 0x02 => "SPARC",
+        X86 = 0x03 => "x86",
+        M68k = 0x04 => "Motorola 68000 (M68k)",
+        M88k = 0x05 => "Motorola 88000 (M88k)",
+        IntelMcu = 0x06 => "Intel MCU",
+        I860 = 0x07 => "Intel 80860",
+        Mips = 0x08 => "MIPS",
+        S370 = 0x09 => "IBM System/370",
+        MipsRs3Le = 0x0A => "MIPS RS3000 Little-endian",
+        PaRisc = 0x0F => "Hewlett-Packard PA-RISC",
+        I960 = 0x13 => "Intel 80960",
+        PowerPc = 0x14 => "PowerPC",
+        PowerPc64 = 0x15 => "PowerPC(64-bit)",
+        S390 = 0x16 => "S390",
+        Spu = 0x17 => "IBM SPU/SPC",
+        V800 = 0x24 => "NEC V800",
+        Fr20 = 0x25 => "Fujitsu FR20",
+        Rh32 = 0x26 => "TRW RH-32",
+        Rce = 0x27 => "Motorola RCE",
+        Arm = 0x28 => "Arm",
+        Alpha = 0x29 => "Digital Alpha",
+        SuperH = 0x2A => "SuperH",
+        SparcV9 = 0x2B => "SPARC Version 9",
+        TriCore = 0x2C => "Siemens TriCore embedded processor",
+        Arc = 0x2D => "Argonaut RISC Core",
+        H8_300 = 0x2E => "Hitachi H8/300",
+        H8_300H = 0x2F => "Hitachi H8/300H",
+        H8S = 0x30 => "Hitachi H8S",
+        H8_500 = 0x31 => "Hitachi H8/500",
+        Ia64 = 0x32 => "IA-64",
+        MipsX = 0x33 => "Stanford MIPS-X",
+        ColdFire = 0x34 => "Motorola ColdFire",
+        M68hc12 = 0x35 => "Motorola M68HC12",
+        Mma = 0x36 => "Fujitsu MMA Multimedia Accelerator",
+        X86_64 = 0x3E => "AMD x86-64",
+        AArch64 = 0xB7 => "Arm64",
+        RiscV = 0xF3 => "RISC-V",
+    }
+);
+
+// object file type (e_type)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObjectType {
+    None,
+    Relocatable,
+    Executable,
+    SharedObject,
+    Core,
+    // ET_LOOS..=ET_HIOS
+    OsSpecific(u16),
+    // ET_LOPROC..=ET_HIPROC
+    ProcessorSpecific(u16),
+    Unknown(u16),
+}
+
+impl From<u16> for ObjectType {
+    fn from(value: u16) -> Self {
+        match value {
+            0x00 => ObjectType::None,
+            0x01 => ObjectType::Relocatable,
+            0x02 => ObjectType::Executable,
+            0x03 => ObjectType::SharedObject,
+            0x04 => ObjectType::Core,
+            0xFE00..=0xFEFF => ObjectType::OsSpecific(value),
+            0xFF00..=0xFFFF => ObjectType::ProcessorSpecific(value),
+            _ => ObjectType::Unknown(value),
+        }
+    }
+}
+
+impl ObjectType {
+    // the value as stored in the header
+    pub fn raw(&self) -> u16 {
+        match self {
+            ObjectType::None => 0x00,
+            ObjectType::Relocatable => 0x01,
+            ObjectType::Executable => 0x02,
+            ObjectType::SharedObject => 0x03,
+            ObjectType::Core => 0x04,
+            ObjectType::OsSpecific(value)
+            | ObjectType::ProcessorSpecific(value)
+            | ObjectType::Unknown(value) => *value,
+        }
+    }
+}
+
+impl Display for ObjectType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ObjectType::None => f.write_str("None"),
+            ObjectType::Relocatable => f.write_str("Relocatable"),
+            ObjectType::Executable => f.write_str("Executable"),
+            ObjectType::SharedObject => f.write_str("Shared object"),
+            ObjectType::Core => f.write_str("Core file"),
+            ObjectType::OsSpecific(value) => write!(f, "OS-specific ({value:#x})"),
+            ObjectType::ProcessorSpecific(value) => write!(f, "Processor-specific ({value:#x})"),
+            ObjectType::Unknown(value) => write!(f, "UNKNOWN ({value:#x})"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::header::{Machine, ObjectType, OsAbi};
+
+    #[test]
+    fn known_values() {
+        assert_eq!(Machine::from(0x3E), Machine::X86_64);
+        assert_eq!(Machine::X86_64.to_string(), "AMD x86-64");
+        assert_eq!(Machine::AArch64.raw(), 0xB7);
+        assert_eq!(OsAbi::from(0x03), OsAbi::Linux);
+        assert_eq!(ObjectType::from(0x03).to_string(), "Shared object");
+    }
+
+    #[test]
+    fn unknown_values() {
+        assert_eq!(Machine::from(0x1234), Machine::Unknown(0x1234));
+        assert_eq!(Machine::from(0x1234).raw(), 0x1234);
+        assert_eq!(OsAbi::from(0x05).to_string(), "UNRECOGNIZED");
+        assert_eq!(ObjectType::from(0xFE10), ObjectType::OsSpecific(0xFE10));
+        assert_eq!(ObjectType::from(0xFF00).raw(), 0xFF00);
+    }
+}
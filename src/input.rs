@@ -0,0 +1,86 @@
+// The bytes of a parsed file: borrowed from the caller, read into memory,
+// or (with the `mmap` feature) mapped, so that only the pages eva actually
+// looks at are ever loaded
+use std::fs::File;
+use std::io::{self, Read};
+use std::ops::{Deref, Range};
+use std::path::Path;
+use std::sync::Arc;
+
+pub enum Input<'a> {
+    Borrowed(&'a [u8]),
+    Owned(Vec<u8>),
+    #[cfg(feature = "mmap")]
+    Mapped(memmap2::Mmap),
+    // part of another input, e.g. an archive member
+    Shared(Arc<Input<'a>>, Range<usize>),
+}
+
+impl Input<'static> {
+    // read everything from `reader`, e.g. stdin
+    pub fn read(mut reader: impl Read) -> io::Result<Self> {
+        let mut contents = Vec::new();
+        reader.read_to_end(&mut contents)?;
+        Ok(Input::Owned(contents))
+    }
+
+    // map regular files when possible; pipes, devices and empty files are read
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let file = File::open(path)?;
+        #[cfg(feature = "mmap")]
+        {
+            let metadata = file.metadata()?;
+            if metadata.is_file() && metadata.len() > 0 {
+                // SAFETY: the mapping is read-only; as with every mmap, the
+                // file being truncated by another process while mapped is
+                // outside what we can guard against
+                return Ok(Input::Mapped(unsafe { memmap2::Mmap::map(&file)? }));
+            }
+        }
+        Input::read(io::BufReader::new(file))
+    }
+}
+
+impl Deref for Input<'_> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            Input::Borrowed(bytes) => bytes,
+            Input::Owned(bytes) => bytes,
+            #[cfg(feature = "mmap")]
+            Input::Mapped(map) => map,
+            Input::Shared(input, range) => &input[range.clone()],
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::input::Input;
+    use std::path::Path;
+    use std::sync::Arc;
+
+    #[test]
+    fn open_file() {
+        let path = std::env::current_exe().unwrap();
+        let input = Input::open(&path).unwrap();
+        #[cfg(feature = "mmap")]
+        assert!(matches!(input, Input::Mapped(_)));
+        assert_eq!(&input[..], &std::fs::read(&path).unwrap()[..]);
+    }
+
+    #[test]
+    fn open_device() {
+        let input = Input::open(Path::new("/dev/null")).unwrap();
+        assert!(matches!(input, Input::Owned(_)));
+        assert!(input.is_empty());
+    }
+
+    #[test]
+    fn shared_range() {
+        let input = Arc::new(Input::Owned(b"!<arch>\n".to_vec()));
+        let part = Input::Shared(input.clone(), 2..6);
+        assert_eq!(&part[..], b"arch");
+    }
+}
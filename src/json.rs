@@ -0,0 +1,456 @@
+// Minimal JSON serialization of the parsed structures. The schema is
+// documented in README.md; only add fields to it, never rename or remove.
+
+use std::fmt::{self, Display, Write};
+
+use crate::archive::ArchiveSymbol;
+use crate::cfg::{Block, Function};
+use crate::disasm::{Instruction, Label, Listing};
+use crate::dynamic::{DynamicEntry, DynamicValue};
+use crate::note::{Note, NoteSection};
+use crate::parse::{Arch, ELFHeader, Pheader};
+use crate::reloc::{Relocation, RelocationSection};
+use crate::section::SectionHeader;
+use crate::symbol::Symbol;
+use crate::utils::Endian;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    UInt(u64),
+    Int(i64),
+    Str(String),
+    Array(Vec<Json>),
+    // keys keep their insertion order
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    // start an object; add fields with `field`
+    pub fn object() -> Json {
+        Json::Object(Vec::new())
+    }
+
+    pub fn field(mut self, key: &str, value: impl Into<Json>) -> Json {
+        if let Json::Object(fields) = &mut self {
+            fields.push((key.to_string(), value.into()));
+        }
+        self
+    }
+
+    fn write(&self, f: &mut fmt::Formatter, indent: usize) -> fmt::Result {
+        match self {
+            Json::Null => f.write_str("null"),
+            Json::Bool(b) => write!(f, "{b}"),
+            Json::UInt(n) => write!(f, "{n}"),
+            Json::Int(n) => write!(f, "{n}"),
+            Json::Str(s) => write_str(f, s),
+            Json::Array(items) if items.is_empty() => f.write_str("[]"),
+            Json::Object(fields) if fields.is_empty() => f.write_str("{}"),
+            Json::Array(items) => {
+                f.write_str("[\n")?;
+                for (i, item) in items.iter().enumerate() {
+                    write!(f, "{:1$}", "", (indent + 1) * 2)?;
+                    item.write(f, indent + 1)?;
+                    f.write_str(if i + 1 < items.len() { ",\n" } else { "\n" })?;
+                }
+                write!(f, "{:1$}]", "", indent * 2)
+            }
+            Json::Object(fields) => {
+                f.write_str("{\n")?;
+                for (i, (key, value)) in fields.iter().enumerate() {
+                    write!(f, "{:1$}", "", (indent + 1) * 2)?;
+                    write_str(f, key)?;
+                    f.write_str(": ")?;
+                    value.write(f, indent + 1)?;
+                    f.write_str(if i + 1 < fields.len() { ",\n" } else { "\n" })?;
+                }
+                write!(f, "{:1$}}}", "", indent * 2)
+            }
+        }
+    }
+}
+
+// pretty-printed with two space indentation
+impl Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.write(f, 0)
+    }
+}
+
+fn write_str(f: &mut fmt::Formatter, s: &str) -> fmt::Result {
+    f.write_char('"')?;
+    for c in s.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => f.write_char(c)?,
+        }
+    }
+    f.write_char('"')
+}
+
+impl From<bool> for Json {
+    fn from(b: bool) -> Json {
+        Json::Bool(b)
+    }
+}
+
+impl From<u8> for Json {
+    fn from(n: u8) -> Json {
+        Json::UInt(n.into())
+    }
+}
+
+impl From<u16> for Json {
+    fn from(n: u16) -> Json {
+        Json::UInt(n.into())
+    }
+}
+
+impl From<u32> for Json {
+    fn from(n: u32) -> Json {
+        Json::UInt(n.into())
+    }
+}
+
+impl From<u64> for Json {
+    fn from(n: u64) -> Json {
+        Json::UInt(n)
+    }
+}
+
+impl From<i64> for Json {
+    fn from(n: i64) -> Json {
+        Json::Int(n)
+    }
+}
+
+impl From<&str> for Json {
+    fn from(s: &str) -> Json {
+        Json::Str(s.to_string())
+    }
+}
+
+impl From<String> for Json {
+    fn from(s: String) -> Json {
+        Json::Str(s)
+    }
+}
+
+impl<T: Into<Json>> From<Option<T>> for Json {
+    fn from(value: Option<T>) -> Json {
+        value.map_or(Json::Null, Into::into)
+    }
+}
+
+impl<T: Into<Json>> From<Vec<T>> for Json {
+    fn from(items: Vec<T>) -> Json {
+        Json::Array(items.into_iter().map(Into::into).collect())
+    }
+}
+
+pub trait ToJson {
+    fn to_json(&self) -> Json;
+}
+
+impl<T: ToJson> ToJson for [T] {
+    fn to_json(&self) -> Json {
+        Json::Array(self.iter().map(ToJson::to_json).collect())
+    }
+}
+
+impl<T: ToJson> ToJson for Option<&[T]> {
+    fn to_json(&self) -> Json {
+        match self {
+            Some(items) => items.to_json(),
+            None => Json::Null,
+        }
+    }
+}
+
+impl ToJson for ELFHeader {
+    fn to_json(&self) -> Json {
+        let class = match self.ei_class {
+            Arch::B32 => "ELF32",
+            Arch::B64 => "ELF64",
+        };
+        let data = match self.ei_data {
+            Endian::Little => "little",
+            Endian::Big => "big",
+        };
+        Json::object()
+            .field("class", class)
+            .field("data", data)
+            .field("ei_version", self.ei_version)
+            .field("osabi", self.ei_osabi)
+            .field("osabi_name", self.osabi().to_string())
+            .field("abiversion", self.ei_abiversion)
+            .field("type", self.e_type)
+            .field("type_name", self.object_type().to_string())
+            .field("machine", self.e_machine)
+            .field("machine_name", self.machine().to_string())
+            .field("version", self.e_version)
+            .field("entry", self.e_entry)
+            .field("phoff", self.e_phoff)
+            .field("shoff", self.e_shoff)
+            .field("flags", self.e_flags)
+            .field("ehsize", self.e_ehsize)
+            .field("phentsize", self.e_phentsize)
+            .field("phnum", self.e_phnum)
+            .field("shentsize", self.e_shentsize)
+            .field("shnum", self.e_shnum)
+            .field("shstrndx", self.e_shstrndx)
+            .field("effective_phnum", self.phnum)
+            .field("effective_shnum", self.shnum)
+            .field("effective_shstrndx", self.shstrndx)
+    }
+}
+
+impl ToJson for Pheader {
+    fn to_json(&self) -> Json {
+        Json::object()
+            .field("type", self.p_type)
+            .field("type_name", self.type_name())
+            .field("flags", self.p_flags)
+            .field("flags_string", self.flags_string())
+            .field("offset", self.p_offset)
+            .field("vaddr", self.p_vaddr)
+            .field("paddr", self.p_paddr)
+            .field("filesz", self.p_filesz)
+            .field("memsz", self.p_memsz)
+            .field("align", self.p_align)
+    }
+}
+
+impl ToJson for SectionHeader<'_> {
+    fn to_json(&self) -> Json {
+        Json::object()
+            .field("name", &*self.name)
+            .field("type", self.sh_type)
+            .field("type_name", self.type_name())
+            .field("flags", self.sh_flags)
+            .field("flags_string", self.flags_string())
+            .field("addr", self.sh_addr)
+            .field("offset", self.sh_offset)
+            .field("size", self.sh_size)
+            .field("link", self.sh_link)
+            .field("info", self.sh_info)
+            .field("addralign", self.sh_addralign)
+            .field("entsize", self.sh_entsize)
+    }
+}
+
+impl ToJson for Symbol<'_> {
+    fn to_json(&self) -> Json {
+        Json::object()
+            .field("name", &*self.name)
+            .field("value", self.st_value)
+            .field("size", self.st_size)
+            .field("bind", self.bind_name())
+            .field("type", self.type_name())
+            .field("visibility", self.visibility_name())
+            .field("shndx", self.shndx)
+    }
+}
+
+impl ToJson for DynamicEntry {
+    fn to_json(&self) -> Json {
+        let string = match &self.value {
+            DynamicValue::Str(s) => Some(s.as_str()),
+            _ => None,
+        };
+        Json::object()
+            .field("tag", self.d_tag)
+            .field("tag_name", self.tag_name())
+            .field("value", self.d_val)
+            .field("string", string)
+            .field("display", self.value_string())
+    }
+}
+
+impl ToJson for Note {
+    fn to_json(&self) -> Json {
+        Json::object()
+            .field("owner", self.owner.as_str())
+            .field("type", self.n_type)
+            .field("type_name", self.type_name())
+            .field("description", self.description())
+    }
+}
+
+impl ToJson for NoteSection {
+    fn to_json(&self) -> Json {
+        Json::object()
+            .field("name", self.name.as_str())
+            .field("offset", self.offset)
+            .field("notes", self.notes.to_json())
+    }
+}
+
+impl ToJson for ArchiveSymbol {
+    fn to_json(&self) -> Json {
+        Json::object()
+            .field("name", self.name.as_str())
+            .field("member", self.member.as_deref())
+            .field("offset", self.offset)
+    }
+}
+
+impl ToJson for Instruction {
+    fn to_json(&self) -> Json {
+        let bytes: Vec<String> = self.bytes.iter().map(|b| format!("{b:02x}")).collect();
+        Json::object()
+            .field("address", self.address)
+            .field("bytes", bytes.join(" "))
+            .field("mnemonic", self.mnemonic.as_str())
+            .field("operands", self.operands.as_str())
+            .field("flow", self.flow.name())
+            .field("target", self.target)
+            .field("reference", self.reference)
+            .field("target_symbol", self.target_symbol.as_deref())
+    }
+}
+
+impl ToJson for Label {
+    fn to_json(&self) -> Json {
+        Json::object()
+            .field("name", self.name.as_str())
+            .field("address", self.address)
+            .field("size", self.size)
+    }
+}
+
+impl ToJson for Listing {
+    fn to_json(&self) -> Json {
+        Json::object()
+            .field("section", self.name.as_str())
+            .field("address", self.address)
+            .field("labels", self.labels.to_json())
+            .field("instructions", self.instructions.to_json())
+    }
+}
+
+impl ToJson for Block {
+    fn to_json(&self) -> Json {
+        let successors: Vec<Json> = self
+            .successors
+            .iter()
+            .map(|(address, edge)| {
+                Json::object()
+                    .field("address", *address)
+                    .field("edge", edge.name())
+            })
+            .collect();
+        Json::object()
+            .field("address", self.address)
+            .field("instructions", self.instructions.to_json())
+            .field("successors", Json::Array(successors))
+    }
+}
+
+impl ToJson for Function {
+    fn to_json(&self) -> Json {
+        Json::object()
+            .field("name", self.name.as_str())
+            .field("section", self.section.as_str())
+            .field("address", self.address)
+            .field("size", self.size)
+            .field("blocks", self.blocks.to_json())
+    }
+}
+
+fn relocation(reloc: &Relocation, e_machine: u16) -> Json {
+    Json::object()
+        .field("offset", reloc.r_offset)
+        .field("info", reloc.r_info)
+        .field("type", reloc.r_type)
+        .field("type_name", reloc.type_name(e_machine))
+        .field("symbol", reloc.symbol_name.as_str())
+        .field("symbol_value", reloc.symbol_value)
+        .field("addend", reloc.r_addend)
+}
+
+// relocation type names depend on the machine, so these don't go through ToJson
+pub fn relocations(tables: &[RelocationSection], e_machine: u16) -> Json {
+    let tables = tables.iter().map(|table| {
+        Json::object()
+            .field("name", table.name.as_str())
+            .field("type", table.sh_type)
+            .field("offset", table.offset)
+            .field(
+                "entries",
+                Json::Array(
+                    table
+                        .entries
+                        .iter()
+                        .map(|r| relocation(r, e_machine))
+                        .collect(),
+                ),
+            )
+    });
+    Json::Array(tables.collect())
+}
+
+// the nm letter of a symbol depends on the section it is in
+pub fn symbols(symbols: Option<&[Symbol]>, sections: &[SectionHeader]) -> Json {
+    let Some(symbols) = symbols else {
+        return Json::Null;
+    };
+    let symbols = symbols.iter().map(|symbol| {
+        symbol
+            .to_json()
+            .field("nm_letter", symbol.nm_letter(sections).to_string())
+    });
+    Json::Array(symbols.collect())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::json::{Json, ToJson};
+    use crate::parse::ELFHeader;
+
+    #[test]
+    fn escaping() {
+        let json = Json::from("a \"quoted\" \\ line\n\x01");
+        assert_eq!(json.to_string(), r#""a \"quoted\" \\ line\n\u0001""#);
+    }
+
+    #[test]
+    fn pretty_object() {
+        let json = Json::object()
+            .field("name", ".text")
+            .field("size", 16_u64)
+            .field("addend", None::<i64>)
+            .field("list", vec![1_u8, 2])
+            .field("empty", Vec::<u8>::new());
+        let expected = "{\n  \"name\": \".text\",\n  \"size\": 16,\n  \"addend\": null,\n  \"list\": [\n    1,\n    2\n  ],\n  \"empty\": []\n}";
+        assert_eq!(json.to_string(), expected);
+    }
+
+    #[test]
+    fn header_fields() {
+        let header = ELFHeader {
+            e_machine: 0x3E,
+            e_entry: 0x401000,
+            ..Default::default()
+        };
+        let Json::Object(fields) = header.to_json() else {
+            panic!("header is not an object");
+        };
+        let get = |key: &str| {
+            fields
+                .iter()
+                .find(|(k, _)| k == key)
+                .map(|(_, v)| v.clone())
+        };
+        assert_eq!(get("class"), Some(Json::from("ELF64")));
+        assert_eq!(get("machine_name"), Some(Json::from("AMD x86-64")));
+        assert_eq!(get("entry"), Some(Json::UInt(0x401000)));
+    }
+}
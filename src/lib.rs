@@ -0,0 +1,25 @@
+// https://en.wikipedia.org/wiki/Executable_and_Linkable_Format
+
+pub mod archive;
+pub mod cfg;
+pub mod color;
+pub mod disasm;
+pub mod dynamic;
+pub mod error;
+pub mod header;
+pub mod input;
+pub mod json;
+pub mod note;
+pub mod parse;
+pub mod reloc;
+pub mod render;
+pub mod scan;
+pub mod section;
+pub mod symbol;
+pub mod utils;
+pub mod validate;
+
+pub use error::ParseError;
+pub use parse::{ELFHeader, ELFParser, Pheader};
+pub use section::SectionHeader;
+pub use symbol::Symbol;
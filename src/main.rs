@@ -1,87 +1,312 @@
-#![allow(unused_imports)]
-use std::{
-    fs::File,
-    io::{BufReader, Read},
-};
+use std::cell::Cell;
+use std::fmt::Display;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use std::process::ExitCode;
 
-// https://en.wikipedia.org/wiki/Executable_and_Linkable_Format
+use eva::color::{paint, ColorChoice};
+use eva::disasm::Syntax;
+use eva::render::{self, JsonRenderer, MarkdownRenderer, Renderer, TextRenderer, Views};
+use eva::scan::Parsed;
+use eva::{archive, scan, validate, ELFParser, ParseError};
+use getopts::{Matches, Options};
 
-mod utils;
-use utils::ValidNums;
-mod parse;
-use parse::{ELFHeader, ELFParser, Pheader};
+// which parts of the file to display
+fn views(matches: &Matches, syntax: Syntax) -> Views {
+    let disassemble = matches.opt_present("D").then_some(syntax);
+    let cfg = matches.opt_present("G").then_some(syntax);
+    if matches.opt_present("a") {
+        return Views {
+            disassemble,
+            cfg,
+            ..Views::all()
+        };
+    }
+    let views = Views {
+        header: matches.opt_present("h"),
+        program_headers: matches.opt_present("l"),
+        sections: matches.opt_present("S"),
+        symbols: matches.opt_present("s"),
+        dynamic: matches.opt_present("d"),
+        relocs: matches.opt_present("r"),
+        notes: matches.opt_present("n"),
+        archive_index: matches.opt_present("c"),
+        disassemble,
+        cfg,
+    };
+    if matches.opts_present(&["h", "l", "S", "s", "d", "r", "n", "c", "D", "G"].map(String::from)) {
+        return views;
+    }
+    // nothing asked for: the header and both header tables
+    Views {
+        header: true,
+        program_headers: true,
+        sections: true,
+        ..views
+    }
+}
 
-mod error;
-use error::ParseError;
+fn options() -> Options {
+    let mut opts = Options::new();
+    opts.optflag("a", "all", "Equivalent to: -h -l -S -s -r -d -n");
+    opts.optflag("h", "file-header", "Display the ELF file header");
+    opts.optflag("l", "program-headers", "Display the program headers");
+    opts.optflag("S", "section-headers", "Display the sections' header");
+    opts.optflag("s", "syms", "Display the symbol tables");
+    opts.optflag("d", "dynamic", "Display the dynamic section");
+    opts.optflag("r", "relocs", "Display the relocations");
+    opts.optflag("n", "notes", "Display the notes");
+    opts.optflag("c", "archive-index", "Display the symbol index of archives");
+    opts.optflag(
+        "D",
+        "disassemble",
+        "Display the code of the executable sections",
+    );
+    opts.optflag(
+        "G",
+        "cfg",
+        "Display the control-flow graphs of the functions as DOT",
+    );
+    opts.optopt(
+        "M",
+        "syntax",
+        "Assembly syntax: att (default) or intel",
+        "SYNTAX",
+    );
+    opts.optflag(
+        "",
+        "strict",
+        "Check the headers and refuse to display malformed files",
+    );
+    opts.optopt(
+        "",
+        "format",
+        "Output format: text (default), json or markdown",
+        "FORMAT",
+    );
+    opts.optflagopt(
+        "",
+        "color",
+        "When to use colors: auto (default), always or never",
+        "WHEN",
+    );
+    opts.optflag(
+        "R",
+        "recursive",
+        "Look for ELF files in directories, recursively",
+    );
+    opts.optopt(
+        "j",
+        "jobs",
+        "Parse up to N files at a time (0: one per CPU)",
+        "N",
+    );
+    opts.optflag("", "help", "Display this information");
+    opts.optflag("v", "version", "Display the version number of eva");
+    opts
+}
 
-fn main() {
+fn main() -> ExitCode {
     let args: Vec<String> = std::env::args().collect();
-    if args.len() != 2 {
-        eprintln!("\x1b[1;31mError: Missing file name.\x1b[0m");
-        return;
-    }
-
-    let file = match File::open(&args[1]) {
-        Ok(f) => f,
+    let opts = options();
+    let matches = match opts.parse(&args[1..]) {
+        Ok(m) => m,
         Err(e) => {
-            eprintln!("{}", e);
-            return;
+            error(ColorChoice::Auto, e);
+            eprintln!("Try 'eva --help' for more information.");
+            return ExitCode::FAILURE;
         }
     };
-
-    let mut file_buf = BufReader::new(file);
-    let mut file_contents = Vec::new();
-    println!();
-    let file_size = if let Ok(s) = file_buf.read_to_end(&mut file_contents) {
-        s
-    } else {
-        eprintln!("\x1b[1;31mError: Cann't read the file\x1b[0m");
-        return;
+    // a bare `--color` means always
+    let color = match matches.opt_default("color", "always") {
+        None => ColorChoice::Auto,
+        Some(name) => match ColorChoice::from_name(&name) {
+            Some(color) => color,
+            None => {
+                error(ColorChoice::Auto, format!("Invalid color choice '{name}'"));
+                return ExitCode::FAILURE;
+            }
+        },
     };
-
-    if file_size < 4 {
-        println!("\x1b[1;31mThe file is NOT in ELF format\x1b[0m");
-        return;
+    if matches.opt_present("help") {
+        print!("{}", opts.usage("Usage: eva <option(s)> elf-file(s)|-"));
+        return ExitCode::SUCCESS;
     }
-    let mut elf_header = ELFHeader::default();
-    _ = elf_header.parse(&file_contents);
-    elf_header.display();
-}
-
-fn parse_pheader64(slice: &[u8]) -> Pheader {
-    let mut cursor = 0;
-    let mut pheader = Pheader::default();
-
-    pheader.p_type = u32::from_little_bytes(&slice[cursor..(cursor + 4)]);
-
-    cursor += 4;
-
-    pheader.p_flags = u32::from_little_bytes(&slice[cursor..(cursor + 4)]);
-
-    cursor += 4;
-
-    pheader.p_offset = u64::from_little_bytes(&slice[cursor..(cursor + 8)]);
-
-    cursor += 8;
-
-    pheader.p_vaddr = u64::from_little_bytes(&slice[cursor..(cursor + 8)]);
-    cursor += 8;
-
-    pheader.p_paddr = u64::from_little_bytes(&slice[cursor..(cursor + 8)]);
-    cursor += 8;
+    if matches.opt_present("v") {
+        println!("eva {}", env!("CARGO_PKG_VERSION"));
+        return ExitCode::SUCCESS;
+    }
+    if matches.free.is_empty() {
+        error(color, "Missing file name.");
+        eprintln!("{}", opts.short_usage("eva"));
+        return ExitCode::FAILURE;
+    }
+    let syntax = match matches.opt_str("M") {
+        None => Syntax::default(),
+        Some(name) => match Syntax::from_name(&name) {
+            Some(syntax) => syntax,
+            None => {
+                error(color, format!("Unknown assembly syntax '{name}'"));
+                return ExitCode::FAILURE;
+            }
+        },
+    };
+    let jobs = match matches.opt_str("j").map(|jobs| jobs.parse::<usize>()) {
+        None => 1,
+        // 0: one per CPU
+        Some(Ok(0)) => std::thread::available_parallelism().map_or(1, |n| n.get()),
+        Some(Ok(jobs)) => jobs,
+        Some(Err(_)) => {
+            error(color, "The number of jobs must be a number");
+            return ExitCode::FAILURE;
+        }
+    };
 
-    pheader.p_filesz = u64::from_little_bytes(&slice[cursor..(cursor + 8)]);
-    cursor += 8;
+    let failed = Cell::new(false);
+    let recursive = matches.opt_present("R");
+    let mut paths = Vec::new();
+    for path in matches.free.iter().map(PathBuf::from) {
+        if !path.is_dir() {
+            paths.push(path);
+        } else if recursive {
+            for found in scan::walk(&path) {
+                match found {
+                    Ok(path) => paths.push(path),
+                    Err((path, e)) => {
+                        error(color, format!("{}: {e}", path.display()));
+                        failed.set(true);
+                    }
+                }
+            }
+        } else {
+            error(color, format!("'{}' is a directory", path.display()));
+            failed.set(true);
+        }
+    }
+    // with several files, each gets a heading and non-ELF ones are skipped;
+    // so do the members of an archive
+    let many = matches.free.len() > 1
+        || recursive
+        || paths.iter().any(|path| archive::is_archive_file(path));
 
-    pheader.p_memsz = u64::from_little_bytes(&slice[cursor..(cursor + 8)]);
-    cursor += 8;
+    let stdout = std::io::stdout().lock();
+    let stdout_color = color.enabled(&stdout);
+    let mut renderer: Box<dyn Renderer> = match matches.opt_str("format").as_deref() {
+        None | Some("text") if many => {
+            Box::new(TextRenderer::new(stdout, stdout_color).with_file_names())
+        }
+        None | Some("text") => Box::new(TextRenderer::new(stdout, stdout_color)),
+        Some("json") if many => Box::new(JsonRenderer::array(stdout)),
+        Some("json") => Box::new(JsonRenderer::new(stdout)),
+        Some("markdown") => Box::new(MarkdownRenderer::new(stdout)),
+        Some(other) => {
+            error(color, format!("Unknown output format '{other}'"));
+            return ExitCode::FAILURE;
+        }
+    };
 
-    pheader.p_align = u64::from_little_bytes(&slice[cursor..(cursor + 8)]);
+    let views = views(&matches, syntax);
+    let mut broken_pipe = false;
+    // false once the output is gone
+    let mut report = |name: &str, result: Result<(), ParseError>| {
+        match result {
+            Ok(()) => {}
+            // e.g. piped into `head`
+            Err(ParseError::IOError(e)) if e.kind() == ErrorKind::BrokenPipe => {
+                broken_pipe = true;
+                return false;
+            }
+            Err(ParseError::BadMagic) => {
+                error(color, "The file is NOT in ELF format");
+                failed.set(true);
+            }
+            Err(e) if many => {
+                error(color, format!("{name}: {e}"));
+                failed.set(true);
+            }
+            Err(e) => {
+                error(color, e);
+                failed.set(true);
+            }
+        }
+        true
+    };
+    // with --strict, files with problems are reported instead of displayed
+    let strict = matches.opt_present("strict");
+    let prepare = |parser: &ELFParser| {
+        // the tables of a malformed file may not parse; leave them alone
+        if strict && !parser.diagnostics().is_empty() {
+            return Ok(());
+        }
+        render::preload(parser, &views)
+    };
+    let valid = |name: &str, parser: &ELFParser| {
+        // found by `prepare` on a worker thread
+        let diagnostics = if strict { parser.diagnostics() } else { &[] };
+        for diagnostic in diagnostics {
+            error(color, format!("{name}: {diagnostic}"));
+            failed.set(true);
+        }
+        diagnostics.is_empty()
+    };
+    // the parser refuses a bad class or byte order before `validate` runs
+    let refused = |name: &str, e: ParseError| {
+        if !strict {
+            return Err(e);
+        }
+        let diagnostic = validate::refused(e)?;
+        error(color, format!("{name}: {diagnostic}"));
+        failed.set(true);
+        Ok(())
+    };
+    scan::parse_files(&paths, jobs, prepare, |path, parsed| {
+        let name = path.to_string_lossy();
+        let archive = match parsed {
+            Ok(Parsed::Archive(archive)) => archive,
+            Ok(Parsed::Elf(parser)) if !valid(&name, &parser) => return true,
+            Ok(Parsed::Elf(parser)) => {
+                let result = render::render(&mut *renderer, &name, &parser, &views);
+                return report(&name, result);
+            }
+            Err(ParseError::BadMagic) if many => return true,
+            Err(e) => return report(&name, refused(&name, e)),
+        };
+        if views.archive_index {
+            let result = render::archive_index(&mut *renderer, &name, &archive);
+            if !report(&name, result) {
+                return false;
+            }
+        }
+        if !views.any_elf() {
+            return true;
+        }
+        for (member, parsed) in archive.members {
+            let name = format!("{name}({member})");
+            let result = match parsed {
+                Ok(parser) if !valid(&name, &parser) => Ok(()),
+                Ok(parser) => render::render(&mut *renderer, &name, &parser, &views),
+                Err(ParseError::BadMagic) => Ok(()),
+                Err(e) => refused(&name, e),
+            };
+            if !report(&name, result) {
+                return false;
+            }
+        }
+        true
+    });
+    if !broken_pipe {
+        _ = renderer.finish();
+    }
 
-    pheader
+    if failed.get() {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
 }
 
-fn _disassemble_elf(exec_section: &[u8]) {
-    _ = exec_section;
-    todo!()
+// print an error message to stderr, in red if it's a terminal
+fn error(color: ColorChoice, message: impl Display) {
+    let color = color.enabled(&std::io::stderr());
+    eprintln!("{}", paint(color, "1;31", format!("Error: {message}")));
 }
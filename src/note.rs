@@ -0,0 +1,696 @@
+use crate::error::ParseError;
+use crate::parse::{Arch, ELFHeader, Pheader, EM_386, EM_AARCH64, EM_X86_64, PT_NOTE};
+use crate::section::{read_str, SectionHeader, SHT_NOTE};
+use crate::utils::{checked_slice, Reader};
+
+// GNU note types (owner "GNU")
+pub const NT_GNU_ABI_TAG: u32 = 1;
+pub const NT_GNU_HWCAP: u32 = 2;
+pub const NT_GNU_BUILD_ID: u32 = 3;
+pub const NT_GNU_GOLD_VERSION: u32 = 4;
+pub const NT_GNU_PROPERTY_TYPE_0: u32 = 5;
+
+// Go note types (owner "Go")
+pub const NT_GO_BUILDID: u32 = 4;
+
+// SystemTap probes (owner "stapsdt")
+pub const NT_STAPSDT: u32 = 3;
+
+// core file note types (owner "CORE" or "LINUX")
+pub const NT_PRSTATUS: u32 = 1;
+pub const NT_PRFPREG: u32 = 2;
+pub const NT_PRPSINFO: u32 = 3;
+pub const NT_TASKSTRUCT: u32 = 4;
+pub const NT_AUXV: u32 = 6;
+pub const NT_PRXFPREG: u32 = 0x4658_5850;
+pub const NT_X86_XSTATE: u32 = 0x202;
+pub const NT_ARM_VFP: u32 = 0x400;
+pub const NT_ARM_TLS: u32 = 0x401;
+pub const NT_ARM_HW_BREAK: u32 = 0x402;
+pub const NT_ARM_HW_WATCH: u32 = 0x403;
+pub const NT_ARM_SYSTEM_CALL: u32 = 0x404;
+pub const NT_ARM_SVE: u32 = 0x405;
+pub const NT_ARM_PAC_MASK: u32 = 0x406;
+pub const NT_SIGINFO: u32 = 0x5349_4749;
+pub const NT_FILE: u32 = 0x4649_4c45;
+
+// GNU property types (NT_GNU_PROPERTY_TYPE_0)
+pub const GNU_PROPERTY_STACK_SIZE: u32 = 1;
+pub const GNU_PROPERTY_NO_COPY_ON_PROTECTED: u32 = 2;
+pub const GNU_PROPERTY_AARCH64_FEATURE_1_AND: u32 = 0xc000_0000;
+pub const GNU_PROPERTY_X86_FEATURE_1_AND: u32 = 0xc000_0002;
+pub const GNU_PROPERTY_X86_ISA_1_NEEDED: u32 = 0xc000_8002;
+pub const GNU_PROPERTY_X86_FEATURE_2_USED: u32 = 0xc001_0001;
+pub const GNU_PROPERTY_X86_ISA_1_USED: u32 = 0xc001_0002;
+
+pub const GNU_PROPERTY_X86_FEATURE_1_IBT: u32 = 0x1;
+pub const GNU_PROPERTY_X86_FEATURE_1_SHSTK: u32 = 0x2;
+pub const GNU_PROPERTY_AARCH64_FEATURE_1_BTI: u32 = 0x1;
+pub const GNU_PROPERTY_AARCH64_FEATURE_1_PAC: u32 = 0x2;
+pub const GNU_PROPERTY_AARCH64_FEATURE_1_GCS: u32 = 0x4;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GnuProperty {
+    StackSize(u64),
+    NoCopyOnProtected,
+    // GNU_PROPERTY_X86_FEATURE_1_IBT / _SHSTK bits
+    X86Feature1(u32),
+    X86Feature2Used(u32),
+    // x86-64 micro-architecture levels (baseline, v2, v3, v4) bits
+    X86IsaNeeded(u32),
+    X86IsaUsed(u32),
+    // GNU_PROPERTY_AARCH64_FEATURE_1_BTI / _PAC / _GCS bits
+    Aarch64Feature1(u32),
+    Other { pr_type: u32, data: Vec<u8> },
+}
+
+impl std::fmt::Display for GnuProperty {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::StackSize(size) => write!(f, "stack size: {size:#x}"),
+            Self::NoCopyOnProtected => write!(f, "no copy on protected"),
+            Self::X86Feature1(bits) => write!(
+                f,
+                "x86 feature: {}",
+                bit_names(
+                    *bits,
+                    &[
+                        (GNU_PROPERTY_X86_FEATURE_1_IBT, "IBT"),
+                        (GNU_PROPERTY_X86_FEATURE_1_SHSTK, "SHSTK"),
+                    ]
+                )
+            ),
+            Self::X86Feature2Used(bits) => write!(f, "x86 feature used: {bits:#x}"),
+            Self::X86IsaNeeded(bits) => write!(f, "x86 ISA needed: {}", isa_names(*bits)),
+            Self::X86IsaUsed(bits) => write!(f, "x86 ISA used: {}", isa_names(*bits)),
+            Self::Aarch64Feature1(bits) => write!(
+                f,
+                "AArch64 feature: {}",
+                bit_names(
+                    *bits,
+                    &[
+                        (GNU_PROPERTY_AARCH64_FEATURE_1_BTI, "BTI"),
+                        (GNU_PROPERTY_AARCH64_FEATURE_1_PAC, "PAC"),
+                        (GNU_PROPERTY_AARCH64_FEATURE_1_GCS, "GCS"),
+                    ]
+                )
+            ),
+            Self::Other { pr_type, data } => {
+                write!(f, "<property {pr_type:#x}, {} bytes>", data.len())
+            }
+        }
+    }
+}
+
+fn bit_names(bits: u32, names: &[(u32, &str)]) -> String {
+    let mut out: Vec<String> = names
+        .iter()
+        .filter(|(bit, _)| bits & bit != 0)
+        .map(|(_, name)| name.to_string())
+        .collect();
+    let unknown = names.iter().fold(bits, |rest, (bit, _)| rest & !bit);
+    if unknown != 0 {
+        out.push(format!("{unknown:#x}"));
+    }
+    if out.is_empty() {
+        return "<None>".to_string();
+    }
+    out.join(", ")
+}
+
+fn isa_names(bits: u32) -> String {
+    bit_names(
+        bits,
+        &[
+            (0x1, "x86-64-baseline"),
+            (0x2, "x86-64-v2"),
+            (0x4, "x86-64-v3"),
+            (0x8, "x86-64-v4"),
+        ],
+    )
+}
+
+// a file mapping recorded in a core dump's NT_FILE note
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MappedFile {
+    pub start: u64,
+    pub end: u64,
+    // offset in the file, in pages
+    pub page_offset: u64,
+    pub path: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NoteKind {
+    // operating system and the minimum kernel version
+    GnuAbiTag {
+        os: String,
+        major: u32,
+        minor: u32,
+        patch: u32,
+    },
+    // lowercase hex
+    GnuBuildId(String),
+    GnuGoldVersion(String),
+    GnuProperty(Vec<GnuProperty>),
+    GoBuildId(String),
+    Stapsdt {
+        provider: String,
+        name: String,
+        location: u64,
+        base: u64,
+        semaphore: u64,
+        arguments: String,
+    },
+    CorePrStatus {
+        pid: u32,
+    },
+    CorePrPsInfo {
+        pid: u32,
+        fname: String,
+        psargs: String,
+    },
+    CoreFile(Vec<MappedFile>),
+    // any note eva doesn't decode further
+    Other,
+}
+
+#[derive(Debug, Clone)]
+pub struct Note {
+    // the note's namespace, e.g. "GNU", "Go" or "CORE"
+    pub owner: String,
+    pub n_type: u32,
+    pub desc: Vec<u8>,
+    pub kind: NoteKind,
+}
+
+impl Note {
+    pub fn type_name(&self) -> String {
+        let name = match (self.owner.as_str(), self.n_type) {
+            ("GNU", NT_GNU_ABI_TAG) => "NT_GNU_ABI_TAG (ABI version tag)",
+            ("GNU", NT_GNU_HWCAP) => "NT_GNU_HWCAP (DSO-supplied software HWCAP info)",
+            ("GNU", NT_GNU_BUILD_ID) => "NT_GNU_BUILD_ID (unique build ID bitstring)",
+            ("GNU", NT_GNU_GOLD_VERSION) => "NT_GNU_GOLD_VERSION (gold version)",
+            ("GNU", NT_GNU_PROPERTY_TYPE_0) => "NT_GNU_PROPERTY_TYPE_0",
+            ("Go", NT_GO_BUILDID) => "GO BUILDID",
+            ("stapsdt", NT_STAPSDT) => "NT_STAPSDT (SystemTap probe descriptors)",
+            ("CORE" | "LINUX", core_type) => match core_type {
+                NT_PRSTATUS => "NT_PRSTATUS (prstatus structure)",
+                NT_PRFPREG => "NT_FPREGSET (floating point registers)",
+                NT_PRPSINFO => "NT_PRPSINFO (prpsinfo structure)",
+                NT_TASKSTRUCT => "NT_TASKSTRUCT (task structure)",
+                NT_AUXV => "NT_AUXV (auxiliary vector)",
+                NT_PRXFPREG => "NT_PRXFPREG (user_xfpregs structure)",
+                NT_X86_XSTATE => "NT_X86_XSTATE (x86 XSAVE extended state)",
+                NT_ARM_VFP => "NT_ARM_VFP (arm VFP registers)",
+                NT_ARM_TLS => "NT_ARM_TLS (AArch TLS registers)",
+                NT_ARM_HW_BREAK => "NT_ARM_HW_BREAK (AArch hardware breakpoint registers)",
+                NT_ARM_HW_WATCH => "NT_ARM_HW_WATCH (AArch hardware watchpoint registers)",
+                NT_ARM_SYSTEM_CALL => "NT_ARM_SYSTEM_CALL (AArch system call number)",
+                NT_ARM_SVE => "NT_ARM_SVE (AArch SVE registers)",
+                NT_ARM_PAC_MASK => "NT_ARM_PAC_MASK (AArch pointer authentication code masks)",
+                NT_SIGINFO => "NT_SIGINFO (siginfo_t data)",
+                NT_FILE => "NT_FILE (mapped files)",
+                _ => return format!("Unknown note type: ({:#010x})", self.n_type),
+            },
+            _ => return format!("Unknown note type: ({:#010x})", self.n_type),
+        };
+        name.to_string()
+    }
+
+    // human readable description of the decoded payload
+    pub fn description(&self) -> Vec<String> {
+        match &self.kind {
+            NoteKind::GnuAbiTag {
+                os,
+                major,
+                minor,
+                patch,
+            } => vec![format!("OS: {os}, ABI: {major}.{minor}.{patch}")],
+            NoteKind::GnuBuildId(id) => vec![format!("Build ID: {id}")],
+            NoteKind::GnuGoldVersion(version) => vec![format!("Version: {version}")],
+            NoteKind::GnuProperty(properties) => properties
+                .iter()
+                .map(|p| format!("Properties: {p}"))
+                .collect(),
+            NoteKind::GoBuildId(id) => vec![format!("Go Build ID: {id}")],
+            NoteKind::Stapsdt {
+                provider,
+                name,
+                location,
+                base,
+                semaphore,
+                arguments,
+            } => vec![
+                format!("Provider: {provider}"),
+                format!("Name: {name}"),
+                format!(
+                    "Location: {location:#018x}, Base: {base:#018x}, Semaphore: {semaphore:#018x}"
+                ),
+                format!("Arguments: {arguments}"),
+            ],
+            NoteKind::CorePrStatus { pid } => vec![format!("PID: {pid}")],
+            NoteKind::CorePrPsInfo { pid, fname, psargs } => {
+                vec![format!("PID: {pid}, name: {fname}, args: {psargs}")]
+            }
+            NoteKind::CoreFile(files) => files
+                .iter()
+                .map(|file| {
+                    format!(
+                        "{:#018x} {:#018x} {:#010x} {}",
+                        file.start, file.end, file.page_offset, file.path
+                    )
+                })
+                .collect(),
+            NoteKind::Other => {
+                let hex: String = self.desc.iter().map(|b| format!("{b:02x}")).collect();
+                if hex.is_empty() {
+                    vec![]
+                } else {
+                    vec![format!("description data: {hex}")]
+                }
+            }
+        }
+    }
+}
+
+// notes of one SHT_NOTE section or PT_NOTE segment
+#[derive(Debug, Clone)]
+pub struct NoteSection {
+    // section name, or "PT_NOTE" for segments
+    pub name: String,
+    pub offset: u64,
+    pub notes: Vec<Note>,
+}
+
+pub fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn decode(
+    owner: &str,
+    n_type: u32,
+    desc: &[u8],
+    header: &ELFHeader,
+) -> Result<NoteKind, ParseError> {
+    let mut reader = Reader::new(desc, header.ei_data);
+    let class = &header.ei_class;
+    let kind = match (owner, n_type) {
+        ("GNU", NT_GNU_ABI_TAG) => {
+            let os = match reader.u32()? {
+                0 => "Linux".to_string(),
+                1 => "Hurd".to_string(),
+                2 => "Solaris".to_string(),
+                3 => "FreeBSD".to_string(),
+                4 => "NetBSD".to_string(),
+                5 => "Syllable".to_string(),
+                other => format!("Unknown({other})"),
+            };
+            NoteKind::GnuAbiTag {
+                os,
+                major: reader.u32()?,
+                minor: reader.u32()?,
+                patch: reader.u32()?,
+            }
+        }
+        ("GNU", NT_GNU_BUILD_ID) => NoteKind::GnuBuildId(hex(desc)),
+        ("GNU", NT_GNU_GOLD_VERSION) => NoteKind::GnuGoldVersion(read_str(desc, 0)),
+        ("GNU", NT_GNU_PROPERTY_TYPE_0) => NoteKind::GnuProperty(parse_properties(desc, header)?),
+        ("Go", NT_GO_BUILDID) => NoteKind::GoBuildId(read_str(desc, 0)),
+        ("stapsdt", NT_STAPSDT) => {
+            let location = reader.word(class)?;
+            let base = reader.word(class)?;
+            let semaphore = reader.word(class)?;
+            let start = reader.position() as usize;
+            let provider = read_str(desc, start);
+            let name = read_str(desc, start + provider.len() + 1);
+            let arguments = read_str(desc, start + provider.len() + name.len() + 2);
+            NoteKind::Stapsdt {
+                provider,
+                name,
+                location,
+                base,
+                semaphore,
+                arguments,
+            }
+        }
+        ("CORE", NT_PRSTATUS) => {
+            // elf_siginfo (12 bytes) and pr_cursig, then two
+            // address-sized signal masks before pr_pid
+            reader.seek(if *class == Arch::B32 { 24 } else { 32 });
+            NoteKind::CorePrStatus { pid: reader.u32()? }
+        }
+        ("CORE", NT_PRPSINFO) if *class == Arch::B64 => {
+            reader.seek(24);
+            let pid = reader.u32()?;
+            reader.seek(40);
+            let fname = read_str(reader.bytes(16)?, 0);
+            let psargs = read_str(reader.bytes(80)?, 0);
+            NoteKind::CorePrPsInfo { pid, fname, psargs }
+        }
+        ("CORE", NT_FILE) => {
+            let count = reader.word(class)?;
+            let _page_size = reader.word(class)?;
+            let mut files = Vec::new();
+            for _ in 0..count {
+                files.push(MappedFile {
+                    start: reader.word(class)?,
+                    end: reader.word(class)?,
+                    page_offset: reader.word(class)?,
+                    path: String::new(),
+                });
+            }
+            let mut cursor = reader.position() as usize;
+            for file in files.iter_mut() {
+                file.path = read_str(desc, cursor);
+                cursor += file.path.len() + 1;
+            }
+            NoteKind::CoreFile(files)
+        }
+        _ => NoteKind::Other,
+    };
+    Ok(kind)
+}
+
+// properties are padded to 8 bytes in ELF64 and 4 bytes in ELF32
+fn parse_properties(desc: &[u8], header: &ELFHeader) -> Result<Vec<GnuProperty>, ParseError> {
+    let align = if header.ei_class == Arch::B32 { 4 } else { 8 };
+    let mut reader = Reader::new(desc, header.ei_data);
+    let x86 = matches!(header.e_machine, EM_386 | EM_X86_64);
+    let aarch64 = header.e_machine == EM_AARCH64;
+    let mut properties = Vec::new();
+    while reader.position() + 8 <= desc.len() as u64 {
+        let pr_type = reader.u32()?;
+        let pr_datasz = reader.u32()? as u64;
+        let start = reader.position();
+        let mut data = Reader::new(reader.bytes(pr_datasz)?, header.ei_data);
+        let property = match pr_type {
+            GNU_PROPERTY_STACK_SIZE => GnuProperty::StackSize(data.word(&header.ei_class)?),
+            GNU_PROPERTY_NO_COPY_ON_PROTECTED => GnuProperty::NoCopyOnProtected,
+            GNU_PROPERTY_X86_FEATURE_1_AND if x86 => GnuProperty::X86Feature1(data.u32()?),
+            GNU_PROPERTY_X86_FEATURE_2_USED if x86 => GnuProperty::X86Feature2Used(data.u32()?),
+            GNU_PROPERTY_X86_ISA_1_NEEDED if x86 => GnuProperty::X86IsaNeeded(data.u32()?),
+            GNU_PROPERTY_X86_ISA_1_USED if x86 => GnuProperty::X86IsaUsed(data.u32()?),
+            GNU_PROPERTY_AARCH64_FEATURE_1_AND if aarch64 => {
+                GnuProperty::Aarch64Feature1(data.u32()?)
+            }
+            _ => GnuProperty::Other {
+                pr_type,
+                data: data.bytes(pr_datasz)?.to_vec(),
+            },
+        };
+        properties.push(property);
+        reader.seek(align_up(start + pr_datasz, align));
+    }
+    Ok(properties)
+}
+
+fn align_up(value: u64, align: u64) -> u64 {
+    value.div_ceil(align) * align
+}
+
+// A note as stored in the file, borrowing its owner and descriptor
+#[derive(Debug, Clone, Copy)]
+pub struct RawNote<'a> {
+    pub owner: &'a str,
+    pub n_type: u32,
+    pub desc: &'a [u8],
+}
+
+// Notes in a SHT_NOTE section or PT_NOTE segment, read one at a time.
+// `align` is the section/segment alignment; 8-byte aligned notes (GNU
+// properties in ELF64) pad the name and descriptor to 8 bytes.
+pub struct NoteIter<'a> {
+    reader: Reader<'a>,
+    len: u64,
+    align: u64,
+}
+
+impl<'a> NoteIter<'a> {
+    pub fn new(data: &'a [u8], header: &ELFHeader, align: u64) -> NoteIter<'a> {
+        NoteIter {
+            reader: Reader::new(data, header.ei_data),
+            len: data.len() as u64,
+            align: if align == 8 { 8 } else { 4 },
+        }
+    }
+
+    fn read(&mut self) -> Result<RawNote<'a>, ParseError> {
+        let reader = &mut self.reader;
+        let namesz = reader.u32()? as u64;
+        let descsz = reader.u32()? as u64;
+        let n_type = reader.u32()?;
+        let name_start = reader.position();
+        let name = reader.bytes(namesz)?;
+        let name = &name[..name.iter().position(|&b| b == 0).unwrap_or(name.len())];
+        reader.seek(align_up(name_start + namesz, self.align));
+        let desc_start = reader.position();
+        let desc = reader.bytes(descsz)?;
+        reader.seek(align_up(desc_start + descsz, self.align));
+        Ok(RawNote {
+            // owners are plain ASCII in practice
+            owner: std::str::from_utf8(name).unwrap_or(""),
+            n_type,
+            desc,
+        })
+    }
+}
+
+impl<'a> Iterator for NoteIter<'a> {
+    type Item = Result<RawNote<'a>, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.reader.position().saturating_add(12) > self.len {
+            return None;
+        }
+        let note = self.read();
+        if note.is_err() {
+            // stop after the first error
+            self.reader.seek(self.len);
+        }
+        Some(note)
+    }
+}
+
+// parse and decode the notes in a SHT_NOTE section or PT_NOTE segment
+pub fn parse_notes(data: &[u8], header: &ELFHeader, align: u64) -> Result<Vec<Note>, ParseError> {
+    NoteIter::new(data, header, align)
+        .map(|note| {
+            let note = note?;
+            Ok(Note {
+                kind: decode(note.owner, note.n_type, note.desc, header)?,
+                owner: note.owner.to_string(),
+                n_type: note.n_type,
+                desc: note.desc.to_vec(),
+            })
+        })
+        .collect()
+}
+
+// the undecoded contents of a SHT_NOTE section or PT_NOTE segment
+pub struct NoteBlock<'a> {
+    // section name, or "PT_NOTE" for segments
+    pub name: String,
+    pub offset: u64,
+    pub data: &'a [u8],
+    pub align: u64,
+}
+
+// The SHT_NOTE sections, or the PT_NOTE segments for files without
+// section headers (core dumps, stripped images)
+pub fn note_blocks<'a>(
+    contents: &'a [u8],
+    pheaders: &[Pheader],
+    sections: &[SectionHeader],
+) -> Result<Vec<NoteBlock<'a>>, ParseError> {
+    let mut blocks = Vec::new();
+    for section in sections.iter().filter(|s| s.sh_type == SHT_NOTE) {
+        blocks.push(NoteBlock {
+            name: section.name.to_string(),
+            offset: section.sh_offset,
+            data: section.data(contents)?,
+            align: section.sh_addralign,
+        });
+    }
+    if !blocks.is_empty() {
+        return Ok(blocks);
+    }
+    for segment in pheaders.iter().filter(|p| p.p_type == PT_NOTE) {
+        blocks.push(NoteBlock {
+            name: "PT_NOTE".to_string(),
+            offset: segment.p_offset,
+            data: checked_slice(contents, segment.p_offset, segment.p_filesz)?,
+            align: segment.p_align,
+        });
+    }
+    Ok(blocks)
+}
+
+pub fn parse_note_sections(
+    contents: &[u8],
+    header: &ELFHeader,
+    pheaders: &[Pheader],
+    sections: &[SectionHeader],
+) -> Result<Vec<NoteSection>, ParseError> {
+    note_blocks(contents, pheaders, sections)?
+        .into_iter()
+        .map(|block| {
+            Ok(NoteSection {
+                notes: parse_notes(block.data, header, block.align)?,
+                name: block.name,
+                offset: block.offset,
+            })
+        })
+        .collect()
+}
+
+// hex string of the NT_GNU_BUILD_ID note, if there is one
+pub fn build_id(tables: &[NoteSection]) -> Option<&str> {
+    tables
+        .iter()
+        .flat_map(|table| &table.notes)
+        .find_map(|note| match &note.kind {
+            NoteKind::GnuBuildId(id) => Some(id.as_str()),
+            _ => None,
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::note::{
+        self, GnuProperty, NoteKind, GNU_PROPERTY_AARCH64_FEATURE_1_AND,
+        GNU_PROPERTY_X86_FEATURE_1_AND, NT_FILE, NT_GNU_ABI_TAG, NT_GNU_BUILD_ID,
+        NT_GNU_PROPERTY_TYPE_0,
+    };
+    use crate::parse::{Arch, ELFHeader, ELFParser, EM_X86_64};
+    use crate::utils::Endian;
+
+    fn note(owner: &str, n_type: u32, desc: &[u8], align: usize, end: Endian) -> Vec<u8> {
+        let word = |w: u32| match end {
+            Endian::Big => w.to_be_bytes(),
+            Endian::Little => w.to_le_bytes(),
+        };
+        let mut bytes = Vec::new();
+        bytes.extend(word(owner.len() as u32 + 1));
+        bytes.extend(word(desc.len() as u32));
+        bytes.extend(word(n_type));
+        bytes.extend(owner.as_bytes());
+        bytes.push(0);
+        bytes.resize(bytes.len().next_multiple_of(align), 0);
+        bytes.extend(desc);
+        bytes.resize(bytes.len().next_multiple_of(align), 0);
+        bytes
+    }
+
+    #[test]
+    fn build_id_and_abi_tag() {
+        let header = ELFHeader {
+            ei_class: Arch::B32,
+            ei_data: Endian::Big,
+            ..Default::default()
+        };
+        let mut bytes = note(
+            "GNU",
+            NT_GNU_BUILD_ID,
+            &[0xde, 0xad, 0xbe, 0xef, 0x01],
+            4,
+            Endian::Big,
+        );
+        let abi: Vec<u8> = [0_u32, 3, 2, 0]
+            .iter()
+            .flat_map(|w| w.to_be_bytes())
+            .collect();
+        bytes.extend(note("GNU", NT_GNU_ABI_TAG, &abi, 4, Endian::Big));
+        let notes = note::parse_notes(&bytes, &header, 4).unwrap();
+        assert_eq!(notes.len(), 2);
+        assert_eq!(
+            notes[0].kind,
+            NoteKind::GnuBuildId("deadbeef01".to_string())
+        );
+        assert_eq!(
+            notes[1].description(),
+            ["OS: Linux, ABI: 3.2.0".to_string()]
+        );
+    }
+
+    #[test]
+    fn x86_properties() {
+        let header = ELFHeader {
+            e_machine: EM_X86_64,
+            ..Default::default()
+        };
+        let mut desc = Vec::new();
+        desc.extend(GNU_PROPERTY_X86_FEATURE_1_AND.to_le_bytes());
+        desc.extend(4_u32.to_le_bytes());
+        desc.extend(3_u32.to_le_bytes());
+        desc.extend(0_u32.to_le_bytes());
+        let bytes = note("GNU", NT_GNU_PROPERTY_TYPE_0, &desc, 8, Endian::Little);
+        let notes = note::parse_notes(&bytes, &header, 8).unwrap();
+        assert_eq!(
+            notes[0].kind,
+            NoteKind::GnuProperty(vec![GnuProperty::X86Feature1(3)])
+        );
+        assert_eq!(
+            notes[0].description(),
+            ["Properties: x86 feature: IBT, SHSTK"]
+        );
+    }
+
+    #[test]
+    fn aarch64_property_on_x86() {
+        let header = ELFHeader {
+            e_machine: EM_X86_64,
+            ..Default::default()
+        };
+        let mut desc = Vec::new();
+        desc.extend(GNU_PROPERTY_AARCH64_FEATURE_1_AND.to_le_bytes());
+        desc.extend(4_u32.to_le_bytes());
+        desc.extend(3_u32.to_le_bytes());
+        desc.extend(0_u32.to_le_bytes());
+        let bytes = note("GNU", NT_GNU_PROPERTY_TYPE_0, &desc, 8, Endian::Little);
+        let notes = note::parse_notes(&bytes, &header, 8).unwrap();
+        assert_eq!(
+            notes[0].kind,
+            NoteKind::GnuProperty(vec![GnuProperty::Other {
+                pr_type: GNU_PROPERTY_AARCH64_FEATURE_1_AND,
+                data: vec![3, 0, 0, 0],
+            }])
+        );
+    }
+
+    #[test]
+    fn core_mapped_files() {
+        let header = ELFHeader::default();
+        let mut desc = Vec::new();
+        for word in [1_u64, 0x1000, 0x40_0000, 0x40_1000, 0] {
+            desc.extend(word.to_le_bytes());
+        }
+        desc.extend(b"/bin/true\0");
+        let bytes = note("CORE", NT_FILE, &desc, 4, Endian::Little);
+        let notes = note::parse_notes(&bytes, &header, 4).unwrap();
+        match &notes[0].kind {
+            NoteKind::CoreFile(files) => {
+                assert_eq!(files[0].start, 0x40_0000);
+                assert_eq!(files[0].path, "/bin/true");
+            }
+            other => panic!("unexpected note {other:?}"),
+        }
+    }
+
+    #[test]
+    fn truncated_note() {
+        let header = ELFHeader::default();
+        let mut bytes = note("GNU", NT_GNU_BUILD_ID, &[1, 2, 3, 4], 4, Endian::Little);
+        bytes.truncate(bytes.len() - 2);
+        assert!(note::parse_notes(&bytes, &header, 4).is_err());
+    }
+
+    #[test]
+    fn own_build_id() {
+        let parser = ELFParser::new(std::env::current_exe().unwrap()).unwrap();
+        if let Some(id) = parser.build_id() {
+            assert!(id.chars().all(|c| c.is_ascii_hexdigit()));
+            assert_eq!(Some(id.as_str()), note::build_id(parser.notes().unwrap()));
+        }
+    }
+}
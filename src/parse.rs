@@ -1,14 +1,29 @@
 #![allow(dead_code)]
 
+use crate::dynamic::{self, DynamicEntry};
 use crate::error::ParseError;
-use crate::utils::{Endian, ValidNums};
-use std::{
-    fs::File,
-    io::{BufReader, Read},
-    path::PathBuf,
-};
-
-#[derive(Debug, Default, Eq, PartialEq)]
+use crate::header::{Machine, ObjectType, OsAbi};
+use crate::input::Input;
+use crate::note::{self, NoteIter, NoteSection, RawNote};
+use crate::reloc::{self, RelocationSection};
+use crate::section::{self, SectionHeader, SHT_DYNSYM, SHT_SYMTAB};
+use crate::symbol::{self, Symbol, SymbolIter, SHN_XINDEX};
+use crate::utils::{checked_slice, Endian, Reader};
+use crate::validate::{validate, Diagnostic};
+use std::{cell::OnceCell, io::Read, path::PathBuf};
+
+// instruction set architectures (e_machine) eva knows more about
+// than just the name
+pub const EM_386: u16 = 0x03;
+pub const EM_MIPS: u16 = 0x08;
+pub const EM_PPC: u16 = 0x14;
+pub const EM_PPC64: u16 = 0x15;
+pub const EM_ARM: u16 = 0x28;
+pub const EM_X86_64: u16 = 0x3E;
+pub const EM_AARCH64: u16 = 0xB7;
+pub const EM_RISCV: u16 = 0xF3;
+
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
 pub enum Arch {
     B32,
     #[default]
@@ -29,8 +44,8 @@ pub struct ELFHeader {
     pub ei_abiversion: u8,
     // type of obj/elf file.
     pub e_type: u16,
-    // constant: 1
-    pub e_version: u8,
+    // constant: 1 (EV_CURRENT), a full word unlike ei_version
+    pub e_version: u32,
     // specific target instruction set arch
     pub e_machine: u16,
     // mem address of the entry point from where the program starts executing
@@ -53,9 +68,41 @@ pub struct ELFHeader {
     pub e_shnum: u16,
     // index of the section header table entry that contains the section names
     pub e_shstrndx: u16,
+    // The real values of the three fields above. They differ when the
+    // values don't fit in 16 bits and are stored in section 0 instead
+    // (extended numbering), so use these for anything but displaying.
+    pub phnum: u32,
+    pub shnum: u64,
+    pub shstrndx: u32,
 }
 
-#[derive(Default)]
+// segment types (p_type)
+pub const PT_NULL: u32 = 0;
+pub const PT_LOAD: u32 = 1;
+pub const PT_DYNAMIC: u32 = 2;
+pub const PT_INTERP: u32 = 3;
+pub const PT_NOTE: u32 = 4;
+pub const PT_SHLIB: u32 = 5;
+pub const PT_PHDR: u32 = 6;
+pub const PT_TLS: u32 = 7;
+pub const PT_GNU_EH_FRAME: u32 = 0x6474_e550;
+pub const PT_GNU_STACK: u32 = 0x6474_e551;
+pub const PT_GNU_RELRO: u32 = 0x6474_e552;
+pub const PT_GNU_PROPERTY: u32 = 0x6474_e553;
+pub const PT_MIPS_REGINFO: u32 = 0x7000_0000;
+pub const PT_MIPS_RTPROC: u32 = 0x7000_0001;
+pub const PT_MIPS_OPTIONS: u32 = 0x7000_0002;
+pub const PT_MIPS_ABIFLAGS: u32 = 0x7000_0003;
+
+// e_phnum when the real count is in section 0's sh_info
+pub const PN_XNUM: u16 = 0xffff;
+
+// segment permissions (p_flags)
+pub const PF_X: u32 = 0x1;
+pub const PF_W: u32 = 0x2;
+pub const PF_R: u32 = 0x4;
+
+#[derive(Default, Debug, Clone)]
 // aligned to u64 to accomodate both ELF32 and ELF64 program
 // headers
 pub struct Pheader {
@@ -70,268 +117,776 @@ pub struct Pheader {
     pub p_align: u64,
 }
 
-impl Pheader {}
+impl Pheader {
+    // parse a single program header entry at the reader's cursor
+    pub fn parse(reader: &mut Reader, class: &Arch) -> Result<Pheader, ParseError> {
+        let mut pheader = Pheader {
+            p_type: reader.u32()?,
+            ..Default::default()
+        };
+
+        if *class == Arch::B32 {
+            // Elf32_Phdr: p_flags comes after p_memsz
+            pheader.p_offset = reader.word(class)?;
+            pheader.p_vaddr = reader.word(class)?;
+            pheader.p_paddr = reader.word(class)?;
+            pheader.p_filesz = reader.word(class)?;
+            pheader.p_memsz = reader.word(class)?;
+            pheader.p_flags = reader.u32()?;
+            pheader.p_align = reader.word(class)?;
+            return Ok(pheader);
+        }
+
+        pheader.p_flags = reader.u32()?;
+        pheader.p_offset = reader.word(class)?;
+        pheader.p_vaddr = reader.word(class)?;
+        pheader.p_paddr = reader.word(class)?;
+        pheader.p_filesz = reader.word(class)?;
+        pheader.p_memsz = reader.word(class)?;
+        pheader.p_align = reader.word(class)?;
+
+        Ok(pheader)
+    }
+
+    // contents of the segment in the file
+    pub fn data<'a>(&self, contents: &'a [u8]) -> Result<&'a [u8], ParseError> {
+        checked_slice(contents, self.p_offset, self.p_filesz)
+    }
+
+    pub fn type_name(&self) -> String {
+        match self.p_type {
+            PT_NULL => "NULL".to_string(),
+            PT_LOAD => "LOAD".to_string(),
+            PT_DYNAMIC => "DYNAMIC".to_string(),
+            PT_INTERP => "INTERP".to_string(),
+            PT_NOTE => "NOTE".to_string(),
+            PT_SHLIB => "SHLIB".to_string(),
+            PT_PHDR => "PHDR".to_string(),
+            PT_TLS => "TLS".to_string(),
+            PT_GNU_EH_FRAME => "GNU_EH_FRAME".to_string(),
+            PT_GNU_STACK => "GNU_STACK".to_string(),
+            PT_GNU_RELRO => "GNU_RELRO".to_string(),
+            PT_GNU_PROPERTY => "GNU_PROPERTY".to_string(),
+            0x6000_0000..=0x6fff_ffff => format!("LOOS+{:#x}", self.p_type - 0x6000_0000),
+            0x7000_0000..=0x7fff_ffff => format!("LOPROC+{:#x}", self.p_type - 0x7000_0000),
+            _ => format!("{:#x}", self.p_type),
+        }
+    }
+
+    // readelf-style permission string, e.g. "R E"
+    pub fn flags_string(&self) -> String {
+        let mut flags = String::new();
+        flags.push(if self.p_flags & PF_R != 0 { 'R' } else { ' ' });
+        flags.push(if self.p_flags & PF_W != 0 { 'W' } else { ' ' });
+        flags.push(if self.p_flags & PF_X != 0 { 'E' } else { ' ' });
+        flags
+    }
+}
+
+// translate a virtual address to a file offset through the PT_LOAD
+// segment that maps it
+pub fn vaddr_to_offset(pheaders: &[Pheader], vaddr: u64) -> Option<u64> {
+    pheaders
+        .iter()
+        .filter(|p| p.p_type == PT_LOAD)
+        .find(|p| vaddr >= p.p_vaddr && vaddr - p.p_vaddr < p.p_filesz)
+        .map(|p| vaddr - p.p_vaddr + p.p_offset)
+}
 
+// Only the ELF header is parsed up front. Everything else is parsed the
+// first time it's asked for, and cached; parse errors are not cached.
+// The cached section and symbol names borrow from `file_contents`; the
+// accessors only hand them out for as long as the parser is borrowed.
 pub struct ELFParser<'a> {
-    cursor: u32,
-    file_contents: &'a [u8],
-    file_size: u32,
     pub elf_header: ELFHeader,
-    pub program_headers: Option<Vec<Pheader>>,
-    //pub section_headers: Option<Vec<SectionHeader>>,
+    program_headers: OnceCell<Vec<Pheader>>,
+    section_headers: OnceCell<Vec<SectionHeader<'a>>>,
+    symbols: OnceCell<Vec<Symbol<'a>>>,
+    dynamic_symbols: OnceCell<Vec<Symbol<'a>>>,
+    dynamic: OnceCell<Vec<DynamicEntry>>,
+    relocations: OnceCell<Vec<RelocationSection>>,
+    notes: OnceCell<Vec<NoteSection>>,
+    diagnostics: OnceCell<Vec<Diagnostic>>,
+    // last, so that it's dropped after the names borrowing from it
+    file_contents: Input<'a>,
 }
 
-impl<'a> ELFParser<'a> {
+// the cached value of `cell`, running `parse` to fill it on first use
+fn cached<T>(
+    cell: &OnceCell<Vec<T>>,
+    parse: impl FnOnce() -> Result<Vec<T>, ParseError>,
+) -> Result<&[T], ParseError> {
+    if let Some(value) = cell.get() {
+        return Ok(value);
+    }
+    let value = parse()?;
+    Ok(cell.get_or_init(|| value))
+}
+
+impl ELFParser<'static> {
+    // map (or read) the file and parse it
     pub fn new(path: PathBuf) -> Result<Self, ParseError> {
-        let file = File::open(path)?;
-        let mut file_buf = BufReader::new(file);
-        let mut file_contents = Vec::new();
-        let file_size = file_buf.read_to_end(&mut file_contents)?;
+        ELFParser::from_input(Input::open(&path)?)
+    }
 
-        if file_size < 4 {
-            return Err(ParseError::BadMagic);
-        }
+    // read everything from `reader` (e.g. stdin) and parse it
+    pub fn from_reader(reader: impl Read) -> Result<Self, ParseError> {
+        ELFParser::from_input(Input::read(reader)?)
+    }
+}
 
-        if file_contents[0] == 0x7f
-            && file_contents[1] == 0x45
-            && file_contents[2] == 0x4C
-            && file_contents[3] == 0x46
-        {
-            println!("\x1b[1;32mELF Magic: \x1b[0m\x1b[1;37m0x7F, E, L, F\x1b[0m");
-        } else {
-            return Err(ParseError::BadMagic);
+impl<'a> ELFParser<'a> {
+    // parse an ELF image that is already in memory, borrowing it
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<Self, ParseError> {
+        ELFParser::from_input(Input::Borrowed(bytes))
+    }
+
+    // parse an input of any kind, e.g. an archive member sharing the archive
+    pub fn from_input(file_contents: Input<'a>) -> Result<Self, ParseError> {
+        Ok(ELFParser {
+            elf_header: file_contents.parse_elf_header()?,
+            file_contents,
+            program_headers: OnceCell::new(),
+            section_headers: OnceCell::new(),
+            symbols: OnceCell::new(),
+            dynamic_symbols: OnceCell::new(),
+            dynamic: OnceCell::new(),
+            relocations: OnceCell::new(),
+            notes: OnceCell::new(),
+            diagnostics: OnceCell::new(),
+        })
+    }
+
+    // raw bytes of the parsed file
+    pub fn contents(&self) -> &[u8] {
+        &self.file_contents
+    }
+
+    // The same bytes, for the cached tables to borrow from. Owned, mapped
+    // and shared inputs keep their bytes in place when the parser moves,
+    // and the parser never changes or drops its input while it lives.
+    fn stable_contents(&self) -> &'a [u8] {
+        let contents: &[u8] = &self.file_contents;
+        // SAFETY: see above; the accessors shorten the borrow to `&self`
+        unsafe { std::slice::from_raw_parts(contents.as_ptr(), contents.len()) }
+    }
+
+    // the problems `validate` finds, checked once and cached like the tables
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        self.diagnostics.get_or_init(|| validate(self))
+    }
+
+    // Each of the tables below is empty when the file doesn't have it.
+
+    pub fn program_headers(&self) -> Result<&[Pheader], ParseError> {
+        cached(&self.program_headers, || {
+            let pheaders = self.file_contents.parse_program_headers(&self.elf_header)?;
+            Ok(pheaders.unwrap_or_default())
+        })
+    }
+
+    pub fn sections(&self) -> Result<&[SectionHeader<'_>], ParseError> {
+        cached(&self.section_headers, || {
+            let contents = self.stable_contents();
+            let sections = contents.parse_section_headers(&self.elf_header)?;
+            Ok(sections.unwrap_or_default())
+        })
+    }
+
+    // entries of .symtab
+    pub fn symbols(&self) -> Result<&[Symbol<'_>], ParseError> {
+        cached(&self.symbols, || self.symbol_table(SHT_SYMTAB))
+    }
+
+    // entries of .dynsym
+    pub fn dynamic_symbols(&self) -> Result<&[Symbol<'_>], ParseError> {
+        cached(&self.dynamic_symbols, || self.symbol_table(SHT_DYNSYM))
+    }
+
+    fn symbol_table(&self, sh_type: u32) -> Result<Vec<Symbol<'a>>, ParseError> {
+        let sections = self.sections()?;
+        let contents = self.stable_contents();
+        let table = symbol::parse_symbol_table(contents, &self.elf_header, sections, sh_type)?;
+        Ok(table.unwrap_or_default())
+    }
+
+    // the dynamic array pointed to by PT_DYNAMIC
+    pub fn dynamic(&self) -> Result<&[DynamicEntry], ParseError> {
+        cached(&self.dynamic, || {
+            let dynamic = dynamic::parse_dynamic(
+                &self.file_contents,
+                &self.elf_header,
+                Some(self.program_headers()?).filter(|p| !p.is_empty()),
+                Some(self.sections()?).filter(|s| !s.is_empty()),
+            )?;
+            Ok(dynamic.unwrap_or_default())
+        })
+    }
+
+    // SHT_REL, SHT_RELA and SHT_RELR tables
+    pub fn relocations(&self) -> Result<&[RelocationSection], ParseError> {
+        cached(&self.relocations, || {
+            reloc::parse_relocations(
+                &self.file_contents,
+                &self.elf_header,
+                self.sections()?,
+                self.program_headers()?,
+                self.dynamic()?,
+            )
+        })
+    }
+
+    // SHT_NOTE sections, or PT_NOTE segments
+    pub fn notes(&self) -> Result<&[NoteSection], ParseError> {
+        cached(&self.notes, || {
+            note::parse_note_sections(
+                &self.file_contents,
+                &self.elf_header,
+                self.program_headers()?,
+                self.sections()?,
+            )
+        })
+    }
+
+    // Walk the entries of .symtab (or .dynsym, with SHT_DYNSYM) straight
+    // from the file, without building the cached table
+    pub fn symbol_iter(&self, sh_type: u32) -> Result<SymbolIter<'_>, ParseError> {
+        let sections = self.sections()?;
+        match sections.iter().find(|section| section.sh_type == sh_type) {
+            Some(symtab) => {
+                SymbolIter::new(&self.file_contents, &self.elf_header, sections, symtab)
+            }
+            None => Ok(SymbolIter::default()),
         }
-        let _elf_header = ELFHeader::default();
-        return Err(ParseError::BadMagic);
+    }
+
+    // Walk the notes straight from the file, without decoding them
+    pub fn note_iter(
+        &self,
+    ) -> Result<impl Iterator<Item = Result<RawNote<'_>, ParseError>>, ParseError> {
+        let blocks = note::note_blocks(
+            &self.file_contents,
+            self.program_headers()?,
+            self.sections()?,
+        )?;
+        let header = &self.elf_header;
+        Ok(blocks
+            .into_iter()
+            .flat_map(move |block| NoteIter::new(block.data, header, block.align)))
+    }
+
+    // the NT_GNU_BUILD_ID note as a hex string
+    pub fn build_id(&self) -> Option<String> {
+        self.note_iter()
+            .ok()?
+            .map_while(Result::ok)
+            .find(|note| note.owner == "GNU" && note.n_type == note::NT_GNU_BUILD_ID)
+            .map(|note| note::hex(note.desc))
+    }
+
+    // find a section (`.text`, `.data`, ...) by its name
+    pub fn section_by_name(&self, name: &str) -> Option<&SectionHeader<'_>> {
+        self.sections()
+            .ok()?
+            .iter()
+            .find(|section| section.name == name)
+    }
+
+    // file contents of a section
+    pub fn section_data(&self, section: &SectionHeader) -> Result<&[u8], ParseError> {
+        section.data(&self.file_contents)
     }
 }
 
 impl ELFHeader {
     // parse the header
     pub fn parse(&mut self, contents: &[u8]) -> Result<usize, ParseError> {
-        let step;
-        let mut cursor = 4;
-        let end;
-        if contents[cursor] == 1 {
-            self.ei_class = Arch::B32;
-            step = 4;
-        } else {
-            self.ei_class = Arch::B64;
-            step = 8;
-        }
-        cursor += 1;
-        if contents[cursor] == 1 {
-            self.ei_data = Endian::Little;
-            end = Endian::Little;
-        } else if contents[cursor] == 2 {
-            self.ei_data = Endian::Big;
-            end = Endian::Big;
-        } else {
-            return Err(ParseError::UnsupportedEndianess);
-        }
-        cursor += 1;
-        self.ei_version = contents[cursor];
-        cursor += 1;
-        self.ei_osabi = contents[cursor];
-        cursor += 1;
-        self.ei_abiversion = contents[cursor];
-        cursor = 16;
-
-        let obj_type = u16::from_bytes(end, &contents[cursor..cursor + 2]);
-
-        self.e_type = obj_type;
-        cursor += 2;
-        let machine_type = u16::from_bytes(end, &contents[cursor..cursor + 2]);
-        self.e_machine = machine_type;
-        // cursor += 2;
-        let version = contents[20] | contents[23];
-        self.e_version = version;
-        let mut cursor = 24_usize;
-        self.e_entry = u64::from_bytes(end, &contents[cursor..(cursor + step)]);
-        cursor += step;
-        self.e_phoff = u64::from_bytes(end, &contents[cursor..(cursor + step)]);
-        cursor += step;
-        self.e_shoff = u64::from_bytes(end, &contents[cursor..(cursor + step)]);
-        cursor += step;
-        self.e_flags = u32::from_bytes(end, &contents[cursor..(cursor + 4)]);
-        cursor += 4;
-        self.e_ehsize = u16::from_bytes(end, &contents[cursor..(cursor + 2)]);
-        cursor += 2;
-        self.e_phentsize = u16::from_bytes(end, &contents[cursor..(cursor + 2)]);
-        cursor += 2;
-        self.e_phnum = u16::from_bytes(end, &contents[cursor..(cursor + 2)]);
-        cursor += 2;
-        self.e_shentsize = u16::from_bytes(end, &contents[cursor..(cursor + 2)]);
-        cursor += 2;
-        self.e_shnum = u16::from_bytes(end, &contents[cursor..(cursor + 2)]);
-        cursor += 2;
-        self.e_shstrndx = u16::from_bytes(end, &contents[cursor..(cursor + 2)]);
-        Ok(cursor + 2)
-    }
-
-    pub fn display(&self) {
-        // we're sure this file is ELF; if not, the `parse`
-        // function already errors out by now
-        println!("\x1b[1;32mELF Magic: \x1b[0m\x1b[1;37m0x7F, E, L, F\x1b[0m");
-
-        if self.ei_class == Arch::B32 {
-            println!("\x1b[1;32mELF bit: \x1b[37m32-bit\x1b[0m");
-        } else {
-            println!("\x1b[1;32mELF bit: \x1b[37m64-bit\x1b[0m");
-        }
-        if self.ei_data == Endian::Big {
-            println!("\x1b[1;32mEndianness \x1b[37mbig-endian\x1b[0m");
-        } else {
-            println!("\x1b[1;32mEndianness \x1b[37mlittle-endian\x1b[0m");
+        if contents.len() < 4 || contents[..4] != [0x7f, 0x45, 0x4C, 0x46] {
+            return Err(ParseError::BadMagic);
         }
-        if self.ei_version == 1 {
-            println!("\x1b[1;32mELF version: \x1b[37m1 (original and current version)\x1b[0m");
+        let mut reader = Reader::new(contents, Endian::Little);
+        reader.seek(4);
+        match reader.u8()? {
+            1 => self.ei_class = Arch::B32,
+            2 => self.ei_class = Arch::B64,
+            _ => return Err(ParseError::UnsupportedClass),
         }
-        print!("\x1b[1;32mTarget operating system is:\x1b[0m ");
-        match self.ei_osabi {
-            0x00 => println!("\x1b[1mUnix - System V\x1b[0m"),
-            0x01 => println!("\x1b[1mHP-UX\x1b[0m"),
-            0x02 => println!("\x1b[1mNetBSD\x1b[0m"),
-            0x03 => println!("\x1b[1mLinux\x1b[0m"),
-            0x04 => println!("\x1b[1mGNU Hurd\x1b[0m"),
-            0x06 => println!("\x1b[1mSolaris\x1b[0m"),
-            0x07 => println!("\x1b[1mAIX(Monterey)\x1b[0m"),
-            0x08 => println!("\x1b[1mIRIX\x1b[0m"),
-            0x09 => println!("\x1b[1mFreeBSD\x1b[0m"),
-            0x0A => println!("\x1b[1mTru64\x1b[0m"),
-            0x0B => println!("\x1b[1mNovell Modesto\x1b[0m"),
-            0x0C => println!("\x1b[1mOpenBSD\x1b[0m"),
-            0x0D => println!("\x1b[1mOpenVMS\x1b[0m"),
-            0x0E => println!("\x1b[1mNonStop Kernel\x1b[0m"),
-            0x0F => println!("\x1b[1mAROS\x1b[0m"),
-            0x010 => println!("\x1b[1mFenixOS\x1b[0m"),
-            0x011 => println!("\x1b[1mNuxi CloudABI\x1b[0m"),
-            0x012 => println!("\x1b[1mStratus Technologies OpenVOS\x1b[0m"),
-            _ => println!("\x1b[1mUNRECOGNIZED\x1b[0m"),
+        match reader.u8()? {
+            1 => self.ei_data = Endian::Little,
+            2 => self.ei_data = Endian::Big,
+            _ => return Err(ParseError::UnsupportedEndianess),
         }
+        reader.set_endian(self.ei_data);
+        self.ei_version = reader.u8()?;
+        self.ei_osabi = reader.u8()?;
+        self.ei_abiversion = reader.u8()?;
+        reader.seek(16);
 
-        println!(
-            "\x1b[1;32mABI Version: \x1b[37m{}\x1b[0m",
-            self.ei_abiversion
-        );
-        print!("\x1b[1;32mELF object file type:\x1b[0m ");
-        match self.e_type {
-            0x01 => println!("\x1b[1mRelocatable\x1b[0m"),
-            0x02 => println!("\x1b[1mExecutable\x1b[0m"),
-            0x03 => println!("\x1b[1mShared object\x1b[0m"),
-            0x04 => println!("\x1b[1mCore file\x1b[0m"),
-            0xFE00 | 0xFEFF => println!("\x1b[1mReserved inclusive range(OS-specific)\x1b[0m"),
-            0xFF00 | 0xFFFF => {
-                println!("\x1b[1mReserved include range (Processor specific)\x1b[0m")
+        // Elf32_Ehdr and Elf64_Ehdr only differ in the width of e_entry,
+        // e_phoff and e_shoff (4 and 8 bytes), so e_flags and everything
+        // after it start at 0x24 in ELF32 and at 0x30 in ELF64
+        self.e_type = reader.u16()?;
+        self.e_machine = reader.u16()?;
+        self.e_version = reader.u32()?;
+        self.e_entry = reader.word(&self.ei_class)?;
+        self.e_phoff = reader.word(&self.ei_class)?;
+        self.e_shoff = reader.word(&self.ei_class)?;
+        self.e_flags = reader.u32()?;
+        self.e_ehsize = reader.u16()?;
+        self.e_phentsize = reader.u16()?;
+        self.e_phnum = reader.u16()?;
+        self.e_shentsize = reader.u16()?;
+        self.e_shnum = reader.u16()?;
+        self.e_shstrndx = reader.u16()?;
+        let size = reader.position() as usize;
+
+        self.phnum = self.e_phnum as u32;
+        self.shnum = self.e_shnum as u64;
+        self.shstrndx = self.e_shstrndx as u32;
+        let extended =
+            self.e_phnum == PN_XNUM || self.e_shnum == 0 || self.e_shstrndx == SHN_XINDEX;
+        if extended && self.e_shoff != 0 {
+            // without a readable section 0 the raw values are all there is
+            reader.seek(self.e_shoff);
+            if let Ok(first) = SectionHeader::parse(&mut reader, &self.ei_class) {
+                if self.e_phnum == PN_XNUM {
+                    self.phnum = first.sh_info;
+                }
+                if self.e_shnum == 0 {
+                    self.shnum = first.sh_size;
+                }
+                if self.e_shstrndx == SHN_XINDEX {
+                    self.shstrndx = first.sh_link;
+                }
             }
-            _ => println!("\x1b[1mUNKNOWN\x1b[0m"),
-        }
-        print!("\x1b[1;32mELF Instruction set machine:\x1b[0m \x1b[1m");
-        match self.e_machine {
-            // source: https://en.wikipedia.org/wiki/Executable_and_Linkable_Format
-            0x01 => println!("AT&T WE 32100"),
-            0x02 => println!("SPARC"),
-            0x03 => println!("x86"),
-            0x04 => println!("Motorola 68000 (M68k)"),
-            0x05 => println!("Motorola 88000 (M88k)"),
-            0x06 => println!("Intel MCU"),
-            0x07 => println!("Intel 80860"),
-            0x08 => println!("MIPS"),
-            0x09 => println!("IBM System/370"),
-            0x0A => println!("MIPS RS3000 Little-endian"),
-            0x0F => println!("Hewlett-Packard PA-RISC"),
-            0x13 => println!("Intel 80960"),
-            0x14 => println!("PowerPC"),
-            0x15 => println!("PowerPC(64-bit)"),
-            0x16 => println!("S390"),
-            0x17 => println!("IBM SPU/SPC"),
-            0x24 => println!("NEC V800"),
-            0x25 => println!("Fujitsu FR20"),
-            0x26 => println!("TRW RH-32"),
-            0x27 => println!("Motorola RCE"),
-            0x28 => println!("Arm"),
-            0x29 => println!("Digital Alpha"),
-            0x2A => println!("SuperH"),
-            0x2B => println!("SPARC Version 9"),
-            0x2C => println!("Siemens TriCore embedded processor"),
-            0x2D => println!("Argonaut RISC Core"),
-            0x2E => println!("Hitachi H8/300"),
-            0x2F => println!("Hitachi H8/300H"),
-            0x30 => println!("Hitachi H8S"),
-            0x31 => println!("Hitachi H8/500"),
-            0x32 => println!("IA-64"),
-            0x33 => println!("Stanford MIPS-X"),
-            0x34 => println!("Motorola ColdFire"),
-            0x35 => println!("Motorola M68HC12"),
-            0x36 => println!("Fujitsu MMA Multimedia Accelerator"),
-            0x3E => println!("AMD x86-64"),
-            0xB7 => println!("Arm64"),
-            0xF3 => println!("RISC-V"),
-            _ => println!("Unspecified/Unknown"),
         }
-        print!("\x1b[0m");
-        println!(
-            "\x1b[1;32mELF Version:\x1b[0m {}\x1b[1m",
-            self.e_version // to work with both big endian and little endian
-        );
-        println!(
-            "\x1b[1;32mStarting Address:\x1b[0m {:#x}\x1b[1m",
-            self.e_entry
-        );
-        println!(
-            "\x1b[1;32mProgram header table start:\x1b[0m {} (Absolute)\x1b[1m",
-            self.e_phoff
-        );
-        println!(
-            "\x1b[1;32mSection header table start:\x1b[0m {} (Absolute)\x1b[1m",
-            self.e_shoff
-        );
-        println!("\x1b[1;32mFlags:\x1b[0m {:#x}\x1b[1m", self.e_flags);
-        println!(
-            "\x1b[1;32mELF Header Size:\x1b[0m {} bytes\x1b[1m",
-            self.e_ehsize
-        );
-        println!(
-            "\x1b[1;32mProgram Header Size:\x1b[0m {} bytes\x1b[1m",
-            self.e_phentsize
-        );
-        println!(
-            "\x1b[1;32mNumber of program headers:\x1b[0m {}\x1b[1m",
-            self.e_phnum
-        );
-        println!(
-            "\x1b[1;32mSize of section headers:\x1b[0m {} bytes\x1b[1m",
-            self.e_shentsize
-        );
-        println!(
-            "\x1b[1;32mNumber of section headers:\x1b[0m {}\x1b[1m",
-            self.e_shnum
-        );
-        println!(
-            "\x1b[1;32mIndex of section headers:\x1b[0m {}\x1b[1m",
-            self.e_shstrndx
-        );
+        Ok(size)
+    }
+
+    pub fn osabi(&self) -> OsAbi {
+        OsAbi::from(self.ei_osabi)
+    }
+
+    pub fn object_type(&self) -> ObjectType {
+        ObjectType::from(self.e_type)
+    }
+
+    pub fn machine(&self) -> Machine {
+        Machine::from(self.e_machine)
     }
 }
 
 pub trait ELFParserExt {
-    fn parse_elf_header(&self) -> ELFHeader;
-    fn parse_program_headers(&self) -> Option<Vec<Pheader>>;
+    fn parse_elf_header(&self) -> Result<ELFHeader, ParseError>;
+    fn parse_program_headers(&self, header: &ELFHeader)
+        -> Result<Option<Vec<Pheader>>, ParseError>;
+    fn parse_section_headers(
+        &self,
+        header: &ELFHeader,
+    ) -> Result<Option<Vec<SectionHeader<'_>>>, ParseError>;
 }
 
 // We provide separate implementation for file and &[u8] types
 // to make it easier for us to accpet ELF file from the stdin.
 
 impl ELFParserExt for [u8] {
-    fn parse_elf_header(&self) -> ELFHeader {
+    fn parse_elf_header(&self) -> Result<ELFHeader, ParseError> {
         let mut elf_head = ELFHeader::default();
-        // IMP: fix this
-        let _cursor = elf_head.parse(&self);
-        elf_head
+        elf_head.parse(self)?;
+        Ok(elf_head)
+    }
+
+    fn parse_program_headers(
+        &self,
+        header: &ELFHeader,
+    ) -> Result<Option<Vec<Pheader>>, ParseError> {
+        if header.e_phoff == 0 || header.phnum == 0 {
+            return Ok(None);
+        }
+        // entries smaller than Elf32_Phdr or Elf64_Phdr would overlap, and
+        // let a small file claim any number of them
+        let expected = match header.ei_class {
+            Arch::B32 => 32,
+            Arch::B64 => 56,
+        };
+        if (header.e_phentsize as u64) < expected {
+            return Err(ParseError::SizeMismatch {
+                field: "e_phentsize",
+                found: header.e_phentsize as u64,
+                expected,
+            });
+        }
+        // with extended numbering the count comes from the file, so don't
+        // allocate for it before the table is known to fit
+        let size = (header.phnum as u64).saturating_mul(header.e_phentsize as u64);
+        checked_slice(self, header.e_phoff, size)?;
+        let mut reader = Reader::new(self, header.ei_data);
+        let mut container: Vec<Pheader> = Vec::new();
+        for i in 0..header.phnum as u64 {
+            reader.seek(header.e_phoff.saturating_add(i * header.e_phentsize as u64));
+            container.push(Pheader::parse(&mut reader, &header.ei_class)?);
+        }
+        Ok(Some(container))
+    }
+
+    fn parse_section_headers(
+        &self,
+        header: &ELFHeader,
+    ) -> Result<Option<Vec<SectionHeader<'_>>>, ParseError> {
+        if header.e_shoff == 0 || header.shnum == 0 {
+            return Ok(None);
+        }
+        Ok(Some(section::parse_section_headers(self, header)?))
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use std::borrow::Cow;
+
+    use crate::error::ParseError;
+    use crate::parse::{
+        Arch, ELFParser, Pheader, EM_386, EM_ARM, EM_PPC, EM_X86_64, PF_R, PF_W, PF_X, PN_XNUM,
+        PT_DYNAMIC, PT_LOAD,
+    };
+    use crate::section::{SHT_PROGBITS, SHT_STRTAB, SHT_SYMTAB};
+    use crate::symbol::SHN_XINDEX;
+    use crate::utils::{Endian, Reader};
+
+    #[test]
+    fn parse_own_executable() {
+        let parser = ELFParser::new(std::env::current_exe().unwrap()).unwrap();
+        let pheaders = parser.program_headers().unwrap();
+        assert_eq!(parser.elf_header.phnum as usize, pheaders.len());
+        assert!(pheaders.iter().any(|p| p.p_type == PT_LOAD));
+    }
+
+    #[test]
+    fn pheader32big() {
+        // PT_LOAD, offset, vaddr, paddr, filesz, memsz, flags, align
+        let words: [u32; 8] = [
+            PT_LOAD,
+            0x100,
+            0x1000_0100,
+            0x100,
+            0x2000,
+            0x3000,
+            PF_R | PF_X,
+            0x10000,
+        ];
+        let bytes: Vec<u8> = words.iter().flat_map(|w| w.to_be_bytes()).collect();
+        let pheader = Pheader::parse(&mut Reader::new(&bytes, Endian::Big), &Arch::B32).unwrap();
+        assert_eq!(pheader.p_type, PT_LOAD);
+        assert_eq!(pheader.p_offset, 0x100);
+        assert_eq!(pheader.p_vaddr, 0x1000_0100);
+        assert_eq!(pheader.p_filesz, 0x2000);
+        assert_eq!(pheader.p_memsz, 0x3000);
+        assert_eq!(pheader.p_flags, PF_R | PF_X);
+        assert_eq!(pheader.p_align, 0x10000);
+        assert_eq!(pheader.flags_string(), "R E");
+    }
+
+    #[test]
+    fn pheader64little() {
+        let mut bytes = Vec::new();
+        bytes.extend(PT_DYNAMIC.to_le_bytes());
+        bytes.extend((PF_R | PF_W).to_le_bytes());
+        for word in [0x2dd8_u64, 0x3dd8, 0x3dd8, 0x1f0, 0x1f0, 8] {
+            bytes.extend(word.to_le_bytes());
+        }
+        let pheader = Pheader::parse(&mut Reader::new(&bytes, Endian::Little), &Arch::B64).unwrap();
+        assert_eq!(pheader.p_type, PT_DYNAMIC);
+        assert_eq!(pheader.p_flags, PF_R | PF_W);
+        assert_eq!(pheader.p_offset, 0x2dd8);
+        assert_eq!(pheader.p_vaddr, 0x3dd8);
+        assert_eq!(pheader.p_memsz, 0x1f0);
+        assert_eq!(pheader.p_align, 8);
+    }
+
+    #[test]
+    fn header32_i386() {
+        let contents = include_bytes!("../tests/fixtures/hello-i386");
+        let parser = ELFParser::from_bytes(contents).unwrap();
+        let header = &parser.elf_header;
+        assert_eq!(header.ei_class, Arch::B32);
+        assert_eq!(header.ei_data, Endian::Little);
+        assert_eq!(header.e_machine, EM_386);
+        assert_eq!(header.e_version, 1);
+        assert_eq!(header.e_entry, 0x0804_8054);
+        assert_eq!(header.e_phoff, 52);
+        assert_eq!(header.e_shoff, 0x154);
+        assert_eq!(header.e_flags, 0);
+        assert_eq!(header.e_ehsize, 52);
+        assert_eq!(header.e_phentsize, 32);
+        assert_eq!(header.e_phnum, 1);
+        assert_eq!(header.e_shentsize, 40);
+        assert_eq!(header.e_shnum, 6);
+        assert_eq!(header.e_shstrndx, 5);
+
+        let load = &parser.program_headers().unwrap()[0];
+        assert_eq!(load.p_type, PT_LOAD);
+        assert_eq!(load.p_vaddr, 0x0804_8054);
+        let names: Vec<&str> = parser
+            .sections()
+            .unwrap()
+            .iter()
+            .map(|s| &*s.name)
+            .collect();
+        assert_eq!(
+            names,
+            ["", ".text", ".rodata", ".symtab", ".strtab", ".shstrtab"]
+        );
+        let start = parser
+            .symbols()
+            .unwrap()
+            .iter()
+            .find(|s| s.name == "_start")
+            .unwrap();
+        assert_eq!((start.st_value, start.st_size), (0x0804_8054, 31));
+    }
+
+    #[test]
+    fn header32_arm() {
+        let contents = include_bytes!("../tests/fixtures/hello-arm.o");
+        let parser = ELFParser::from_bytes(contents).unwrap();
+        let header = &parser.elf_header;
+        assert_eq!(header.ei_class, Arch::B32);
+        assert_eq!(header.e_machine, EM_ARM);
+        assert_eq!(header.e_version, 1);
+        // EF_ARM_EABI_VER5, right after the 4-byte e_shoff
+        assert_eq!(header.e_flags, 0x0500_0000);
+        assert_eq!(header.e_shoff, 256);
+        assert_eq!(header.e_ehsize, 52);
+        assert_eq!(header.e_shentsize, 40);
+        assert_eq!(header.e_shnum, 6);
+        assert_eq!(header.e_shstrndx, 1);
+
+        assert!(parser.program_headers().unwrap().is_empty());
+        let start = parser
+            .symbols()
+            .unwrap()
+            .iter()
+            .find(|s| s.name == "_start")
+            .unwrap();
+        assert_eq!(start.st_size, 32);
+        let relocations = parser.relocations().unwrap();
+        assert_eq!(relocations[0].name, ".rel.text");
+        assert_eq!(relocations[0].entries[0].r_offset, 0x20);
+        assert_eq!(relocations[0].entries[0].symbol_name, ".rodata");
+    }
+
+    #[test]
+    fn header32_big_endian() {
+        let mut bytes = vec![0x7f, b'E', b'L', b'F', 1, 2, 1, 0];
+        bytes.resize(16, 0);
+        bytes.extend(2_u16.to_be_bytes());
+        bytes.extend(EM_PPC.to_be_bytes());
+        // e_version, e_entry, e_phoff, e_shoff, e_flags
+        for word in [0x0102_0304_u32, 0x1000_0000, 0, 0, 0x8000_0000] {
+            bytes.extend(word.to_be_bytes());
+        }
+        for half in [52_u16, 32, 0, 40, 0, 0] {
+            bytes.extend(half.to_be_bytes());
+        }
+        assert_eq!(bytes.len(), 52);
+        let header = ELFParser::from_bytes(&bytes).unwrap().elf_header;
+        assert_eq!(header.e_version, 0x0102_0304);
+        assert_eq!(header.e_entry, 0x1000_0000);
+        assert_eq!(header.e_flags, 0x8000_0000);
+        assert_eq!(header.e_ehsize, 52);
+        assert_eq!(header.e_shentsize, 40);
+    }
+
+    #[test]
+    fn extended_numbering() {
+        // a header with all three counts escaped, one segment, and three
+        // sections: the null one holding the real counts, .text, .shstrtab
+        let mut bytes = vec![0x7f, b'E', b'L', b'F', 2, 1, 1, 0];
+        bytes.resize(16, 0);
+        bytes.extend(1_u16.to_le_bytes());
+        bytes.extend(EM_X86_64.to_le_bytes());
+        bytes.extend(1_u32.to_le_bytes());
+        for word in [0_u64, 64, 120] {
+            bytes.extend(word.to_le_bytes());
+        }
+        bytes.extend(0_u32.to_le_bytes());
+        for half in [64, 56, PN_XNUM, 64, 0, SHN_XINDEX] {
+            bytes.extend(half.to_le_bytes());
+        }
+        let mut load = PT_LOAD.to_le_bytes().to_vec();
+        load.resize(56, 0);
+        bytes.extend(load);
+        let strings = b"\0.text\0.shstrtab\0";
+        let strings_offset = 120 + 3 * 64;
+        // sh_name, sh_type, then flags, addr, offset, size, link + info, align, entsize
+        let sections: [(u32, u32, [u64; 7]); 3] = [
+            (0, 0, [0, 0, 0, 3, 2 | 1 << 32, 0, 0]),
+            (1, SHT_PROGBITS, [6, 0, 0, 0, 0, 16, 0]),
+            (
+                7,
+                SHT_STRTAB,
+                [0, 0, strings_offset, strings.len() as u64, 0, 1, 0],
+            ),
+        ];
+        for (name, sh_type, words) in sections {
+            bytes.extend(name.to_le_bytes());
+            bytes.extend(sh_type.to_le_bytes());
+            for word in words {
+                bytes.extend(word.to_le_bytes());
+            }
+        }
+        bytes.extend(strings);
+
+        let parser = ELFParser::from_bytes(&bytes).unwrap();
+        let header = &parser.elf_header;
+        assert_eq!((header.e_phnum, header.phnum), (PN_XNUM, 1));
+        assert_eq!((header.e_shnum, header.shnum), (0, 3));
+        assert_eq!((header.e_shstrndx, header.shstrndx), (SHN_XINDEX, 2));
+        assert_eq!(parser.program_headers().unwrap()[0].p_type, PT_LOAD);
+        let names: Vec<&str> = parser
+            .sections()
+            .unwrap()
+            .iter()
+            .map(|s| &*s.name)
+            .collect();
+        assert_eq!(names, ["", ".text", ".shstrtab"]);
+        assert!(crate::validate::validate(&parser).is_empty());
+    }
+
+    #[test]
+    fn entry_size() {
+        let mut contents = std::fs::read(std::env::current_exe().unwrap()).unwrap();
+        // e_phentsize 0: 65534 entries would fit in no room at all
+        contents[0x36..0x38].fill(0);
+        contents[0x38..0x3a].copy_from_slice(&0xfffe_u16.to_le_bytes());
+        let parser = ELFParser::from_bytes(&contents).unwrap();
+        assert!(matches!(
+            parser.program_headers(),
+            Err(ParseError::SizeMismatch {
+                field: "e_phentsize",
+                found: 0,
+                expected: 56
+            })
+        ));
+    }
+
+    #[test]
+    fn reject_non_elf() {
+        let parser = ELFParser::new("Cargo.toml".into());
+        assert!(matches!(parser, Err(ParseError::BadMagic)));
+    }
+
+    #[test]
+    fn truncated_header() {
+        let bytes = [0x7f, b'E', b'L', b'F', 2, 1, 1, 0, 0, 0];
+        let parser = ELFParser::from_bytes(&bytes);
+        assert!(matches!(
+            parser,
+            Err(ParseError::Truncated {
+                offset: 16,
+                needed: 2
+            })
+        ));
+    }
+
+    #[test]
+    fn truncated_files_never_panic() {
+        let contents = std::fs::read(std::env::current_exe().unwrap()).unwrap();
+        // cut the file at every offset inside the headers, and at a
+        // spread of offsets through the rest of it
+        let cuts = (0..4096).chain((4096..contents.len()).step_by(4093));
+        for len in cuts {
+            if let Ok(parser) = ELFParser::from_bytes(&contents[..len]) {
+                parse_everything(&parser);
+            }
+        }
+    }
+
+    // fill every cache, ignoring errors
+    fn parse_everything(parser: &ELFParser) {
+        _ = parser.program_headers();
+        _ = parser.sections();
+        _ = parser.symbols();
+        _ = parser.dynamic_symbols();
+        _ = parser.relocations();
+        _ = parser.notes();
+        if let Ok(symbols) = parser.symbol_iter(SHT_SYMTAB) {
+            symbols.for_each(drop);
+        }
+        _ = parser.build_id();
+    }
+
+    #[test]
+    fn corrupted_offsets_never_panic() {
+        let mut contents = std::fs::read(std::env::current_exe().unwrap()).unwrap();
+        // e_phoff, e_shoff and e_shstrndx pointing far outside the file
+        contents[0x20..0x28].copy_from_slice(&u64::MAX.to_le_bytes());
+        contents[0x28..0x30].copy_from_slice(&(u64::MAX - 8).to_le_bytes());
+        contents[0x3e..0x40].copy_from_slice(&0xfffe_u16.to_le_bytes());
+        let parser = ELFParser::from_bytes(&contents).unwrap();
+        assert!(parser.program_headers().is_err());
+        assert!(parser.sections().is_err());
+        parse_everything(&parser);
+    }
+
+    #[test]
+    fn tables_are_parsed_lazily() {
+        let mut contents = std::fs::read(std::env::current_exe().unwrap()).unwrap();
+        // only the section header table is broken
+        contents[0x28..0x30].copy_from_slice(&(u64::MAX - 8).to_le_bytes());
+        let parser = ELFParser::from_bytes(&contents).unwrap();
+        assert!(!parser.program_headers().unwrap().is_empty());
+        assert!(parser.sections().is_err());
+        // errors are not cached as empty tables
+        assert!(parser.sections().is_err());
+    }
+
+    #[test]
+    fn symbol_iter_matches_table() {
+        let parser = ELFParser::new(std::env::current_exe().unwrap()).unwrap();
+        let symbols = parser.symbols().unwrap();
+        let iter = parser.symbol_iter(SHT_SYMTAB).unwrap();
+        assert_eq!(iter.len(), symbols.len());
+        for (entry, symbol) in iter.zip(symbols) {
+            let raw = entry.unwrap();
+            assert_eq!(raw.name, symbol.name);
+            assert_eq!(raw.st_value, symbol.st_value);
+        }
+    }
+
+    #[test]
+    fn cached_names_borrow_from_the_file() {
+        // read rather than mapped, so the bytes are in a Vec
+        let file = std::fs::File::open(std::env::current_exe().unwrap()).unwrap();
+        let parser = ELFParser::from_reader(file).unwrap();
+        let sections = parser.sections().unwrap();
+        assert!(sections.iter().all(|s| matches!(s.name, Cow::Borrowed(_))));
+        let symbols = parser.symbols().unwrap();
+        assert!(symbols.iter().all(|s| matches!(s.name, Cow::Borrowed(_))));
+        // the names stay put when the parser moves
+        let name = sections[1].name.as_ptr();
+        let moved = Box::new(parser);
+        assert_eq!(moved.sections().unwrap()[1].name.as_ptr(), name);
+    }
+
+    #[test]
+    fn parse_own_symbols() {
+        let parser = ELFParser::new(std::env::current_exe().unwrap()).unwrap();
+        let symbols = parser.symbols().unwrap();
+        // the null symbol always comes first
+        assert_eq!(symbols[0].name, "");
+        assert!(symbols
+            .iter()
+            .any(|s| s.name.contains("parse_own_symbols") && s.type_name() == "FUNC"));
     }
 
-    fn parse_program_headers(&self) -> Option<Vec<Pheader>> {
-        let container: Vec<Pheader> = Vec::new();
-        Some(container)
+    #[test]
+    fn find_sections_by_name() {
+        let parser = ELFParser::new(std::env::current_exe().unwrap()).unwrap();
+        let text = parser.section_by_name(".text").unwrap();
+        assert_eq!(text.sh_type, crate::section::SHT_PROGBITS);
+        assert!(!parser.section_data(text).unwrap().is_empty());
+        assert!(parser.section_by_name(".no_such_section").is_none());
     }
 }
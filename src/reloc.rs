@@ -0,0 +1,678 @@
+use std::collections::{hash_map::Entry, HashMap};
+
+use crate::dynamic::{DynamicEntry, DT_RELR, DT_RELRSZ};
+use crate::error::ParseError;
+use crate::parse::{
+    vaddr_to_offset, Arch, ELFHeader, Pheader, EM_386, EM_AARCH64, EM_ARM, EM_MIPS, EM_PPC,
+    EM_PPC64, EM_RISCV, EM_X86_64,
+};
+use crate::section::{SectionHeader, SHT_DYNSYM, SHT_REL, SHT_RELA, SHT_RELR, SHT_SYMTAB};
+use crate::symbol::{self, Symbol, STT_SECTION};
+use crate::utils::{checked_slice, Reader};
+
+#[derive(Debug, Clone, Default)]
+pub struct Relocation {
+    // location to patch: a section offset in relocatable objects,
+    // a virtual address otherwise
+    pub r_offset: u64,
+    // raw r_info as stored in the file
+    pub r_info: u64,
+    pub r_type: u32,
+    // index into the linked symbol table
+    pub r_sym: u32,
+    // only present in SHT_RELA entries
+    pub r_addend: Option<i64>,
+    // resolved through the linked symbol table
+    pub symbol_name: String,
+    pub symbol_value: u64,
+}
+
+impl Relocation {
+    // parse a single REL (`rela == false`) or RELA entry at the reader's cursor
+    pub fn parse(
+        reader: &mut Reader,
+        header: &ELFHeader,
+        rela: bool,
+    ) -> Result<Relocation, ParseError> {
+        let class = &header.ei_class;
+        let mut reloc = Relocation {
+            r_offset: reader.word(class)?,
+            ..Default::default()
+        };
+        if *class == Arch::B64 && header.e_machine == EM_MIPS {
+            // Elf64_Mips_Rel: r_sym followed by r_ssym and three one
+            // byte types, independent of the file's byte order
+            let start = reader.position();
+            reloc.r_sym = reader.u32()?;
+            let types = reader.bytes(4)?;
+            reloc.r_type = types[3] as u32 | (types[2] as u32) << 8 | (types[1] as u32) << 16;
+            reader.seek(start);
+            reloc.r_info = reader.u64()?;
+        } else if *class == Arch::B64 {
+            reloc.r_info = reader.u64()?;
+            reloc.r_sym = (reloc.r_info >> 32) as u32;
+            reloc.r_type = reloc.r_info as u32;
+        } else {
+            reloc.r_info = reader.u32()? as u64;
+            reloc.r_sym = (reloc.r_info >> 8) as u32;
+            reloc.r_type = reloc.r_info as u32 & 0xff;
+        }
+        if rela {
+            reloc.r_addend = Some(match class {
+                Arch::B32 => reader.u32()? as i32 as i64,
+                Arch::B64 => reader.u64()? as i64,
+            });
+        }
+        Ok(reloc)
+    }
+
+    pub fn type_name(&self, e_machine: u16) -> String {
+        reloc_type_name(e_machine, self.r_type)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RelocationSection {
+    // name of the section, or the dynamic tag the table came from
+    pub name: String,
+    // SHT_REL, SHT_RELA or SHT_RELR
+    pub sh_type: u32,
+    // file offset of the table
+    pub offset: u64,
+    pub entries: Vec<Relocation>,
+}
+
+// Expand a SHT_RELR / DT_RELR table. Even entries are addresses to
+// relocate; odd entries are bitmaps of the following word-sized slots.
+pub fn parse_relr(table: &[u8], header: &ELFHeader) -> Result<Vec<Relocation>, ParseError> {
+    let class = &header.ei_class;
+    let word: u64 = if *class == Arch::B32 { 4 } else { 8 };
+    let relative = relative_type(header.e_machine);
+    let mut reader = Reader::new(table, header.ei_data);
+    let mut relocs = Vec::new();
+    let mut base = 0;
+    for _ in 0..table.len() as u64 / word {
+        let entry = reader.word(class)?;
+        if entry & 1 == 0 {
+            relocs.push(relative_reloc(entry, relative));
+            base = entry.wrapping_add(word);
+            continue;
+        }
+        let mut bitmap = entry >> 1;
+        let mut slot = base;
+        while bitmap != 0 {
+            if bitmap & 1 != 0 {
+                relocs.push(relative_reloc(slot, relative));
+            }
+            bitmap >>= 1;
+            slot = slot.wrapping_add(word);
+        }
+        base = base.wrapping_add((word * 8 - 1) * word);
+    }
+    Ok(relocs)
+}
+
+fn relative_reloc(r_offset: u64, r_type: u32) -> Relocation {
+    Relocation {
+        r_offset,
+        r_info: r_type as u64,
+        r_type,
+        ..Default::default()
+    }
+}
+
+// Parse every SHT_REL, SHT_RELA and SHT_RELR section. Files without
+// a SHT_RELR section still get their DT_RELR table from the dynamic array.
+pub fn parse_relocations(
+    contents: &[u8],
+    header: &ELFHeader,
+    sections: &[SectionHeader],
+    pheaders: &[Pheader],
+    dynamic: &[DynamicEntry],
+) -> Result<Vec<RelocationSection>, ParseError> {
+    // .rela.dyn and .rela.plt share .dynsym; only parse it once
+    let mut symtabs: HashMap<u32, Vec<Symbol>> = HashMap::new();
+    let mut tables = Vec::new();
+
+    for section in sections {
+        let entries = match section.sh_type {
+            SHT_REL | SHT_RELA => {
+                let rela = section.sh_type == SHT_RELA;
+                let entsize = match section.sh_entsize {
+                    0 => default_entsize(&header.ei_class, rela),
+                    size => size,
+                };
+                let symbols = match sections.get(section.sh_link as usize) {
+                    Some(symtab)
+                        if symtab.sh_type == SHT_SYMTAB || symtab.sh_type == SHT_DYNSYM =>
+                    {
+                        match symtabs.entry(section.sh_link) {
+                            Entry::Occupied(parsed) => &*parsed.into_mut(),
+                            Entry::Vacant(slot) => &*slot
+                                .insert(symbol::parse_symbols(contents, header, sections, symtab)?),
+                        }
+                    }
+                    _ => &Vec::new(),
+                };
+                let table = section.data(contents)?;
+                let mut reader = Reader::new(table, header.ei_data);
+                let mut entries = Vec::new();
+                for i in 0..table.len() as u64 / entsize {
+                    reader.seek(i * entsize);
+                    let mut reloc = Relocation::parse(&mut reader, header, rela)?;
+                    if let Some(symbol) = symbols.get(reloc.r_sym as usize) {
+                        reloc.symbol_name = match sections.get(symbol.shndx as usize) {
+                            // section symbols are nameless; use the section's name
+                            Some(target) if symbol.st_type == STT_SECTION => {
+                                target.name.to_string()
+                            }
+                            _ => symbol.name.to_string(),
+                        };
+                        reloc.symbol_value = symbol.st_value;
+                    }
+                    entries.push(reloc);
+                }
+                entries
+            }
+            SHT_RELR => parse_relr(section.data(contents)?, header)?,
+            _ => continue,
+        };
+        tables.push(RelocationSection {
+            name: section.name.to_string(),
+            sh_type: section.sh_type,
+            offset: section.sh_offset,
+            entries,
+        });
+    }
+
+    if !sections.iter().any(|s| s.sh_type == SHT_RELR) {
+        let find = |tag| dynamic.iter().find(|e| e.d_tag == tag).map(|e| e.d_val);
+        if let (Some(addr), Some(size)) = (find(DT_RELR), find(DT_RELRSZ)) {
+            if let Some(offset) = vaddr_to_offset(pheaders, addr) {
+                tables.push(RelocationSection {
+                    name: "DT_RELR".to_string(),
+                    sh_type: SHT_RELR,
+                    offset,
+                    entries: parse_relr(checked_slice(contents, offset, size)?, header)?,
+                });
+            }
+        }
+    }
+    Ok(tables)
+}
+
+fn default_entsize(class: &Arch, rela: bool) -> u64 {
+    match (class, rela) {
+        (Arch::B32, false) => 8,
+        (Arch::B32, true) => 12,
+        (Arch::B64, false) => 16,
+        (Arch::B64, true) => 24,
+    }
+}
+
+// the R_*_RELATIVE type that DT_RELR entries stand for
+fn relative_type(e_machine: u16) -> u32 {
+    match e_machine {
+        EM_386 | EM_X86_64 => 8,
+        EM_AARCH64 => 1027,
+        EM_ARM => 23,
+        EM_RISCV => 3,
+        EM_PPC | EM_PPC64 => 22,
+        EM_MIPS => 3,
+        _ => 0,
+    }
+}
+
+pub fn reloc_type_name(e_machine: u16, r_type: u32) -> String {
+    let (prefix, name) = match e_machine {
+        EM_X86_64 => ("R_X86_64_", x86_64_name(r_type)),
+        EM_386 => ("R_386_", i386_name(r_type)),
+        EM_AARCH64 => ("R_AARCH64_", aarch64_name(r_type)),
+        EM_ARM => ("R_ARM_", arm_name(r_type)),
+        EM_RISCV => ("R_RISCV_", riscv_name(r_type)),
+        EM_PPC => ("R_PPC_", ppc_name(r_type)),
+        EM_PPC64 => ("R_PPC64_", ppc64_name(r_type)),
+        EM_MIPS => ("R_MIPS_", mips_name(r_type)),
+        _ => ("", None),
+    };
+    match name {
+        Some(name) => format!("{prefix}{name}"),
+        None => format!("{prefix}<unknown: {r_type:#x}>"),
+    }
+}
+
+fn x86_64_name(r_type: u32) -> Option<&'static str> {
+    Some(match r_type {
+        0 => "NONE",
+        1 => "64",
+        2 => "PC32",
+        3 => "GOT32",
+        4 => "PLT32",
+        5 => "COPY",
+        6 => "GLOB_DAT",
+        7 => "JUMP_SLOT",
+        8 => "RELATIVE",
+        9 => "GOTPCREL",
+        10 => "32",
+        11 => "32S",
+        12 => "16",
+        13 => "PC16",
+        14 => "8",
+        15 => "PC8",
+        16 => "DTPMOD64",
+        17 => "DTPOFF64",
+        18 => "TPOFF64",
+        19 => "TLSGD",
+        20 => "TLSLD",
+        21 => "DTPOFF32",
+        22 => "GOTTPOFF",
+        23 => "TPOFF32",
+        24 => "PC64",
+        25 => "GOTOFF64",
+        26 => "GOTPC32",
+        27 => "GOT64",
+        28 => "GOTPCREL64",
+        29 => "GOTPC64",
+        30 => "GOTPLT64",
+        31 => "PLTOFF64",
+        32 => "SIZE32",
+        33 => "SIZE64",
+        34 => "GOTPC32_TLSDESC",
+        35 => "TLSDESC_CALL",
+        36 => "TLSDESC",
+        37 => "IRELATIVE",
+        38 => "RELATIVE64",
+        41 => "GOTPCRELX",
+        42 => "REX_GOTPCRELX",
+        _ => return None,
+    })
+}
+
+fn i386_name(r_type: u32) -> Option<&'static str> {
+    Some(match r_type {
+        0 => "NONE",
+        1 => "32",
+        2 => "PC32",
+        3 => "GOT32",
+        4 => "PLT32",
+        5 => "COPY",
+        6 => "GLOB_DAT",
+        7 => "JUMP_SLOT",
+        8 => "RELATIVE",
+        9 => "GOTOFF",
+        10 => "GOTPC",
+        11 => "32PLT",
+        14 => "TLS_TPOFF",
+        15 => "TLS_IE",
+        16 => "TLS_GOTIE",
+        17 => "TLS_LE",
+        18 => "TLS_GD",
+        19 => "TLS_LDM",
+        20 => "16",
+        21 => "PC16",
+        22 => "8",
+        23 => "PC8",
+        35 => "TLS_DTPMOD32",
+        36 => "TLS_DTPOFF32",
+        37 => "TLS_TPOFF32",
+        38 => "SIZE32",
+        39 => "TLS_GOTDESC",
+        40 => "TLS_DESC_CALL",
+        41 => "TLS_DESC",
+        42 => "IRELATIVE",
+        43 => "GOT32X",
+        _ => return None,
+    })
+}
+
+fn aarch64_name(r_type: u32) -> Option<&'static str> {
+    Some(match r_type {
+        0 => "NONE",
+        257 => "ABS64",
+        258 => "ABS32",
+        259 => "ABS16",
+        260 => "PREL64",
+        261 => "PREL32",
+        262 => "PREL16",
+        263 => "MOVW_UABS_G0",
+        264 => "MOVW_UABS_G0_NC",
+        265 => "MOVW_UABS_G1",
+        266 => "MOVW_UABS_G1_NC",
+        267 => "MOVW_UABS_G2",
+        268 => "MOVW_UABS_G2_NC",
+        269 => "MOVW_UABS_G3",
+        270 => "MOVW_SABS_G0",
+        271 => "MOVW_SABS_G1",
+        272 => "MOVW_SABS_G2",
+        273 => "LD_PREL_LO19",
+        274 => "ADR_PREL_LO21",
+        275 => "ADR_PREL_PG_HI21",
+        276 => "ADR_PREL_PG_HI21_NC",
+        277 => "ADD_ABS_LO12_NC",
+        278 => "LDST8_ABS_LO12_NC",
+        279 => "TSTBR14",
+        280 => "CONDBR19",
+        282 => "JUMP26",
+        283 => "CALL26",
+        284 => "LDST16_ABS_LO12_NC",
+        285 => "LDST32_ABS_LO12_NC",
+        286 => "LDST64_ABS_LO12_NC",
+        299 => "LDST128_ABS_LO12_NC",
+        311 => "ADR_GOT_PAGE",
+        312 => "LD64_GOT_LO12_NC",
+        541 => "TLSIE_ADR_GOTTPREL_PAGE21",
+        542 => "TLSIE_LD64_GOTTPREL_LO12_NC",
+        549 => "TLSLE_ADD_TPREL_HI12",
+        551 => "TLSLE_ADD_TPREL_LO12_NC",
+        560 => "TLSDESC_ADR_PAGE21",
+        561 => "TLSDESC_LD64_LO12",
+        562 => "TLSDESC_ADD_LO12",
+        569 => "TLSDESC_CALL",
+        1024 => "COPY",
+        1025 => "GLOB_DAT",
+        1026 => "JUMP_SLOT",
+        1027 => "RELATIVE",
+        1028 => "TLS_DTPMOD",
+        1029 => "TLS_DTPREL",
+        1030 => "TLS_TPREL",
+        1031 => "TLSDESC",
+        1032 => "IRELATIVE",
+        _ => return None,
+    })
+}
+
+fn arm_name(r_type: u32) -> Option<&'static str> {
+    Some(match r_type {
+        0 => "NONE",
+        1 => "PC24",
+        2 => "ABS32",
+        3 => "REL32",
+        4 => "LDR_PC_G0",
+        5 => "ABS16",
+        6 => "ABS12",
+        7 => "THM_ABS5",
+        8 => "ABS8",
+        9 => "SBREL32",
+        10 => "THM_CALL",
+        11 => "THM_PC8",
+        17 => "TLS_DTPMOD32",
+        18 => "TLS_DTPOFF32",
+        19 => "TLS_TPOFF32",
+        20 => "COPY",
+        21 => "GLOB_DAT",
+        22 => "JUMP_SLOT",
+        23 => "RELATIVE",
+        24 => "GOTOFF32",
+        25 => "BASE_PREL",
+        26 => "GOT_BREL",
+        27 => "PLT32",
+        28 => "CALL",
+        29 => "JUMP24",
+        30 => "THM_JUMP24",
+        40 => "V4BX",
+        42 => "PREL31",
+        43 => "MOVW_ABS_NC",
+        44 => "MOVT_ABS",
+        45 => "MOVW_PREL_NC",
+        46 => "MOVT_PREL",
+        47 => "THM_MOVW_ABS_NC",
+        48 => "THM_MOVT_ABS",
+        102 => "THM_JUMP11",
+        103 => "THM_JUMP8",
+        104 => "TLS_GD32",
+        106 => "TLS_IE32",
+        107 => "TLS_LE32",
+        160 => "IRELATIVE",
+        _ => return None,
+    })
+}
+
+fn riscv_name(r_type: u32) -> Option<&'static str> {
+    Some(match r_type {
+        0 => "NONE",
+        1 => "32",
+        2 => "64",
+        3 => "RELATIVE",
+        4 => "COPY",
+        5 => "JUMP_SLOT",
+        6 => "TLS_DTPMOD32",
+        7 => "TLS_DTPMOD64",
+        8 => "TLS_DTPREL32",
+        9 => "TLS_DTPREL64",
+        10 => "TLS_TPREL32",
+        11 => "TLS_TPREL64",
+        12 => "TLSDESC",
+        16 => "BRANCH",
+        17 => "JAL",
+        18 => "CALL",
+        19 => "CALL_PLT",
+        20 => "GOT_HI20",
+        21 => "TLS_GOT_HI20",
+        22 => "TLS_GD_HI20",
+        23 => "PCREL_HI20",
+        24 => "PCREL_LO12_I",
+        25 => "PCREL_LO12_S",
+        26 => "HI20",
+        27 => "LO12_I",
+        28 => "LO12_S",
+        29 => "TPREL_HI20",
+        30 => "TPREL_LO12_I",
+        31 => "TPREL_LO12_S",
+        32 => "TPREL_ADD",
+        33 => "ADD8",
+        34 => "ADD16",
+        35 => "ADD32",
+        36 => "ADD64",
+        37 => "SUB8",
+        38 => "SUB16",
+        39 => "SUB32",
+        40 => "SUB64",
+        43 => "ALIGN",
+        44 => "RVC_BRANCH",
+        45 => "RVC_JUMP",
+        51 => "RELAX",
+        52 => "SUB6",
+        53 => "SET6",
+        54 => "SET8",
+        55 => "SET16",
+        56 => "SET32",
+        57 => "32_PCREL",
+        58 => "IRELATIVE",
+        59 => "PLT32",
+        60 => "SET_ULEB128",
+        61 => "SUB_ULEB128",
+        _ => return None,
+    })
+}
+
+fn ppc_name(r_type: u32) -> Option<&'static str> {
+    Some(match r_type {
+        0 => "NONE",
+        1 => "ADDR32",
+        2 => "ADDR24",
+        3 => "ADDR16",
+        4 => "ADDR16_LO",
+        5 => "ADDR16_HI",
+        6 => "ADDR16_HA",
+        7 => "ADDR14",
+        10 => "REL24",
+        11 => "REL14",
+        18 => "PLTREL24",
+        19 => "COPY",
+        20 => "GLOB_DAT",
+        21 => "JMP_SLOT",
+        22 => "RELATIVE",
+        23 => "LOCAL24PC",
+        24 => "UADDR32",
+        25 => "UADDR16",
+        26 => "REL32",
+        67 => "TLS",
+        68 => "DTPMOD32",
+        73 => "TPREL32",
+        78 => "DTPREL32",
+        248 => "IRELATIVE",
+        249 => "REL16",
+        250 => "REL16_LO",
+        251 => "REL16_HI",
+        252 => "REL16_HA",
+        _ => return None,
+    })
+}
+
+fn ppc64_name(r_type: u32) -> Option<&'static str> {
+    Some(match r_type {
+        0 => "NONE",
+        1 => "ADDR32",
+        2 => "ADDR24",
+        3 => "ADDR16",
+        4 => "ADDR16_LO",
+        5 => "ADDR16_HI",
+        6 => "ADDR16_HA",
+        10 => "REL24",
+        19 => "COPY",
+        20 => "GLOB_DAT",
+        21 => "JMP_SLOT",
+        22 => "RELATIVE",
+        26 => "REL32",
+        38 => "ADDR64",
+        44 => "REL64",
+        47 => "TOC16",
+        48 => "TOC16_LO",
+        49 => "TOC16_HI",
+        50 => "TOC16_HA",
+        51 => "TOC",
+        63 => "TOC16_DS",
+        64 => "TOC16_LO_DS",
+        68 => "DTPMOD64",
+        73 => "TPREL64",
+        78 => "DTPREL64",
+        247 => "JMP_IREL",
+        248 => "IRELATIVE",
+        249 => "REL16",
+        250 => "REL16_LO",
+        251 => "REL16_HI",
+        252 => "REL16_HA",
+        _ => return None,
+    })
+}
+
+fn mips_name(r_type: u32) -> Option<&'static str> {
+    // 64-bit MIPS packs up to three types; name the first one
+    Some(match r_type & 0xff {
+        0 => "NONE",
+        1 => "16",
+        2 => "32",
+        3 => "REL32",
+        4 => "26",
+        5 => "HI16",
+        6 => "LO16",
+        7 => "GPREL16",
+        8 => "LITERAL",
+        9 => "GOT16",
+        10 => "PC16",
+        11 => "CALL16",
+        12 => "GPREL32",
+        18 => "64",
+        19 => "GOT_DISP",
+        20 => "GOT_PAGE",
+        21 => "GOT_OFST",
+        22 => "GOT_HI16",
+        23 => "GOT_LO16",
+        24 => "SUB",
+        28 => "HIGHER",
+        29 => "HIGHEST",
+        30 => "CALL_HI16",
+        31 => "CALL_LO16",
+        37 => "JALR",
+        38 => "TLS_DTPMOD32",
+        39 => "TLS_DTPREL32",
+        40 => "TLS_DTPMOD64",
+        41 => "TLS_DTPREL64",
+        42 => "TLS_GD",
+        43 => "TLS_LDM",
+        44 => "TLS_DTPREL_HI16",
+        45 => "TLS_DTPREL_LO16",
+        46 => "TLS_GOTTPREL",
+        47 => "TLS_TPREL32",
+        48 => "TLS_TPREL64",
+        49 => "TLS_TPREL_HI16",
+        50 => "TLS_TPREL_LO16",
+        126 => "COPY",
+        127 => "JUMP_SLOT",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::parse::{Arch, ELFHeader, ELFParser, EM_386, EM_AARCH64, EM_X86_64};
+    use crate::reloc::{parse_relr, reloc_type_name, Relocation};
+    use crate::utils::{Endian, Reader};
+
+    #[test]
+    fn rel32() {
+        let header = ELFHeader {
+            ei_class: Arch::B32,
+            e_machine: EM_386,
+            ..Default::default()
+        };
+        let mut bytes = Vec::new();
+        bytes.extend(0x804_a00c_u32.to_le_bytes());
+        bytes.extend((5_u32 << 8 | 7).to_le_bytes());
+        let reloc =
+            Relocation::parse(&mut Reader::new(&bytes, Endian::Little), &header, false).unwrap();
+        assert_eq!(reloc.r_offset, 0x804_a00c);
+        assert_eq!(reloc.r_sym, 5);
+        assert_eq!(reloc.type_name(EM_386), "R_386_JUMP_SLOT");
+        assert_eq!(reloc.r_addend, None);
+    }
+
+    #[test]
+    fn rela64big() {
+        let header = ELFHeader {
+            ei_data: Endian::Big,
+            e_machine: EM_AARCH64,
+            ..Default::default()
+        };
+        let mut bytes = Vec::new();
+        bytes.extend(0x10_u64.to_be_bytes());
+        bytes.extend((3_u64 << 32 | 283).to_be_bytes());
+        bytes.extend((-4_i64).to_be_bytes());
+        let reloc =
+            Relocation::parse(&mut Reader::new(&bytes, Endian::Big), &header, true).unwrap();
+        assert_eq!(reloc.r_sym, 3);
+        assert_eq!(reloc.type_name(EM_AARCH64), "R_AARCH64_CALL26");
+        assert_eq!(reloc.r_addend, Some(-4));
+    }
+
+    #[test]
+    fn relr() {
+        let header = ELFHeader {
+            e_machine: EM_X86_64,
+            ..Default::default()
+        };
+        let mut bytes = Vec::new();
+        // address 0x1000, then a bitmap for 0x1008, 0x1018 and 0x1020
+        bytes.extend(0x1000_u64.to_le_bytes());
+        bytes.extend((0b1101_u64 << 1 | 1).to_le_bytes());
+        let relocs = parse_relr(&bytes, &header).unwrap();
+        let offsets: Vec<u64> = relocs.iter().map(|r| r.r_offset).collect();
+        assert_eq!(offsets, [0x1000, 0x1008, 0x1018, 0x1020]);
+        assert_eq!(relocs[0].type_name(EM_X86_64), "R_X86_64_RELATIVE");
+    }
+
+    #[test]
+    fn unknown_type() {
+        assert_eq!(reloc_type_name(EM_X86_64, 0x99), "R_X86_64_<unknown: 0x99>");
+    }
+
+    #[test]
+    fn own_relocations() {
+        let parser = ELFParser::new(std::env::current_exe().unwrap()).unwrap();
+        let tables = parser.relocations().unwrap();
+        assert!(tables
+            .iter()
+            .flat_map(|t| &t.entries)
+            .any(|r| !r.symbol_name.is_empty()));
+    }
+}
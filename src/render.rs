@@ -0,0 +1,1185 @@
+// Output of the parsed structures. The parser never prints; a `Renderer`
+// decides what the views look like and where they are written.
+use std::fmt::Display;
+use std::io::{self, Write};
+
+use crate::archive::{ArchiveSymbol, ElfArchive};
+use crate::cfg::{self, Function};
+use crate::color;
+use crate::disasm::{self, Listing, Syntax};
+use crate::dynamic::DynamicEntry;
+use crate::error::ParseError;
+use crate::json::{self, Json, ToJson};
+use crate::note::NoteSection;
+use crate::parse::{Arch, ELFHeader, ELFParser, Pheader};
+use crate::reloc::RelocationSection;
+use crate::section::SectionHeader;
+use crate::symbol::Symbol;
+use crate::utils::Endian;
+
+// which parts of the file to render
+#[derive(Default, Debug, Clone, Copy)]
+pub struct Views {
+    pub header: bool,
+    pub program_headers: bool,
+    pub sections: bool,
+    pub symbols: bool,
+    pub dynamic: bool,
+    pub relocs: bool,
+    pub notes: bool,
+    // the symbol index of archives
+    pub archive_index: bool,
+    // the code of the executable sections, in this syntax
+    pub disassemble: Option<Syntax>,
+    // the control-flow graphs of the functions, in this syntax
+    pub cfg: Option<Syntax>,
+}
+
+impl Views {
+    pub fn all() -> Views {
+        Views {
+            header: true,
+            program_headers: true,
+            sections: true,
+            symbols: true,
+            dynamic: true,
+            relocs: true,
+            notes: true,
+            // left out, as by readelf's --all
+            archive_index: false,
+            disassemble: None,
+            cfg: None,
+        }
+    }
+
+    // whether any view of the ELF files themselves is on
+    pub fn any_elf(&self) -> bool {
+        self.header
+            || self.program_headers
+            || self.sections
+            || self.symbols
+            || self.dynamic
+            || self.relocs
+            || self.notes
+            || self.disassemble.is_some()
+            || self.cfg.is_some()
+    }
+}
+
+// Tables the file doesn't have are passed as `None`.
+pub trait Renderer {
+    // called before the views of each file
+    fn begin(&mut self, path: &str) -> io::Result<()>;
+    fn header(&mut self, header: &ELFHeader) -> io::Result<()>;
+    fn program_headers(&mut self, pheaders: Option<&[Pheader]>) -> io::Result<()>;
+    fn sections(&mut self, sections: Option<&[SectionHeader]>) -> io::Result<()>;
+    fn dynamic(&mut self, entries: Option<&[DynamicEntry]>) -> io::Result<()>;
+    fn relocations(
+        &mut self,
+        tables: Option<&[RelocationSection]>,
+        e_machine: u16,
+    ) -> io::Result<()>;
+    // .dynsym and .symtab; the sections give the nm letters
+    fn symbols(
+        &mut self,
+        dynamic: Option<&[Symbol]>,
+        symbols: Option<&[Symbol]>,
+        sections: &[SectionHeader],
+    ) -> io::Result<()>;
+    fn notes(&mut self, tables: Option<&[NoteSection]>) -> io::Result<()>;
+    fn disassembly(&mut self, listings: Option<&[Listing]>) -> io::Result<()>;
+    fn cfg(&mut self, functions: Option<&[Function]>) -> io::Result<()>;
+    // the symbol index of the archive at `path`, with `size` bytes of names
+    fn archive_index(&mut self, path: &str, symbols: &[ArchiveSymbol], size: u64)
+        -> io::Result<()>;
+    // called after the views of each file
+    fn end(&mut self) -> io::Result<()>;
+    // called once, after the last file
+    fn finish(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+// tables the file doesn't have are empty
+fn present<T>(items: &[T]) -> Option<&[T]> {
+    Some(items).filter(|items| !items.is_empty())
+}
+
+// Parse the tables the views need without rendering them, e.g. on a
+// worker thread before handing the parser over for rendering
+pub fn preload(parser: &ELFParser, views: &Views) -> Result<(), ParseError> {
+    if views.program_headers {
+        parser.program_headers()?;
+    }
+    if views.sections {
+        parser.sections()?;
+    }
+    if views.dynamic {
+        parser.dynamic()?;
+    }
+    if views.relocs {
+        parser.relocations()?;
+    }
+    if views.symbols {
+        parser.dynamic_symbols()?;
+        parser.symbols()?;
+    }
+    if views.notes {
+        parser.notes()?;
+    }
+    if views.disassemble.is_some() || views.cfg.is_some() {
+        // and the tables the labels come from
+        parser.sections()?;
+        parser.symbols()?;
+        parser.dynamic_symbols()?;
+        parser.relocations()?;
+    }
+    Ok(())
+}
+
+// Render the requested views, in the order readelf uses. Only the tables
+// that are displayed get parsed.
+pub fn render(
+    renderer: &mut dyn Renderer,
+    path: &str,
+    parser: &ELFParser,
+    views: &Views,
+) -> Result<(), ParseError> {
+    renderer.begin(path)?;
+    if views.header {
+        renderer.header(&parser.elf_header)?;
+    }
+    if views.program_headers {
+        renderer.program_headers(present(parser.program_headers()?))?;
+    }
+    if views.sections {
+        renderer.sections(present(parser.sections()?))?;
+    }
+    if views.dynamic {
+        renderer.dynamic(present(parser.dynamic()?))?;
+    }
+    if views.relocs {
+        let relocations = present(parser.relocations()?);
+        renderer.relocations(relocations, parser.elf_header.e_machine)?;
+    }
+    if views.symbols {
+        let dynamic = present(parser.dynamic_symbols()?);
+        let symbols = present(parser.symbols()?);
+        renderer.symbols(dynamic, symbols, parser.sections()?)?;
+    }
+    if views.notes {
+        renderer.notes(present(parser.notes()?))?;
+    }
+    let mut listings = None;
+    if let Some(syntax) = views.disassemble {
+        let disassembly = disasm::disassemble(parser, syntax)?;
+        renderer.disassembly(present(&disassembly))?;
+        listings = Some(disassembly);
+    }
+    if let Some(syntax) = views.cfg {
+        // the graphs are built from the listings -D showed, when it did
+        let listings = match listings {
+            Some(listings) if views.disassemble == views.cfg => listings,
+            _ => disasm::disassemble(parser, syntax)?,
+        };
+        renderer.cfg(present(&cfg::functions(&listings)))?;
+    }
+    Ok(renderer.end()?)
+}
+
+// Render the symbol index of an archive, as an entry of its own before
+// the members
+pub fn archive_index(
+    renderer: &mut dyn Renderer,
+    path: &str,
+    archive: &ElfArchive,
+) -> Result<(), ParseError> {
+    renderer.begin(path)?;
+    renderer.archive_index(path, &archive.symbols, archive.names_size)?;
+    Ok(renderer.end()?)
+}
+
+// the header as label/value pairs, shared by the text and markdown output
+fn header_fields(header: &ELFHeader) -> Vec<(&'static str, String)> {
+    let bits = match header.ei_class {
+        Arch::B32 => "32-bit",
+        Arch::B64 => "64-bit",
+    };
+    let endian = match header.ei_data {
+        Endian::Big => "big-endian",
+        Endian::Little => "little-endian",
+    };
+    let ei_version = match header.ei_version {
+        1 => "1 (original and current version)".to_string(),
+        version => version.to_string(),
+    };
+    vec![
+        ("ELF Magic", "0x7F, E, L, F".to_string()),
+        ("ELF bit", bits.to_string()),
+        ("Endianness", endian.to_string()),
+        ("ELF version", ei_version),
+        ("Target operating system is", header.osabi().to_string()),
+        ("ABI Version", header.ei_abiversion.to_string()),
+        ("ELF object file type", header.object_type().to_string()),
+        ("ELF Instruction set machine", header.machine().to_string()),
+        ("ELF Version", header.e_version.to_string()),
+        ("Starting Address", format!("{:#x}", header.e_entry)),
+        (
+            "Program header table start",
+            format!("{} (Absolute)", header.e_phoff),
+        ),
+        (
+            "Section header table start",
+            format!("{} (Absolute)", header.e_shoff),
+        ),
+        ("Flags", format!("{:#x}", header.e_flags)),
+        ("ELF Header Size", format!("{} bytes", header.e_ehsize)),
+        (
+            "Program Header Size",
+            format!("{} bytes", header.e_phentsize),
+        ),
+        (
+            "Number of program headers",
+            extended(header.e_phnum, header.phnum),
+        ),
+        (
+            "Size of section headers",
+            format!("{} bytes", header.e_shentsize),
+        ),
+        (
+            "Number of section headers",
+            extended(header.e_shnum, header.shnum),
+        ),
+        (
+            "Index of section headers",
+            extended(header.e_shstrndx, header.shstrndx),
+        ),
+    ]
+}
+
+// a header count, followed by the real one when extended numbering
+// stores it in section 0, like readelf's "0 (70000)"
+fn extended(raw: u16, real: impl Into<u64>) -> String {
+    let real = real.into();
+    if raw as u64 == real {
+        raw.to_string()
+    } else {
+        format!("{raw} ({real})")
+    }
+}
+
+// readelf-style "symbol + addend" column of a relocation, and the symbol
+// value column (empty when there is no symbol)
+fn relocation_target(reloc: &crate::reloc::Relocation) -> (String, String) {
+    if reloc.r_sym == 0 {
+        // just the addend, as readelf shows it without a symbol
+        let addend = match reloc.r_addend {
+            Some(a) if a < 0 => format!("-{:x}", a.unsigned_abs()),
+            Some(a) => format!("{a:x}"),
+            None => String::new(),
+        };
+        return (String::new(), addend);
+    }
+    let addend = match reloc.r_addend {
+        Some(a) if a < 0 => format!(" - {:x}", a.unsigned_abs()),
+        Some(a) => format!(" + {a:x}"),
+        None => String::new(),
+    };
+    (
+        format!("{:016x}", reloc.symbol_value),
+        format!("{}{}", reloc.symbol_name, addend),
+    )
+}
+
+const TITLE: &str = "1;32";
+const KEY: &str = "1;37";
+
+// the bytes of an instruction in hex, as objdump shows them
+fn hex_bytes(bytes: &[u8]) -> String {
+    let bytes: Vec<String> = bytes.iter().map(|b| format!("{b:02x}")).collect();
+    bytes.join(" ")
+}
+
+// the instruction with its operands, the symbol its target is in, and what
+// a rip-relative operand refers to (unless the operand is already the
+// address, as in AArch64's adrp x0, 0x1000)
+pub(crate) fn instruction_text(instruction: &disasm::Instruction) -> String {
+    let mut text = format!("{:<6} {}", instruction.mnemonic, instruction.operands);
+    text.truncate(text.trim_end().len());
+    if let Some(symbol) = &instruction.target_symbol {
+        text += &format!(" <{symbol}>");
+    }
+    if let Some(reference) = instruction.reference {
+        let address = format!("{reference:#x}");
+        if !instruction.operands.ends_with(&address) {
+            text += &format!("        # {address}");
+        }
+    }
+    text
+}
+
+// readelf-like tables, with or without ANSI colors
+pub struct TextRenderer<W: Write> {
+    out: W,
+    color: bool,
+    // print "File: <path>" before each file
+    file_names: bool,
+}
+
+impl<W: Write> TextRenderer<W> {
+    pub fn new(out: W, color: bool) -> Self {
+        TextRenderer {
+            out,
+            color,
+            file_names: false,
+        }
+    }
+
+    // name each file before its views, for when there are several
+    pub fn with_file_names(self) -> Self {
+        TextRenderer {
+            file_names: true,
+            ..self
+        }
+    }
+
+    // colored output, for terminals
+    pub fn colored(out: W) -> Self {
+        TextRenderer::new(out, true)
+    }
+
+    pub fn plain(out: W) -> Self {
+        TextRenderer::new(out, false)
+    }
+
+    fn paint(&self, style: &str, text: impl Display) -> String {
+        color::paint(self.color, style, text)
+    }
+
+    fn symbol_table(
+        &mut self,
+        title: &str,
+        symbols: &[Symbol],
+        sections: &[SectionHeader],
+    ) -> io::Result<()> {
+        let line = format!(
+            "Symbol table '{}' contains {} entries:",
+            title,
+            symbols.len()
+        );
+        writeln!(self.out, "{}", self.paint(TITLE, line))?;
+        let line = format!(
+            "{:>6} {:<18} {:>6} {:<7} {:<6} {:<9} {:>4} {:<2} Name",
+            "Num", "Value", "Size", "Type", "Bind", "Vis", "Ndx", "Nm"
+        );
+        writeln!(self.out, "{}", self.paint(TITLE, line))?;
+        for (i, symbol) in symbols.iter().enumerate() {
+            writeln!(
+                self.out,
+                "{} {:#018x} {:>6} {:<7} {:<6} {:<9} {:>4} {:<2} {}",
+                self.paint(KEY, format!("{i:>6}")),
+                symbol.st_value,
+                symbol.st_size,
+                symbol.type_name(),
+                symbol.bind_name(),
+                symbol.visibility_name(),
+                symbol.section_index_name(),
+                symbol.nm_letter(sections),
+                symbol.name
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write> Renderer for TextRenderer<W> {
+    fn begin(&mut self, path: &str) -> io::Result<()> {
+        if self.file_names {
+            writeln!(self.out)?;
+            writeln!(self.out, "{}", self.paint(TITLE, format!("File: {path}")))?;
+        }
+        Ok(())
+    }
+
+    fn header(&mut self, header: &ELFHeader) -> io::Result<()> {
+        writeln!(self.out)?;
+        for (label, value) in header_fields(header) {
+            let label = self.paint(TITLE, format!("{label}:"));
+            writeln!(self.out, "{} {}", label, self.paint("1", value))?;
+        }
+        Ok(())
+    }
+
+    fn program_headers(&mut self, pheaders: Option<&[Pheader]>) -> io::Result<()> {
+        writeln!(self.out)?;
+        let Some(pheaders) = pheaders else {
+            return writeln!(self.out, "There are no program headers in this file.");
+        };
+        let line = format!(
+            "{:<14} {:<10} {:<18} {:<18} {:<10} {:<10} {:<5} {:<8}",
+            "Type", "Offset", "VirtAddr", "PhysAddr", "FileSiz", "MemSiz", "Flags", "Align"
+        );
+        writeln!(self.out, "{}", self.paint(TITLE, line))?;
+        for pheader in pheaders {
+            writeln!(
+                self.out,
+                "{} {:#010x} {:#018x} {:#018x} {:#010x} {:#010x} {:<5} {:#x}",
+                self.paint(KEY, format!("{:<14}", pheader.type_name())),
+                pheader.p_offset,
+                pheader.p_vaddr,
+                pheader.p_paddr,
+                pheader.p_filesz,
+                pheader.p_memsz,
+                pheader.flags_string(),
+                pheader.p_align
+            )?;
+        }
+        Ok(())
+    }
+
+    fn sections(&mut self, sections: Option<&[SectionHeader]>) -> io::Result<()> {
+        writeln!(self.out)?;
+        let Some(sections) = sections else {
+            return writeln!(self.out, "There are no sections in this file.");
+        };
+        let line = format!(
+            "{:>4} {:<20} {:<14} {:<18} {:<10} {:<10} {:<6} {:<5} {:>4} {:>4} {:>5}",
+            "Nr",
+            "Name",
+            "Type",
+            "Address",
+            "Offset",
+            "Size",
+            "EntSz",
+            "Flags",
+            "Link",
+            "Info",
+            "Align"
+        );
+        writeln!(self.out, "{}", self.paint(TITLE, line))?;
+        for (i, section) in sections.iter().enumerate() {
+            writeln!(
+                self.out,
+                "{} {:<20} {:<14} {:#018x} {:#010x} {:#010x} {:<6x} {:<5} {:>4} {:>4} {:>5}",
+                self.paint(KEY, format!("{i:>4}")),
+                section.name,
+                section.type_name(),
+                section.sh_addr,
+                section.sh_offset,
+                section.sh_size,
+                section.sh_entsize,
+                section.flags_string(),
+                section.sh_link,
+                section.sh_info,
+                section.sh_addralign
+            )?;
+        }
+        Ok(())
+    }
+
+    fn dynamic(&mut self, entries: Option<&[DynamicEntry]>) -> io::Result<()> {
+        writeln!(self.out)?;
+        let Some(entries) = entries else {
+            return writeln!(self.out, "There is no dynamic section in this file.");
+        };
+        let line = format!("Dynamic section contains {} entries:", entries.len());
+        writeln!(self.out, "{}", self.paint(TITLE, line))?;
+        let line = format!("{:<18} {:<16} Name/Value", "Tag", "Type");
+        writeln!(self.out, "{}", self.paint(TITLE, line))?;
+        for entry in entries {
+            writeln!(
+                self.out,
+                "{:#018x} {} {}",
+                entry.d_tag,
+                self.paint(KEY, format!("{:<16}", format!("({})", entry.tag_name()))),
+                entry.value_string()
+            )?;
+        }
+        Ok(())
+    }
+
+    fn relocations(
+        &mut self,
+        tables: Option<&[RelocationSection]>,
+        e_machine: u16,
+    ) -> io::Result<()> {
+        writeln!(self.out)?;
+        let Some(tables) = tables else {
+            return writeln!(self.out, "There are no relocations in this file.");
+        };
+        for table in tables {
+            let line = format!(
+                "Relocation section '{}' at offset {:#x} contains {} entries:",
+                table.name,
+                table.offset,
+                table.entries.len()
+            );
+            writeln!(self.out, "{}", self.paint(TITLE, line))?;
+            let line = format!(
+                "{:<18} {:<18} {:<24} {:<18} Sym. Name + Addend",
+                "Offset", "Info", "Type", "Sym. Value"
+            );
+            writeln!(self.out, "{}", self.paint(TITLE, line))?;
+            for reloc in &table.entries {
+                let (value, target) = relocation_target(reloc);
+                writeln!(
+                    self.out,
+                    "{:016x}   {:016x}   {} {:<18} {}",
+                    reloc.r_offset,
+                    reloc.r_info,
+                    self.paint(KEY, format!("{:<24}", reloc.type_name(e_machine))),
+                    value,
+                    target
+                )?;
+            }
+            writeln!(self.out)?;
+        }
+        Ok(())
+    }
+
+    fn symbols(
+        &mut self,
+        dynamic: Option<&[Symbol]>,
+        symbols: Option<&[Symbol]>,
+        sections: &[SectionHeader],
+    ) -> io::Result<()> {
+        writeln!(self.out)?;
+        if let Some(dynamic) = dynamic {
+            self.symbol_table(".dynsym", dynamic, sections)?;
+            writeln!(self.out)?;
+        }
+        if let Some(symbols) = symbols {
+            self.symbol_table(".symtab", symbols, sections)?;
+        }
+        Ok(())
+    }
+
+    fn notes(&mut self, tables: Option<&[NoteSection]>) -> io::Result<()> {
+        writeln!(self.out)?;
+        let Some(tables) = tables else {
+            return writeln!(self.out, "There are no notes in this file.");
+        };
+        for table in tables {
+            let line = format!(
+                "Displaying notes found in: {} (offset {:#x})",
+                table.name, table.offset
+            );
+            writeln!(self.out, "{}", self.paint(TITLE, line))?;
+            let line = format!("  {:<20} {:<10} Description", "Owner", "Data size");
+            writeln!(self.out, "{}", self.paint(TITLE, line))?;
+            for note in &table.notes {
+                writeln!(
+                    self.out,
+                    "  {} {:#010x} {}",
+                    self.paint(KEY, format!("{:<20}", note.owner)),
+                    note.desc.len(),
+                    note.type_name()
+                )?;
+                for line in note.description() {
+                    writeln!(self.out, "    {line}")?;
+                }
+            }
+            writeln!(self.out)?;
+        }
+        Ok(())
+    }
+
+    fn disassembly(&mut self, listings: Option<&[Listing]>) -> io::Result<()> {
+        let Some(listings) = listings else {
+            writeln!(self.out)?;
+            return writeln!(self.out, "There is no code in this file.");
+        };
+        // the layout of objdump -d, seven bytes to a line
+        for listing in listings {
+            writeln!(self.out)?;
+            let line = format!("Disassembly of section {}:", listing.name);
+            writeln!(self.out, "{}", self.paint(TITLE, line))?;
+            // objdump names the start of the section after it when no
+            // label does, and leaves a blank line before the other labels
+            if listing.label(listing.address).is_none() {
+                writeln!(self.out)?;
+                let line = format!("{:016x} <{}>:", listing.address, listing.name);
+                writeln!(self.out, "{}", self.paint(KEY, line))?;
+            }
+            for instruction in &listing.instructions {
+                if let Some(label) = listing.label(instruction.address) {
+                    writeln!(self.out)?;
+                    let line = format!("{:016x} <{}>:", label.address, label.name);
+                    writeln!(self.out, "{}", self.paint(KEY, line))?;
+                }
+                let mut chunks = instruction.bytes.chunks(7);
+                let first = chunks.next().unwrap_or_default();
+                writeln!(
+                    self.out,
+                    "{:>8x}:\t{:<21}\t{}",
+                    instruction.address,
+                    hex_bytes(first) + " ",
+                    instruction_text(instruction)
+                )?;
+                let mut address = instruction.address;
+                for chunk in chunks {
+                    address = address.wrapping_add(7);
+                    writeln!(self.out, "{address:>8x}:\t{} ", hex_bytes(chunk))?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn cfg(&mut self, functions: Option<&[Function]>) -> io::Result<()> {
+        let Some(functions) = functions else {
+            writeln!(self.out)?;
+            return writeln!(self.out, "There is no code in this file.");
+        };
+        // DOT for Graphviz, e.g. eva -G a.out | dot -Tsvg
+        write!(self.out, "{}", cfg::dot(functions, instruction_text))
+    }
+
+    fn archive_index(
+        &mut self,
+        path: &str,
+        symbols: &[ArchiveSymbol],
+        size: u64,
+    ) -> io::Result<()> {
+        if symbols.is_empty() {
+            return writeln!(self.out, "Archive '{path}' has no index.");
+        }
+        let line = format!(
+            "Index of archive {path}: ({} entries, {size:#x} bytes in the symbol table)",
+            symbols.len()
+        );
+        writeln!(self.out, "{}", self.paint(TITLE, line))?;
+        // symbols of the same member are listed together
+        let mut offset = None;
+        for symbol in symbols {
+            if offset != Some(symbol.offset) {
+                offset = Some(symbol.offset);
+                let member = match &symbol.member {
+                    Some(member) => format!("{path}({member})"),
+                    None => "<unknown member>".to_string(),
+                };
+                let line = format!("Contents of binary {member} at offset {:#x}", symbol.offset);
+                writeln!(self.out, "{}", self.paint(KEY, line))?;
+            }
+            writeln!(self.out, "\t{}", symbol.name)?;
+        }
+        Ok(())
+    }
+
+    fn end(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
+
+// GitHub-flavored markdown tables
+pub struct MarkdownRenderer<W: Write> {
+    out: W,
+}
+
+impl<W: Write> MarkdownRenderer<W> {
+    pub fn new(out: W) -> Self {
+        MarkdownRenderer { out }
+    }
+
+    fn heading(&mut self, title: &str) -> io::Result<()> {
+        writeln!(self.out, "\n## {title}\n")
+    }
+
+    fn row<T: Display>(&mut self, cells: &[T]) -> io::Result<()> {
+        let cells: Vec<String> = cells
+            .iter()
+            .map(|cell| cell.to_string().replace('|', "\\|"))
+            .collect();
+        writeln!(self.out, "| {} |", cells.join(" | "))
+    }
+
+    fn table_head(&mut self, columns: &[&str]) -> io::Result<()> {
+        self.row(columns)?;
+        self.row(&vec!["---"; columns.len()])
+    }
+
+    fn symbol_table(
+        &mut self,
+        title: &str,
+        symbols: &[Symbol],
+        sections: &[SectionHeader],
+    ) -> io::Result<()> {
+        self.heading(&format!("Symbol table `{title}`"))?;
+        self.table_head(&[
+            "Num", "Value", "Size", "Type", "Bind", "Vis", "Ndx", "Nm", "Name",
+        ])?;
+        for (i, symbol) in symbols.iter().enumerate() {
+            self.row(&[
+                i.to_string(),
+                format!("{:#x}", symbol.st_value),
+                symbol.st_size.to_string(),
+                symbol.type_name(),
+                symbol.bind_name(),
+                symbol.visibility_name().to_string(),
+                symbol.section_index_name(),
+                symbol.nm_letter(sections).to_string(),
+                symbol.name.to_string(),
+            ])?;
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write> Renderer for MarkdownRenderer<W> {
+    fn begin(&mut self, path: &str) -> io::Result<()> {
+        writeln!(self.out, "# `{path}`")
+    }
+
+    fn header(&mut self, header: &ELFHeader) -> io::Result<()> {
+        self.heading("ELF header")?;
+        self.table_head(&["Field", "Value"])?;
+        for (label, value) in header_fields(header) {
+            self.row(&[label.to_string(), value])?;
+        }
+        Ok(())
+    }
+
+    fn program_headers(&mut self, pheaders: Option<&[Pheader]>) -> io::Result<()> {
+        self.heading("Program headers")?;
+        let Some(pheaders) = pheaders else {
+            return writeln!(self.out, "There are no program headers in this file.");
+        };
+        self.table_head(&[
+            "Type", "Offset", "VirtAddr", "PhysAddr", "FileSiz", "MemSiz", "Flags", "Align",
+        ])?;
+        for pheader in pheaders {
+            self.row(&[
+                pheader.type_name(),
+                format!("{:#x}", pheader.p_offset),
+                format!("{:#x}", pheader.p_vaddr),
+                format!("{:#x}", pheader.p_paddr),
+                format!("{:#x}", pheader.p_filesz),
+                format!("{:#x}", pheader.p_memsz),
+                pheader.flags_string(),
+                format!("{:#x}", pheader.p_align),
+            ])?;
+        }
+        Ok(())
+    }
+
+    fn sections(&mut self, sections: Option<&[SectionHeader]>) -> io::Result<()> {
+        self.heading("Section headers")?;
+        let Some(sections) = sections else {
+            return writeln!(self.out, "There are no sections in this file.");
+        };
+        self.table_head(&[
+            "Nr", "Name", "Type", "Address", "Offset", "Size", "EntSz", "Flags", "Link", "Info",
+            "Align",
+        ])?;
+        for (i, section) in sections.iter().enumerate() {
+            self.row(&[
+                i.to_string(),
+                section.name.to_string(),
+                section.type_name(),
+                format!("{:#x}", section.sh_addr),
+                format!("{:#x}", section.sh_offset),
+                format!("{:#x}", section.sh_size),
+                format!("{:#x}", section.sh_entsize),
+                section.flags_string(),
+                section.sh_link.to_string(),
+                section.sh_info.to_string(),
+                section.sh_addralign.to_string(),
+            ])?;
+        }
+        Ok(())
+    }
+
+    fn dynamic(&mut self, entries: Option<&[DynamicEntry]>) -> io::Result<()> {
+        self.heading("Dynamic section")?;
+        let Some(entries) = entries else {
+            return writeln!(self.out, "There is no dynamic section in this file.");
+        };
+        self.table_head(&["Tag", "Type", "Name/Value"])?;
+        for entry in entries {
+            self.row(&[
+                format!("{:#x}", entry.d_tag),
+                entry.tag_name(),
+                entry.value_string(),
+            ])?;
+        }
+        Ok(())
+    }
+
+    fn relocations(
+        &mut self,
+        tables: Option<&[RelocationSection]>,
+        e_machine: u16,
+    ) -> io::Result<()> {
+        self.heading("Relocations")?;
+        let Some(tables) = tables else {
+            return writeln!(self.out, "There are no relocations in this file.");
+        };
+        for table in tables {
+            writeln!(
+                self.out,
+                "\n### `{}` at offset {:#x}\n",
+                table.name, table.offset
+            )?;
+            self.table_head(&["Offset", "Info", "Type", "Sym. Value", "Sym. Name + Addend"])?;
+            for reloc in &table.entries {
+                let (value, target) = relocation_target(reloc);
+                self.row(&[
+                    format!("{:#x}", reloc.r_offset),
+                    format!("{:#x}", reloc.r_info),
+                    reloc.type_name(e_machine),
+                    value,
+                    target,
+                ])?;
+            }
+        }
+        Ok(())
+    }
+
+    fn symbols(
+        &mut self,
+        dynamic: Option<&[Symbol]>,
+        symbols: Option<&[Symbol]>,
+        sections: &[SectionHeader],
+    ) -> io::Result<()> {
+        if let Some(dynamic) = dynamic {
+            self.symbol_table(".dynsym", dynamic, sections)?;
+        }
+        if let Some(symbols) = symbols {
+            self.symbol_table(".symtab", symbols, sections)?;
+        }
+        Ok(())
+    }
+
+    fn notes(&mut self, tables: Option<&[NoteSection]>) -> io::Result<()> {
+        self.heading("Notes")?;
+        let Some(tables) = tables else {
+            return writeln!(self.out, "There are no notes in this file.");
+        };
+        for table in tables {
+            writeln!(
+                self.out,
+                "\n### `{}` at offset {:#x}\n",
+                table.name, table.offset
+            )?;
+            self.table_head(&["Owner", "Data size", "Type", "Description"])?;
+            for note in &table.notes {
+                self.row(&[
+                    note.owner.clone(),
+                    format!("{:#x}", note.desc.len()),
+                    note.type_name(),
+                    note.description().join("<br>"),
+                ])?;
+            }
+        }
+        Ok(())
+    }
+
+    fn disassembly(&mut self, listings: Option<&[Listing]>) -> io::Result<()> {
+        self.heading("Disassembly")?;
+        let Some(listings) = listings else {
+            return writeln!(self.out, "There is no code in this file.");
+        };
+        for listing in listings {
+            writeln!(
+                self.out,
+                "\n### `{}` at {:#x}",
+                listing.name, listing.address
+            )?;
+            if listing.label(listing.address).is_none() {
+                writeln!(self.out)?;
+                self.table_head(&["Address", "Bytes", "Instruction"])?;
+            }
+            for instruction in &listing.instructions {
+                // a table for each label
+                if let Some(label) = listing.label(instruction.address) {
+                    writeln!(
+                        self.out,
+                        "\n#### `{}` at {:#x}\n",
+                        label.name, label.address
+                    )?;
+                    self.table_head(&["Address", "Bytes", "Instruction"])?;
+                }
+                self.row(&[
+                    format!("{:#x}", instruction.address),
+                    hex_bytes(&instruction.bytes),
+                    instruction_text(instruction),
+                ])?;
+            }
+        }
+        Ok(())
+    }
+
+    fn cfg(&mut self, functions: Option<&[Function]>) -> io::Result<()> {
+        self.heading("Control-flow graphs")?;
+        let Some(functions) = functions else {
+            return writeln!(self.out, "There is no code in this file.");
+        };
+        let dot = cfg::dot(functions, instruction_text);
+        writeln!(self.out, "```dot\n{dot}```")
+    }
+
+    fn archive_index(
+        &mut self,
+        _path: &str,
+        symbols: &[ArchiveSymbol],
+        size: u64,
+    ) -> io::Result<()> {
+        self.heading("Archive index")?;
+        if symbols.is_empty() {
+            return writeln!(self.out, "The archive has no index.");
+        }
+        writeln!(
+            self.out,
+            "{} entries, {size} bytes of names.\n",
+            symbols.len()
+        )?;
+        self.table_head(&["Symbol", "Member", "Offset"])?;
+        for symbol in symbols {
+            self.row(&[
+                symbol.name.clone(),
+                symbol.member.clone().unwrap_or_default(),
+                format!("{:#x}", symbol.offset),
+            ])?;
+        }
+        Ok(())
+    }
+
+    fn end(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
+
+// one JSON object per file, with the schema documented in README.md;
+// requested views the file doesn't have are null
+pub struct JsonRenderer<W: Write> {
+    out: W,
+    object: Json,
+    // number of files written so far, when writing an array of them
+    array: Option<usize>,
+}
+
+impl<W: Write> JsonRenderer<W> {
+    pub fn new(out: W) -> Self {
+        JsonRenderer {
+            out,
+            object: Json::object(),
+            array: None,
+        }
+    }
+
+    // a JSON array with one object per file, for when there are several
+    pub fn array(out: W) -> Self {
+        JsonRenderer {
+            array: Some(0),
+            ..JsonRenderer::new(out)
+        }
+    }
+
+    fn add(&mut self, key: &str, value: Json) {
+        let object = std::mem::replace(&mut self.object, Json::Null);
+        self.object = object.field(key, value);
+    }
+}
+
+impl<W: Write> Renderer for JsonRenderer<W> {
+    fn begin(&mut self, path: &str) -> io::Result<()> {
+        self.object = Json::object().field("file", path);
+        Ok(())
+    }
+
+    fn header(&mut self, header: &ELFHeader) -> io::Result<()> {
+        self.add("header", header.to_json());
+        Ok(())
+    }
+
+    fn program_headers(&mut self, pheaders: Option<&[Pheader]>) -> io::Result<()> {
+        self.add("program_headers", pheaders.to_json());
+        Ok(())
+    }
+
+    fn sections(&mut self, sections: Option<&[SectionHeader]>) -> io::Result<()> {
+        self.add("sections", sections.to_json());
+        Ok(())
+    }
+
+    fn dynamic(&mut self, entries: Option<&[DynamicEntry]>) -> io::Result<()> {
+        self.add("dynamic", entries.to_json());
+        Ok(())
+    }
+
+    fn relocations(
+        &mut self,
+        tables: Option<&[RelocationSection]>,
+        e_machine: u16,
+    ) -> io::Result<()> {
+        let relocations = match tables {
+            Some(tables) => json::relocations(tables, e_machine),
+            None => Json::Null,
+        };
+        self.add("relocations", relocations);
+        Ok(())
+    }
+
+    fn symbols(
+        &mut self,
+        dynamic: Option<&[Symbol]>,
+        symbols: Option<&[Symbol]>,
+        sections: &[SectionHeader],
+    ) -> io::Result<()> {
+        self.add("dynamic_symbols", json::symbols(dynamic, sections));
+        self.add("symbols", json::symbols(symbols, sections));
+        Ok(())
+    }
+
+    fn notes(&mut self, tables: Option<&[NoteSection]>) -> io::Result<()> {
+        self.add("notes", tables.to_json());
+        Ok(())
+    }
+
+    fn disassembly(&mut self, listings: Option<&[Listing]>) -> io::Result<()> {
+        self.add("disassembly", listings.to_json());
+        Ok(())
+    }
+
+    fn cfg(&mut self, functions: Option<&[Function]>) -> io::Result<()> {
+        self.add("cfg", functions.to_json());
+        Ok(())
+    }
+
+    fn archive_index(
+        &mut self,
+        _path: &str,
+        symbols: &[ArchiveSymbol],
+        _size: u64,
+    ) -> io::Result<()> {
+        self.add("archive_index", symbols.to_json());
+        Ok(())
+    }
+
+    fn end(&mut self) -> io::Result<()> {
+        match &mut self.array {
+            None => writeln!(self.out, "{}", self.object)?,
+            Some(count) => {
+                let separator = if *count == 0 { "[\n" } else { ",\n" };
+                *count += 1;
+                // strings are escaped, so every newline is between values
+                let object = self.object.to_string().replace('\n', "\n  ");
+                write!(self.out, "{separator}  {object}")?;
+            }
+        }
+        self.out.flush()
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        match self.array {
+            Some(0) => writeln!(self.out, "[]")?,
+            Some(_) => writeln!(self.out, "\n]")?,
+            None => {}
+        }
+        self.out.flush()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::disasm::Syntax;
+    use crate::parse::ELFParser;
+    use crate::reloc::Relocation;
+    use crate::render::{self, JsonRenderer, MarkdownRenderer, Renderer, TextRenderer, Views};
+
+    fn render_own(renderer: &mut dyn render::Renderer) {
+        let parser = ELFParser::new(std::env::current_exe().unwrap()).unwrap();
+        render::render(renderer, "eva", &parser, &Views::all()).unwrap();
+    }
+
+    #[test]
+    fn plain_has_no_escapes() {
+        let mut out = Vec::new();
+        render_own(&mut TextRenderer::plain(&mut out));
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.contains("ELF Instruction set machine:"));
+        assert!(text.contains("Symbol table '.symtab'"));
+        assert!(!text.contains('\x1b'));
+
+        let mut out = Vec::new();
+        render_own(&mut TextRenderer::colored(&mut out));
+        assert!(String::from_utf8(out).unwrap().contains("\x1b[1;32m"));
+    }
+
+    #[test]
+    fn markdown_tables() {
+        let mut out = Vec::new();
+        render_own(&mut MarkdownRenderer::new(&mut out));
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.starts_with("# `eva`"));
+        assert!(text.contains("\n## Section headers\n\n| Nr | Name |"));
+        assert!(text.contains("| --- | --- |"));
+    }
+
+    #[test]
+    fn json_keys() {
+        let mut out = Vec::new();
+        render_own(&mut JsonRenderer::new(&mut out));
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.starts_with("{\n  \"file\": \"eva\",\n  \"header\": {"));
+        assert!(text.contains("\n  \"notes\": "));
+        assert!(text.contains("\"nm_letter\": \"T\""));
+        assert!(text.ends_with("}\n"));
+    }
+
+    #[test]
+    fn disassembly_and_cfg() {
+        let contents = include_bytes!("../tests/fixtures/hello-arm64.o");
+        let parser = ELFParser::from_bytes(contents).unwrap();
+        let views = Views {
+            disassemble: Some(Syntax::Att),
+            cfg: Some(Syntax::Att),
+            ..Views::default()
+        };
+        let mut out = Vec::new();
+        render::render(&mut JsonRenderer::new(&mut out), "hello", &parser, &views).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.contains("\n  \"disassembly\": ["));
+        assert!(text.contains("\n  \"cfg\": ["));
+        assert!(text.contains("\"edge\": \"not_taken\""));
+    }
+
+    #[test]
+    fn json_array() {
+        let mut out = Vec::new();
+        let mut renderer = JsonRenderer::array(&mut out);
+        render_own(&mut renderer);
+        render_own(&mut renderer);
+        renderer.finish().unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.starts_with("[\n  {\n    \"file\": \"eva\",\n"));
+        assert!(text.contains("\n  },\n  {\n"));
+        assert!(text.ends_with("\n  }\n]\n"));
+
+        let mut out = Vec::new();
+        JsonRenderer::array(&mut out).finish().unwrap();
+        assert_eq!(out, b"[]\n");
+    }
+
+    #[test]
+    fn addend_without_symbol() {
+        let mut reloc = Relocation {
+            r_offset: 0x4000,
+            r_info: 8,
+            r_type: 8,
+            r_sym: 0,
+            r_addend: Some(-0x10),
+            symbol_name: String::new(),
+            symbol_value: 0,
+        };
+        assert_eq!(
+            render::relocation_target(&reloc),
+            (String::new(), "-10".to_string())
+        );
+        reloc.r_addend = Some(0x10);
+        assert_eq!(
+            render::relocation_target(&reloc),
+            (String::new(), "10".to_string())
+        );
+    }
+}
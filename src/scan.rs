@@ -0,0 +1,194 @@
+// Turning command line paths into parsed files: walking directories, and
+// parsing many files on several threads while keeping their order
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Condvar, Mutex};
+use std::{fs, io, thread};
+
+use crate::archive::{self, ElfArchive};
+use crate::error::ParseError;
+use crate::input::Input;
+use crate::parse::ELFParser;
+
+// A file found while walking a directory, or the path that couldn't be read
+pub type Found = Result<PathBuf, (PathBuf, io::Error)>;
+
+// Every regular file under `dir`, in sorted order. Symlinks to files are
+// followed, symlinks to directories aren't (so there are no loops), and
+// special files like FIFOs are left alone since opening them can block.
+pub fn walk(dir: &Path) -> Vec<Found> {
+    let mut found = Vec::new();
+    walk_into(dir, &mut found);
+    found
+}
+
+fn walk_into(dir: &Path, found: &mut Vec<Found>) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) => return found.push(Err((dir.to_path_buf(), e))),
+    };
+    let mut paths: Vec<PathBuf> = entries.filter_map(|e| Some(e.ok()?.path())).collect();
+    paths.sort();
+    for path in paths {
+        let metadata = match fs::symlink_metadata(&path) {
+            Ok(metadata) => metadata,
+            Err(e) => {
+                found.push(Err((path, e)));
+                continue;
+            }
+        };
+        if metadata.is_dir() {
+            walk_into(&path, found);
+        } else if metadata.is_file() || fs::metadata(&path).is_ok_and(|m| m.is_file()) {
+            found.push(Ok(path));
+        }
+    }
+}
+
+// an ELF file, or an archive of them
+pub enum Parsed {
+    Elf(Box<ELFParser<'static>>),
+    Archive(ElfArchive<'static>),
+}
+
+// parse one file; `-` is stdin
+pub fn open(path: &Path) -> Result<Parsed, ParseError> {
+    let input = if path == Path::new("-") {
+        Input::read(io::stdin().lock())?
+    } else {
+        Input::open(path)?
+    };
+    if archive::is_archive(&input) {
+        Ok(Parsed::Archive(ElfArchive::parse(input)?))
+    } else {
+        Ok(Parsed::Elf(Box::new(ELFParser::from_input(input)?)))
+    }
+}
+
+// Parse `paths` on up to `jobs` threads, running `prepare` on each file
+// (and archive member) in its worker (e.g. to fill the caches that will be displayed), and pass
+// the results to `each` on the calling thread, in the order of `paths`.
+// Stops early when `each` returns false.
+pub fn parse_files<P, E>(paths: &[PathBuf], jobs: usize, prepare: P, mut each: E)
+where
+    P: Fn(&ELFParser) -> Result<(), ParseError> + Sync,
+    E: FnMut(&Path, Result<Parsed, ParseError>) -> bool,
+{
+    let parse = |path: &Path| {
+        let parsed = open(path)?;
+        match &parsed {
+            Parsed::Elf(parser) => prepare(parser)?,
+            // errors aren't cached, so a broken member fails again when
+            // it's rendered, without taking the other members down
+            Parsed::Archive(archive) => {
+                for (_, member) in archive.members.iter() {
+                    if let Ok(parser) = member {
+                        _ = prepare(parser);
+                    }
+                }
+            }
+        }
+        Ok(parsed)
+    };
+    let jobs = jobs.clamp(1, paths.len().max(1));
+    if jobs == 1 {
+        for path in paths {
+            if !each(path, parse(path)) {
+                return;
+            }
+        }
+        return;
+    }
+
+    // workers stay at most `window` files ahead of the one being handed
+    // to `each`, so a slow consumer doesn't keep the whole tree in memory
+    let window = jobs * 2;
+    let next = AtomicUsize::new(0);
+    let handed = (Mutex::new(0), Condvar::new());
+    let (sender, receiver) = mpsc::channel();
+    thread::scope(|scope| {
+        for _ in 0..jobs {
+            let sender = sender.clone();
+            let (next, handed, parse) = (&next, &handed, &parse);
+            scope.spawn(move || loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let Some(path) = paths.get(index) else {
+                    break;
+                };
+                let (lock, turn) = handed;
+                let mut done = lock.lock().unwrap();
+                while index >= *done + window {
+                    done = turn.wait(done).unwrap();
+                }
+                drop(done);
+                if sender.send((index, parse(path))).is_err() {
+                    break;
+                }
+            });
+        }
+        drop(sender);
+
+        // results arrive in any order; hold them back until it's their turn
+        let mut pending = HashMap::new();
+        let mut wanted = 0;
+        for (index, result) in receiver.iter() {
+            pending.insert(index, result);
+            while let Some(result) = pending.remove(&wanted) {
+                let stop = !each(&paths[wanted], result);
+                wanted += 1;
+                let (lock, turn) = &handed;
+                if stop {
+                    // let the workers run out of paths, and wake them up
+                    next.store(paths.len(), Ordering::Relaxed);
+                    *lock.lock().unwrap() = paths.len();
+                    turn.notify_all();
+                    return;
+                }
+                *lock.lock().unwrap() = wanted;
+                turn.notify_all();
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod test {
+    use crate::error::ParseError;
+    use crate::scan;
+    use std::path::PathBuf;
+
+    #[test]
+    fn walk_sorted() {
+        let found = scan::walk(std::path::Path::new("src"));
+        let files: Vec<PathBuf> = found.into_iter().map(Result::unwrap).collect();
+        assert!(files.contains(&PathBuf::from("src/scan.rs")));
+        assert!(files.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn parallel_keeps_order() {
+        let exe = std::env::current_exe().unwrap();
+        let paths: Vec<PathBuf> = (0..16)
+            .map(|i| match i % 3 {
+                0 => PathBuf::from("Cargo.toml"),
+                _ => exe.clone(),
+            })
+            .collect();
+        let mut seen = Vec::new();
+        scan::parse_files(
+            &paths,
+            4,
+            |_| Ok(()),
+            |path, result| {
+                assert_eq!(path == exe, matches!(result, Ok(scan::Parsed::Elf(_))));
+                if path != exe {
+                    assert!(matches!(result, Err(ParseError::BadMagic)));
+                }
+                seen.push(path.to_path_buf());
+                true
+            },
+        );
+        assert_eq!(seen, paths);
+    }
+}
@@ -0,0 +1,277 @@
+use std::borrow::Cow;
+
+use crate::error::ParseError;
+use crate::parse::{Arch, ELFHeader};
+use crate::utils::{checked_slice, Reader};
+
+// section types (sh_type)
+pub const SHT_NULL: u32 = 0;
+pub const SHT_PROGBITS: u32 = 1;
+pub const SHT_SYMTAB: u32 = 2;
+pub const SHT_STRTAB: u32 = 3;
+pub const SHT_RELA: u32 = 4;
+pub const SHT_HASH: u32 = 5;
+pub const SHT_DYNAMIC: u32 = 6;
+pub const SHT_NOTE: u32 = 7;
+pub const SHT_NOBITS: u32 = 8;
+pub const SHT_REL: u32 = 9;
+pub const SHT_SHLIB: u32 = 10;
+pub const SHT_DYNSYM: u32 = 11;
+pub const SHT_INIT_ARRAY: u32 = 14;
+pub const SHT_FINI_ARRAY: u32 = 15;
+pub const SHT_PREINIT_ARRAY: u32 = 16;
+pub const SHT_GROUP: u32 = 17;
+pub const SHT_SYMTAB_SHNDX: u32 = 18;
+pub const SHT_RELR: u32 = 19;
+pub const SHT_GNU_ATTRIBUTES: u32 = 0x6fff_fff5;
+pub const SHT_GNU_HASH: u32 = 0x6fff_fff6;
+pub const SHT_GNU_VERDEF: u32 = 0x6fff_fffd;
+pub const SHT_GNU_VERNEED: u32 = 0x6fff_fffe;
+pub const SHT_GNU_VERSYM: u32 = 0x6fff_ffff;
+
+// section flags (sh_flags)
+pub const SHF_WRITE: u64 = 0x1;
+pub const SHF_ALLOC: u64 = 0x2;
+pub const SHF_EXECINSTR: u64 = 0x4;
+pub const SHF_MERGE: u64 = 0x10;
+pub const SHF_STRINGS: u64 = 0x20;
+pub const SHF_INFO_LINK: u64 = 0x40;
+pub const SHF_LINK_ORDER: u64 = 0x80;
+pub const SHF_OS_NONCONFORMING: u64 = 0x100;
+pub const SHF_GROUP: u64 = 0x200;
+pub const SHF_TLS: u64 = 0x400;
+pub const SHF_COMPRESSED: u64 = 0x800;
+
+#[derive(Default, Debug, Clone)]
+// aligned to u64 to accomodate both ELF32 and ELF64 section
+// headers
+pub struct SectionHeader<'a> {
+    // borrowed from the section header string table (copied only when it
+    // isn't valid UTF-8)
+    pub name: Cow<'a, str>,
+    // offset of the name in the section header string table
+    pub sh_name: u32,
+    pub sh_type: u32,
+    pub sh_flags: u64,
+    // virtual address of the section in memory, if loaded
+    pub sh_addr: u64,
+    // offset of the section in the file
+    pub sh_offset: u64,
+    pub sh_size: u64,
+    // index of an associated section; meaning depends on sh_type
+    pub sh_link: u32,
+    pub sh_info: u32,
+    pub sh_addralign: u64,
+    // size of each entry for sections holding fixed-size entries
+    pub sh_entsize: u64,
+}
+
+impl SectionHeader<'_> {
+    // parse a single section header entry at the reader's cursor; the name
+    // is resolved later
+    pub fn parse(reader: &mut Reader, class: &Arch) -> Result<SectionHeader<'static>, ParseError> {
+        Ok(SectionHeader {
+            name: Cow::Borrowed(""),
+            sh_name: reader.u32()?,
+            sh_type: reader.u32()?,
+            // class-dependent fields are 4 bytes in ELF32, 8 in ELF64
+            sh_flags: reader.word(class)?,
+            sh_addr: reader.word(class)?,
+            sh_offset: reader.word(class)?,
+            sh_size: reader.word(class)?,
+            sh_link: reader.u32()?,
+            sh_info: reader.u32()?,
+            sh_addralign: reader.word(class)?,
+            sh_entsize: reader.word(class)?,
+        })
+    }
+
+    // contents of the section in the file. SHT_NOBITS sections
+    // (.bss and friends) occupy no space in the file
+    pub fn data<'a>(&self, contents: &'a [u8]) -> Result<&'a [u8], ParseError> {
+        if self.sh_type == SHT_NOBITS {
+            return Ok(&[]);
+        }
+        checked_slice(contents, self.sh_offset, self.sh_size)
+    }
+
+    pub fn type_name(&self) -> String {
+        match self.sh_type {
+            SHT_NULL => "NULL".to_string(),
+            SHT_PROGBITS => "PROGBITS".to_string(),
+            SHT_SYMTAB => "SYMTAB".to_string(),
+            SHT_STRTAB => "STRTAB".to_string(),
+            SHT_RELA => "RELA".to_string(),
+            SHT_HASH => "HASH".to_string(),
+            SHT_DYNAMIC => "DYNAMIC".to_string(),
+            SHT_NOTE => "NOTE".to_string(),
+            SHT_NOBITS => "NOBITS".to_string(),
+            SHT_REL => "REL".to_string(),
+            SHT_SHLIB => "SHLIB".to_string(),
+            SHT_DYNSYM => "DYNSYM".to_string(),
+            SHT_INIT_ARRAY => "INIT_ARRAY".to_string(),
+            SHT_FINI_ARRAY => "FINI_ARRAY".to_string(),
+            SHT_PREINIT_ARRAY => "PREINIT_ARRAY".to_string(),
+            SHT_GROUP => "GROUP".to_string(),
+            SHT_SYMTAB_SHNDX => "SYMTAB_SHNDX".to_string(),
+            SHT_RELR => "RELR".to_string(),
+            SHT_GNU_ATTRIBUTES => "GNU_ATTRIBUTES".to_string(),
+            SHT_GNU_HASH => "GNU_HASH".to_string(),
+            SHT_GNU_VERDEF => "VERDEF".to_string(),
+            SHT_GNU_VERNEED => "VERNEED".to_string(),
+            SHT_GNU_VERSYM => "VERSYM".to_string(),
+            0x6000_0000..=0x6fff_ffff => format!("LOOS+{:#x}", self.sh_type - 0x6000_0000),
+            0x7000_0000..=0x7fff_ffff => format!("LOPROC+{:#x}", self.sh_type - 0x7000_0000),
+            0x8000_0000..=0xffff_ffff => format!("LOUSER+{:#x}", self.sh_type - 0x8000_0000),
+            _ => format!("{:#x}", self.sh_type),
+        }
+    }
+
+    // readelf-style flag letters
+    pub fn flags_string(&self) -> String {
+        let mut flags = String::new();
+        for (bit, letter) in [
+            (SHF_WRITE, 'W'),
+            (SHF_ALLOC, 'A'),
+            (SHF_EXECINSTR, 'X'),
+            (SHF_MERGE, 'M'),
+            (SHF_STRINGS, 'S'),
+            (SHF_INFO_LINK, 'I'),
+            (SHF_LINK_ORDER, 'L'),
+            (SHF_OS_NONCONFORMING, 'O'),
+            (SHF_GROUP, 'G'),
+            (SHF_TLS, 'T'),
+            (SHF_COMPRESSED, 'C'),
+        ] {
+            if self.sh_flags & bit != 0 {
+                flags.push(letter);
+            }
+        }
+        flags
+    }
+}
+
+// null-terminated string starting at `offset` in a string table
+pub fn read_str(table: &[u8], offset: usize) -> String {
+    str_at(table, offset).into_owned()
+}
+
+// like `read_str`, but borrowing from the table unless the string
+// isn't valid UTF-8
+pub fn str_at(table: &[u8], offset: usize) -> Cow<'_, str> {
+    if offset >= table.len() {
+        return Cow::Borrowed("");
+    }
+    let bytes = &table[offset..];
+    let len = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..len])
+}
+
+// parse the section header table and resolve the section names
+// through the e_shstrndx string table
+pub fn parse_section_headers<'a>(
+    contents: &'a [u8],
+    header: &ELFHeader,
+) -> Result<Vec<SectionHeader<'a>>, ParseError> {
+    // entries smaller than Elf32_Shdr or Elf64_Shdr would overlap, and let
+    // a small file claim any number of them
+    let expected = match header.ei_class {
+        Arch::B32 => 40,
+        Arch::B64 => 64,
+    };
+    if (header.e_shentsize as u64) < expected {
+        return Err(ParseError::SizeMismatch {
+            field: "e_shentsize",
+            found: header.e_shentsize as u64,
+            expected,
+        });
+    }
+    // with extended numbering the count comes from the file, so don't
+    // trust it further than the file goes, nor allocate for it up front
+    let size = header.shnum.saturating_mul(header.e_shentsize as u64);
+    checked_slice(contents, header.e_shoff, size)?;
+    let mut reader = Reader::new(contents, header.ei_data);
+    let mut sections = Vec::new();
+    for i in 0..header.shnum {
+        reader.seek(header.e_shoff.saturating_add(i * header.e_shentsize as u64));
+        sections.push(SectionHeader::parse(&mut reader, &header.ei_class)?);
+    }
+
+    if let Some(strtab) = sections.get(header.shstrndx as usize) {
+        let table = strtab.data(contents)?;
+        for section in sections.iter_mut() {
+            section.name = str_at(table, section.sh_name as usize);
+        }
+    }
+    Ok(sections)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::error::ParseError;
+    use crate::parse::{Arch, ELFParser};
+    use crate::section::{read_str, SectionHeader, SHF_ALLOC, SHF_EXECINSTR, SHT_PROGBITS};
+    use crate::utils::{Endian, Reader};
+
+    #[test]
+    fn section32big() {
+        let words: [u32; 10] = [0x1b, SHT_PROGBITS, 0x6, 0x1000, 0x400, 0x80, 0, 0, 4, 0];
+        let bytes: Vec<u8> = words.iter().flat_map(|w| w.to_be_bytes()).collect();
+        let section =
+            SectionHeader::parse(&mut Reader::new(&bytes, Endian::Big), &Arch::B32).unwrap();
+        assert_eq!(section.sh_name, 0x1b);
+        assert_eq!(section.sh_flags, SHF_ALLOC | SHF_EXECINSTR);
+        assert_eq!(section.sh_addr, 0x1000);
+        assert_eq!(section.sh_offset, 0x400);
+        assert_eq!(section.sh_size, 0x80);
+        assert_eq!(section.sh_addralign, 4);
+        assert_eq!(section.flags_string(), "AX");
+    }
+
+    #[test]
+    fn section64little() {
+        let mut bytes = Vec::new();
+        bytes.extend(0x1b_u32.to_le_bytes());
+        bytes.extend(SHT_PROGBITS.to_le_bytes());
+        for word in [0x6_u64, 0x40_1000, 0x1000, 0x200] {
+            bytes.extend(word.to_le_bytes());
+        }
+        bytes.extend(0_u32.to_le_bytes());
+        bytes.extend(0_u32.to_le_bytes());
+        bytes.extend(16_u64.to_le_bytes());
+        bytes.extend(0_u64.to_le_bytes());
+        let section =
+            SectionHeader::parse(&mut Reader::new(&bytes, Endian::Little), &Arch::B64).unwrap();
+        assert_eq!(section.sh_addr, 0x40_1000);
+        assert_eq!(section.sh_offset, 0x1000);
+        assert_eq!(section.sh_size, 0x200);
+        assert_eq!(section.sh_addralign, 16);
+    }
+
+    #[test]
+    fn string_table() {
+        let table = b"\0.text\0.data\0";
+        assert_eq!(read_str(table, 1), ".text");
+        assert_eq!(read_str(table, 7), ".data");
+        assert_eq!(read_str(table, 3), "ext");
+        assert_eq!(read_str(table, 100), "");
+    }
+
+    #[test]
+    fn entry_size() {
+        // e_shentsize of ELF32 at 0x2e: 0, so 65535 entries (from e_shnum)
+        // would take no room in the file
+        let mut contents = include_bytes!("../tests/fixtures/hello-i386").to_vec();
+        contents[0x2e..0x30].fill(0);
+        contents[0x30..0x32].fill(0xff);
+        let parser = ELFParser::from_bytes(&contents).unwrap();
+        assert!(matches!(
+            parser.sections(),
+            Err(ParseError::SizeMismatch {
+                field: "e_shentsize",
+                found: 0,
+                expected: 40
+            })
+        ));
+    }
+}
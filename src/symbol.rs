@@ -0,0 +1,368 @@
+use std::borrow::Cow;
+
+use crate::error::ParseError;
+use crate::parse::{Arch, ELFHeader};
+use crate::section::{
+    str_at, SectionHeader, SHF_ALLOC, SHF_EXECINSTR, SHF_WRITE, SHT_NOBITS, SHT_SYMTAB_SHNDX,
+};
+use crate::utils::Reader;
+
+// symbol binding (upper nibble of st_info)
+pub const STB_LOCAL: u8 = 0;
+pub const STB_GLOBAL: u8 = 1;
+pub const STB_WEAK: u8 = 2;
+pub const STB_GNU_UNIQUE: u8 = 10;
+
+// symbol type (lower nibble of st_info)
+pub const STT_NOTYPE: u8 = 0;
+pub const STT_OBJECT: u8 = 1;
+pub const STT_FUNC: u8 = 2;
+pub const STT_SECTION: u8 = 3;
+pub const STT_FILE: u8 = 4;
+pub const STT_COMMON: u8 = 5;
+pub const STT_TLS: u8 = 6;
+pub const STT_GNU_IFUNC: u8 = 10;
+
+// symbol visibility (lower two bits of st_other)
+pub const STV_DEFAULT: u8 = 0;
+pub const STV_INTERNAL: u8 = 1;
+pub const STV_HIDDEN: u8 = 2;
+pub const STV_PROTECTED: u8 = 3;
+
+// special section indices
+pub const SHN_UNDEF: u16 = 0;
+pub const SHN_ABS: u16 = 0xfff1;
+pub const SHN_COMMON: u16 = 0xfff2;
+pub const SHN_XINDEX: u16 = 0xffff;
+
+#[derive(Default, Debug, Clone)]
+pub struct Symbol<'a> {
+    // borrowed from the string table linked to the symbol table (copied
+    // only when it isn't valid UTF-8)
+    pub name: Cow<'a, str>,
+    // offset of the name in the linked string table
+    pub st_name: u32,
+    pub st_value: u64,
+    pub st_size: u64,
+    pub st_bind: u8,
+    pub st_type: u8,
+    pub st_visibility: u8,
+    // index of the section the symbol is defined in, or SHN_XINDEX when
+    // it doesn't fit and is in the SHT_SYMTAB_SHNDX section instead
+    pub st_shndx: u16,
+    // st_shndx, or the index from SHT_SYMTAB_SHNDX for SHN_XINDEX
+    pub shndx: u32,
+}
+
+impl Symbol<'_> {
+    // parse a single symbol table entry at the reader's cursor; the name
+    // is resolved by SymbolIter
+    pub fn parse(reader: &mut Reader, class: &Arch) -> Result<Symbol<'static>, ParseError> {
+        let mut symbol = Symbol {
+            st_name: reader.u32()?,
+            ..Default::default()
+        };
+        let (info, other);
+        if *class == Arch::B32 {
+            // Elf32_Sym: st_value and st_size come before st_info
+            symbol.st_value = reader.word(class)?;
+            symbol.st_size = reader.word(class)?;
+            info = reader.u8()?;
+            other = reader.u8()?;
+            symbol.st_shndx = reader.u16()?;
+        } else {
+            info = reader.u8()?;
+            other = reader.u8()?;
+            symbol.st_shndx = reader.u16()?;
+            symbol.st_value = reader.word(class)?;
+            symbol.st_size = reader.word(class)?;
+        }
+        symbol.shndx = symbol.st_shndx as u32;
+        symbol.st_bind = info >> 4;
+        symbol.st_type = info & 0xf;
+        symbol.st_visibility = other & 0x3;
+        Ok(symbol)
+    }
+
+    pub fn bind_name(&self) -> String {
+        match self.st_bind {
+            STB_LOCAL => "LOCAL".to_string(),
+            STB_GLOBAL => "GLOBAL".to_string(),
+            STB_WEAK => "WEAK".to_string(),
+            STB_GNU_UNIQUE => "UNIQUE".to_string(),
+            _ => format!("<{}>", self.st_bind),
+        }
+    }
+
+    pub fn type_name(&self) -> String {
+        match self.st_type {
+            STT_NOTYPE => "NOTYPE".to_string(),
+            STT_OBJECT => "OBJECT".to_string(),
+            STT_FUNC => "FUNC".to_string(),
+            STT_SECTION => "SECTION".to_string(),
+            STT_FILE => "FILE".to_string(),
+            STT_COMMON => "COMMON".to_string(),
+            STT_TLS => "TLS".to_string(),
+            STT_GNU_IFUNC => "IFUNC".to_string(),
+            _ => format!("<{}>", self.st_type),
+        }
+    }
+
+    pub fn visibility_name(&self) -> &'static str {
+        match self.st_visibility {
+            STV_DEFAULT => "DEFAULT",
+            STV_INTERNAL => "INTERNAL",
+            STV_HIDDEN => "HIDDEN",
+            _ => "PROTECTED",
+        }
+    }
+
+    pub fn section_index_name(&self) -> String {
+        match self.st_shndx {
+            SHN_UNDEF => "UND".to_string(),
+            SHN_ABS => "ABS".to_string(),
+            SHN_COMMON => "COM".to_string(),
+            // no SHT_SYMTAB_SHNDX entry to resolve it with
+            SHN_XINDEX if self.shndx == SHN_XINDEX as u32 => "XIDX".to_string(),
+            _ => self.shndx.to_string(),
+        }
+    }
+
+    // `nm`-style one letter summary of the symbol
+    pub fn nm_letter(&self, sections: &[SectionHeader]) -> char {
+        let letter = match self.st_shndx {
+            SHN_UNDEF if self.st_bind == STB_WEAK => return 'w',
+            SHN_UNDEF => return 'U',
+            SHN_ABS => 'a',
+            SHN_COMMON => return 'C',
+            _ if self.st_type == STT_GNU_IFUNC => return 'i',
+            _ if self.st_bind == STB_GNU_UNIQUE => return 'u',
+            _ => match sections.get(self.shndx as usize) {
+                Some(section) if section.sh_flags & SHF_EXECINSTR != 0 => 't',
+                Some(section) if section.sh_type == SHT_NOBITS => 'b',
+                Some(section) if section.sh_flags & SHF_WRITE != 0 => 'd',
+                Some(section) if section.sh_flags & SHF_ALLOC != 0 => 'r',
+                _ => 'n',
+            },
+        };
+        if self.st_bind == STB_WEAK {
+            if self.st_type == STT_OBJECT {
+                'V'
+            } else {
+                'W'
+            }
+        } else if self.st_bind == STB_LOCAL {
+            letter
+        } else {
+            letter.to_ascii_uppercase()
+        }
+    }
+}
+
+// Entries of a SHT_SYMTAB or SHT_DYNSYM section, decoded one at a time,
+// with their names borrowed from the linked string table so nothing is
+// allocated per entry.
+#[derive(Default)]
+pub struct SymbolIter<'a> {
+    reader: Reader<'a>,
+    strtab: &'a [u8],
+    // the SHT_SYMTAB_SHNDX section of the table, if any
+    xindex: Reader<'a>,
+    class: Arch,
+    entsize: u64,
+    index: u64,
+    count: u64,
+}
+
+impl<'a> SymbolIter<'a> {
+    pub fn new(
+        contents: &'a [u8],
+        header: &ELFHeader,
+        sections: &[SectionHeader],
+        symtab: &SectionHeader,
+    ) -> Result<SymbolIter<'a>, ParseError> {
+        let entsize = match symtab.sh_entsize {
+            0 if header.ei_class == Arch::B32 => 16,
+            0 => 24,
+            size => size,
+        };
+        let table = symtab.data(contents)?;
+        let strtab = match sections.get(symtab.sh_link as usize) {
+            Some(section) => section.data(contents)?,
+            None => &[],
+        };
+        // it links back to the table; sections are compared by their
+        // place in the file since `symtab` may be a copy
+        let xindex = sections.iter().find(|section| {
+            section.sh_type == SHT_SYMTAB_SHNDX
+                && sections
+                    .get(section.sh_link as usize)
+                    .is_some_and(|linked| {
+                        linked.sh_type == symtab.sh_type && linked.sh_offset == symtab.sh_offset
+                    })
+        });
+        let xindex = match xindex {
+            Some(section) => section.data(contents)?,
+            None => &[],
+        };
+        Ok(SymbolIter {
+            reader: Reader::new(table, header.ei_data),
+            strtab,
+            xindex: Reader::new(xindex, header.ei_data),
+            class: header.ei_class,
+            entsize,
+            index: 0,
+            count: table.len() as u64 / entsize,
+        })
+    }
+}
+
+impl ExactSizeIterator for SymbolIter<'_> {}
+
+impl<'a> Iterator for SymbolIter<'a> {
+    type Item = Result<Symbol<'a>, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index >= self.count {
+            return None;
+        }
+        self.reader.seek(self.index * self.entsize);
+        self.index += 1;
+        let mut symbol = match Symbol::parse(&mut self.reader, &self.class) {
+            Ok(symbol) => symbol,
+            Err(e) => {
+                // stop after the first error
+                self.index = self.count;
+                return Some(Err(e));
+            }
+        };
+        if symbol.st_shndx == SHN_XINDEX {
+            // one word per symbol; a short table leaves it unresolved
+            self.xindex.seek((self.index - 1) * 4);
+            if let Ok(shndx) = self.xindex.u32() {
+                symbol.shndx = shndx;
+            }
+        }
+        let name = str_at(self.strtab, symbol.st_name as usize);
+        Some(Ok(Symbol { name, ..symbol }))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let left = (self.count - self.index) as usize;
+        (left, Some(left))
+    }
+}
+
+// parse the entries of a SHT_SYMTAB or SHT_DYNSYM section and resolve
+// their names through the string table in sh_link
+pub fn parse_symbols<'a>(
+    contents: &'a [u8],
+    header: &ELFHeader,
+    sections: &[SectionHeader],
+    symtab: &SectionHeader,
+) -> Result<Vec<Symbol<'a>>, ParseError> {
+    SymbolIter::new(contents, header, sections, symtab)?.collect()
+}
+
+// symbols of the first section of type `sh_type` (SHT_SYMTAB or SHT_DYNSYM)
+pub fn parse_symbol_table<'a>(
+    contents: &'a [u8],
+    header: &ELFHeader,
+    sections: &[SectionHeader],
+    sh_type: u32,
+) -> Result<Option<Vec<Symbol<'a>>>, ParseError> {
+    match sections.iter().find(|section| section.sh_type == sh_type) {
+        Some(symtab) => Ok(Some(parse_symbols(contents, header, sections, symtab)?)),
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::parse::{Arch, ELFHeader};
+    use crate::section::{
+        SectionHeader, SHF_ALLOC, SHF_EXECINSTR, SHT_STRTAB, SHT_SYMTAB, SHT_SYMTAB_SHNDX,
+    };
+    use crate::symbol::{
+        self, Symbol, SHN_XINDEX, STB_GLOBAL, STB_WEAK, STT_FUNC, STT_OBJECT, STV_HIDDEN,
+    };
+    use crate::utils::{Endian, Reader};
+
+    #[test]
+    fn symbol32big() {
+        let mut bytes = Vec::new();
+        bytes.extend(0x10_u32.to_be_bytes());
+        bytes.extend(0x1000_0400_u32.to_be_bytes());
+        bytes.extend(0x40_u32.to_be_bytes());
+        bytes.push(STB_GLOBAL << 4 | STT_FUNC);
+        bytes.push(STV_HIDDEN);
+        bytes.extend(7_u16.to_be_bytes());
+        let symbol = Symbol::parse(&mut Reader::new(&bytes, Endian::Big), &Arch::B32).unwrap();
+        assert_eq!(symbol.st_name, 0x10);
+        assert_eq!(symbol.st_value, 0x1000_0400);
+        assert_eq!(symbol.st_size, 0x40);
+        assert_eq!(symbol.type_name(), "FUNC");
+        assert_eq!(symbol.bind_name(), "GLOBAL");
+        assert_eq!(symbol.visibility_name(), "HIDDEN");
+        assert_eq!(symbol.st_shndx, 7);
+    }
+
+    #[test]
+    fn symbol64little() {
+        let mut bytes = Vec::new();
+        bytes.extend(0x20_u32.to_le_bytes());
+        bytes.push(STB_WEAK << 4 | STT_OBJECT);
+        bytes.push(0);
+        bytes.extend(0_u16.to_le_bytes());
+        bytes.extend(0_u64.to_le_bytes());
+        bytes.extend(8_u64.to_le_bytes());
+        let symbol = Symbol::parse(&mut Reader::new(&bytes, Endian::Little), &Arch::B64).unwrap();
+        assert_eq!(symbol.st_size, 8);
+        assert_eq!(symbol.section_index_name(), "UND");
+        assert_eq!(symbol.nm_letter(&[]), 'w');
+    }
+
+    #[test]
+    fn extended_section_index() {
+        // a null symbol and one in section 70000, which only fits in the
+        // SHT_SYMTAB_SHNDX section after the string table
+        let mut contents = vec![0; 24];
+        contents.extend(1_u32.to_le_bytes());
+        contents.push(STB_GLOBAL << 4 | STT_FUNC);
+        contents.push(0);
+        contents.extend(SHN_XINDEX.to_le_bytes());
+        contents.extend([0; 16]);
+        contents.extend(b"\0big\0\0\0\0");
+        contents.extend(0_u32.to_le_bytes());
+        contents.extend(70000_u32.to_le_bytes());
+
+        let mut sections = vec![SectionHeader::default(); 70001];
+        let table = |sh_type, sh_offset, sh_size, sh_link| SectionHeader {
+            sh_type,
+            sh_offset,
+            sh_size,
+            sh_link,
+            ..Default::default()
+        };
+        sections[1] = table(SHT_SYMTAB, 0, 48, 2);
+        sections[2] = table(SHT_STRTAB, 48, 8, 0);
+        sections[3] = table(SHT_SYMTAB_SHNDX, 56, 8, 1);
+        sections[70000].sh_flags = SHF_ALLOC | SHF_EXECINSTR;
+
+        let header = ELFHeader::default();
+        let symbols = symbol::parse_symbol_table(&contents, &header, &sections, SHT_SYMTAB)
+            .unwrap()
+            .unwrap();
+        assert_eq!(symbols[1].name, "big");
+        assert_eq!(symbols[1].st_shndx, SHN_XINDEX);
+        assert_eq!(symbols[1].section_index_name(), "70000");
+        assert_eq!(symbols[1].nm_letter(&sections), 'T');
+
+        // without the table the index stays unresolved
+        sections[3].sh_type = 0;
+        let symbols = symbol::parse_symbol_table(&contents, &header, &sections, SHT_SYMTAB)
+            .unwrap()
+            .unwrap();
+        assert_eq!(symbols[1].section_index_name(), "XIDX");
+    }
+}
@@ -1,3 +1,6 @@
+use crate::error::ParseError;
+use crate::parse::Arch;
+
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub enum Endian {
     Big,
@@ -5,7 +8,8 @@ pub enum Endian {
     Little,
 }
 
-// byte sequence to integer
+// byte sequence to integer. `seq` must hold at least as many bytes
+// as the integer is wide; go through `Reader` when reading from a file
 pub trait ValidNums {
     fn from_big_bytes(seq: &[u8]) -> Self;
     fn from_little_bytes(seq: &[u8]) -> Self;
@@ -14,42 +18,42 @@ pub trait ValidNums {
 
 impl ValidNums for u16 {
     fn from_big_bytes(seq: &[u8]) -> Self {
-        Self::from_be_bytes(seq[..2 as usize].try_into().unwrap())
+        Self::from_be_bytes(seq[..2].try_into().unwrap())
     }
     fn from_little_bytes(seq: &[u8]) -> Self {
-        Self::from_le_bytes(seq[..2 as usize].try_into().unwrap())
+        Self::from_le_bytes(seq[..2].try_into().unwrap())
     }
     fn from_bytes(en: Endian, seq: &[u8]) -> Self {
         match en {
-            Endian::Big => Self::from_big_bytes(seq[..2 as usize].try_into().unwrap()),
-            Endian::Little => Self::from_le_bytes(seq[..2 as usize].try_into().unwrap()),
+            Endian::Big => Self::from_big_bytes(seq[..2].try_into().unwrap()),
+            Endian::Little => Self::from_le_bytes(seq[..2].try_into().unwrap()),
         }
     }
 }
 impl ValidNums for u32 {
     fn from_big_bytes(seq: &[u8]) -> Self {
-        Self::from_be_bytes(seq[..4 as usize].try_into().unwrap())
+        Self::from_be_bytes(seq[..4].try_into().unwrap())
     }
     fn from_little_bytes(seq: &[u8]) -> Self {
-        Self::from_le_bytes(seq[..4 as usize].try_into().unwrap())
+        Self::from_le_bytes(seq[..4].try_into().unwrap())
     }
     fn from_bytes(en: Endian, seq: &[u8]) -> Self {
         match en {
-            Endian::Big => Self::from_big_bytes(seq[..4 as usize].try_into().unwrap()),
-            Endian::Little => Self::from_le_bytes(seq[..4 as usize].try_into().unwrap()),
+            Endian::Big => Self::from_big_bytes(seq[..4].try_into().unwrap()),
+            Endian::Little => Self::from_le_bytes(seq[..4].try_into().unwrap()),
         }
     }
 }
 impl ValidNums for u64 {
     fn from_big_bytes(seq: &[u8]) -> Self {
-        let len = if seq.len() > 8 { 8 as usize } else { seq.len() };
+        let len = if seq.len() > 8 { 8 } else { seq.len() };
         if seq.len() == 4 {
             return u32::from_be_bytes(seq[..len].try_into().unwrap()) as u64;
         }
         Self::from_be_bytes(seq[..len].try_into().unwrap())
     }
     fn from_little_bytes(seq: &[u8]) -> Self {
-        let len = if seq.len() > 8 { 8 as usize } else { seq.len() };
+        let len = if seq.len() > 8 { 8 } else { seq.len() };
         if len == 4 {
             return u32::from_le_bytes(seq[..len].try_into().unwrap()) as u64;
         }
@@ -57,8 +61,92 @@ impl ValidNums for u64 {
     }
     fn from_bytes(en: Endian, seq: &[u8]) -> Self {
         match en {
-            Endian::Big => Self::from_big_bytes(seq[..seq.len() as usize].try_into().unwrap()),
-            Endian::Little => Self::from_le_bytes(seq[..seq.len() as usize].try_into().unwrap()),
+            Endian::Big => Self::from_big_bytes(seq),
+            Endian::Little => Self::from_little_bytes(seq),
+        }
+    }
+}
+
+// bounds-checked view of `len` bytes starting at `offset`
+pub fn checked_slice(data: &[u8], offset: u64, len: u64) -> Result<&[u8], ParseError> {
+    let truncated = || ParseError::Truncated {
+        offset,
+        needed: len,
+    };
+    let start = usize::try_from(offset).map_err(|_| truncated())?;
+    let size = usize::try_from(len).map_err(|_| truncated())?;
+    match start.checked_add(size) {
+        Some(end) if end <= data.len() => Ok(&data[start..end]),
+        _ => Err(truncated()),
+    }
+}
+
+// Cursor over untrusted bytes. Every read is bounds-checked and
+// reports the offset it failed at instead of panicking.
+#[derive(Default)]
+pub struct Reader<'a> {
+    data: &'a [u8],
+    endian: Endian,
+    cursor: u64,
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(data: &'a [u8], endian: Endian) -> Self {
+        Reader {
+            data,
+            endian,
+            cursor: 0,
+        }
+    }
+
+    pub fn set_endian(&mut self, endian: Endian) {
+        self.endian = endian;
+    }
+
+    pub fn endian(&self) -> Endian {
+        self.endian
+    }
+
+    pub fn position(&self) -> u64 {
+        self.cursor
+    }
+
+    // the cursor may be placed anywhere; reads past the end error out
+    pub fn seek(&mut self, offset: u64) {
+        self.cursor = offset;
+    }
+
+    pub fn skip(&mut self, len: u64) {
+        self.cursor = self.cursor.saturating_add(len);
+    }
+
+    pub fn bytes(&mut self, len: u64) -> Result<&'a [u8], ParseError> {
+        let slice = checked_slice(self.data, self.cursor, len)?;
+        self.cursor += len;
+        Ok(slice)
+    }
+
+    pub fn u8(&mut self) -> Result<u8, ParseError> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    pub fn u16(&mut self) -> Result<u16, ParseError> {
+        Ok(u16::from_bytes(self.endian, self.bytes(2)?))
+    }
+
+    pub fn u32(&mut self) -> Result<u32, ParseError> {
+        Ok(u32::from_bytes(self.endian, self.bytes(4)?))
+    }
+
+    pub fn u64(&mut self) -> Result<u64, ParseError> {
+        Ok(u64::from_bytes(self.endian, self.bytes(8)?))
+    }
+
+    // address-sized field: 4 bytes in ELF32, 8 bytes in ELF64
+    pub fn word(&mut self, class: &Arch) -> Result<u64, ParseError> {
+        match class {
+            Arch::B32 => Ok(self.u32()? as u64),
+            Arch::B64 => self.u64(),
         }
     }
 }
@@ -67,7 +155,9 @@ impl ValidNums for u64 {
 
 #[cfg(test)]
 mod test {
-    use crate::utils::ValidNums;
+    use crate::error::ParseError;
+    use crate::parse::Arch;
+    use crate::utils::{checked_slice, Endian, Reader, ValidNums};
     #[test]
     fn bit16big() {
         let seq = [0x0, 0xFE];
@@ -141,4 +231,38 @@ mod test {
         let big = u64::from_little_bytes(&seq);
         assert!(big == u64::from_le_bytes(seq[..8].try_into().unwrap()));
     }
+
+    #[test]
+    fn reader_words() {
+        let seq = [0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC, 0xDE, 0xF0];
+        let mut reader = Reader::new(&seq, Endian::Big);
+        assert_eq!(reader.u16().unwrap(), 0x1234);
+        assert_eq!(reader.word(&Arch::B32).unwrap(), 0x5678_9ABC);
+        reader.seek(0);
+        reader.set_endian(Endian::Little);
+        assert_eq!(reader.word(&Arch::B64).unwrap(), 0xF0DE_BC9A_7856_3412);
+        assert_eq!(reader.position(), 8);
+    }
+    #[test]
+    fn reader_truncated() {
+        let seq = [0x7f, b'E', b'L', b'F', 0x2];
+        let mut reader = Reader::new(&seq, Endian::Little);
+        reader.seek(3);
+        assert!(matches!(
+            reader.u32(),
+            Err(ParseError::Truncated {
+                offset: 3,
+                needed: 4
+            })
+        ));
+        reader.seek(u64::MAX);
+        assert!(reader.u8().is_err());
+    }
+    #[test]
+    fn slice_overflow() {
+        let seq = [0_u8; 16];
+        assert_eq!(checked_slice(&seq, 8, 8).unwrap().len(), 8);
+        assert!(checked_slice(&seq, 8, 9).is_err());
+        assert!(checked_slice(&seq, u64::MAX, 2).is_err());
+    }
 }
@@ -0,0 +1,324 @@
+// Strict validation: checks that the header and the tables it points to
+// are consistent, which parsing alone doesn't require
+use std::fmt::{self, Display};
+
+use crate::error::ParseError;
+use crate::parse::{Arch, ELFParser};
+use crate::section::SHT_NOBITS;
+
+pub const ELFCLASS32: u8 = 1;
+pub const ELFCLASS64: u8 = 2;
+pub const EV_CURRENT: u32 = 1;
+
+// offsets into e_ident
+const EI_CLASS: u64 = 4;
+const EI_DATA: u64 = 5;
+const EI_VERSION: u64 = 6;
+
+// a problem, and the offset of the bytes it is about
+#[derive(Debug)]
+pub struct Diagnostic {
+    pub offset: u64,
+    pub error: ParseError,
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:#x}: {}", self.offset, self.error)
+    }
+}
+
+// The identification bytes the parser refuses outright, as the diagnostic
+// `validate` would report if it got that far; other errors are returned.
+pub fn refused(error: ParseError) -> Result<Diagnostic, ParseError> {
+    let offset = match error {
+        ParseError::UnsupportedClass => EI_CLASS,
+        ParseError::UnsupportedEndianess => EI_DATA,
+        _ => return Err(error),
+    };
+    Ok(Diagnostic { offset, error })
+}
+
+// sizes of the header structures, and the offsets of the header fields
+// that come after the class-dependent ones
+struct Layout {
+    ehsize: u64,
+    phentsize: u64,
+    shentsize: u64,
+    e_phoff: u64,
+    e_shoff: u64,
+    e_ehsize: u64,
+    e_phentsize: u64,
+    e_shentsize: u64,
+    e_shstrndx: u64,
+}
+
+const LAYOUT32: Layout = Layout {
+    ehsize: 52,
+    phentsize: 32,
+    shentsize: 40,
+    e_phoff: 0x1c,
+    e_shoff: 0x20,
+    e_ehsize: 0x28,
+    e_phentsize: 0x2a,
+    e_shentsize: 0x2e,
+    e_shstrndx: 0x32,
+};
+
+const LAYOUT64: Layout = Layout {
+    ehsize: 64,
+    phentsize: 56,
+    shentsize: 64,
+    e_phoff: 0x20,
+    e_shoff: 0x28,
+    e_ehsize: 0x34,
+    e_phentsize: 0x36,
+    e_shentsize: 0x3a,
+    e_shstrndx: 0x3e,
+};
+
+// Every problem found, in file order of the checks: the identification,
+// the header fields, then the tables and what they point to. An empty
+// list means the file passed.
+pub fn validate(parser: &ELFParser) -> Vec<Diagnostic> {
+    let mut found = Vec::new();
+    let mut report = |offset, error| found.push(Diagnostic { offset, error });
+    let contents = parser.contents();
+    let header = &parser.elf_header;
+    let file_size = contents.len() as u64;
+    let past_end = |what: String, end| ParseError::OutOfFile {
+        what,
+        end,
+        size: file_size,
+    };
+
+    // the parser already refused any class but ELFCLASS32 and ELFCLASS64
+    if header.ei_version as u32 != EV_CURRENT {
+        report(EI_VERSION, ParseError::UnsupportedVersion);
+    }
+    if header.e_version != EV_CURRENT {
+        report(0x14, ParseError::UnsupportedVersion);
+    }
+
+    let layout = match header.ei_class {
+        Arch::B32 => &LAYOUT32,
+        Arch::B64 => &LAYOUT64,
+    };
+    let sizes = [
+        (
+            "e_ehsize",
+            layout.e_ehsize,
+            header.e_ehsize,
+            layout.ehsize,
+            true,
+        ),
+        (
+            "e_phentsize",
+            layout.e_phentsize,
+            header.e_phentsize,
+            layout.phentsize,
+            header.phnum > 0,
+        ),
+        (
+            "e_shentsize",
+            layout.e_shentsize,
+            header.e_shentsize,
+            layout.shentsize,
+            header.shnum > 0,
+        ),
+    ];
+    for (field, offset, found, expected, used) in sizes {
+        let found = found as u64;
+        if used && found != expected {
+            report(
+                offset,
+                ParseError::SizeMismatch {
+                    field,
+                    found,
+                    expected,
+                },
+            );
+        }
+    }
+    if header.shnum > 0 && header.shstrndx as u64 >= header.shnum {
+        let index = header.shstrndx as u64;
+        let count = header.shnum;
+        report(
+            layout.e_shstrndx,
+            ParseError::ShstrndxOutOfRange { index, count },
+        );
+    }
+
+    // the header and the two tables, as (name, start, end)
+    let mut regions = vec![("ELF header", 0, layout.ehsize)];
+    let tables = [
+        (
+            "program header table",
+            layout.e_phoff,
+            header.e_phoff,
+            header.phnum as u64,
+            header.e_phentsize,
+        ),
+        (
+            "section header table",
+            layout.e_shoff,
+            header.e_shoff,
+            header.shnum,
+            header.e_shentsize,
+        ),
+    ];
+    for (what, field, start, count, entsize) in tables {
+        if start == 0 || count == 0 {
+            continue;
+        }
+        let end = start.saturating_add(count.saturating_mul(entsize as u64));
+        if end > file_size {
+            report(field, past_end(what.to_string(), end));
+        } else {
+            regions.push((what, start, end));
+        }
+    }
+    for (i, &(second, start, end)) in regions.iter().enumerate() {
+        for &(first, other_start, other_end) in &regions[..i] {
+            if start < other_end && other_start < end {
+                let offset = start.max(other_start);
+                report(offset, ParseError::Overlap { first, second });
+            }
+        }
+    }
+
+    // what the tables point to, reported at the offset of their entry; a
+    // table that doesn't parse was reported above
+    let phentsize = header.e_phentsize as u64;
+    for (i, pheader) in parser
+        .program_headers()
+        .unwrap_or_default()
+        .iter()
+        .enumerate()
+    {
+        let end = pheader.p_offset.saturating_add(pheader.p_filesz);
+        if end > file_size {
+            let entry = header.e_phoff + i as u64 * phentsize;
+            report(entry, past_end(format!("segment {i}"), end));
+        }
+    }
+    let shentsize = header.e_shentsize as u64;
+    for (i, section) in parser.sections().unwrap_or_default().iter().enumerate() {
+        let end = section.sh_offset.saturating_add(section.sh_size);
+        if section.sh_type != SHT_NOBITS && end > file_size {
+            let entry = header.e_shoff + i as u64 * shentsize;
+            report(
+                entry,
+                past_end(format!("section {i} ({})", section.name), end),
+            );
+        }
+    }
+    found
+}
+
+#[cfg(test)]
+mod test {
+    use crate::error::ParseError;
+    use crate::parse::ELFParser;
+    use crate::validate::{refused, validate};
+
+    fn check(contents: &[u8]) -> Vec<(u64, ParseError)> {
+        let parser = ELFParser::from_bytes(contents).unwrap();
+        validate(&parser)
+            .into_iter()
+            .map(|d| (d.offset, d.error))
+            .collect()
+    }
+
+    #[test]
+    fn well_formed() {
+        let exe = std::fs::read(std::env::current_exe().unwrap()).unwrap();
+        assert!(check(&exe).is_empty());
+        assert!(check(include_bytes!("../tests/fixtures/hello-i386")).is_empty());
+        assert!(check(include_bytes!("../tests/fixtures/hello-arm.o")).is_empty());
+    }
+
+    #[test]
+    fn bad_identification() {
+        let mut contents = include_bytes!("../tests/fixtures/hello-i386").to_vec();
+        contents[4] = 7;
+        let error = ELFParser::from_bytes(&contents).err().unwrap();
+        assert!(matches!(error, ParseError::UnsupportedClass));
+        let diagnostic = refused(error).unwrap();
+        assert_eq!(diagnostic.to_string(), "0x4: Unsupported ELF class");
+        assert!(matches!(
+            refused(ParseError::BadMagic),
+            Err(ParseError::BadMagic)
+        ));
+
+        let mut contents = include_bytes!("../tests/fixtures/hello-i386").to_vec();
+        contents[6] = 0;
+        contents[0x14] = 2;
+        let found = check(&contents);
+        assert!(matches!(found[0], (6, ParseError::UnsupportedVersion)));
+        assert!(matches!(found[1], (0x14, ParseError::UnsupportedVersion)));
+    }
+
+    #[test]
+    fn bad_header_fields() {
+        let mut contents = include_bytes!("../tests/fixtures/hello-i386").to_vec();
+        // e_ehsize, e_shentsize and e_shstrndx of ELF32
+        contents[0x28] = 64;
+        contents[0x2e] = 64;
+        contents[0x32] = 9;
+        let found = check(&contents);
+        assert!(matches!(
+            found[0],
+            (
+                0x28,
+                ParseError::SizeMismatch {
+                    field: "e_ehsize",
+                    found: 64,
+                    expected: 52
+                }
+            )
+        ));
+        assert!(matches!(
+            found[1],
+            (
+                0x2e,
+                ParseError::SizeMismatch {
+                    field: "e_shentsize",
+                    ..
+                }
+            )
+        ));
+        assert!(matches!(
+            found[2],
+            (0x32, ParseError::ShstrndxOutOfRange { index: 9, count: 6 })
+        ));
+
+        // the parser keeps what it found
+        let parser = ELFParser::from_bytes(&contents).unwrap();
+        assert_eq!(parser.diagnostics().len(), found.len());
+        assert!(std::ptr::eq(parser.diagnostics(), parser.diagnostics()));
+    }
+
+    #[test]
+    fn bad_tables() {
+        let mut contents = include_bytes!("../tests/fixtures/hello-i386").to_vec();
+        // the program header table starting inside the ELF header, and
+        // the section header table running off the end
+        contents[0x1c] = 0x30;
+        contents[0x20..0x24].copy_from_slice(&0x1000_u32.to_le_bytes());
+        let found = check(&contents);
+        assert!(
+            matches!(&found[0], (0x20, ParseError::OutOfFile { what, .. }) if what == "section header table")
+        );
+        assert!(matches!(
+            found[1],
+            (
+                0x30,
+                ParseError::Overlap {
+                    first: "ELF header",
+                    second: "program header table"
+                }
+            )
+        ));
+    }
+}
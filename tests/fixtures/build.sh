@@ -0,0 +1,11 @@
+#!/bin/sh
+# Rebuilds the test fixtures from their sources. Needs GNU binutils
+# and llvm-mc; the outputs are committed so the tests don't need either.
+set -e
+cd "$(dirname "$0")"
+as --32 -o hello-i386.o hello-i386.s
+ld -m elf_i386 -n -o hello-i386 hello-i386.o
+rm hello-i386.o
+llvm-mc --triple=armv7-linux-gnueabihf -filetype=obj -o hello-arm.o hello-arm.s
+llvm-mc --triple=aarch64-linux-gnu -mattr=+v8.5a -filetype=obj -o hello-arm64.o hello-arm64.s
+llvm-mc --triple=riscv32 -mattr=+m,+a,+c,-relax -filetype=obj -o hello-riscv.o hello-riscv.s
@@ -0,0 +1,20 @@
+@ write(1, msg, len); exit(0) through svc
+	.syntax unified
+	.arm
+	.text
+	.globl _start
+	.type _start, %function
+_start:
+	mov r0, #1
+	ldr r1, =msg
+	mov r2, #6
+	mov r7, #4
+	svc #0
+	mov r0, #0
+	mov r7, #1
+	svc #0
+	.size _start, . - _start
+
+	.section .rodata
+msg:
+	.ascii "hello\n"
@@ -0,0 +1,33 @@
+// write(1, msg, len); exit(0) through svc, with a PAC-signed call
+	.text
+	.globl _start
+	.type _start, %function
+_start:
+	bti c
+	paciasp
+	stp x29, x30, [sp, #-16]!
+	mov x29, sp
+	bl greet
+	ldp x29, x30, [sp], #16
+	autiasp
+	mov x0, #0
+	mov x8, #93
+	svc #0
+	.size _start, . - _start
+
+	.type greet, %function
+greet:
+	mov x0, #1
+	adrp x1, msg
+	add x1, x1, :lo12:msg
+	mov x2, #6
+	mov x8, #64
+	svc #0
+	cbz x0, 1f
+	ret
+1:	b greet
+	.size greet, . - greet
+
+	.section .rodata
+msg:
+	.ascii "hello\n"
@@ -0,0 +1,19 @@
+# write(1, msg, len); exit(0) through int 0x80
+	.section .text
+	.globl _start
+	.type _start, @function
+_start:
+	movl $4, %eax
+	movl $1, %ebx
+	movl $msg, %ecx
+	movl $len, %edx
+	int $0x80
+	movl $1, %eax
+	xorl %ebx, %ebx
+	int $0x80
+	.size _start, . - _start
+
+	.section .rodata
+msg:
+	.ascii "hello\n"
+	.set len, . - msg
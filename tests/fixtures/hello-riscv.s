@@ -0,0 +1,34 @@
+# write(1, msg, len); exit(0) through ecall, on an RV32IMAC microcontroller
+	.attribute arch, "rv32i2p0_m2p0_a2p0_c2p0"
+	.text
+	.globl _start
+	.type _start, @function
+_start:
+	addi sp, sp, -16
+	sw ra, 12(sp)
+	jal greet
+	lw ra, 12(sp)
+	addi sp, sp, 16
+	li a0, 0
+	li a7, 93
+	ecall
+	.size _start, . - _start
+
+	.type greet, @function
+greet:
+	li a0, 1
+	lui a1, %hi(msg)
+	addi a1, a1, %lo(msg)
+	li a2, 6
+	li a7, 64
+	ecall
+	beqz a0, 1f
+	mul a0, a0, a2
+	amoadd.w.aq zero, a0, (a1)
+	ret
+1:	j greet
+	.size greet, . - greet
+
+	.section .rodata
+msg:
+	.ascii "hello\n"